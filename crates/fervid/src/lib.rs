@@ -22,8 +22,17 @@
 //! let mut transform_errors = Vec::new();
 //! let transform_options = fervid_transform::TransformSfcOptions {
 //!   is_prod: true,
+//!   is_ssr: false,
+//!   hoist_static: true,
 //!   scope_id: "filehash",
-//!   filename: "input.vue"
+//!   filename: "input.vue",
+//!   source: input,
+//!   css_modules_pattern: None,
+//!   css_modules_mode: Default::default(),
+//!   transform_asset_urls: None,
+//!   style_preprocessor: None,
+//!   css_output: Default::default(),
+//!   type_resolve: Default::default(),
 //! };
 //! let transform_result = fervid_transform::transform_sfc(sfc, transform_options, &mut transform_errors);
 //!
@@ -44,7 +53,7 @@
 //! );
 //!
 //! // (Optional) Stringify the code
-//! let compiled_code = fervid_codegen::CodegenContext::stringify(input, &sfc_module, FileName::Custom("input.vue".into()), false, false);
+//! let compiled_code = fervid_codegen::CodegenContext::stringify(input, &sfc_module, FileName::Custom("input.vue".into()), false, false, Some(&ctx.comments));
 //! ```
 
 extern crate lazy_static;
@@ -56,14 +65,29 @@ pub mod parser_old;
 use errors::CompileError;
 use fervid_codegen::CodegenContext;
 pub use fervid_core::*;
+pub use fervid_css::{CssModulesMode, CssOutputOptions};
+pub use fervid_transform::{
+    script::resolve_type::{NativeTypeResolveFs, TypeResolveFs, TypeScopeCache},
+    style::{PreprocessedStyle, StylePreprocessor},
+    template::AssetUrlOptions,
+};
 use fervid_parser::SfcParser;
-use fervid_transform::{style::should_transform_style_block, transform_sfc, TransformSfcOptions};
+use fervid_css::{parse_css_targets, CssError, CssErrorKind};
+use fervid_transform::{
+    error::TransformError,
+    style::create_style_scope,
+    script::resolve_type::TypeResolveOptions,
+    transform_sfc, TransformSfcOptions,
+};
 use fxhash::FxHasher32;
 use std::{
     borrow::Cow,
     hash::{Hash, Hasher},
 };
-use swc_core::{common::FileName, ecma::ast::Expr};
+use swc_core::{
+    common::{FileName, DUMMY_SP},
+    ecma::ast::Expr,
+};
 
 // TODO Add severity to errors
 // TODO Better structs
@@ -87,12 +111,36 @@ pub struct CompileOptions<'o> {
     // linked or globally installed). In such cases a custom `require` can be
     // passed to correctly resolve the preprocessors.
     // preprocessCustomRequire?: (id: string) => any;
-    // Configure what tags/attributes to transform into asset url imports,
-    // or disable the transform altogether with `false`.
-    // transformAssetUrls?: AssetURLOptions | AssetURLTagConfig | boolean;
+    /// What tags/attributes to transform into asset url imports, `None` uses the defaults.
+    /// Empty `tags` disable the transform altogether.
+    pub transform_asset_urls: Option<AssetUrlOptions>,
 
     // script
     pub gen_default_as: Option<Cow<'o, str>>,
+    /// Hoist the literal constants of `<script setup>` to the module scope, default is `true`
+    pub hoist_static: Option<bool>,
+
+    // style
+    /// Pattern for the names in `<style module>`, e.g. `[name]_[local]_[hash]`
+    pub css_modules_pattern: Option<Cow<'o, str>>,
+    /// Whether names in `<style module>` are local or global by default
+    pub css_modules_mode: Option<CssModulesMode>,
+    /// Converts `<style lang="scss">` and other non-CSS blocks to CSS before the style transforms
+    pub style_preprocessor: Option<&'o dyn StylePreprocessor>,
+    /// Minify the `<style>` blocks, e.g. remove duplicate declarations and compress colors
+    pub css_minify: Option<bool>,
+    /// Browserslist query to lower and prefix the `<style>` blocks for, e.g. `chrome 80, safari 13`
+    pub css_targets: Option<Cow<'o, str>>,
+
+    // types
+    /// File system for the types imported in `defineProps<T>()` and `defineEmits<T>()`.
+    /// Without it, only the types declared in the SFC itself are resolved.
+    pub type_resolve_fs: Option<&'o dyn TypeResolveFs>,
+    /// Parsed type files shared between compilations
+    pub type_scope_cache: Option<&'o TypeScopeCache>,
+    /// Files with the global types used in the macros, e.g. `src/global.d.ts`.
+    /// Requires `type_resolve_fs`.
+    pub global_type_files: Option<Cow<'o, [String]>>,
 
     // fervid-specific
     pub source_map: Option<bool>,
@@ -105,6 +153,9 @@ pub struct CompileResult {
     pub styles: Vec<CompileEmittedStyle>,
     pub other_assets: Vec<CompileEmittedAsset>,
     pub source_map: Option<String>,
+    /// Files other than the SFC which affect the compilation result,
+    /// e.g. the imported types of `defineProps<Props>()` or `<style src="./foo.css">`
+    pub deps: Vec<String>,
 }
 
 pub struct CompileEmittedStyle {
@@ -112,6 +163,8 @@ pub struct CompileEmittedStyle {
     pub is_compiled: bool,
     pub lang: String,
     pub is_scoped: bool,
+    /// Map into the original SFC when `source_map` is enabled and the block was compiled
+    pub source_map: Option<String>,
 }
 
 pub struct CompileEmittedAsset {
@@ -128,12 +181,14 @@ pub fn compile(source: &str, options: CompileOptions) -> Result<CompileResult, C
 
     // Options
     let is_prod = options.is_prod.unwrap_or_default();
+    let is_ssr = options.ssr.unwrap_or_default();
 
     // Parse
     let mut sfc_parsing_errors = Vec::new();
     let mut parser = SfcParser::new(source, &mut sfc_parsing_errors);
     let sfc = parser.parse_sfc()?;
     all_errors.extend(sfc_parsing_errors.into_iter().map(From::from));
+    let is_slotted = sfc.slotted;
 
     // For scopes
     // TODO Research if it's better to compute that on the caller site or here
@@ -144,18 +199,62 @@ pub fn compile(source: &str, options: CompileOptions) -> Result<CompileResult, C
         format!("{:x}", num)
     };
 
+    // Styles post-processing
+    let css_targets = options.css_targets.as_deref().and_then(|query| {
+        parse_css_targets(query)
+            .map_err(|message| {
+                all_errors.push(CompileError::TransformError(TransformError::CssError(
+                    CssError {
+                        span: DUMMY_SP,
+                        kind: CssErrorKind::InvalidTargets(message),
+                    },
+                )))
+            })
+            .ok()
+    });
+    let css_output = CssOutputOptions {
+        minify: options.css_minify.unwrap_or_default(),
+        targets: css_targets,
+        source_map: options.source_map.unwrap_or(false),
+    };
+
     // Transform
+    let transform_asset_urls = options.transform_asset_urls.unwrap_or_default();
     let mut transform_errors = Vec::new();
     let transform_options = TransformSfcOptions {
         is_prod,
+        is_ssr,
+        hoist_static: options.hoist_static.unwrap_or(true),
         scope_id: &file_hash,
         filename: &options.filename,
+        source,
+        css_modules_pattern: options.css_modules_pattern.as_deref(),
+        css_modules_mode: options.css_modules_mode.unwrap_or_default(),
+        transform_asset_urls: Some(&transform_asset_urls),
+        style_preprocessor: options.style_preprocessor,
+        css_output,
+        type_resolve: TypeResolveOptions {
+            fs: options.type_resolve_fs,
+            cache: options.type_scope_cache,
+            global_type_files: options.global_type_files.as_deref().unwrap_or_default(),
+        },
     };
     let transform_result = transform_sfc(sfc, transform_options, &mut transform_errors);
     all_errors.extend(transform_errors.into_iter().map(From::from));
 
     // Codegen
     let mut ctx = CodegenContext::with_bindings_helper(transform_result.bindings_helper);
+    ctx.is_ssr = is_ssr;
+    ctx.ssr_css_vars = transform_result.ssr_css_vars;
+    ctx.hoist_static = !is_ssr;
+    ctx.cache_handlers = !is_ssr;
+
+    ctx.is_slotted = is_slotted;
+
+    // Scope is needed for slots. In SSR, it is also rendered into the HTML by the template itself
+    if transform_result.style_blocks.iter().any(|b| b.is_scoped) {
+        ctx.scope_id = Some(create_style_scope(&file_hash).into());
+    }
 
     let template_expr: Option<Expr> = transform_result
         .template_block
@@ -176,16 +275,21 @@ pub fn compile(source: &str, options: CompileOptions) -> Result<CompileResult, C
         FileName::Custom(options.filename.to_string()),
         options.source_map.unwrap_or(false),
         false,
+        Some(&ctx.comments),
     );
 
+    let deps = transform_result.deps;
+    let mut style_source_maps = transform_result.style_source_maps.into_iter();
+    let mut style_compiled = transform_result.style_compiled.into_iter();
     let styles = transform_result
         .style_blocks
         .into_iter()
         .map(|style_block| CompileEmittedStyle {
             code: style_block.content.to_string(),
-            is_compiled: should_transform_style_block(&style_block),
+            is_compiled: style_compiled.next().unwrap_or_default(),
             lang: style_block.lang.to_string(),
             is_scoped: style_block.is_scoped,
+            source_map: style_source_maps.next().flatten(),
         })
         .collect();
 
@@ -209,6 +313,7 @@ pub fn compile(source: &str, options: CompileOptions) -> Result<CompileResult, C
         styles,
         other_assets,
         source_map,
+        deps,
     })
 }
 
@@ -239,16 +344,33 @@ pub fn compile_sync_naive(source: &str, is_prod: bool) -> Result<String, String>
     };
 
     // Transform
+    let transform_asset_urls = AssetUrlOptions::default();
     let mut transform_errors = Vec::new();
     let tranform_options = TransformSfcOptions {
         is_prod,
+        is_ssr: false,
+        hoist_static: true,
         scope_id: &file_hash,
         filename: "anonymous.vue".into(),
+        source,
+        css_modules_pattern: None,
+        css_modules_mode: Default::default(),
+        transform_asset_urls: Some(&transform_asset_urls),
+        style_preprocessor: None,
+        css_output: Default::default(),
+        type_resolve: Default::default(),
     };
     let transform_result = transform_sfc(sfc, tranform_options, &mut transform_errors);
 
     // Codegen
     let mut ctx = CodegenContext::with_bindings_helper(transform_result.bindings_helper);
+    ctx.hoist_static = true;
+    ctx.cache_handlers = true;
+
+    // Scope is needed for slots and to not hoist the nodes which must receive it
+    if transform_result.style_blocks.iter().any(|b| b.is_scoped) {
+        ctx.scope_id = Some(create_style_scope(&file_hash).into());
+    }
 
     let template_expr: Option<Expr> = transform_result
         .template_block
@@ -262,8 +384,14 @@ pub fn compile_sync_naive(source: &str, is_prod: bool) -> Result<String, String>
         None,
     );
 
-    let (compiled_code, _map) =
-        CodegenContext::stringify(&source, &sfc_module, FileName::Anon, false, false);
+    let (compiled_code, _map) = CodegenContext::stringify(
+        &source,
+        &sfc_module,
+        FileName::Anon,
+        false,
+        false,
+        Some(&ctx.comments),
+    );
 
     Ok(compiled_code)
 }
@@ -304,6 +304,8 @@ fn parse_directive<'i>(
                     }
                 }),
                 modifiers,
+                key_modifiers: vec![],
+                is_handler_cacheable: false,
                 span: DUMMY_SP
             }));
         }
@@ -349,6 +351,7 @@ fn parse_directive<'i>(
                                 iterable,
                                 itervar,
                                 patch_flags: Default::default(),
+                                fragment_key: None,
                                 span: DUMMY_SP
                             }
                         );
@@ -371,6 +374,7 @@ fn parse_directive<'i>(
                         value: model_binding,
                         update_handler: None,
                         modifiers,
+                        is_handler_cacheable: false,
                         span: DUMMY_SP, // TODO
                     });
                 }
@@ -168,6 +168,7 @@ fn parse_root_block<'a>(
                         content: $content,
                         lang,
                         is_setup,
+                        generic: None,
                         span: DUMMY_SP,
                     });
                 } else {
@@ -175,6 +176,7 @@ fn parse_root_block<'a>(
                         content: $content,
                         lang,
                         is_setup,
+                        generic: None,
                         span: DUMMY_SP,
                     })
                 }
@@ -242,6 +244,7 @@ fn parse_root_block<'a>(
         content: content.into(),
         is_scoped,
         is_module,
+        module_name: None,
         span: DUMMY_SP
     });
 
@@ -64,6 +64,10 @@ impl CodegenContext {
         // Hints on what was processed and what to do next
         let mut result_hints = GenerateAttributesResultHints::default();
 
+        // Static event names with the index of their handler in `out`
+        // and whether the handlers were already merged into an array
+        let mut event_handlers: Vec<(&FervidAtom, usize, bool)> = Vec::new();
+
         for attribute in attributes {
             match attribute {
                 // First, we check the special case: `class` and `style` attributes
@@ -189,9 +193,10 @@ impl CodegenContext {
                     event: Some(event),
                     handler,
                     modifiers,
+                    key_modifiers,
+                    is_handler_cacheable,
                     span,
                 }) => {
-                    // TODO Use _cache
                     let span = *span;
 
                     // Transform or default to () => {}
@@ -201,54 +206,34 @@ impl CodegenContext {
                         .to_owned()
                         .unwrap_or_else(|| Box::new(empty_arrow_expr(span)));
 
-                    // To generate as an array of `["modifier1", "modifier2"]`
-                    let modifiers: Vec<Option<ExprOrSpread>> = modifiers
-                        .iter()
-                        .map(|modifier| {
-                            Some(ExprOrSpread {
-                                spread: None,
-                                expr: Box::from(Expr::Lit(Lit::Str(Str {
-                                    span,
-                                    value: modifier.to_owned(),
-                                    raw: None,
-                                }))),
-                            })
-                        })
-                        .collect();
-
-                    let handler_expr = if modifiers.len() != 0 {
-                        let with_modifiers_import =
-                            self.get_and_add_import_ident(VueImports::WithModifiers);
-
-                        // `_withModifiers(transformed, ["modifier"]))`
-                        Box::new(Expr::Call(CallExpr {
+                    // `_withModifiers(handler, ["stop"])`
+                    let mut handler_expr = if !modifiers.is_empty() {
+                        self.generate_modifiers_call(
+                            VueImports::WithModifiers,
+                            handler,
+                            modifiers,
                             span,
-                            callee: Callee::Expr(Box::from(Expr::Ident(Ident {
-                                span,
-                                sym: with_modifiers_import,
-                                optional: false,
-                            }))),
-                            args: vec![
-                                ExprOrSpread {
-                                    expr: handler,
-                                    spread: None,
-                                },
-                                ExprOrSpread {
-                                    expr: Box::from(Expr::Array(ArrayLit {
-                                        span,
-                                        elems: modifiers,
-                                    })),
-                                    spread: None,
-                                },
-                            ],
-                            type_args: None,
-                        }))
+                        )
                     } else {
                         // No modifiers, leave expression the same
                         handler
                     };
 
-                    // TODO Cache
+                    // `_withKeys(handler, ["enter"])`
+                    if !key_modifiers.is_empty() {
+                        handler_expr = self.generate_modifiers_call(
+                            VueImports::WithKeys,
+                            handler_expr,
+                            key_modifiers,
+                            span,
+                        );
+                    }
+
+                    // Cache the handler so that it is always the same function,
+                    // e.g. `_cache[0] || (_cache[0] = $event => foo($event))`
+                    if *is_handler_cacheable && self.can_cache_handlers() {
+                        handler_expr = self.generate_cached_handler(handler_expr);
+                    }
 
                     // TODO Dynamic events are hard, but similar to `v-on`
                     // IN:
@@ -268,6 +253,27 @@ impl CodegenContext {
 
                     match event {
                         StrOrExpr::Str(event_name_str) => {
+                            // Handlers of the same event are merged into an array, e.g.
+                            // `@keyup.enter="a" @keyup.esc="b"` becomes
+                            // `onKeyup: [_withKeys(a, ["enter"]), _withKeys(b, ["esc"])]`
+                            let existing = event_handlers
+                                .iter_mut()
+                                .find(|(name, _, _)| *name == event_name_str);
+                            if let Some((_, idx, is_array)) = existing {
+                                if let PropOrSpread::Prop(ref mut prop) = out[*idx] {
+                                    if let Prop::KeyValue(ref mut key_value) = **prop {
+                                        merge_handlers(
+                                            &mut key_value.value,
+                                            handler_expr,
+                                            *is_array,
+                                        );
+                                        *is_array = true;
+                                        continue;
+                                    }
+                                }
+                            }
+                            event_handlers.push((event_name_str, out.len(), false));
+
                             // e.g. `onClick: _ctx.handleClick` or `onClick: _withModifiers(() => {}, ["stop"])
                             out.push(PropOrSpread::Prop(Box::from(Prop::KeyValue(
                                 KeyValueProp {
@@ -277,7 +283,8 @@ impl CodegenContext {
                             ))));
                         }
 
-                        // TODO Instead of pushing to `out`, signify that `mergeProps` and `toHandlerKey` are needed
+                        // The event is already wrapped in `_toHandlerKey` by the transform
+                        // TODO Instead of pushing to `out`, signify that `mergeProps` is needed
                         StrOrExpr::Expr(event_name_expr) => {
                             out.push(PropOrSpread::Prop(Box::new(Prop::KeyValue(KeyValueProp {
                                 key: PropName::Computed(ComputedPropName {
@@ -518,6 +525,56 @@ impl CodegenContext {
 
         has_js_bindings
     }
+
+    /// Wraps a handler in a modifiers guard,
+    /// e.g. `_withModifiers(handler, ["stop"])` or `_withKeys(handler, ["enter"])`
+    fn generate_modifiers_call(
+        &mut self,
+        helper: VueImports,
+        handler: Box<Expr>,
+        modifiers: &[FervidAtom],
+        span: Span,
+    ) -> Box<Expr> {
+        let helper_ident = self.get_and_add_import_ident(helper);
+
+        // To generate as an array of `["modifier1", "modifier2"]`
+        let modifiers: Vec<Option<ExprOrSpread>> = modifiers
+            .iter()
+            .map(|modifier| {
+                Some(ExprOrSpread {
+                    spread: None,
+                    expr: Box::from(Expr::Lit(Lit::Str(Str {
+                        span,
+                        value: modifier.to_owned(),
+                        raw: None,
+                    }))),
+                })
+            })
+            .collect();
+
+        Box::new(Expr::Call(CallExpr {
+            span,
+            callee: Callee::Expr(Box::from(Expr::Ident(Ident {
+                span,
+                sym: helper_ident,
+                optional: false,
+            }))),
+            args: vec![
+                ExprOrSpread {
+                    expr: handler,
+                    spread: None,
+                },
+                ExprOrSpread {
+                    expr: Box::from(Expr::Array(ArrayLit {
+                        span,
+                        elems: modifiers,
+                    })),
+                    spread: None,
+                },
+            ],
+            type_args: None,
+        }))
+    }
 }
 
 fn generate_regular_style(style: &str, span: Span) -> ObjectLit {
@@ -556,6 +613,33 @@ fn generate_regular_style(style: &str, span: Span) -> ObjectLit {
 }
 
 /// Generates () => {}
+/// Adds a handler to the existing handler of the same event.
+/// When `is_array` is `false`, the existing handler is wrapped in an array first.
+fn merge_handlers(existing: &mut Box<Expr>, handler: Box<Expr>, is_array: bool) {
+    if !is_array {
+        let first = std::mem::replace(
+            existing,
+            Box::new(Expr::Array(ArrayLit {
+                span: DUMMY_SP,
+                elems: Vec::with_capacity(2),
+            })),
+        );
+        if let Expr::Array(ref mut array_lit) = **existing {
+            array_lit.elems.push(Some(ExprOrSpread {
+                spread: None,
+                expr: first,
+            }));
+        }
+    }
+
+    if let Expr::Array(ref mut array_lit) = **existing {
+        array_lit.elems.push(Some(ExprOrSpread {
+            spread: None,
+            expr: handler,
+        }));
+    }
+}
+
 fn empty_arrow_expr(span: Span) -> Expr {
     Expr::Arrow(ArrowExpr {
         span,
@@ -573,7 +657,7 @@ fn empty_arrow_expr(span: Span) -> Expr {
 
 #[cfg(test)]
 mod tests {
-    use fervid_core::{AttributeOrBinding, VOnDirective};
+    use fervid_core::{AttributeOrBinding, FervidAtom, VOnDirective};
     use swc_core::{common::DUMMY_SP, ecma::ast::ObjectLit};
 
     use crate::{
@@ -674,6 +758,8 @@ mod tests {
                 event: Some("onClick".into()),
                 handler: None,
                 modifiers: vec![],
+                key_modifiers: vec![],
+                is_handler_cacheable: false,
                 span: DUMMY_SP,
             })],
             r"{onClick:()=>{}}",
@@ -685,6 +771,8 @@ mod tests {
                 event: Some("onMultiWordEvent".into()),
                 handler: None,
                 modifiers: vec![],
+                key_modifiers: vec![],
+                is_handler_cacheable: false,
                 span: DUMMY_SP,
             })],
             r"{onMultiWordEvent:()=>{}}",
@@ -730,6 +818,8 @@ mod tests {
                 event: Some("onClick".into()),
                 handler: None,
                 modifiers: vec!["stop".into(), "prevent".into(), "self".into()],
+                key_modifiers: vec![],
+                is_handler_cacheable: false,
                 span: DUMMY_SP,
             })],
             r#"{onClick:_withModifiers(()=>{},["stop","prevent","self"])}"#,
@@ -741,12 +831,126 @@ mod tests {
                 event: Some("onClick".into()),
                 handler: Some(js("$event => handleClick($event, foo, bar)")),
                 modifiers: vec!["stop".into()],
+                key_modifiers: vec![],
+                is_handler_cacheable: false,
                 span: DUMMY_SP,
             })],
             r#"{onClick:_withModifiers($event=>handleClick($event,foo,bar),["stop"])}"#,
         );
     }
 
+    #[test]
+    fn it_generates_v_on_key_modifiers() {
+        // @keyup.enter="submit"
+        test_out(
+            vec![AttributeOrBinding::VOn(VOnDirective {
+                event: Some("onKeyup".into()),
+                handler: Some(js("submit")),
+                modifiers: vec![],
+                key_modifiers: vec!["enter".into()],
+                is_handler_cacheable: false,
+                span: DUMMY_SP,
+            })],
+            r#"{onKeyup:_withKeys(submit,["enter"])}"#,
+        );
+
+        // @keyup.ctrl.s="save"
+        test_out(
+            vec![AttributeOrBinding::VOn(VOnDirective {
+                event: Some("onKeyup".into()),
+                handler: Some(js("save")),
+                modifiers: vec!["ctrl".into()],
+                key_modifiers: vec!["s".into()],
+                is_handler_cacheable: false,
+                span: DUMMY_SP,
+            })],
+            r#"{onKeyup:_withKeys(_withModifiers(save,["ctrl"]),["s"])}"#,
+        );
+    }
+
+    #[test]
+    fn it_merges_v_on_of_the_same_event() {
+        let key_handler = |handler: &str, key: &str| {
+            AttributeOrBinding::VOn(VOnDirective {
+                event: Some("onKeyup".into()),
+                handler: Some(js(handler)),
+                modifiers: vec![],
+                key_modifiers: vec![key.into()],
+                is_handler_cacheable: false,
+                span: DUMMY_SP,
+            })
+        };
+
+        // @keyup.enter="a" @keyup.esc="b"
+        test_out(
+            vec![key_handler("a", "enter"), key_handler("b", "esc")],
+            r#"{onKeyup:[_withKeys(a,["enter"]),_withKeys(b,["esc"])]}"#,
+        );
+
+        // @keyup.enter="a" @click="c" @keyup.esc="b" @keyup.tab="d"
+        test_out(
+            vec![
+                key_handler("a", "enter"),
+                v_on_attribute("onClick", "c"),
+                key_handler("b", "esc"),
+                key_handler("d", "tab"),
+            ],
+            r#"{onKeyup:[_withKeys(a,["enter"]),_withKeys(b,["esc"]),_withKeys(d,["tab"])],onClick:c}"#,
+        );
+    }
+
+    #[test]
+    fn it_caches_v_on() {
+        let cacheable = |handler: Option<&str>, modifiers: Vec<FervidAtom>| {
+            AttributeOrBinding::VOn(VOnDirective {
+                event: Some("onClick".into()),
+                handler: handler.map(js),
+                modifiers,
+                key_modifiers: vec![],
+                is_handler_cacheable: true,
+                span: DUMMY_SP,
+            })
+        };
+
+        let mut ctx = CodegenContext {
+            cache_handlers: true,
+            ..Default::default()
+        };
+        let mut out = ObjectLit {
+            span: DUMMY_SP,
+            props: vec![],
+        };
+        ctx.generate_attributes(
+            &[
+                // @click
+                cacheable(None, vec![]),
+                // @click.stop="$event => handleClick($event, foo)"
+                cacheable(
+                    Some("$event => handleClick($event, foo)"),
+                    vec!["stop".into()],
+                ),
+                // @click="handleClick" (not cacheable)
+                v_on_attribute("onClick", "handleClick"),
+            ],
+            &mut out.props,
+        );
+        assert_eq!(
+            crate::test_utils::to_str(out),
+            r#"{onClick:[_cache[0]||(_cache[0]=()=>{}),_cache[1]||(_cache[1]=_withModifiers($event=>handleClick($event,foo),["stop"])),handleClick]}"#
+        );
+        assert_eq!(ctx.next_cache_index, 2);
+
+        // Nothing is cached inside `v-once`
+        ctx.is_cache_disabled = true;
+        let mut out = ObjectLit {
+            span: DUMMY_SP,
+            props: vec![],
+        };
+        ctx.generate_attributes(&[cacheable(None, vec![])], &mut out.props);
+        assert_eq!(crate::test_utils::to_str(out), r"{onClick:()=>{}}");
+        assert_eq!(ctx.next_cache_index, 2);
+    }
+
     fn test_out(input: Vec<AttributeOrBinding>, expected: &str) {
         let mut ctx = CodegenContext::default();
         let mut out = ObjectLit {
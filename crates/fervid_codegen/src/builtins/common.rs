@@ -1,7 +1,7 @@
-use fervid_core::{fervid_atom, AttributeOrBinding, ElementNode};
+use fervid_core::{AttributeOrBinding, ElementNode};
 use swc_core::{
-    common::{Span, DUMMY_SP},
-    ecma::ast::{Expr, Ident, KeyValueProp, Lit, Number, ObjectLit, Prop, PropName, PropOrSpread},
+    common::Span,
+    ecma::ast::{Expr, ObjectLit},
 };
 
 use crate::CodegenContext;
@@ -23,26 +23,7 @@ impl CodegenContext {
     }
 
     /// Generates the slots expression for builtins.
-    ///
-    /// Additionally adds `_: 1` to the slots object.
     pub(crate) fn generate_builtin_slots(&mut self, element_node: &ElementNode) -> Option<Expr> {
-        let mut slots = self.generate_component_children(element_node);
-        if let Some(Expr::Object(ref mut obj)) = slots {
-            obj.props
-                .push(PropOrSpread::Prop(Box::new(Prop::KeyValue(KeyValueProp {
-                    key: PropName::Ident(Ident {
-                        span: DUMMY_SP,
-                        sym: fervid_atom!("_"),
-                        optional: false,
-                    }),
-                    value: Box::new(Expr::Lit(Lit::Num(Number {
-                        span: DUMMY_SP,
-                        value: 1.0,
-                        raw: None,
-                    }))),
-                }))));
-        }
-
-        slots
+        self.generate_component_children(element_node)
     }
 }
@@ -1,7 +1,7 @@
 use fervid_core::{check_attribute_name, fervid_atom, AttributeOrBinding, ElementNode, VueImports};
 use swc_core::ecma::ast::{
-    ArrayLit, CallExpr, Callee, Expr, ExprOrSpread, Ident, Lit, MemberExpr, MemberProp, ObjectLit,
-    Str,
+    ArrayLit, Bool, CallExpr, Callee, Expr, ExprOrSpread, Ident, Lit, MemberExpr, MemberProp,
+    ObjectLit, Str,
 };
 
 use crate::CodegenContext;
@@ -13,6 +13,9 @@ impl CodegenContext {
     /// ```js
     /// renderSlot(_ctx.$slots, "slot-name", /*optional*/ { slot: attributes }, /*optional*/ [slot, children])
     /// ```
+    ///
+    /// When the component has scoped styles without `:slotted()`,
+    /// `noSlotted` is passed as the fifth argument: `renderSlot(_ctx.$slots, "default", {}, undefined, true)`
     pub fn generate_slot(&mut self, element_node: &ElementNode) -> Expr {
         let span = element_node.span;
 
@@ -32,7 +35,12 @@ impl CodegenContext {
         let has_attributes =
             element_node.starting_tag.attributes.len() > idx_of_name.map_or(0, |_| 1);
 
-        let render_slot_args_len = if has_children {
+        // Slot content does not need the slotted scope id when `:slotted()` is not used
+        let no_slotted = self.scope_id.is_some() && !self.is_slotted;
+
+        let render_slot_args_len = if no_slotted {
+            5
+        } else if has_children {
             4
         } else if has_attributes {
             3
@@ -118,7 +126,7 @@ impl CodegenContext {
                 spread: None,
                 expr: Box::new(Expr::Object(attrs_obj)),
             });
-        } else if has_children {
+        } else if has_children || no_slotted {
             // Pushes `{}` as third argument
             render_slot_args.push(ExprOrSpread {
                 spread: None,
@@ -150,6 +158,24 @@ impl CodegenContext {
                     elems: slot_children,
                 })),
             });
+        } else if no_slotted {
+            // Pushes `undefined` as fourth argument
+            render_slot_args.push(ExprOrSpread {
+                spread: None,
+                expr: Box::new(Expr::Ident(Ident {
+                    span,
+                    sym: fervid_atom!("undefined"),
+                    optional: false,
+                })),
+            });
+        }
+
+        // Fifth arg (optional): `noSlotted`
+        if no_slotted {
+            render_slot_args.push(ExprOrSpread {
+                spread: None,
+                expr: Box::new(Expr::Lit(Lit::Bool(Bool { span, value: true }))),
+            });
         }
 
         // `renderSlot(_ctx.$slots, "slot-name", { slot: attributes }, [slot, children])`
@@ -363,6 +389,29 @@ mod tests {
         );
     }
 
+    #[test]
+    fn it_generates_no_slotted_for_scoped_styles() {
+        let mut ctx = CodegenContext {
+            scope_id: Some(fervid_atom!("data-v-abcd1234")),
+            ..Default::default()
+        };
+
+        // <slot />
+        let out = ctx.generate_slot(&slot!(vec![], vec![]));
+        assert_eq!(
+            crate::test_utils::to_str(out),
+            r#"_renderSlot(_ctx.$slots,"default",{},undefined,true)"#
+        );
+
+        // <slot foo="bar" />, but `:slotted()` is used
+        ctx.is_slotted = true;
+        let out = ctx.generate_slot(&slot!(vec![regular_attribute("foo", "bar")], vec![]));
+        assert_eq!(
+            crate::test_utils::to_str(out),
+            r#"_renderSlot(_ctx.$slots,"default",{foo:"bar"})"#
+        );
+    }
+
     fn test_out(input: ElementNode, expected: &str) {
         let mut ctx = CodegenContext::default();
         let out = ctx.generate_slot(&input);
@@ -4,7 +4,7 @@ use swc_core::ecma::ast::{Expr, Ident};
 use crate::CodegenContext;
 
 impl CodegenContext {
-    pub fn generate_transition(&mut self, element_node: &ElementNode) -> Expr {
+    pub fn generate_transition(&mut self, element_node: &ElementNode, wrap_in_block: bool) -> Expr {
         let span = element_node.span;
 
         // _Transition
@@ -24,7 +24,7 @@ impl CodegenContext {
             transition_attrs,
             transition_slots,
             &element_node.patch_hints,
-            false,
+            wrap_in_block,
             span,
         )
     }
@@ -127,7 +127,7 @@ mod tests {
 
     fn test_out(input: ElementNode, expected: &str) {
         let mut ctx = CodegenContext::default();
-        let out = ctx.generate_transition(&input);
+        let out = ctx.generate_transition(&input, false);
         assert_eq!(crate::test_utils::to_str(out), expected)
     }
 }
@@ -4,7 +4,11 @@ use swc_core::ecma::ast::{Expr, Ident};
 use crate::CodegenContext;
 
 impl CodegenContext {
-    pub fn generate_transition_group(&mut self, element_node: &ElementNode) -> Expr {
+    pub fn generate_transition_group(
+        &mut self,
+        element_node: &ElementNode,
+        wrap_in_block: bool,
+    ) -> Expr {
         let span = element_node.span;
 
         // _TransitionGroup
@@ -24,7 +28,7 @@ impl CodegenContext {
             transition_group_attrs,
             transition_group_slots,
             &element_node.patch_hints,
-            false,
+            wrap_in_block,
             span,
         )
     }
@@ -127,7 +131,7 @@ mod tests {
 
     fn test_out(input: ElementNode, expected: &str) {
         let mut ctx = CodegenContext::default();
-        let out = ctx.generate_transition_group(&input);
+        let out = ctx.generate_transition_group(&input, false);
         assert_eq!(crate::test_utils::to_str(out), expected)
     }
 }
@@ -1,13 +1,13 @@
 use fervid_core::{
-    fervid_atom, ComponentBinding, ElementNode, FervidAtom, Node, PatchHints, StartingTag,
-    StrOrExpr, VSlotDirective, VueDirectives, VueImports,
+    fervid_atom, BuiltinType, ComponentBinding, ConditionalNodeSequence, ElementKind, ElementNode,
+    FervidAtom, Node, PatchFlags, PatchHints, StartingTag, StrOrExpr, VSlotDirective, VueImports,
 };
 use swc_core::{
-    common::{Span, DUMMY_SP},
+    common::{Span, Spanned, DUMMY_SP},
     ecma::ast::{
-        ArrayLit, ArrowExpr, BindingIdent, BlockStmtOrExpr, CallExpr, Callee, Expr, ExprOrSpread,
-        Ident, KeyValueProp, Lit, Null, Number, ObjectLit, Pat, Prop, PropOrSpread, Str,
-        VarDeclarator,
+        ArrayLit, ArrowExpr, BindingIdent, BlockStmt, BlockStmtOrExpr, CallExpr, Callee, CondExpr,
+        Expr, ExprOrSpread, Ident, KeyValueProp, Lit, Null, Number, ObjectLit, Pat, Prop, PropName,
+        PropOrSpread, ReturnStmt, Stmt, Str, VarDeclarator,
     },
 };
 
@@ -243,7 +243,7 @@ impl CodegenContext {
         result
     }
 
-    fn generate_component_attributes<'e>(&mut self, component_node: &'e ElementNode) -> ObjectLit {
+    pub(crate) fn generate_component_attributes<'e>(&mut self, component_node: &'e ElementNode) -> ObjectLit {
         let mut result_props = Vec::new();
 
         self.generate_attributes(&component_node.starting_tag.attributes, &mut result_props);
@@ -282,6 +282,7 @@ impl CodegenContext {
         component_node: &ElementNode,
     ) -> Option<Expr> {
         let mut result_static_slots = Vec::new();
+        let mut result_dynamic_slots = Vec::new();
         let total_children = component_node.children.len();
 
         // No children work, return immediately
@@ -318,6 +319,9 @@ impl CodegenContext {
         let mut has_encountered_default_slot = false;
         // let mut default_slot_is_not_template = false;
 
+        // Keys of the `<template v-if v-slot>` branches
+        let mut conditional_branch_key = 0;
+
         // Generate the default slot items into the `default_slot_children` vec,
         // and named slots into the `result` vec.
         while slotted_iterator.has_more() {
@@ -380,12 +384,7 @@ impl CodegenContext {
 
                 // At this point, we have `<template v-slot="maybeSomeBinding">`
                 // We need to generate it as if it was a named slot
-                self.generate_named_slot(
-                    v_slot_directive,
-                    &children,
-                    &directives,
-                    &mut result_static_slots,
-                );
+                self.generate_named_slot(v_slot_directive, children, &mut result_static_slots);
 
                 // Advance the iterator forward
                 slotted_iterator.next();
@@ -396,26 +395,42 @@ impl CodegenContext {
                     continue;
                 };
 
-                let Node::Element(slotted_node) = slotted_node else {
-                    unreachable!("Only element node can be slotted")
-                };
-
-                // Get `v-slot`
-                let Some(ref directives) = slotted_node.starting_tag.directives else {
-                    unreachable!("Slotted node should have a v-slot directive");
-                };
-                let Some(ref v_slot_directive) = directives.v_slot else {
-                    unreachable!("Slotted node should have a v-slot directive");
-                };
+                match slotted_node {
+                    // `<template v-if v-slot:named>` is added with `createSlots`
+                    Node::ConditionalSeq(conditional_seq) => {
+                        let conditional_slot = self.generate_conditional_slot(
+                            conditional_seq,
+                            &mut conditional_branch_key,
+                        );
+                        result_dynamic_slots.push(conditional_slot);
+                    }
 
-                // TODO Components with v-slot are not supported yet?..
+                    Node::Element(slotted_node) => {
+                        // Get `v-slot`
+                        let Some(ref directives) = slotted_node.starting_tag.directives else {
+                            unreachable!("Slotted node should have a v-slot directive");
+                        };
+                        let Some(ref v_slot_directive) = directives.v_slot else {
+                            unreachable!("Slotted node should have a v-slot directive");
+                        };
+
+                        // TODO Components with v-slot are not supported yet?..
+
+                        // `<template v-for v-slot:named>` is added with `createSlots` as well
+                        if directives.v_for.is_some() {
+                            let dynamic_slot = self.generate_dynamic_slot(slotted_node, None);
+                            result_dynamic_slots.push(dynamic_slot);
+                        } else {
+                            self.generate_named_slot(
+                                v_slot_directive,
+                                &slotted_node.children,
+                                &mut result_static_slots,
+                            );
+                        }
+                    }
 
-                self.generate_named_slot(
-                    v_slot_directive,
-                    &slotted_node.children,
-                    &directives,
-                    &mut result_static_slots,
-                );
+                    _ => unreachable!("Only element node can be slotted"),
+                }
             }
 
             slotted_iterator.toggle_mode();
@@ -438,10 +453,72 @@ impl CodegenContext {
             ));
         }
 
-        // TODO Add `createSlots` if needed
-        Some(Expr::Object(ObjectLit {
+        // Slots flag, `_: 1` is STABLE, `_: 2` is DYNAMIC and `_: 3` is FORWARDED
+        // https://github.com/vuejs/core/blob/v3.4.21/packages/compiler-core/src/transforms/vSlot.ts#L345
+        let slot_flag = if !result_dynamic_slots.is_empty()
+            || component_node
+                .patch_hints
+                .flags
+                .contains(PatchFlags::DynamicSlots)
+        {
+            2
+        } else if has_forwarded_slots(&component_node.children) {
+            3
+        } else {
+            1
+        };
+        result_static_slots.push(PropOrSpread::Prop(Box::new(Prop::KeyValue(KeyValueProp {
+            key: PropName::Ident(Ident {
+                span: component_span,
+                sym: fervid_atom!("_"),
+                optional: false,
+            }),
+            value: Box::new(Expr::Lit(Lit::Num(Number {
+                span: component_span,
+                value: slot_flag.into(),
+                raw: None,
+            }))),
+        }))));
+
+        let static_slots = Expr::Object(ObjectLit {
             span: component_span,
             props: result_static_slots,
+        });
+
+        if result_dynamic_slots.is_empty() {
+            return Some(static_slots);
+        }
+
+        // `_createSlots({ static: _withCtx(...), _: 2 }, [dynamic, slots])`
+        Some(Expr::Call(CallExpr {
+            span: component_span,
+            callee: Callee::Expr(Box::new(Expr::Ident(Ident {
+                span: component_span,
+                sym: self.get_and_add_import_ident(VueImports::CreateSlots),
+                optional: false,
+            }))),
+            args: vec![
+                ExprOrSpread {
+                    spread: None,
+                    expr: Box::new(static_slots),
+                },
+                ExprOrSpread {
+                    spread: None,
+                    expr: Box::new(Expr::Array(ArrayLit {
+                        span: component_span,
+                        elems: result_dynamic_slots
+                            .into_iter()
+                            .map(|expr| {
+                                Some(ExprOrSpread {
+                                    spread: None,
+                                    expr: Box::new(expr),
+                                })
+                            })
+                            .collect(),
+                    })),
+                },
+            ],
+            type_args: None,
         }))
     }
 
@@ -451,65 +528,188 @@ impl CodegenContext {
         &mut self,
         v_slot: &VSlotDirective,
         slot_children: &[Node],
-        directives: &VueDirectives,
         out_static_slots: &mut Vec<PropOrSpread>,
     ) {
-        // Extra logic is needed if this is more than just `<template v-slot>`
-        let is_complex = directives.v_if.is_some()
-            || directives.v_else_if.is_some()
-            || directives.v_else.is_some()
-            || directives.v_for.is_some();
-
-        if is_complex {
-            todo!("createSlots is not supported yet");
-            // https://play.vuejs.org/#eNqVVNtuozAQ/ZUpWolGKo2y+xaRqFVfdr9gH0qlOngIVo2NbEMbRfz7js0l0G33okTgmfGcMzfmHN3X9W3bYLSNUpsbUTuw6JoaJFPHXRY5m0X7TImq1sbBGQwW0EFhdAUxucWZylSulXVQ2SPsvP06/o5SavipjeRX8SpT6bqHJiASBpolQY/xWjKHLRoCiktB4PgWeDkWrJHEnykAzhy7XvVnIELXGDVK4MOwW+hDIACv6vyLHvRfRuKwqiUxkgSQctFCm1RYoQ0KyFxabvbnc8CErgM6ThF2Xboma8+QClU3zntrjpKyIo+QVjDypqpOSU5mrchGmWYRCQehOIkH3SjuNXe5FPkLab4QgXKw20PJFJf44PXXvXYVYDPnf+lVksC9lOBKBE0PA1wYzJ1o0QIzCOKotEEOooDnNhHFMwgLhSe8hR/8xeuFiy3k2ng/SJIhZop6LA5FaqV226EH0wWAUkyX14tSfuj/yE+KVSJ/miH4aoLSr3/A+RSPGmXpLAo/RKZBKsy/hBH6SwJKi72zAKFgM7XL5/UbULoemjj1NAzLjLwfHU3y49MoeVKt8v7lp8JzB6PDN0cKqmAE6zCMAbXcUHc8xoEZslxqNt69m2vm8tdBMTCNY7qEr2HLhWUHiX7wiGmWdGprpvalODH6RvzxUg0/XwZHFOhHpiLFskM+5qFY9eA98bZJoc1Y628hznnjLlxLBCq7aOk060d0Ez3o6v3OWi4TCnHcVzS1QqH30Mp/VO9310cLhgqKtELKsEJu+rz/unTI4f/WzbRa5qvk00Uyr0D3Cy9D1W8=
-            // idk if that should be wrapped in block or not
-            // _createSlots({
-            //     default: _withCtx(() => [
-            //       _createTextVNode(" hi ")
-            //     ]),
-            //     _: 2 /* DYNAMIC */
-            //   }, [
-            //     _renderList(1, (i) => {
-            //       return {
-            //         name: "memes",
-            //         fn: _withCtx(() => [
-            //           _createTextVNode(" hi")
-            //         ])
-            //       }
-            //     })
-            //   ]), 1040 /* FULL_PROPS, DYNAMIC_SLOTS */)
-            // let generated = self.generate_node(slotted_node, false);
+        let slot_name = v_slot
+            .slot_name
+            .to_owned()
+            .unwrap_or_else(|| StrOrExpr::Str(fervid_atom!("default")));
+        let span = DUMMY_SP; // todo?
+
+        let slot_fn = self.generate_slot_fn(slot_children, v_slot.value.as_deref(), span);
+
+        out_static_slots.push(PropOrSpread::Prop(Box::new(Prop::KeyValue(KeyValueProp {
+            key: str_or_expr_to_propname(slot_name, span),
+            value: Box::new(slot_fn),
+        }))));
+    }
+
+    /// Generates a ternary of the dynamic slots for `<template v-if v-slot:named>`.
+    ///
+    /// ## Example
+    /// IN:
+    /// ```html
+    /// <template v-if="foo" #foo>foo</template>
+    /// <template v-else-if="bar" #bar>bar</template>
+    /// ```
+    ///
+    /// OUT:
+    /// ```js
+    /// foo ? {
+    ///   name: "foo",
+    ///   fn: _withCtx(() => [_createTextVNode("foo")]),
+    ///   key: "0"
+    /// } : bar ? {
+    ///   name: "bar",
+    ///   fn: _withCtx(() => [_createTextVNode("bar")]),
+    ///   key: "1"
+    /// } : undefined
+    /// ```
+    fn generate_conditional_slot(
+        &mut self,
+        conditional_seq: &ConditionalNodeSequence,
+        next_key: &mut u32,
+    ) -> Expr {
+        // Branches are generated in the source order so that the keys are sequential
+        let mut branches = Vec::with_capacity(conditional_seq.else_if_nodes.len() + 1);
+        for conditional in std::iter::once(conditional_seq.if_node.as_ref())
+            .chain(conditional_seq.else_if_nodes.iter())
+        {
+            let branch = self.generate_dynamic_slot(&conditional.node, Some(*next_key));
+            branches.push((&conditional.condition, branch));
+            *next_key += 1;
+        }
+
+        // Either the `v-else` slot or `undefined`
+        let mut result = if let Some(ref else_node) = conditional_seq.else_node {
+            let branch = self.generate_dynamic_slot(else_node, Some(*next_key));
+            *next_key += 1;
+            branch
         } else {
-            // Generate the children of the `<template v-slot>`
-            let total_children = slot_children.len();
-            let mut slotted_children_results = Vec::with_capacity(total_children);
-            let mut slotted_children_iter = slot_children.iter();
-
-            self.generate_node_sequence(
-                &mut slotted_children_iter,
-                &mut slotted_children_results,
-                total_children,
-                false,
-            );
-
-            let slot_name = v_slot
-                .slot_name
-                .to_owned()
-                .unwrap_or_else(|| StrOrExpr::Str(fervid_atom!("default")));
-            let span = DUMMY_SP; // todo?
-
-            out_static_slots.push(self.generate_slot_shell(
-                slot_name,
-                slotted_children_results,
-                v_slot.value.as_deref(),
+            Expr::Ident(Ident {
+                span: DUMMY_SP,
+                sym: fervid_atom!("undefined"),
+                optional: false,
+            })
+        };
+
+        // Combine the branches into one ternary, starting from the last one
+        for (condition, branch) in branches.into_iter().rev() {
+            result = Expr::Cond(CondExpr {
+                span: condition.span(),
+                test: Box::new(condition.to_owned()),
+                cons: Box::new(branch),
+                alt: Box::new(result),
+            });
+        }
+
+        result
+    }
+
+    /// Generates a dynamic slot descriptor for `createSlots`.
+    ///
+    /// - `{ name: "foo", fn: _withCtx(() => [/*...*/]), key: "0" }` for a `v-if` branch;
+    /// - `_renderList(list, (item) => { return { name: item, fn: _withCtx(() => [/*...*/]) } })`
+    ///   for a `<template v-for v-slot>`.
+    fn generate_dynamic_slot(&mut self, slot_node: &ElementNode, key: Option<u32>) -> Expr {
+        let span = slot_node.span;
+        let directives = slot_node.starting_tag.directives.as_deref();
+        let v_slot = directives.and_then(|d| d.v_slot.as_ref());
+
+        // Slot name is either a string or a dynamic expression
+        let slot_name = match v_slot.and_then(|v_slot| v_slot.slot_name.as_ref()) {
+            Some(StrOrExpr::Str(name)) => Expr::Lit(Lit::Str(Str {
+                span,
+                value: name.to_owned(),
+                raw: None,
+            })),
+            Some(StrOrExpr::Expr(expr)) => (**expr).to_owned(),
+            None => Expr::Lit(Lit::Str(Str {
                 span,
+                value: fervid_atom!("default"),
+                raw: None,
+            })),
+        };
+
+        let slot_fn = self.generate_slot_fn(
+            &slot_node.children,
+            v_slot.and_then(|v_slot| v_slot.value.as_deref()),
+            span,
+        );
+
+        macro_rules! prop {
+            ($name: literal, $value: expr) => {
+                PropOrSpread::Prop(Box::new(Prop::KeyValue(KeyValueProp {
+                    key: PropName::Ident(Ident {
+                        span,
+                        sym: fervid_atom!($name),
+                        optional: false,
+                    }),
+                    value: Box::new($value),
+                })))
+            };
+        }
+
+        // { name: "foo", fn: _withCtx(() => [/*...*/]) }
+        let mut props = Vec::with_capacity(3);
+        props.push(prop!("name", slot_name));
+        props.push(prop!("fn", slot_fn));
+        if let Some(key) = key {
+            props.push(prop!(
+                "key",
+                Expr::Lit(Lit::Str(Str {
+                    span,
+                    value: FervidAtom::from(key.to_string()),
+                    raw: None,
+                }))
             ));
         }
+        let slot_object = Expr::Object(ObjectLit { span, props });
+
+        let Some(v_for) = directives.and_then(|d| d.v_for.as_ref()) else {
+            return slot_object;
+        };
+
+        // _renderList(list, (item) => { return { name, fn } })
+        let render_list_arrow = Expr::Arrow(ArrowExpr {
+            span,
+            params: vec![Pat::Expr(v_for.itervar.to_owned())],
+            body: Box::new(BlockStmtOrExpr::BlockStmt(BlockStmt {
+                span,
+                stmts: vec![Stmt::Return(ReturnStmt {
+                    span,
+                    arg: Some(Box::new(slot_object)),
+                })],
+            })),
+            is_async: false,
+            is_generator: false,
+            type_params: None,
+            return_type: None,
+        });
+
+        Expr::Call(CallExpr {
+            span,
+            callee: Callee::Expr(Box::new(Expr::Ident(Ident {
+                span,
+                sym: self.get_and_add_import_ident(VueImports::RenderList),
+                optional: false,
+            }))),
+            args: vec![
+                ExprOrSpread {
+                    spread: None,
+                    expr: v_for.iterable.to_owned(),
+                },
+                ExprOrSpread {
+                    spread: None,
+                    expr: Box::new(render_list_arrow),
+                },
+            ],
+            type_args: None,
+        })
     }
 
     /// Creates the SWC identifier from a tag name. Will fetch from cache if present
-    fn get_component_identifier(&mut self, tag_name: &FervidAtom, span: Span) -> Expr {
+    pub(crate) fn get_component_identifier(&mut self, tag_name: &FervidAtom, span: Span) -> Expr {
         // Cached
         let existing_component_binding = self.bindings_helper.components.get(tag_name);
         match existing_component_binding {
@@ -573,6 +773,40 @@ impl CodegenContext {
         slot_binding: Option<&Pat>,
         span: Span,
     ) -> PropOrSpread {
+        PropOrSpread::Prop(Box::new(Prop::KeyValue(KeyValueProp {
+            key: str_or_expr_to_propname(slot_name, span),
+            value: Box::new(self.generate_with_ctx(slot_children, slot_binding, span)),
+        })))
+    }
+
+    /// Generates the children of a slot and wraps them in `withCtx`
+    fn generate_slot_fn(
+        &mut self,
+        slot_children: &[Node],
+        slot_binding: Option<&Pat>,
+        span: Span,
+    ) -> Expr {
+        let total_children = slot_children.len();
+        let mut slotted_children_results = Vec::with_capacity(total_children);
+        let mut slotted_children_iter = slot_children.iter();
+
+        self.generate_node_sequence(
+            &mut slotted_children_iter,
+            &mut slotted_children_results,
+            total_children,
+            false,
+        );
+
+        self.generate_with_ctx(slotted_children_results, slot_binding, span)
+    }
+
+    /// Generates `withCtx((_maybeCtx_) => [slot, children])`
+    fn generate_with_ctx(
+        &mut self,
+        slot_children: Vec<Expr>,
+        slot_binding: Option<&Pat>,
+        span: Span,
+    ) -> Expr {
         // e.g. child1, child2, child3
         let children_elems = slot_children
             .into_iter()
@@ -598,33 +832,55 @@ impl CodegenContext {
             Vec::new()
         };
 
-        PropOrSpread::Prop(Box::new(Prop::KeyValue(KeyValueProp {
-            key: str_or_expr_to_propname(slot_name, span),
-            value: Box::new(Expr::Call(CallExpr {
+        Expr::Call(CallExpr {
+            span,
+            // withCtx
+            callee: Callee::Expr(Box::new(Expr::Ident(Ident {
                 span,
-                // withCtx
-                callee: Callee::Expr(Box::new(Expr::Ident(Ident {
+                sym: self.get_and_add_import_ident(VueImports::WithCtx),
+                optional: false,
+            }))),
+            args: vec![ExprOrSpread {
+                spread: None,
+                // () => [child1, child2, child3]
+                expr: Box::new(Expr::Arrow(ArrowExpr {
                     span,
-                    sym: self.get_and_add_import_ident(VueImports::WithCtx),
-                    optional: false,
-                }))),
-                args: vec![ExprOrSpread {
-                    spread: None,
-                    // () => [child1, child2, child3]
-                    expr: Box::new(Expr::Arrow(ArrowExpr {
-                        span,
-                        params,
-                        body: Box::new(BlockStmtOrExpr::Expr(Box::new(Expr::Array(children_arr)))),
-                        is_async: false,
-                        is_generator: false,
-                        type_params: None,
-                        return_type: None,
-                    })),
-                }],
-                type_args: None,
-            })),
-        })))
+                    params,
+                    body: Box::new(BlockStmtOrExpr::Expr(Box::new(Expr::Array(children_arr)))),
+                    is_async: false,
+                    is_generator: false,
+                    type_params: None,
+                    return_type: None,
+                })),
+            }],
+            type_args: None,
+        })
+    }
+}
+
+/// Checks if the slot content renders a `<slot>` of the current component,
+/// i.e. the slots are forwarded from the parent
+fn has_forwarded_slots(children: &[Node]) -> bool {
+    fn is_forwarding(element_node: &ElementNode) -> bool {
+        matches!(element_node.kind, ElementKind::Builtin(BuiltinType::Slot))
+            || has_forwarded_slots(&element_node.children)
     }
+
+    children.iter().any(|child| match child {
+        Node::Element(element_node) => is_forwarding(element_node),
+        Node::ConditionalSeq(conditional_seq) => {
+            is_forwarding(&conditional_seq.if_node.node)
+                || conditional_seq
+                    .else_if_nodes
+                    .iter()
+                    .any(|conditional| is_forwarding(&conditional.node))
+                || conditional_seq
+                    .else_node
+                    .as_deref()
+                    .map_or(false, is_forwarding)
+        }
+        _ => false,
+    })
 }
 
 #[inline]
@@ -634,7 +890,7 @@ fn null(span: Span) -> Box<Expr> {
 
 #[cfg(test)]
 mod tests {
-    use fervid_core::{ElementKind, Interpolation, Node, StartingTag};
+    use fervid_core::{Conditional, Interpolation, StartingTag, VForDirective, VueDirectives};
 
     use crate::test_utils::{js, regular_attribute, v_bind_attribute};
 
@@ -733,7 +989,7 @@ mod tests {
                 patch_hints: Default::default(),
                 span: DUMMY_SP,
             },
-            r#"_createVNode(_component_test_component,null,{"default":_withCtx(()=>[_createTextVNode("hello from component"),_createElementVNode("div",null,"hello from div")])})"#,
+            r#"_createVNode(_component_test_component,null,{"default":_withCtx(()=>[_createTextVNode("hello from component"),_createElementVNode("div",null,"hello from div")]),_:1})"#,
             false,
         );
 
@@ -784,7 +1040,7 @@ mod tests {
                 patch_hints: Default::default(),
                 span: DUMMY_SP,
             },
-            r#"_createVNode(_component_test_component,null,{"default":_withCtx(()=>[_createTextVNode("hello from component"),_createElementVNode("div",null,"hello from div")])})"#,
+            r#"_createVNode(_component_test_component,null,{"default":_withCtx(()=>[_createTextVNode("hello from component"),_createElementVNode("div",null,"hello from div")]),_:1})"#,
             false,
         );
     }
@@ -838,7 +1094,7 @@ mod tests {
                 patch_hints: Default::default(),
                 span: DUMMY_SP,
             },
-            r#"_createVNode(_component_test_component,null,{"foo-bar":_withCtx(()=>[_createTextVNode("hello from component"),_createElementVNode("div",null,"hello from div")])})"#,
+            r#"_createVNode(_component_test_component,null,{"foo-bar":_withCtx(()=>[_createTextVNode("hello from component"),_createElementVNode("div",null,"hello from div")]),_:1})"#,
             false,
         );
     }
@@ -921,7 +1177,7 @@ mod tests {
                 patch_hints: Default::default(),
                 span: DUMMY_SP,
             },
-            r#"_createVNode(_component_test_component,null,{"foo-bar":_withCtx(()=>[_createTextVNode("hello from slot "+_toDisplayString(one),1)]),baz:_withCtx(()=>[_createTextVNode("hello from slot "),_createElementVNode("b",null,"two")])})"#,
+            r#"_createVNode(_component_test_component,null,{"foo-bar":_withCtx(()=>[_createTextVNode("hello from slot "+_toDisplayString(one),1)]),baz:_withCtx(()=>[_createTextVNode("hello from slot "),_createElementVNode("b",null,"two")]),_:1})"#,
             false,
         );
     }
@@ -977,7 +1233,7 @@ mod tests {
                 patch_hints: Default::default(),
                 span: DUMMY_SP,
             },
-            r#"_createVNode(_component_test_component,null,{"foo-bar":_withCtx(()=>[_createTextVNode("hello from slot")]),"default":_withCtx(()=>[_createTextVNode("hello from component"),_createElementVNode("div",null,"hello from div")])})"#,
+            r#"_createVNode(_component_test_component,null,{"foo-bar":_withCtx(()=>[_createTextVNode("hello from slot")]),"default":_withCtx(()=>[_createTextVNode("hello from component"),_createElementVNode("div",null,"hello from div")]),_:1})"#,
             false,
         );
 
@@ -1049,7 +1305,7 @@ mod tests {
                 patch_hints: Default::default(),
                 span: DUMMY_SP,
             },
-            r#"_createVNode(_component_test_component,null,{"default":_withCtx(()=>[_createTextVNode("hello from default"),_createElementVNode("div",null,"hello from div")]),"foo-bar":_withCtx(()=>[_createTextVNode("hello from slot")])})"#,
+            r#"_createVNode(_component_test_component,null,{"default":_withCtx(()=>[_createTextVNode("hello from default"),_createElementVNode("div",null,"hello from div")]),"foo-bar":_withCtx(()=>[_createTextVNode("hello from slot")]),_:1})"#,
             false,
         );
 
@@ -1102,7 +1358,7 @@ mod tests {
                 patch_hints: Default::default(),
                 span: DUMMY_SP,
             },
-            r#"_createVNode(_component_test_component,null,{"foo-bar":_withCtx(()=>[_createTextVNode("hello from slot")]),"default":_withCtx(()=>[_createTextVNode("hello from component"),_createElementVNode("div",null,"hello from div")])})"#,
+            r#"_createVNode(_component_test_component,null,{"foo-bar":_withCtx(()=>[_createTextVNode("hello from slot")]),"default":_withCtx(()=>[_createTextVNode("hello from component"),_createElementVNode("div",null,"hello from div")]),_:1})"#,
             false,
         );
     }
@@ -1196,7 +1452,119 @@ mod tests {
                 patch_hints: Default::default(),
                 span: DUMMY_SP,
             },
-            r#"_createVNode(_component_test_component,null,{"foo-bar":_withCtx(()=>[_createTextVNode("hello from slot")]),"default":_withCtx(()=>[_createTextVNode("hello from default"),_createElementVNode("div",null,"hello from div")]),baz:_withCtx(()=>[_createTextVNode("hello from baz")])})"#,
+            r#"_createVNode(_component_test_component,null,{"foo-bar":_withCtx(()=>[_createTextVNode("hello from slot")]),"default":_withCtx(()=>[_createTextVNode("hello from default"),_createElementVNode("div",null,"hello from div")]),baz:_withCtx(()=>[_createTextVNode("hello from baz")]),_:1})"#,
+            false,
+        );
+    }
+
+    #[test]
+    fn it_generates_dynamic_slots() {
+        // Convenience
+        let slot_template = |slot_name: StrOrExpr, directives: VueDirectives, text: &str| {
+            ElementNode {
+                starting_tag: StartingTag {
+                    tag_name: "template".into(),
+                    attributes: vec![],
+                    directives: Some(Box::new(VueDirectives {
+                        v_slot: Some(VSlotDirective {
+                            slot_name: Some(slot_name),
+                            value: None,
+                        }),
+                        ..directives
+                    })),
+                },
+                children: vec![Node::Text(text.into(), DUMMY_SP)],
+                template_scope: 0,
+                kind: ElementKind::Element,
+                patch_hints: Default::default(),
+                span: DUMMY_SP,
+            }
+        };
+
+        // <test-component>
+        //   <template v-if="foo" v-slot:foo>foo</template>
+        //   <template v-else v-slot:bar>bar</template>
+        //   <template v-for="item in list" v-slot:[item]>item</template>
+        //   <template v-slot:baz>baz</template>
+        // </test-component>
+        test_out(
+            ElementNode {
+                starting_tag: StartingTag {
+                    tag_name: "test-component".into(),
+                    attributes: vec![],
+                    directives: None,
+                },
+                children: vec![
+                    Node::ConditionalSeq(ConditionalNodeSequence {
+                        if_node: Box::new(Conditional {
+                            condition: *js("foo"),
+                            node: slot_template("foo".into(), Default::default(), "foo"),
+                        }),
+                        else_if_nodes: vec![],
+                        else_node: Some(Box::new(slot_template(
+                            "bar".into(),
+                            Default::default(),
+                            "bar",
+                        ))),
+                    }),
+                    Node::Element(slot_template(
+                        StrOrExpr::Expr(js("item")),
+                        VueDirectives {
+                            v_for: Some(VForDirective {
+                                iterable: js("list"),
+                                itervar: js("item"),
+                                patch_flags: Default::default(),
+                                fragment_key: None,
+                                span: DUMMY_SP,
+                            }),
+                            ..Default::default()
+                        },
+                        "item",
+                    )),
+                    Node::Element(slot_template("baz".into(), Default::default(), "baz")),
+                ],
+                template_scope: 0,
+                kind: ElementKind::Component,
+                patch_hints: PatchHints {
+                    flags: PatchFlags::DynamicSlots.into(),
+                    props: vec![],
+                    should_use_block: false,
+                },
+                span: DUMMY_SP,
+            },
+            r#"_createVNode(_component_test_component,null,_createSlots({baz:_withCtx(()=>[_createTextVNode("baz")]),_:2},[foo?{name:"foo",fn:_withCtx(()=>[_createTextVNode("foo")]),key:"0"}:{name:"bar",fn:_withCtx(()=>[_createTextVNode("bar")]),key:"1"},_renderList(list,(item)=>{return{name:item,fn:_withCtx(()=>[_createTextVNode("item")])};})]),1024)"#,
+            false,
+        );
+    }
+
+    #[test]
+    fn it_generates_forwarded_slots_flag() {
+        // <test-component><slot /></test-component>
+        test_out(
+            ElementNode {
+                starting_tag: StartingTag {
+                    tag_name: "test-component".into(),
+                    attributes: vec![],
+                    directives: None,
+                },
+                children: vec![Node::Element(ElementNode {
+                    starting_tag: StartingTag {
+                        tag_name: "slot".into(),
+                        attributes: vec![],
+                        directives: None,
+                    },
+                    children: vec![],
+                    template_scope: 0,
+                    kind: ElementKind::Builtin(BuiltinType::Slot),
+                    patch_hints: Default::default(),
+                    span: DUMMY_SP,
+                })],
+                template_scope: 0,
+                kind: ElementKind::Component,
+                patch_hints: Default::default(),
+                span: DUMMY_SP,
+            },
+            r#"_createVNode(_component_test_component,null,{"default":_withCtx(()=>[_renderSlot(_ctx.$slots,"default")]),_:3})"#,
             false,
         );
     }
@@ -1,10 +1,34 @@
+use fervid_core::{FervidAtom, VueSsrImportsSet};
 use fervid_transform::BindingsHelper;
+use swc_core::{common::comments::SingleThreadedComments, ecma::ast::Expr};
 
 #[derive(Debug, Default)]
 pub struct CodegenContext {
     pub bindings_helper: BindingsHelper,
     pub is_cache_disabled: bool,
-    pub next_cache_index: u8,
+    pub next_cache_index: u32,
+    /// Cache the event handlers in `_cache`, so that the same function is passed on every render
+    pub cache_handlers: bool,
+    /// Hoist the static nodes and props out of the render function
+    pub hoist_static: bool,
+    /// Expressions hoisted to the module-level `_hoisted_N` constants
+    pub hoists: Vec<Expr>,
+    /// Comments of the generated code, e.g. `/*#__PURE__*/` of the hoisted vnodes.
+    /// Should be passed to [`CodegenContext::stringify`].
+    pub comments: SingleThreadedComments,
+    /// Number of the positions reserved for `comments`
+    pub(crate) comment_positions: u32,
+    /// Generate `ssrRender` instead of `render`
+    pub is_ssr: bool,
+    /// Scope attribute (e.g. `data-v-7ba5bd90`) when the component has scoped styles.
+    /// In SSR, it is also rendered on the elements.
+    pub scope_id: Option<FervidAtom>,
+    /// Whether scoped styles use `:slotted()`, meaning that slot content needs the slotted scope id
+    pub is_slotted: bool,
+    /// Helpers from `vue/server-renderer` used by SSR code generation
+    pub ssr_imports: VueSsrImportsSet,
+    /// `v-bind()`s of the `<style>` blocks, declared in SSR as `const _cssVars = { style: ... }`
+    pub ssr_css_vars: Option<Box<Expr>>,
 }
 
 impl CodegenContext {
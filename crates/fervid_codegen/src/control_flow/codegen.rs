@@ -1,14 +1,18 @@
-use fervid_core::{ElementKind, ElementNode, Node, VueImports};
+use fervid_core::{fervid_atom, ElementKind, ElementNode, Node, PatchFlags, VueImports};
 use smallvec::SmallVec;
 use swc_core::{
-    common::{BytePos, Span, SyntaxContext},
+    common::{BytePos, Span, SyntaxContext, DUMMY_SP},
     ecma::ast::{
-        BinExpr, BinaryOp, CallExpr, Callee, Expr, ExprOrSpread, Ident, Lit, Number, ParenExpr,
-        SeqExpr,
+        AssignExpr, AssignOp, AssignTarget, BinExpr, BinaryOp, Bool, CallExpr, Callee,
+        ComputedPropName, Expr, ExprOrSpread, Ident, Lit, MemberExpr, MemberProp, Number,
+        ParenExpr, SeqExpr, SimpleAssignTarget,
     },
 };
 
-use crate::context::CodegenContext;
+use crate::{
+    context::CodegenContext,
+    hoist::{is_static_node, stringify::find_static_chunk},
+};
 
 type TextNodesConcatenationVec = SmallVec<[Expr; 3]>;
 
@@ -49,9 +53,37 @@ impl CodegenContext {
             self.is_cache_disabled = true;
         }
 
+        let directives = element_node.starting_tag.directives.as_deref();
+        let has_v_memo = directives.map_or(false, |d| d.v_memo.is_some());
+        let is_component = matches!(element_node.kind, ElementKind::Component);
+
+        // Decide whether the element itself is a block
+        // https://github.com/vuejs/core/blob/v3.4.21/packages/compiler-core/src/transforms/vFor.ts#L154
+        let wrap_in_block = match directives.and_then(|d| d.v_for.as_ref()) {
+            // Items of an unstable `v-for` Fragment are blocks, so are the `<template>` Fragments
+            Some(v_for) => {
+                !v_for.patch_flags.contains(PatchFlags::StableFragment)
+                    || element_node.starting_tag.tag_name == "template"
+                    || (has_v_memo && !is_component)
+            }
+
+            None => {
+                wrap_in_block
+                    || element_node.patch_hints.should_use_block
+                    || (has_v_memo && !is_component)
+                    || (matches!(element_node.kind, ElementKind::Element)
+                        && matches!(
+                            element_node.starting_tag.tag_name.as_ref(),
+                            "svg" | "foreignObject" | "math"
+                        ))
+            }
+        };
+
         // Generate the relevant render code depending on ElementKind
         let mut result = match element_node.kind {
-            ElementKind::Builtin(builtin_type) => self.generate_builtin(element_node, builtin_type),
+            ElementKind::Builtin(builtin_type) => {
+                self.generate_builtin(element_node, builtin_type, wrap_in_block)
+            }
             ElementKind::Element => self.generate_element_vnode(element_node, wrap_in_block),
             ElementKind::Component => self.generate_component_vnode(element_node, wrap_in_block),
         };
@@ -104,6 +136,7 @@ impl CodegenContext {
         let mut text_nodes = TextNodesConcatenationVec::new();
         let mut text_nodes_span = [BytePos(0), BytePos(0)];
         let mut patch_flag_text = false;
+        let mut is_text_static = true;
 
         macro_rules! maybe_concatenate_text_nodes {
             () => {
@@ -120,7 +153,14 @@ impl CodegenContext {
                         },
                         patch_flag_text,
                     );
-                    out.push(concatenation);
+
+                    // Static `createTextVNode` can be hoisted
+                    if !should_inline && is_text_static && self.can_hoist_nodes() {
+                        let hoisted = self.hoist(concatenation);
+                        out.push(hoisted);
+                    } else {
+                        out.push(concatenation);
+                    }
 
                     // Reset text nodes
                     text_nodes.clear();
@@ -135,13 +175,54 @@ impl CodegenContext {
             };
         }
 
-        while let Some(node) = iter.next() {
+        let nodes: Vec<&Node> = iter.collect();
+        let mut idx = 0;
+        // End of the last static chunk which was too small to be stringified
+        let mut chunk_end = 0;
+
+        while idx < nodes.len() {
+            // Long runs of static nodes are stringified into a single static VNode
+            if idx >= chunk_end && self.can_stringify_static() {
+                let (chunk_len, should_stringify) = find_static_chunk(&nodes, idx);
+                if should_stringify {
+                    maybe_concatenate_text_nodes!();
+                    patch_flag_text = false;
+                    is_text_static = true;
+
+                    let static_vnode = self.generate_static_vnode(&nodes[idx..idx + chunk_len]);
+                    let hoisted = self.hoist(static_vnode);
+                    out.push(hoisted);
+                    idx += chunk_len;
+                    continue;
+                }
+                chunk_end = idx + chunk_len.max(1);
+            }
+
+            let node = nodes[idx];
+            idx += 1;
+
+            // Static elements are created only once, outside of the render function
+            if let Node::Element(element_node) = node {
+                if self.can_hoist_nodes() && is_static_node(node) {
+                    maybe_concatenate_text_nodes!();
+                    patch_flag_text = false;
+                    is_text_static = true;
+
+                    let hoisted = self.generate_hoisted_element(element_node);
+                    out.push(hoisted);
+                    continue;
+                }
+            }
+
             let generated = self.generate_node(node, false);
             let is_text_node = matches!(node, Node::Text(_, _) | Node::Interpolation { .. });
 
             if let Node::Interpolation(interpolation) = node {
                 patch_flag_text |= interpolation.patch_flag;
             }
+            if is_text_node {
+                is_text_static &= is_static_node(node);
+            }
 
             if is_text_node {
                 text_nodes.push(generated);
@@ -156,6 +237,7 @@ impl CodegenContext {
                 // Process the text nodes from before
                 maybe_concatenate_text_nodes!();
                 patch_flag_text = false;
+                is_text_static = true;
 
                 out.push(generated);
             }
@@ -168,6 +250,27 @@ impl CodegenContext {
     /// Wraps the expression in openBlock construction,
     /// e.g. `(openBlock(), expr)`
     pub fn wrap_in_open_block(&mut self, expr: Expr, span: Span) -> Expr {
+        self.wrap_in_open_block_with(expr, false, span)
+    }
+
+    /// Wraps the expression in openBlock construction, optionally disabling
+    /// the tracking of dynamic children, e.g. `(openBlock(true), expr)`.
+    /// Tracking is disabled for `v-for` fragments with unstable children.
+    pub fn wrap_in_open_block_with(
+        &mut self,
+        expr: Expr,
+        disable_tracking: bool,
+        span: Span,
+    ) -> Expr {
+        let open_block_args = if disable_tracking {
+            vec![ExprOrSpread {
+                spread: None,
+                expr: Box::new(Expr::Lit(Lit::Bool(Bool { span, value: true }))),
+            }]
+        } else {
+            Vec::new()
+        };
+
         Expr::Paren(ParenExpr {
             span,
             expr: Box::new(Expr::Seq(SeqExpr {
@@ -181,7 +284,7 @@ impl CodegenContext {
                             sym: self.get_and_add_import_ident(VueImports::OpenBlock),
                             optional: false,
                         }))),
-                        args: Vec::new(),
+                        args: open_block_args,
                         type_args: None,
                     })),
                     Box::new(expr),
@@ -207,12 +310,62 @@ impl CodegenContext {
 
     /// Produce the index for a next `cache[idx]` entry.
     /// This is useful for a `v-once` or event handlers.
-    pub fn allocate_next_cache_entry(&mut self) -> u8 {
+    pub fn allocate_next_cache_entry(&mut self) -> u32 {
         let idx = self.next_cache_index;
         self.next_cache_index += 1;
         idx
     }
 
+    /// Whether event handlers can be cached at this point of code generation.
+    /// Caching is not done inside `v-once`, because it is cached as a whole.
+    #[inline]
+    pub(crate) fn can_cache_handlers(&self) -> bool {
+        self.cache_handlers && !self.is_cache_disabled
+    }
+
+    /// Wraps the handler in `_cache[idx] || (_cache[idx] = handler)`
+    pub(crate) fn generate_cached_handler(&mut self, handler: Box<Expr>) -> Box<Expr> {
+        let cache_idx = self.allocate_next_cache_entry();
+
+        // `_cache[idx]`
+        let cache_member_expr = MemberExpr {
+            span: DUMMY_SP,
+            obj: Box::new(Expr::Ident(Ident {
+                span: DUMMY_SP,
+                sym: fervid_atom!("_cache"),
+                optional: false,
+            })),
+            prop: MemberProp::Computed(ComputedPropName {
+                span: DUMMY_SP,
+                expr: Box::new(Expr::Lit(Lit::Num(Number {
+                    span: DUMMY_SP,
+                    value: cache_idx as f64,
+                    raw: None,
+                }))),
+            }),
+        };
+
+        // `(_cache[idx] = handler)`
+        let cache_assign = Box::new(Expr::Paren(ParenExpr {
+            span: DUMMY_SP,
+            expr: Box::new(Expr::Assign(AssignExpr {
+                span: DUMMY_SP,
+                op: AssignOp::Assign,
+                left: AssignTarget::Simple(SimpleAssignTarget::Member(
+                    cache_member_expr.to_owned(),
+                )),
+                right: handler,
+            })),
+        }));
+
+        Box::new(Expr::Bin(BinExpr {
+            span: DUMMY_SP,
+            op: BinaryOp::LogicalOr,
+            left: Box::new(Expr::Member(cache_member_expr)),
+            right: cache_assign,
+        }))
+    }
+
     fn concatenate_text_nodes(
         &mut self,
         text_nodes_concatenation: &mut TextNodesConcatenationVec,
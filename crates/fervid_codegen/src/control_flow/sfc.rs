@@ -4,16 +4,16 @@ use fervid_core::{
 use swc_core::{
     atoms::Atom,
     common::{
-        collections::AHashMap, source_map::SourceMapGenConfig, BytePos, FileName, SourceMap,
-        DUMMY_SP,
+        collections::AHashMap, comments::Comments, source_map::SourceMapGenConfig, BytePos,
+        FileName, SourceMap, DUMMY_SP,
     },
     ecma::{
         ast::{
             ArrowExpr, AssignExpr, BindingIdent, BlockStmt, BlockStmtOrExpr, CallExpr, Callee,
             Decl, ExportDefaultExpr, Expr, ExprOrSpread, ExprStmt, Function, GetterProp, Ident,
-            ImportDecl, MethodProp, Module, ModuleDecl, ModuleItem, ObjectLit, Param, Pat, Prop,
-            PropName, PropOrSpread, ReturnStmt, SetterProp, Stmt, Str, VarDecl, VarDeclKind,
-            VarDeclarator,
+            ImportDecl, ImportDefaultSpecifier, ImportSpecifier, MethodProp, Module, ModuleDecl,
+            ModuleItem, ObjectLit, Param, Pat, Prop, PropName, PropOrSpread, ReturnStmt,
+            SetterProp, Stmt, Str, VarDecl, VarDeclKind, VarDeclarator,
         },
         visit::{noop_visit_type, Visit, VisitWith},
     },
@@ -26,6 +26,10 @@ impl CodegenContext {
     // TODO Generation mode? Is it relevant?
     // TODO Generating module? Or instead taking a module? Or generating an expression and merging?
     pub fn generate_sfc_template(&mut self, sfc_template: &SfcTemplateBlock) -> Option<Expr> {
+        if self.is_ssr {
+            return self.generate_ssr_sfc_template(sfc_template);
+        }
+
         // #11: Optimization: multiple template roots
         // and all are text nodes (must be ensured by Transformer),
         // generate node sequence
@@ -62,7 +66,11 @@ impl CodegenContext {
             match template_generation_mode {
                 // Generates the render expression and appends it to the end of the `setup` function.
                 TemplateGenerationMode::Inline => {
-                    let render_arrow = self.generate_render_arrow(template_expr);
+                    let render_arrow = if self.is_ssr {
+                        self.generate_ssr_render_arrow(template_expr)
+                    } else {
+                        self.generate_render_arrow(template_expr)
+                    };
 
                     let setup_function = synthetic_setup_fn.get_or_insert_with(|| {
                         Box::new(Function {
@@ -90,8 +98,13 @@ impl CodegenContext {
 
                 // Generates the render Function to be used as a property in exported object.
                 // `render(_ctx, _cache, $props, $setup, $data, $options) { /*...*/ }`
+                // or `ssrRender(_ctx, _push, _parent, _attrs, $props, $setup, $data, $options) { /*...*/ }`
                 TemplateGenerationMode::RenderFn => {
-                    let render_fn = self.generate_render_fn(template_expr);
+                    let (render_fn, render_fn_name) = if self.is_ssr {
+                        (self.generate_ssr_render_fn(template_expr), "ssrRender")
+                    } else {
+                        (self.generate_render_fn(template_expr), "render")
+                    };
 
                     // When a synthetic setup function is present,
                     // we need to return bindings as its last statement
@@ -118,7 +131,7 @@ impl CodegenContext {
                         .push(PropOrSpread::Prop(Box::new(Prop::Method(MethodProp {
                             key: PropName::Ident(Ident {
                                 span: DUMMY_SP,
-                                sym: FervidAtom::from("render"),
+                                sym: FervidAtom::from(render_fn_name),
                                 optional: false,
                             }),
                             function: Box::new(render_fn),
@@ -213,6 +226,9 @@ impl CodegenContext {
             }))
         };
 
+        // Hoists are generated before the imports, because they may use e.g. `_pushScopeId`
+        let hoists = self.generate_hoists();
+
         // Append the Vue imports
         // TODO Smart merging with user imports?
         let used_imports = self.generate_imports();
@@ -233,6 +249,53 @@ impl CodegenContext {
                 })));
         }
 
+        // Append the SSR helper imports
+        let used_ssr_imports = self.generate_ssr_imports();
+        if !used_ssr_imports.is_empty() {
+            script
+                .body
+                .push(ModuleItem::ModuleDecl(ModuleDecl::Import(ImportDecl {
+                    span: DUMMY_SP,
+                    specifiers: used_ssr_imports,
+                    src: Box::new(Str {
+                        span: DUMMY_SP,
+                        value: FervidAtom::from("vue/server-renderer"),
+                        raw: None,
+                    }),
+                    type_only: false,
+                    with: None,
+                    phase: Default::default(),
+                })));
+        }
+
+        // Append the asset imports, e.g. `import _imports_0 from './logo.png'`
+        for (idx, path) in self.bindings_helper.asset_imports.iter().enumerate() {
+            script
+                .body
+                .push(ModuleItem::ModuleDecl(ModuleDecl::Import(ImportDecl {
+                    span: DUMMY_SP,
+                    specifiers: vec![ImportSpecifier::Default(ImportDefaultSpecifier {
+                        span: DUMMY_SP,
+                        local: Ident {
+                            span: DUMMY_SP,
+                            sym: FervidAtom::from(format!("_imports_{idx}")),
+                            optional: false,
+                        },
+                    })],
+                    src: Box::new(Str {
+                        span: DUMMY_SP,
+                        value: path.to_owned(),
+                        raw: None,
+                    }),
+                    type_only: false,
+                    with: None,
+                    phase: Default::default(),
+                })));
+        }
+
+        // Append the hoisted static nodes and props, e.g. `const _hoisted_1 = { class: "foo" }`
+        script.body.extend(hoists);
+
         // Append the default export/const
         script.body.push(gen_default_as);
 
@@ -512,12 +575,15 @@ impl CodegenContext {
         }
     }
 
+    /// Converts the generated module to a string and an optional source map.
+    /// `comments` are usually the ones of [`CodegenContext`], e.g. `/*#__PURE__*/` of the hoists.
     pub fn stringify<T>(
         source: &str,
         module: &T,
         filename: FileName,
         generate_source_map: bool,
         minify: bool,
+        comments: Option<&dyn Comments>,
     ) -> (String, Option<String>)
     where
         T: Node + VisitWith<IdentCollector>,
@@ -543,7 +609,7 @@ impl CodegenContext {
 
             let mut emitter = Emitter {
                 cfg: emitter_cfg,
-                comments: None,
+                comments,
                 wr: writer,
                 cm: cm.clone(),
             };
@@ -246,6 +246,8 @@ mod tests {
                         event: Some("event".into()),
                         handler: Some(js("baz")),
                         modifiers: vec![],
+                        key_modifiers: vec![],
+                        is_handler_cacheable: false,
                         span: DUMMY_SP,
                     }),
                 ],
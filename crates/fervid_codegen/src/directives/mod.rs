@@ -215,7 +215,7 @@ impl CodegenContext {
         Expr::Array(directive_arr)
     }
 
-    fn get_custom_directive_ident(&mut self, directive_name: &FervidAtom, span: Span) -> Expr {
+    pub(crate) fn get_custom_directive_ident(&mut self, directive_name: &FervidAtom, span: Span) -> Expr {
         // Check directive existence and early exit
         let existing_directive_binding = self.bindings_helper.custom_directives.get(directive_name);
         match existing_directive_binding {
@@ -1,8 +1,8 @@
-use fervid_core::{fervid_atom, VForDirective, VueImports};
+use fervid_core::{fervid_atom, PatchFlags, VForDirective, VueImports};
 use swc_core::{
     common::DUMMY_SP,
     ecma::ast::{
-        ArrowExpr, AssignExpr, AssignOp, AssignTarget, BinExpr, BinaryOp, BindingIdent, BlockStmt, BlockStmtOrExpr, CallExpr, Callee, Decl, Expr, ExprOrSpread, ExprStmt, Ident, IfStmt, Lit, MemberExpr, Null, Number, Pat, ReturnStmt, SimpleAssignTarget, Stmt, VarDecl, VarDeclKind, VarDeclarator
+        ArrowExpr, AssignExpr, AssignOp, AssignTarget, BinExpr, BinaryOp, BindingIdent, BlockStmt, BlockStmtOrExpr, CallExpr, Callee, Decl, Expr, ExprOrSpread, ExprStmt, Ident, IfStmt, KeyValueProp, Lit, MemberExpr, Null, Number, ObjectLit, Pat, Prop, PropName, PropOrSpread, ReturnStmt, SimpleAssignTarget, Stmt, VarDecl, VarDeclKind, VarDeclarator
     },
 };
 
@@ -66,7 +66,7 @@ impl CodegenContext {
         });
         create_element_block_args.push(ExprOrSpread {
             spread: None,
-            expr: Box::new(Expr::Lit(Lit::Null(Null { span }))),
+            expr: generate_fragment_attrs(v_for),
         });
         create_element_block_args.push(ExprOrSpread {
             spread: None,
@@ -92,7 +92,9 @@ impl CodegenContext {
             type_args: None,
         });
 
-        self.wrap_in_open_block(create_element_block, span)
+        // Fragment children are not tracked unless they are stable
+        let disable_tracking = !v_for.patch_flags.contains(PatchFlags::StableFragment);
+        self.wrap_in_open_block_with(create_element_block, disable_tracking, span)
     }
 
     /// Generates `v-for` in combination with `v-memo`.
@@ -186,7 +188,7 @@ impl CodegenContext {
         // 2.2. `null` (or `{ key: <number> }` in some rare cases)
         let fragment_attrs = ExprOrSpread {
             spread: None,
-            expr: Box::new(Expr::Lit(Lit::Null(Null { span }))),
+            expr: generate_fragment_attrs(v_for),
         };
 
         // 2.3. Fragment render function - just convert to ExprOrSpread
@@ -222,7 +224,9 @@ impl CodegenContext {
             type_args: None,
         });
 
-        self.wrap_in_open_block(create_element_block, span)
+        // Fragment children are not tracked unless they are stable
+        let disable_tracking = !v_for.patch_flags.contains(PatchFlags::StableFragment);
+        self.wrap_in_open_block_with(create_element_block, disable_tracking, span)
     }
 
     /// Generates the arrow function for [generate_v_for_memoized].
@@ -411,14 +415,70 @@ impl CodegenContext {
     }
 }
 
+/// Generates `null` or `{ key: <number> }` when `v-for` is a branch of `v-if`
+fn generate_fragment_attrs(v_for: &VForDirective) -> Box<Expr> {
+    let span = v_for.span;
+
+    let Some(key) = v_for.fragment_key else {
+        return Box::new(Expr::Lit(Lit::Null(Null { span })));
+    };
+
+    Box::new(Expr::Object(ObjectLit {
+        span,
+        props: vec![PropOrSpread::Prop(Box::new(Prop::KeyValue(KeyValueProp {
+            key: PropName::Ident(Ident {
+                span,
+                sym: fervid_atom!("key"),
+                optional: false,
+            }),
+            value: Box::new(Expr::Lit(Lit::Num(Number {
+                span,
+                value: key.into(),
+                raw: None,
+            }))),
+        })))],
+    }))
+}
+
 #[cfg(test)]
 mod tests {
-    use fervid_core::PatchFlags;
 
     use crate::test_utils::js;
 
     use super::*;
 
+    #[test]
+    fn it_generates_v_for() {
+        // `<div v-for="item in 3"></div>`
+        let mut v_for = VForDirective {
+            iterable: js("3"),
+            itervar: js("item"),
+            patch_flags: PatchFlags::StableFragment.into(),
+            fragment_key: None,
+            span: DUMMY_SP,
+        };
+
+        let mut ctx = CodegenContext::default();
+        let res = ctx.generate_v_for(&v_for, js("_createElementVNode(\"div\")"));
+        assert_eq!(
+            crate::test_utils::to_str(res),
+            "(_openBlock(),_createElementBlock(_Fragment,null,_renderList(3,(item)=>_createElementVNode(\"div\")),64))"
+        );
+
+        // `<div v-if="false"></div><div v-else v-for="item in list"></div>`
+        // Unstable Fragment does not track its children
+        v_for.iterable = js("list");
+        v_for.patch_flags = PatchFlags::UnkeyedFragment.into();
+        v_for.fragment_key = Some(1);
+
+        let mut ctx = CodegenContext::default();
+        let res = ctx.generate_v_for(&v_for, js("(_openBlock(),_createElementBlock(\"div\"))"));
+        assert_eq!(
+            crate::test_utils::to_str(res),
+            "(_openBlock(true),_createElementBlock(_Fragment,{key:1},_renderList(list,(item)=>(_openBlock(),_createElementBlock(\"div\"))),256))"
+        );
+    }
+
     #[test]
     fn it_generates_v_for_memoized() {
         let mut ctx = CodegenContext::default();
@@ -428,6 +488,7 @@ mod tests {
             iterable: js("3"),
             itervar: js("item"),
             patch_flags: PatchFlags::StableFragment.into(),
+            fragment_key: None,
             span: DUMMY_SP,
         };
 
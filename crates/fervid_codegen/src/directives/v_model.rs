@@ -18,7 +18,7 @@ impl CodegenContext {
     /// 2. Update handler, e.g. `"onUpdate:modelValue": $event => smth.value = $event`;
     /// 3. (Optional) Modifiers, e.g. `modelModifiers: { lazy: true }` for `v-model.lazy="smth"`.
     pub fn generate_v_model_for_component(
-        &self,
+        &mut self,
         v_model: &VModelDirective,
         out: &mut Vec<PropOrSpread>,
     ) {
@@ -44,11 +44,10 @@ impl CodegenContext {
 
         // 4. Push the update code,
         // e.g. `v-model="smth"` -> `"onUpdate:modelValue": $event => ((_ctx.smth) = $event)`
-        // TODO Cache like so `_cache[1] || (_cache[1] = `
-        if let Some(ref update_handler) = v_model.update_handler {
+        if let Some(update_handler) = self.generate_v_model_update_handler(v_model) {
             out.push(PropOrSpread::Prop(Box::new(Prop::KeyValue(KeyValueProp {
                 key: event_handler_propname,
-                value: update_handler.to_owned(),
+                value: update_handler,
             }))));
         }
 
@@ -109,7 +108,7 @@ impl CodegenContext {
 
     /// Generates the `v-model` for an element.
     /// This generates the update handler
-    pub fn generate_v_model_for_element(&self, v_model: &VModelDirective, out: &mut Vec<PropOrSpread>) {
+    pub fn generate_v_model_for_element(&mut self, v_model: &VModelDirective, out: &mut Vec<PropOrSpread>) {
         let span = v_model.span;
         let mut buf = String::new();
 
@@ -125,14 +124,25 @@ impl CodegenContext {
 
         // 3. Push the update handler code,
         // e.g. `v-model="smth"` -> `"onUpdate:modelValue": $event => ((_ctx.smth) = $event)`
-        // TODO Cache like so `_cache[1] || (_cache[1] = `
-        if let Some(ref update_handler) = v_model.update_handler {
+        if let Some(update_handler) = self.generate_v_model_update_handler(v_model) {
             out.push(PropOrSpread::Prop(Box::new(Prop::KeyValue(KeyValueProp {
                 key: event_handler_propname,
-                value: update_handler.to_owned(),
+                value: update_handler,
             }))));
         }
     }
+
+    /// Generates the update handler, caching it when possible,
+    /// e.g. `_cache[0] || (_cache[0] = $event => ((_ctx.smth) = $event))`
+    fn generate_v_model_update_handler(&mut self, v_model: &VModelDirective) -> Option<Box<Expr>> {
+        let update_handler = v_model.update_handler.to_owned()?;
+
+        if v_model.is_handler_cacheable && self.can_cache_handlers() {
+            Some(self.generate_cached_handler(update_handler))
+        } else {
+            Some(update_handler)
+        }
+    }
 }
 
 /// Generates the propname for `v-model` update handler.
@@ -202,6 +212,7 @@ mod tests {
                 value: js("foo"),
                 update_handler: js("$event=>((foo)=$event)").into(),
                 modifiers: Vec::new(),
+                is_handler_cacheable: false,
                 span: DUMMY_SP,
             }],
             r#"{modelValue:foo,"onUpdate:modelValue":$event=>((foo)=$event)}"#,
@@ -217,6 +228,7 @@ mod tests {
                 value: js("foo"),
                 update_handler: js("$event=>((foo)=$event)").into(),
                 modifiers: Vec::new(),
+                is_handler_cacheable: false,
                 span: DUMMY_SP,
             }],
             r#"{simple:foo,"onUpdate:simple":$event=>((foo)=$event)}"#,
@@ -229,6 +241,7 @@ mod tests {
                 value: js("bar"),
                 update_handler: js("$event=>((bar)=$event)").into(),
                 modifiers: Vec::new(),
+                is_handler_cacheable: false,
                 span: DUMMY_SP,
             }],
             r#"{modelValue:bar,"onUpdate:modelValue":$event=>((bar)=$event)}"#,
@@ -241,6 +254,7 @@ mod tests {
                 value: js("baz"),
                 update_handler: js("$event=>((baz)=$event)").into(),
                 modifiers: Vec::new(),
+                is_handler_cacheable: false,
                 span: DUMMY_SP,
             }],
             r#"{"model-value":baz,"onUpdate:modelValue":$event=>((baz)=$event)}"#,
@@ -256,6 +270,7 @@ mod tests {
                 value: js("foo"),
                 update_handler: js("$event=>((foo)=$event)").into(),
                 modifiers: vec!["lazy".into(), "trim".into()],
+                is_handler_cacheable: false,
                 span: DUMMY_SP,
             }],
             r#"{modelValue:foo,"onUpdate:modelValue":$event=>((foo)=$event),modelModifiers:{lazy:true,trim:true}}"#,
@@ -268,6 +283,7 @@ mod tests {
                 value: js("foo"),
                 update_handler: js("$event=>((foo)=$event)").into(),
                 modifiers: vec!["custom-modifier".into()],
+                is_handler_cacheable: false,
                 span: DUMMY_SP,
             }],
             r#"{modelValue:foo,"onUpdate:modelValue":$event=>((foo)=$event),modelModifiers:{"custom-modifier":true}}"#,
@@ -280,6 +296,7 @@ mod tests {
                 value: js("bazQux"),
                 update_handler: js("$event=>((bazQux)=$event)").into(),
                 modifiers: vec!["custom-modifier".into()],
+                is_handler_cacheable: false,
                 span: DUMMY_SP,
             }],
             r#"{"foo-bar":bazQux,"onUpdate:fooBar":$event=>((bazQux)=$event),"foo-barModifiers":{"custom-modifier":true}}"#,
@@ -295,6 +312,7 @@ mod tests {
                 value: js("bar"),
                 update_handler: js("$event=>((bar)=$event)").into(),
                 modifiers: Vec::new(),
+                is_handler_cacheable: false,
                 span: DUMMY_SP,
             }],
             r#"{[foo]:bar,["onUpdate:"+foo]:$event=>((bar)=$event)}"#,
@@ -307,14 +325,43 @@ mod tests {
                 value: js("bar"),
                 update_handler: js("$event=>((bar)=$event)").into(),
                 modifiers: vec!["baz".into()],
+                is_handler_cacheable: false,
                 span: DUMMY_SP,
             }],
             r#"{[foo]:bar,["onUpdate:"+foo]:$event=>((bar)=$event),[foo+"Modifiers"]:{baz:true}}"#,
         );
     }
 
+    #[test]
+    fn it_caches_update_handler() {
+        let v_model = |is_handler_cacheable: bool| VModelDirective {
+            argument: None,
+            value: js("foo"),
+            update_handler: Some(js("$event => ((foo) = $event)")),
+            modifiers: vec![],
+            is_handler_cacheable,
+            span: DUMMY_SP,
+        };
+
+        let mut ctx = CodegenContext {
+            cache_handlers: true,
+            ..Default::default()
+        };
+        let mut out = ObjectLit {
+            span: DUMMY_SP,
+            props: vec![],
+        };
+        ctx.generate_v_model_for_component(&v_model(true), &mut out.props);
+        ctx.generate_v_model_for_element(&v_model(true), &mut out.props);
+        ctx.generate_v_model_for_element(&v_model(false), &mut out.props);
+        assert_eq!(
+            crate::test_utils::to_str(out),
+            r#"{modelValue:foo,"onUpdate:modelValue":_cache[0]||(_cache[0]=$event=>((foo)=$event)),"onUpdate:modelValue":_cache[1]||(_cache[1]=$event=>((foo)=$event)),"onUpdate:modelValue":$event=>((foo)=$event)}"#
+        );
+    }
+
     fn test_out(input: Vec<VModelDirective>, expected: &str) {
-        let ctx = CodegenContext::default();
+        let mut ctx = CodegenContext::default();
         let mut out = ObjectLit {
             span: DUMMY_SP,
             props: vec![],
@@ -1,5 +1,6 @@
 use fervid_core::{
-    AttributeOrBinding, ElementNode, StartingTag, StrOrExpr, VBindDirective, VueImports,
+    AttributeOrBinding, ElementNode, PatchFlags, StartingTag, StrOrExpr, VBindDirective,
+    VueImports,
 };
 use swc_core::{
     common::DUMMY_SP,
@@ -12,7 +13,7 @@ use swc_core::{
     },
 };
 
-use crate::{context::CodegenContext, control_flow::SlottedIterator};
+use crate::{context::CodegenContext, control_flow::SlottedIterator, hoist::has_static_props};
 
 impl CodegenContext {
     pub fn generate_element_vnode(
@@ -25,7 +26,7 @@ impl CodegenContext {
 
         // Generate attributes
         let attributes = self.generate_element_attributes(element_node);
-        let attributes_expr = if attributes.len() != 0 {
+        let mut attributes_expr = if attributes.len() != 0 {
             Some(Expr::Object(ObjectLit {
                 span,
                 props: attributes,
@@ -34,6 +35,15 @@ impl CodegenContext {
             None
         };
 
+        // Static props of an element with dynamic children can still be hoisted
+        let flags = element_node.patch_hints.flags;
+        if self.can_hoist_props()
+            && (flags.is_empty() || flags == PatchFlags::Text || flags == PatchFlags::NeedPatch)
+            && has_static_props(element_node)
+        {
+            attributes_expr = attributes_expr.map(|expr| self.hoist(expr));
+        }
+
         // There is a special case here: `<template>` with `v-if`/`v-else-if`/`v-else`/`v-for`
         let should_generate_fragment_instead = (wrap_in_block
             && element_node.starting_tag.tag_name == "template")
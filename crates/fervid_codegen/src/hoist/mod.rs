@@ -0,0 +1,535 @@
+//! Static hoisting: moves the parts of the render function which never change
+//! to the module-level `const _hoisted_N = ...` declarations.
+//!
+//! https://github.com/vuejs/core/blob/v3.4.21/packages/compiler-core/src/transforms/hoistStatic.ts
+
+use fervid_core::{
+    fervid_atom, AttributeOrBinding, ElementKind, ElementNode, FervidAtom, Node, PatchFlags,
+    PatchFlagsSet, StrOrExpr, VueDirectives, VueImports,
+};
+use fervid_transform::script::utils::is_static;
+use swc_core::{
+    common::{comments::Comments, BytePos, Span, DUMMY_SP},
+    ecma::ast::{
+        ArrowExpr, AssignExpr, AssignOp, AssignTarget, BindingIdent, BlockStmtOrExpr, CallExpr,
+        Callee, Decl, Expr, ExprOrSpread, Ident, Lit, ModuleItem, Null, Number, ParenExpr, Pat,
+        SeqExpr, SimpleAssignTarget, Stmt, Str, VarDecl, VarDeclKind, VarDeclarator,
+    },
+};
+
+use crate::context::CodegenContext;
+
+pub(crate) mod stringify;
+
+impl CodegenContext {
+    /// Whether static nodes can be hoisted at this point of code generation.
+    ///
+    /// Hoisting is not done inside `v-once`, because it is cached anyway.
+    #[inline]
+    pub(crate) fn can_hoist_nodes(&self) -> bool {
+        self.hoist_static && !self.is_cache_disabled
+    }
+
+    /// Whether static props objects can be hoisted at this point of code generation
+    #[inline]
+    pub(crate) fn can_hoist_props(&self) -> bool {
+        self.hoist_static && !self.is_cache_disabled
+    }
+
+    /// Moves the expression to a `_hoisted_N` constant and returns the identifier to use instead
+    pub fn hoist(&mut self, expr: Expr) -> Expr {
+        self.hoists.push(expr);
+
+        Expr::Ident(Ident {
+            span: DUMMY_SP,
+            sym: hoisted_ident_sym(self.hoists.len()),
+            optional: false,
+        })
+    }
+
+    /// Generates a fully static element and hoists it with the `HOISTED` patch flag,
+    /// e.g. `_createElementVNode("div", null, "static", -1)`.
+    ///
+    /// With scoped styles, the element is created as `_withScopeId(() => _createElementVNode(...))`,
+    /// because hoisted nodes are created outside of the render function,
+    /// and the scope id would not be applied to them otherwise.
+    pub(crate) fn generate_hoisted_element(&mut self, element_node: &ElementNode) -> Expr {
+        // Children are a part of the hoisted element, they should not be hoisted separately
+        let old_hoist_static = self.hoist_static;
+        self.hoist_static = false;
+        let mut expr = self.generate_element_vnode(element_node, false);
+        self.hoist_static = old_hoist_static;
+
+        if let Expr::Call(ref mut call_expr) = expr {
+            // Pad the omitted props and children with `null`
+            while call_expr.args.len() < 3 {
+                call_expr.args.push(ExprOrSpread {
+                    spread: None,
+                    expr: Box::new(Expr::Lit(Lit::Null(Null { span: DUMMY_SP }))),
+                });
+            }
+
+            call_expr.args.push(ExprOrSpread {
+                spread: None,
+                expr: Box::new(Expr::Lit(Lit::Num(Number {
+                    span: DUMMY_SP,
+                    value: PatchFlagsSet::from(PatchFlags::Hoisted).bits().into(),
+                    raw: None,
+                }))),
+            });
+        }
+
+        if self.scope_id.is_some() {
+            if let Expr::Call(ref mut call_expr) = expr {
+                call_expr.span = self.pure_span();
+            }
+
+            expr = Expr::Call(CallExpr {
+                span: DUMMY_SP,
+                callee: Callee::Expr(Box::new(Expr::Ident(Ident {
+                    span: DUMMY_SP,
+                    sym: fervid_atom!("_withScopeId"),
+                    optional: false,
+                }))),
+                args: vec![ExprOrSpread {
+                    spread: None,
+                    expr: Box::new(Expr::Arrow(ArrowExpr {
+                        span: DUMMY_SP,
+                        params: vec![],
+                        body: Box::new(BlockStmtOrExpr::Expr(Box::new(expr))),
+                        is_async: false,
+                        is_generator: false,
+                        type_params: None,
+                        return_type: None,
+                    })),
+                }],
+                type_args: None,
+            });
+        }
+
+        self.hoist(expr)
+    }
+
+    /// Generates `const _hoisted_1 = ...` declarations for all the hoisted expressions.
+    /// The calls are marked as `/*#__PURE__*/` to be tree-shaken when unused.
+    pub fn generate_hoists(&mut self) -> Vec<ModuleItem> {
+        let mut hoists = std::mem::take(&mut self.hoists);
+        for expr in hoists.iter_mut() {
+            if let Expr::Call(ref mut call_expr) = expr {
+                call_expr.span = self.pure_span();
+            }
+        }
+
+        let mut result = Vec::with_capacity(hoists.len() + 1);
+
+        // `const _withScopeId = n => (_pushScopeId("data-v-abc"), n = n(), _popScopeId(), n)`
+        if let Some(scope_id) = self.scope_id.to_owned() {
+            let uses_scope_id = hoists.iter().any(|expr| {
+                matches!(
+                    expr,
+                    Expr::Call(CallExpr {
+                        callee: Callee::Expr(callee),
+                        ..
+                    }) if matches!(**callee, Expr::Ident(ref ident) if ident.sym == "_withScopeId")
+                )
+            });
+            if uses_scope_id {
+                let init = self.generate_with_scope_id(scope_id);
+                result.push(const_decl(fervid_atom!("_withScopeId"), init));
+            }
+        }
+
+        result.extend(
+            hoists
+                .into_iter()
+                .enumerate()
+                .map(|(idx, expr)| const_decl(hoisted_ident_sym(idx + 1), expr)),
+        );
+
+        result
+    }
+
+    /// Generates `n => (_pushScopeId("data-v-abc"), n = n(), _popScopeId(), n)`
+    fn generate_with_scope_id(&mut self, scope_id: FervidAtom) -> Expr {
+        let n = || Ident {
+            span: DUMMY_SP,
+            sym: fervid_atom!("n"),
+            optional: false,
+        };
+        let call = |callee: Expr, args: Vec<Expr>| {
+            Box::new(Expr::Call(CallExpr {
+                span: DUMMY_SP,
+                callee: Callee::Expr(Box::new(callee)),
+                args: args
+                    .into_iter()
+                    .map(|arg| ExprOrSpread {
+                        spread: None,
+                        expr: Box::new(arg),
+                    })
+                    .collect(),
+                type_args: None,
+            }))
+        };
+
+        let push_scope_id = Expr::Ident(Ident {
+            span: DUMMY_SP,
+            sym: self.get_and_add_import_ident(VueImports::PushScopeId),
+            optional: false,
+        });
+        let pop_scope_id = Expr::Ident(Ident {
+            span: DUMMY_SP,
+            sym: self.get_and_add_import_ident(VueImports::PopScopeId),
+            optional: false,
+        });
+
+        let exprs = vec![
+            call(
+                push_scope_id,
+                vec![Expr::Lit(Lit::Str(Str {
+                    span: DUMMY_SP,
+                    value: scope_id,
+                    raw: None,
+                }))],
+            ),
+            Box::new(Expr::Assign(AssignExpr {
+                span: DUMMY_SP,
+                op: AssignOp::Assign,
+                left: AssignTarget::Simple(SimpleAssignTarget::Ident(BindingIdent {
+                    id: n(),
+                    type_ann: None,
+                })),
+                right: call(Expr::Ident(n()), vec![]),
+            })),
+            call(pop_scope_id, vec![]),
+            Box::new(Expr::Ident(n())),
+        ];
+
+        Expr::Arrow(ArrowExpr {
+            span: DUMMY_SP,
+            params: vec![Pat::Ident(BindingIdent {
+                id: n(),
+                type_ann: None,
+            })],
+            body: Box::new(BlockStmtOrExpr::Expr(Box::new(Expr::Paren(ParenExpr {
+                span: DUMMY_SP,
+                expr: Box::new(Expr::Seq(SeqExpr {
+                    span: DUMMY_SP,
+                    exprs,
+                })),
+            })))),
+            is_async: false,
+            is_generator: false,
+            type_params: None,
+            return_type: None,
+        })
+    }
+
+    /// Creates a span with the `/*#__PURE__*/` comment attached.
+    /// The positions are taken from the end of the range which `swc` reserves for comments,
+    /// so that they never clash with the positions of the source.
+    fn pure_span(&mut self) -> Span {
+        self.comment_positions += 1;
+        let pos = BytePos(BytePos::SYNTHESIZED.0 - self.comment_positions);
+        self.comments.add_pure_comment(pos);
+
+        Span::new(pos, pos, Default::default())
+    }
+}
+
+/// `const name = init`
+fn const_decl(name: FervidAtom, init: Expr) -> ModuleItem {
+    ModuleItem::Stmt(Stmt::Decl(Decl::Var(Box::new(VarDecl {
+        span: DUMMY_SP,
+        kind: VarDeclKind::Const,
+        declare: false,
+        decls: vec![VarDeclarator {
+            span: DUMMY_SP,
+            name: Pat::Ident(BindingIdent {
+                id: Ident {
+                    span: DUMMY_SP,
+                    sym: name,
+                    optional: false,
+                },
+                type_ann: None,
+            }),
+            init: Some(Box::new(init)),
+            definite: false,
+        }],
+    }))))
+}
+
+#[inline]
+fn hoisted_ident_sym(idx: usize) -> FervidAtom {
+    FervidAtom::from(format!("_hoisted_{idx}"))
+}
+
+/// Checks if the node and all of its descendants never change,
+/// meaning that the node can be created only once
+pub fn is_static_node(node: &Node) -> bool {
+    match node {
+        Node::Text(_, _) | Node::Comment(_, _) => true,
+        Node::Interpolation(interpolation) => is_static(&interpolation.value),
+        Node::Element(element_node) => is_static_element(element_node),
+        Node::ConditionalSeq(_) => false,
+    }
+}
+
+/// Checks if the element is a plain HTML element without any dynamic parts
+pub fn is_static_element(element_node: &ElementNode) -> bool {
+    matches!(element_node.kind, ElementKind::Element)
+        && element_node.starting_tag.tag_name != "template"
+        && element_node.patch_hints.flags.is_empty()
+        && element_node.patch_hints.props.is_empty()
+        && !element_node.patch_hints.should_use_block
+        && element_node
+            .starting_tag
+            .directives
+            .as_ref()
+            .map_or(true, |directives| !has_render_directives(directives))
+        && has_static_props(element_node)
+        && element_node.children.iter().all(is_static_node)
+}
+
+/// Checks if the props object of an element can be hoisted,
+/// i.e. all the attributes are static and no directive adds props to it
+pub fn has_static_props(element_node: &ElementNode) -> bool {
+    let has_prop_directives =
+        element_node
+            .starting_tag
+            .directives
+            .as_ref()
+            .map_or(false, |directives| {
+                !directives.v_model.is_empty()
+                    || directives.v_html.is_some()
+                    || directives.v_text.is_some()
+            });
+
+    !has_prop_directives
+        && element_node
+            .starting_tag
+            .attributes
+            .iter()
+            .all(|attr| match attr {
+                AttributeOrBinding::RegularAttribute { .. } => true,
+                AttributeOrBinding::VBind(v_bind) => {
+                    matches!(v_bind.argument, Some(StrOrExpr::Str(_))) && is_static(&v_bind.value)
+                }
+                AttributeOrBinding::VOn(_) => false,
+            })
+}
+
+/// Checks if there are directives affecting how or whether the element is rendered
+fn has_render_directives(directives: &VueDirectives) -> bool {
+    !directives.custom.is_empty()
+        || directives.v_else.is_some()
+        || directives.v_else_if.is_some()
+        || directives.v_for.is_some()
+        || directives.v_html.is_some()
+        || directives.v_if.is_some()
+        || directives.v_memo.is_some()
+        || !directives.v_model.is_empty()
+        || directives.v_once.is_some()
+        || directives.v_show.is_some()
+        || directives.v_slot.is_some()
+        || directives.v_text.is_some()
+}
+
+#[cfg(test)]
+mod tests {
+    use fervid_core::{Interpolation, PatchHints};
+    use swc_core::{common::FileName, ecma::ast::Module};
+
+    use super::*;
+    use crate::test_utils::{
+        element, interpolation, js, regular_attribute, v_bind_attribute, v_on_attribute,
+    };
+
+    #[test]
+    fn it_hoists_static_elements() {
+        // <div><span class="foo">static</span>{{ msg }}</div>
+        test_out(
+            element(
+                "div",
+                vec![],
+                vec![
+                    Node::Element(element(
+                        "span",
+                        vec![regular_attribute("class", "foo")],
+                        vec![Node::Text("static".into(), DUMMY_SP)],
+                    )),
+                    interpolation("msg"),
+                ],
+            ),
+            r#"_createElementVNode("div",null,[_hoisted_1,_createTextVNode(_toDisplayString(msg),1)])"#,
+            r#"const _hoisted_1=_createElementVNode("span",{class:"foo"},"static",-1);"#,
+            false,
+        );
+
+        // <div><br/><span :foo="1">{{ 1 + 1 }}</span><span>{{ msg }}</span></div>
+        test_out(
+            element(
+                "div",
+                vec![],
+                vec![
+                    Node::Element(element("br", vec![], vec![])),
+                    Node::Element(element(
+                        "span",
+                        vec![v_bind_attribute("foo", "1")],
+                        vec![Node::Interpolation(Interpolation {
+                            value: js("1 + 1"),
+                            template_scope: 0,
+                            patch_flag: false,
+                            span: DUMMY_SP,
+                        })],
+                    )),
+                    Node::Element(ElementNode {
+                        patch_hints: text_patch_hints(),
+                        ..element("span", vec![], vec![interpolation("msg")])
+                    }),
+                ],
+            ),
+            r#"_createElementVNode("div",null,[_hoisted_1,_hoisted_2,_createElementVNode("span",null,_toDisplayString(msg),1)])"#,
+            r#"const _hoisted_1=_createElementVNode("br",null,null,-1);const _hoisted_2=_createElementVNode("span",{foo:1},_toDisplayString(1+1),-1);"#,
+            false,
+        );
+    }
+
+    #[test]
+    fn it_hoists_static_text() {
+        // <div>static text<span>{{ msg }}</span></div>
+        test_out(
+            element(
+                "div",
+                vec![],
+                vec![
+                    Node::Text("static text".into(), DUMMY_SP),
+                    Node::Element(ElementNode {
+                        patch_hints: text_patch_hints(),
+                        ..element("span", vec![], vec![interpolation("msg")])
+                    }),
+                ],
+            ),
+            r#"_createElementVNode("div",null,[_hoisted_1,_createElementVNode("span",null,_toDisplayString(msg),1)])"#,
+            r#"const _hoisted_1=_createTextVNode("static text");"#,
+            false,
+        );
+    }
+
+    #[test]
+    fn it_hoists_static_props() {
+        // <div class="foo" :bar="true">{{ msg }}</div>
+        test_out(
+            ElementNode {
+                patch_hints: text_patch_hints(),
+                ..element(
+                    "div",
+                    vec![
+                        regular_attribute("class", "foo"),
+                        v_bind_attribute("bar", "true"),
+                    ],
+                    vec![interpolation("msg")],
+                )
+            },
+            r#"(_openBlock(),_createElementBlock("div",_hoisted_1,_toDisplayString(msg),1))"#,
+            r#"const _hoisted_1={bar:true,class:"foo"};"#,
+            true,
+        );
+
+        // Props with event handlers are not static
+        // <div class="foo" @click="handle">{{ msg }}</div>
+        test_out(
+            ElementNode {
+                patch_hints: text_patch_hints(),
+                ..element(
+                    "div",
+                    vec![
+                        regular_attribute("class", "foo"),
+                        v_on_attribute("onClick", "handle"),
+                    ],
+                    vec![interpolation("msg")],
+                )
+            },
+            r#"_createElementVNode("div",{onClick:handle,class:"foo"},_toDisplayString(msg),1)"#,
+            "",
+            false,
+        );
+    }
+
+    #[test]
+    fn it_hoists_nodes_with_scope_id() {
+        // <div class="foo"><span>static</span>{{ msg }}</div>
+        let input = element(
+            "div",
+            vec![regular_attribute("class", "foo")],
+            vec![
+                Node::Element(element(
+                    "span",
+                    vec![],
+                    vec![Node::Text("static".into(), DUMMY_SP)],
+                )),
+                interpolation("msg"),
+            ],
+        );
+
+        let mut ctx = CodegenContext {
+            hoist_static: true,
+            scope_id: Some("data-v-abcd1234".into()),
+            ..Default::default()
+        };
+        let out = ctx.generate_element_vnode(&input, false);
+        assert_eq!(
+            crate::test_utils::to_str(out),
+            r#"_createElementVNode("div",_hoisted_1,[_hoisted_2,_createTextVNode(_toDisplayString(msg),1)])"#
+        );
+
+        let hoists = Module {
+            span: DUMMY_SP,
+            body: ctx.generate_hoists(),
+            shebang: None,
+        };
+        let (hoists, _) = CodegenContext::stringify(
+            "",
+            &hoists,
+            FileName::Anon,
+            false,
+            true,
+            Some(&ctx.comments),
+        );
+        assert_eq!(
+            hoists,
+            r#"const _withScopeId=n=>(_pushScopeId("data-v-abcd1234"),n=n(),_popScopeId(),n);const _hoisted_1={class:"foo"};const _hoisted_2=/*#__PURE__*/_withScopeId(()=>/*#__PURE__*/_createElementVNode("span",null,"static",-1));"#
+        );
+        assert!(ctx
+            .bindings_helper
+            .vue_imports
+            .contains(VueImports::PushScopeId));
+        assert!(ctx
+            .bindings_helper
+            .vue_imports
+            .contains(VueImports::PopScopeId));
+    }
+
+    fn text_patch_hints() -> PatchHints {
+        PatchHints {
+            flags: PatchFlags::Text.into(),
+            props: vec![],
+            should_use_block: false,
+        }
+    }
+
+    fn test_out(input: ElementNode, expected: &str, expected_hoists: &str, wrap_in_block: bool) {
+        let mut ctx = CodegenContext {
+            hoist_static: true,
+            ..Default::default()
+        };
+        let out = ctx.generate_element_vnode(&input, wrap_in_block);
+        assert_eq!(crate::test_utils::to_str(out), expected);
+
+        let hoists = Module {
+            span: DUMMY_SP,
+            body: ctx.generate_hoists(),
+            shebang: None,
+        };
+        assert_eq!(crate::test_utils::to_str(hoists), expected_hoists);
+    }
+}
@@ -0,0 +1,532 @@
+//! Static stringification: long runs of static nodes are serialized to an HTML string
+//! and created using a single `_createStaticVNode("<div>...</div>", n)` call.
+//!
+//! https://github.com/vuejs/core/blob/v3.4.21/packages/compiler-dom/src/transforms/stringifyStatic.ts
+
+use fervid_core::{
+    is_boolean_attr, is_known_html_attr, is_svg_tag, is_void_tag, AttributeOrBinding, ElementNode,
+    Node, StrOrExpr, VBindDirective, VueImports,
+};
+use swc_core::{
+    common::DUMMY_SP,
+    ecma::ast::{
+        CallExpr, Callee, Expr, ExprOrSpread, Ident, Lit, Number, Str, UnaryExpr, UnaryOp,
+    },
+};
+
+use super::is_static_element;
+use crate::{context::CodegenContext, ssr::escape_html, text::condense_whitespace};
+
+/// A chunk is stringified when it has at least this many nodes...
+const NODE_COUNT: usize = 20;
+
+/// ...or at least this many elements with attributes
+const ELEMENT_WITH_BINDING_COUNT: usize = 5;
+
+impl CodegenContext {
+    /// Whether static nodes can be stringified at this point of code generation.
+    ///
+    /// Unlike [`CodegenContext::can_hoist_nodes`], this is allowed with scoped styles,
+    /// because the scope id is a part of the generated HTML string.
+    #[inline]
+    pub(crate) fn can_stringify_static(&self) -> bool {
+        self.hoist_static && !self.is_cache_disabled
+    }
+
+    /// Generates `_createStaticVNode("<html>", n)` for a chunk found by [`find_static_chunk`]
+    pub(crate) fn generate_static_vnode(&mut self, nodes: &[&Node]) -> Expr {
+        let mut html = String::new();
+        let mut number_of_nodes = 0;
+        let mut was_text = false;
+
+        for node in nodes {
+            // Adjacent text nodes end up in one DOM node
+            let is_text = is_text_node(node);
+            if !(is_text && was_text) {
+                number_of_nodes += 1;
+            }
+            was_text = is_text;
+
+            self.stringify_node(node, false, &mut html);
+        }
+
+        Expr::Call(CallExpr {
+            span: DUMMY_SP,
+            callee: Callee::Expr(Box::new(Expr::Ident(Ident {
+                span: DUMMY_SP,
+                sym: self.get_and_add_import_ident(VueImports::CreateStaticVNode),
+                optional: false,
+            }))),
+            args: vec![
+                ExprOrSpread {
+                    spread: None,
+                    expr: Box::new(Expr::Lit(Lit::Str(Str {
+                        span: DUMMY_SP,
+                        value: html.into(),
+                        raw: None,
+                    }))),
+                },
+                ExprOrSpread {
+                    spread: None,
+                    expr: Box::new(Expr::Lit(Lit::Num(Number {
+                        span: DUMMY_SP,
+                        value: number_of_nodes as f64,
+                        raw: None,
+                    }))),
+                },
+            ],
+            type_args: None,
+        })
+    }
+
+    fn stringify_node(&self, node: &Node, is_svg: bool, out: &mut String) {
+        match node {
+            Node::Text(contents, _) => out.push_str(&escape_html(&condense_whitespace(contents))),
+            Node::Interpolation(interpolation) => {
+                if let Some(value) = evaluate_constant(&interpolation.value) {
+                    out.push_str(&escape_html(&value.to_display_string()));
+                }
+            }
+            Node::Comment(comment, _) => {
+                out.push_str("<!--");
+                out.push_str(&escape_html(comment));
+                out.push_str("-->");
+            }
+            Node::Element(element_node) => self.stringify_element(element_node, is_svg, out),
+            // Never a part of a chunk, see `is_stringifiable_chunk_node`
+            Node::ConditionalSeq(_) => {}
+        }
+    }
+
+    fn stringify_element(&self, element_node: &ElementNode, is_svg: bool, out: &mut String) {
+        let tag_name = &element_node.starting_tag.tag_name;
+        let is_svg = is_svg || is_svg_tag(tag_name);
+
+        out.push('<');
+        out.push_str(tag_name);
+
+        for attr in element_node.starting_tag.attributes.iter() {
+            match attr {
+                AttributeOrBinding::RegularAttribute { name, value, .. } => {
+                    push_attr(out, name, value);
+                }
+                AttributeOrBinding::VBind(v_bind) => {
+                    let Some(StrOrExpr::Str(ref name)) = v_bind.argument else {
+                        continue;
+                    };
+                    let Some(value) = evaluate_constant(&v_bind.value) else {
+                        continue;
+                    };
+
+                    let name = attr_name(name, v_bind);
+                    match value {
+                        Constant::Null => {}
+                        Constant::Bool(false) if is_boolean_attr(&name) => {}
+                        value => {
+                            let value = value.to_display_string();
+                            out.push(' ');
+                            out.push_str(&name);
+                            out.push_str("=\"");
+                            out.push_str(&escape_html(&value));
+                            out.push('"');
+                        }
+                    }
+                }
+                AttributeOrBinding::VOn(_) => {}
+            }
+        }
+
+        if let Some(ref scope_id) = self.scope_id {
+            out.push(' ');
+            out.push_str(scope_id);
+        }
+
+        out.push('>');
+
+        if is_void_tag(tag_name) {
+            return;
+        }
+
+        let children_are_svg = is_svg && tag_name != "foreignObject";
+        for child in element_node.children.iter() {
+            self.stringify_node(child, children_are_svg, out);
+        }
+
+        out.push_str("</");
+        out.push_str(tag_name);
+        out.push('>');
+    }
+}
+
+/// Finds the chunk of consecutive stringifiable nodes starting at `start`.
+///
+/// Returns the length of the chunk and whether it is large enough to be stringified.
+/// When the chunk is not stringified, its nodes should be generated as usual.
+pub(crate) fn find_static_chunk(nodes: &[&Node], start: usize) -> (usize, bool) {
+    // The chunk cannot take a part of a text sequence, it is generated as a single text node
+    if start > 0 && is_text_node(nodes[start - 1]) && is_text_node(nodes[start]) {
+        return (0, false);
+    }
+
+    let mut end = start;
+    while end < nodes.len() && is_stringifiable_chunk_node(nodes[end]) {
+        end += 1;
+    }
+    while end > start
+        && end < nodes.len()
+        && is_text_node(nodes[end])
+        && is_text_node(nodes[end - 1])
+    {
+        end -= 1;
+    }
+
+    let mut node_count = 0;
+    let mut element_with_binding_count = 0;
+    let mut was_text = false;
+    for node in nodes[start..end].iter() {
+        let is_text = is_text_node(node);
+        if let Node::Element(element_node) = node {
+            let (nc, ec) = analyze_element(element_node);
+            node_count += nc;
+            element_with_binding_count += ec;
+        } else if !(is_text && was_text) {
+            node_count += 1;
+        }
+        was_text = is_text;
+    }
+
+    let should_stringify =
+        node_count >= NODE_COUNT || element_with_binding_count >= ELEMENT_WITH_BINDING_COUNT;
+
+    (end - start, should_stringify)
+}
+
+/// Whether the node can be a part of a stringified chunk
+fn is_stringifiable_chunk_node(node: &Node) -> bool {
+    match node {
+        Node::Text(_, _) => true,
+        Node::Interpolation(interpolation) => evaluate_constant(&interpolation.value).is_some(),
+        Node::Element(element_node) => {
+            is_static_element(element_node) && is_stringifiable_element(element_node, false)
+        }
+        Node::Comment(_, _) | Node::ConditionalSeq(_) => false,
+    }
+}
+
+/// Checks that the element and all its descendants can be represented as plain HTML.
+/// The element itself must be static, i.e. [`is_static_element`] returned `true`.
+fn is_stringifiable_element(element_node: &ElementNode, is_svg: bool) -> bool {
+    let tag_name = &element_node.starting_tag.tag_name;
+    if is_non_stringifiable_tag(tag_name) {
+        return false;
+    }
+
+    let is_svg = is_svg || is_svg_tag(tag_name);
+
+    let are_attrs_stringifiable =
+        element_node
+            .starting_tag
+            .attributes
+            .iter()
+            .all(|attr| match attr {
+                AttributeOrBinding::RegularAttribute { name, .. } => {
+                    is_stringifiable_attr(name, is_svg)
+                }
+                AttributeOrBinding::VBind(v_bind) => {
+                    let Some(StrOrExpr::Str(ref name)) = v_bind.argument else {
+                        return false;
+                    };
+                    let name = attr_name(name, v_bind);
+
+                    !v_bind.is_prop
+                        && is_stringifiable_attr(&name, is_svg)
+                        && match evaluate_constant(&v_bind.value) {
+                            // `normalizeClass` and `normalizeStyle` are not replicated here
+                            Some(Constant::Str(_)) => true,
+                            Some(_) => name != "class" && name != "style",
+                            None => false,
+                        }
+                }
+                AttributeOrBinding::VOn(_) => false,
+            });
+
+    let children_are_svg = is_svg && tag_name != "foreignObject";
+
+    are_attrs_stringifiable
+        && element_node.children.iter().all(|child| match child {
+            Node::Text(_, _) | Node::Comment(_, _) => true,
+            Node::Interpolation(interpolation) => evaluate_constant(&interpolation.value).is_some(),
+            Node::Element(child) => is_stringifiable_element(child, children_are_svg),
+            Node::ConditionalSeq(_) => false,
+        })
+}
+
+/// Counts the nodes and the elements with attributes in an element subtree
+fn analyze_element(element_node: &ElementNode) -> (usize, usize) {
+    let mut node_count = 1;
+    let mut element_with_binding_count = 0;
+    if !element_node.starting_tag.attributes.is_empty() {
+        element_with_binding_count += 1;
+    }
+
+    for child in element_node.children.iter() {
+        if let Node::Element(child) = child {
+            let (nc, ec) = analyze_element(child);
+            node_count += nc;
+            element_with_binding_count += ec;
+        } else {
+            node_count += 1;
+        }
+    }
+
+    (node_count, element_with_binding_count)
+}
+
+/// Elements which cannot be created via `innerHTML` outside of their parent,
+/// e.g. `<tr>` is dropped by the HTML parser outside of a `<table>`
+fn is_non_stringifiable_tag(tag_name: &str) -> bool {
+    matches!(
+        tag_name,
+        "caption" | "thead" | "tr" | "th" | "tbody" | "td" | "tfoot" | "colgroup" | "col"
+    )
+}
+
+/// Only the attributes which Vue would always set as attributes (and not DOM properties)
+/// can be a part of the HTML string
+fn is_stringifiable_attr(name: &str, is_svg: bool) -> bool {
+    if name.starts_with("data-") || name.starts_with("aria-") {
+        return true;
+    }
+
+    if is_svg {
+        name != "innerHTML" && name != "textContent"
+    } else {
+        is_known_html_attr(name)
+    }
+}
+
+#[inline]
+fn is_text_node(node: &Node) -> bool {
+    matches!(node, Node::Text(_, _) | Node::Interpolation(_))
+}
+
+/// Gets the attribute name of a `v-bind`, applying the `.camel` modifier
+fn attr_name(name: &str, v_bind: &VBindDirective) -> String {
+    if !v_bind.is_camel {
+        return name.to_owned();
+    }
+
+    let mut out = String::with_capacity(name.len());
+    let mut should_uppercase = false;
+    for ch in name.chars() {
+        if ch == '-' {
+            should_uppercase = true;
+        } else if should_uppercase {
+            out.push(ch.to_ascii_uppercase());
+            should_uppercase = false;
+        } else {
+            out.push(ch);
+        }
+    }
+    out
+}
+
+/// A value of a constant expression which can be serialized to HTML
+enum Constant {
+    Null,
+    Bool(bool),
+    Str(String),
+}
+
+impl Constant {
+    /// Same as `toDisplayString` from Vue
+    fn to_display_string(&self) -> String {
+        match self {
+            Constant::Null => String::new(),
+            Constant::Bool(v) => v.to_string(),
+            Constant::Str(s) => s.to_owned(),
+        }
+    }
+}
+
+/// Evaluates the literal expressions, e.g. `"foo"`, `1`, `-1.5`, `true` or `null`.
+/// Other expressions are not evaluated, so the nodes using them are not stringified.
+fn evaluate_constant(expr: &Expr) -> Option<Constant> {
+    match expr {
+        Expr::Lit(Lit::Str(s)) => Some(Constant::Str(s.value.to_string())),
+        Expr::Lit(Lit::Bool(b)) => Some(Constant::Bool(b.value)),
+        Expr::Lit(Lit::Null(_)) => Some(Constant::Null),
+        Expr::Lit(Lit::Num(n)) => number_to_string(n.value).map(Constant::Str),
+        Expr::Unary(UnaryExpr {
+            op: UnaryOp::Minus,
+            arg,
+            ..
+        }) => match arg.as_ref() {
+            Expr::Lit(Lit::Num(n)) => number_to_string(-n.value).map(Constant::Str),
+            _ => None,
+        },
+        Expr::Tpl(tpl) if tpl.exprs.is_empty() => tpl
+            .quasis
+            .first()
+            .and_then(|quasi| quasi.cooked.as_ref())
+            .map(|cooked| Constant::Str(cooked.to_string())),
+        Expr::Paren(paren) => evaluate_constant(&paren.expr),
+        _ => None,
+    }
+}
+
+/// Converts the number the same way JS does.
+/// Numbers which JS would print in the exponential notation are not supported.
+fn number_to_string(value: f64) -> Option<String> {
+    if value == 0.0 {
+        return Some("0".to_owned());
+    }
+
+    let abs = value.abs();
+    if !(1e-6..1e21).contains(&abs) {
+        return None;
+    }
+
+    Some(value.to_string())
+}
+
+fn push_attr(out: &mut String, name: &str, value: &str) {
+    out.push(' ');
+    out.push_str(name);
+    if !value.is_empty() {
+        out.push_str("=\"");
+        out.push_str(&escape_html(value));
+        out.push('"');
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use swc_core::{common::DUMMY_SP, ecma::ast::Module};
+
+    use super::*;
+    use crate::test_utils::{element, interpolation, regular_attribute, v_bind_attribute};
+
+    #[test]
+    fn it_stringifies_elements_with_attributes() {
+        // <div><span class="a">1</span>...<span class="a">5</span>{{ msg }}</div>
+        let mut children: Vec<Node> = (1..=5)
+            .map(|idx| {
+                Node::Element(element(
+                    "span",
+                    vec![regular_attribute("class", "a")],
+                    vec![Node::Text(idx.to_string().into(), DUMMY_SP)],
+                ))
+            })
+            .collect();
+        children.push(interpolation("msg"));
+
+        test_out(
+            element("div", vec![], children),
+            None,
+            r#"_createElementVNode("div",null,[_hoisted_1,_createTextVNode(_toDisplayString(msg),1)])"#,
+            r#"const _hoisted_1=_createStaticVNode('<span class="a">1</span><span class="a">2</span><span class="a">3</span><span class="a">4</span><span class="a">5</span>',5);"#,
+        );
+    }
+
+    #[test]
+    fn it_stringifies_many_nodes() {
+        // <div><ul><li>0</li>...<li>9</li></ul>{{ msg }}</div>
+        let items = (0..10)
+            .map(|idx| {
+                Node::Element(element(
+                    "li",
+                    vec![],
+                    vec![Node::Text(idx.to_string().into(), DUMMY_SP)],
+                ))
+            })
+            .collect();
+
+        test_out(
+            element(
+                "div",
+                vec![],
+                vec![
+                    Node::Element(element("ul", vec![], items)),
+                    interpolation("msg"),
+                ],
+            ),
+            None,
+            r#"_createElementVNode("div",null,[_hoisted_1,_createTextVNode(_toDisplayString(msg),1)])"#,
+            r#"const _hoisted_1=_createStaticVNode("<ul><li>0</li><li>1</li><li>2</li><li>3</li><li>4</li><li>5</li><li>6</li><li>7</li><li>8</li><li>9</li></ul>",1);"#,
+        );
+    }
+
+    #[test]
+    fn it_adds_scope_id_and_escapes() {
+        // <div><p title="a&quot;b" :id="'x<y'" :hidden="false" data-n="1">1 < 2</p>...{{ msg }}</div>
+        let mut children: Vec<Node> = (0..5)
+            .map(|_| {
+                Node::Element(element(
+                    "p",
+                    vec![
+                        regular_attribute("title", "a\"b"),
+                        v_bind_attribute("hidden", "false"),
+                        v_bind_attribute("data-n", "1"),
+                    ],
+                    vec![Node::Text("1 < 2".into(), DUMMY_SP)],
+                ))
+            })
+            .collect();
+        children.push(interpolation("msg"));
+
+        let p = r#"<p title="a&quot;b" data-n="1" data-v-abcd1234>1 &lt; 2</p>"#;
+        test_out(
+            element("div", vec![], children),
+            Some("data-v-abcd1234"),
+            r#"_createElementVNode("div",null,[_hoisted_1,_createTextVNode(_toDisplayString(msg),1)])"#,
+            &format!(r#"const _hoisted_1=_createStaticVNode('{p}{p}{p}{p}{p}',5);"#),
+        );
+    }
+
+    #[test]
+    fn it_does_not_stringify_small_or_unsupported_chunks() {
+        // Too few elements with attributes: hoisted one by one
+        let mut children: Vec<Node> = (0..4)
+            .map(|_| Node::Element(element("i", vec![regular_attribute("id", "a")], vec![])))
+            .collect();
+        children.push(interpolation("msg"));
+        test_out(
+            element("div", vec![], children),
+            None,
+            r#"_createElementVNode("div",null,[_hoisted_1,_hoisted_2,_hoisted_3,_hoisted_4,_createTextVNode(_toDisplayString(msg),1)])"#,
+            r#"const _hoisted_1=_createElementVNode("i",{id:"a"},null,-1);const _hoisted_2=_createElementVNode("i",{id:"a"},null,-1);const _hoisted_3=_createElementVNode("i",{id:"a"},null,-1);const _hoisted_4=_createElementVNode("i",{id:"a"},null,-1);"#,
+        );
+
+        // Unknown attributes and table rows cannot be a part of the HTML string
+        let unknown_attr = element("i", vec![regular_attribute("foo", "a")], vec![]);
+        let tr = element("tr", vec![regular_attribute("id", "a")], vec![]);
+        for unsupported in [unknown_attr, tr] {
+            let mut nodes: Vec<Node> = (0..5)
+                .map(|_| Node::Element(element("i", vec![regular_attribute("id", "a")], vec![])))
+                .collect();
+            nodes.insert(2, Node::Element(unsupported));
+            let nodes: Vec<&Node> = nodes.iter().collect();
+
+            assert_eq!(find_static_chunk(&nodes, 0), (2, false));
+            assert_eq!(find_static_chunk(&nodes, 3), (3, false));
+        }
+    }
+
+    fn test_out(input: ElementNode, scope_id: Option<&str>, expected: &str, expected_hoists: &str) {
+        let mut ctx = CodegenContext {
+            hoist_static: true,
+            scope_id: scope_id.map(Into::into),
+            ..Default::default()
+        };
+        let out = ctx.generate_element_vnode(&input, false);
+        assert_eq!(crate::test_utils::to_str(out), expected);
+
+        let hoists = Module {
+            span: DUMMY_SP,
+            body: ctx.generate_hoists(),
+            shebang: None,
+        };
+        assert_eq!(crate::test_utils::to_str(hoists), expected_hoists);
+    }
+}
@@ -1,4 +1,4 @@
-use fervid_core::{FervidAtom, VueImports};
+use fervid_core::{FervidAtom, VueImports, VueSsrImports};
 use swc_core::{
     common::DUMMY_SP,
     ecma::ast::{Ident, ImportNamedSpecifier, ImportSpecifier, ModuleExportName},
@@ -21,37 +21,53 @@ impl CodegenContext {
         vue_import.as_atom()
     }
 
+    pub fn get_and_add_ssr_import_ident(&mut self, ssr_import: VueSsrImports) -> FervidAtom {
+        self.ssr_imports |= ssr_import;
+        ssr_import.as_atom()
+    }
+
     /// Generates all the imports used by template generation.
     /// All of the imports come from 'vue'.
     pub fn generate_imports(&self) -> Vec<ImportSpecifier> {
-        let mut result = Vec::new();
-        for import in self.bindings_helper.vue_imports.into_iter() {
-            let import_raw = import.as_str();
-
-            let import_local = Ident {
-                span: DUMMY_SP,
-                sym: import.as_atom(),
-                optional: false,
-            };
-
-            let import_vue = Some(ModuleExportName::Ident(Ident {
-                span: DUMMY_SP,
-                sym: FervidAtom::from(&import_raw[1..]),
-                optional: false,
-            }));
-
-            result.push(ImportSpecifier::Named(ImportNamedSpecifier {
-                span: DUMMY_SP,
-                local: import_local,
-                imported: import_vue,
-                is_type_only: false,
-            }));
-        }
+        self.bindings_helper
+            .vue_imports
+            .into_iter()
+            .map(|import| generate_import_specifier(import.as_str()))
+            .collect()
+    }
 
-        result
+    /// Generates all the imports used by SSR template generation.
+    /// All of the imports come from 'vue/server-renderer'.
+    pub fn generate_ssr_imports(&self) -> Vec<ImportSpecifier> {
+        self.ssr_imports
+            .into_iter()
+            .map(|import| generate_import_specifier(import.as_str()))
+            .collect()
     }
 }
 
+/// Generates `imported as _imported` from the local name `_imported`
+fn generate_import_specifier(import_raw: &str) -> ImportSpecifier {
+    let import_local = Ident {
+        span: DUMMY_SP,
+        sym: FervidAtom::from(import_raw),
+        optional: false,
+    };
+
+    let import_vue = Some(ModuleExportName::Ident(Ident {
+        span: DUMMY_SP,
+        sym: FervidAtom::from(&import_raw[1..]),
+        optional: false,
+    }));
+
+    ImportSpecifier::Named(ImportNamedSpecifier {
+        span: DUMMY_SP,
+        local: import_local,
+        imported: import_vue,
+        is_type_only: false,
+    })
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -0,0 +1,538 @@
+use fervid_core::{
+    check_attribute_name, fervid_atom, AttributeOrBinding, BuiltinType, ElementNode, Node,
+    StrOrExpr, VBindDirective, VueImports, VueSsrImports,
+};
+use swc_core::{
+    common::DUMMY_SP,
+    ecma::ast::{
+        ArrayLit, BlockStmt, Bool, Expr, ExprOrSpread, IfStmt, KeyValueProp, Lit, MemberExpr,
+        MemberProp, Number, ObjectLit, Pat, Prop, PropOrSpread, ReturnStmt, Stmt,
+    },
+};
+
+use super::{call_expr, ident_expr, ident_pat, null_expr, ssr_arrow, str_expr, SsrBuffer};
+use crate::{context::CodegenContext, utils::str_or_expr_to_propname};
+
+/// Props of `<TransitionGroup>` which are not rendered as attributes of its tag
+const TRANSITION_GROUP_PROPS: [&str; 7] = [
+    "tag",
+    "name",
+    "appear",
+    "css",
+    "mode",
+    "duration",
+    "move-class",
+];
+
+impl CodegenContext {
+    /// Generates `_push(_ssrRenderComponent(_component_foo, { props }, { slots }, _parent))`
+    pub fn generate_ssr_component(
+        &mut self,
+        component_node: &ElementNode,
+        buf: &mut SsrBuffer,
+        is_root: bool,
+    ) {
+        let span = component_node.span;
+
+        let component_identifier =
+            self.get_component_identifier(&component_node.starting_tag.tag_name, span);
+
+        let attributes_obj = self.generate_component_attributes(component_node);
+        let props = self.ssr_component_props(attributes_obj, component_node, is_root);
+        let slots = self.generate_ssr_component_slots(component_node);
+
+        let mut args = vec![
+            component_identifier,
+            props.unwrap_or_else(null_expr),
+            slots.unwrap_or_else(null_expr),
+            ident_expr(fervid_atom!("_parent")),
+        ];
+        if buf.is_slot {
+            args.push(ident_expr(fervid_atom!("_scopeId")));
+        }
+
+        let render_component = self.ssr_call(VueSsrImports::SsrRenderComponent, args);
+        buf.push_call(render_component);
+    }
+
+    /// Generates the Vue builtins: `<slot>`, `<Teleport>`, `<Suspense>`, etc.
+    pub fn generate_ssr_builtin(
+        &mut self,
+        element_node: &ElementNode,
+        builtin_type: BuiltinType,
+        buf: &mut SsrBuffer,
+        is_root: bool,
+    ) {
+        match builtin_type {
+            BuiltinType::Slot => self.generate_ssr_slot_outlet(element_node, buf),
+            BuiltinType::Teleport => self.generate_ssr_teleport(element_node, buf),
+            BuiltinType::Suspense => self.generate_ssr_suspense(element_node, buf),
+            BuiltinType::TransitionGroup => self.generate_ssr_transition_group(element_node, buf),
+
+            // `<Transition>` and `<KeepAlive>` render their only child as-is
+            BuiltinType::Transition | BuiltinType::KeepAlive => {
+                let is_single_child = element_node.children.len() == 1;
+                for child in element_node.children.iter() {
+                    self.generate_ssr_node(child, buf, is_root && is_single_child);
+                }
+            }
+
+            // `<component :is>` is only known at runtime, thus it is rendered as a VNode
+            BuiltinType::Component => {
+                let vnode = self.generate_component_builtin(element_node);
+                let render_vnode = self.ssr_call(
+                    VueSsrImports::SsrRenderVNode,
+                    vec![
+                        ident_expr(fervid_atom!("_push")),
+                        vnode,
+                        ident_expr(fervid_atom!("_parent")),
+                    ],
+                );
+                buf.push_stmt(expr_stmt(render_vnode));
+            }
+        }
+    }
+
+    /// Merges component props with `v-bind="obj"`, `_attrs` (for root) and `v-show`
+    fn ssr_component_props(
+        &mut self,
+        attributes_obj: ObjectLit,
+        component_node: &ElementNode,
+        is_root: bool,
+    ) -> Option<Expr> {
+        let mut merge_args = Vec::with_capacity(3);
+        if !attributes_obj.props.is_empty() {
+            merge_args.push(Expr::Object(attributes_obj));
+        }
+
+        // `v-bind="obj"`, e.g. the `_cssVars` of the root
+        for attr in component_node.starting_tag.attributes.iter() {
+            if let AttributeOrBinding::VBind(VBindDirective {
+                argument: None,
+                value,
+                ..
+            }) = attr
+            {
+                merge_args.push((**value).to_owned());
+            }
+        }
+
+        if let Some(v_show) = component_node
+            .starting_tag
+            .directives
+            .as_ref()
+            .and_then(|d| d.v_show.as_ref())
+        {
+            merge_args.push(Expr::Object(ObjectLit {
+                span: DUMMY_SP,
+                props: vec![PropOrSpread::Prop(Box::new(Prop::KeyValue(KeyValueProp {
+                    key: str_or_expr_to_propname(StrOrExpr::Str(fervid_atom!("style")), DUMMY_SP),
+                    value: Box::new(Expr::Cond(swc_core::ecma::ast::CondExpr {
+                        span: DUMMY_SP,
+                        test: v_show.to_owned(),
+                        cons: Box::new(null_expr()),
+                        alt: Box::new(Expr::Object(ObjectLit {
+                            span: DUMMY_SP,
+                            props: vec![PropOrSpread::Prop(Box::new(Prop::KeyValue(
+                                KeyValueProp {
+                                    key: str_or_expr_to_propname(
+                                        StrOrExpr::Str(fervid_atom!("display")),
+                                        DUMMY_SP,
+                                    ),
+                                    value: Box::new(str_expr("none", DUMMY_SP)),
+                                },
+                            )))],
+                        })),
+                    })),
+                })))],
+            }));
+        }
+
+        if is_root {
+            merge_args.push(ident_expr(fervid_atom!("_attrs")));
+        }
+
+        match merge_args.len() {
+            0 => None,
+            1 => merge_args.pop(),
+            _ => Some(call_expr(
+                self.get_and_add_import_ident(VueImports::MergeProps),
+                merge_args,
+            )),
+        }
+    }
+
+    /// Generates the slots object for `_ssrRenderComponent`.
+    ///
+    /// Each slot function renders using `_push` when it is available
+    /// and falls back to returning VNodes otherwise:
+    /// ```js
+    /// {
+    ///   default: _withCtx((_, _push, _parent, _scopeId) => {
+    ///     if (_push) {
+    ///       _push(`<span${_scopeId}>hi</span>`)
+    ///     } else {
+    ///       return [_createVNode("span", null, "hi")]
+    ///     }
+    ///   }),
+    ///   _: 1
+    /// }
+    /// ```
+    fn generate_ssr_component_slots(&mut self, component_node: &ElementNode) -> Option<Expr> {
+        if component_node.children.is_empty() {
+            return None;
+        }
+
+        let mut slots = Vec::<PropOrSpread>::new();
+        let mut default_children = Vec::<&Node>::new();
+
+        // `<Comp v-slot="{ foo }">` makes all the children belong to the default slot
+        let component_v_slot = component_node
+            .starting_tag
+            .directives
+            .as_ref()
+            .and_then(|d| d.v_slot.as_ref());
+
+        for child in component_node.children.iter() {
+            let named_slot = match child {
+                Node::Element(ElementNode {
+                    starting_tag,
+                    children,
+                    ..
+                }) if component_v_slot.is_none() && starting_tag.tag_name == "template" => {
+                    starting_tag
+                        .directives
+                        .as_ref()
+                        .and_then(|d| d.v_slot.as_ref())
+                        .map(|v_slot| (v_slot, children))
+                }
+                _ => None,
+            };
+
+            match named_slot {
+                Some((v_slot, slot_children)) => {
+                    let slot_name = v_slot
+                        .slot_name
+                        .to_owned()
+                        .unwrap_or_else(|| StrOrExpr::Str(fervid_atom!("default")));
+                    let slot_children: Vec<&Node> = slot_children.iter().collect();
+                    let slot = self.generate_ssr_slot_fn(
+                        slot_name,
+                        &slot_children,
+                        v_slot.value.as_deref(),
+                    );
+                    slots.push(slot);
+                }
+                None => default_children.push(child),
+            }
+        }
+
+        if !default_children.is_empty() {
+            let slot = self.generate_ssr_slot_fn(
+                StrOrExpr::Str(fervid_atom!("default")),
+                &default_children,
+                component_v_slot.and_then(|v_slot| v_slot.value.as_deref()),
+            );
+            slots.push(slot);
+        }
+
+        // `_: 1` (STABLE)
+        slots.push(PropOrSpread::Prop(Box::new(Prop::KeyValue(KeyValueProp {
+            key: str_or_expr_to_propname(StrOrExpr::Str(fervid_atom!("_")), DUMMY_SP),
+            value: Box::new(Expr::Lit(Lit::Num(Number {
+                span: DUMMY_SP,
+                value: 1.0,
+                raw: None,
+            }))),
+        }))));
+
+        Some(Expr::Object(ObjectLit {
+            span: DUMMY_SP,
+            props: slots,
+        }))
+    }
+
+    /// Generates `name: _withCtx((binding, _push, _parent, _scopeId) => { /* both modes */ })`
+    fn generate_ssr_slot_fn(
+        &mut self,
+        slot_name: StrOrExpr,
+        slot_children: &[&Node],
+        slot_binding: Option<&Pat>,
+    ) -> PropOrSpread {
+        // Server branch
+        let mut slot_buf = SsrBuffer::new(true);
+        for child in slot_children.iter() {
+            self.generate_ssr_node(child, &mut slot_buf, false);
+        }
+        let ssr_stmts = slot_buf.finish();
+
+        // Client branch (used when the slot is rendered as VNodes, e.g. by `<component :is>`)
+        let mut vnodes = Vec::with_capacity(slot_children.len());
+        self.generate_node_sequence(
+            &mut slot_children.iter().copied(),
+            &mut vnodes,
+            slot_children.len(),
+            false,
+        );
+        let vnodes_arr = Expr::Array(ArrayLit {
+            span: DUMMY_SP,
+            elems: vnodes
+                .into_iter()
+                .map(|expr| {
+                    Some(ExprOrSpread {
+                        spread: None,
+                        expr: Box::new(expr),
+                    })
+                })
+                .collect(),
+        });
+
+        let if_stmt = Stmt::If(IfStmt {
+            span: DUMMY_SP,
+            test: Box::new(ident_expr(fervid_atom!("_push"))),
+            cons: Box::new(Stmt::Block(BlockStmt {
+                span: DUMMY_SP,
+                stmts: ssr_stmts,
+            })),
+            alt: Some(Box::new(Stmt::Block(BlockStmt {
+                span: DUMMY_SP,
+                stmts: vec![Stmt::Return(ReturnStmt {
+                    span: DUMMY_SP,
+                    arg: Some(Box::new(vnodes_arr)),
+                })],
+            }))),
+        });
+
+        let params = vec![
+            slot_binding.cloned().unwrap_or_else(|| ident_pat("_")),
+            ident_pat("_push"),
+            ident_pat("_parent"),
+            ident_pat("_scopeId"),
+        ];
+
+        let with_ctx = call_expr(
+            self.get_and_add_import_ident(VueImports::WithCtx),
+            vec![Expr::Arrow(ssr_arrow(params, vec![if_stmt]))],
+        );
+
+        PropOrSpread::Prop(Box::new(Prop::KeyValue(KeyValueProp {
+            key: str_or_expr_to_propname(slot_name, DUMMY_SP),
+            value: Box::new(with_ctx),
+        })))
+    }
+
+    /// Generates `_ssrRenderSlot(_ctx.$slots, "name", { props }, () => { fallback }, _push, _parent)`,
+    /// with an optional slot scope id as the last argument
+    fn generate_ssr_slot_outlet(&mut self, element_node: &ElementNode, buf: &mut SsrBuffer) {
+        let attributes = &element_node.starting_tag.attributes;
+        let idx_of_name = attributes
+            .iter()
+            .position(|attr| check_attribute_name(attr, "name"));
+
+        let name_expr = match idx_of_name.map(|idx| &attributes[idx]) {
+            Some(AttributeOrBinding::RegularAttribute { value, .. }) => str_expr(value, DUMMY_SP),
+            Some(AttributeOrBinding::VBind(v_bind)) => (*v_bind.value).to_owned(),
+            _ => str_expr("default", DUMMY_SP),
+        };
+
+        // Slot props are all the attributes except `name`
+        let slot_attributes: Vec<AttributeOrBinding> = attributes
+            .iter()
+            .enumerate()
+            .filter(|(idx, _)| Some(*idx) != idx_of_name)
+            .map(|(_, attr)| attr.to_owned())
+            .collect();
+        let mut slot_props = Vec::with_capacity(slot_attributes.len());
+        self.generate_attributes(&slot_attributes, &mut slot_props);
+
+        let fallback = if element_node.children.is_empty() {
+            null_expr()
+        } else {
+            let mut fallback_buf = SsrBuffer::new(buf.is_slot);
+            self.generate_ssr_children(&element_node.children, &mut fallback_buf);
+            Expr::Arrow(ssr_arrow(vec![], fallback_buf.finish()))
+        };
+
+        let mut args = vec![
+            Expr::Member(MemberExpr {
+                span: DUMMY_SP,
+                obj: Box::new(ident_expr(fervid_atom!("_ctx"))),
+                prop: MemberProp::Ident(swc_core::ecma::ast::Ident {
+                    span: DUMMY_SP,
+                    sym: fervid_atom!("$slots"),
+                    optional: false,
+                }),
+            }),
+            name_expr,
+            Expr::Object(ObjectLit {
+                span: DUMMY_SP,
+                props: slot_props,
+            }),
+            fallback,
+            ident_expr(fervid_atom!("_push")),
+            ident_expr(fervid_atom!("_parent")),
+        ];
+        // Slot content gets the slotted scope id when `:slotted()` is used
+        match self.scope_id {
+            Some(ref scope_id) if self.is_slotted => {
+                args.push(str_expr(&format!("{}-s", scope_id), DUMMY_SP));
+            }
+            _ if buf.is_slot => {
+                args.push(ident_expr(fervid_atom!("_scopeId")));
+            }
+            _ => {}
+        }
+
+        let render_slot = self.ssr_call(VueSsrImports::SsrRenderSlot, args);
+        buf.push_stmt(expr_stmt(render_slot));
+    }
+
+    /// Generates `_ssrRenderTeleport(_push, (_push) => { /* children */ }, to, disabled, _parent)`
+    fn generate_ssr_teleport(&mut self, element_node: &ElementNode, buf: &mut SsrBuffer) {
+        let attributes = &element_node.starting_tag.attributes;
+
+        let to = find_attr_expr(attributes, "to").unwrap_or_else(null_expr);
+        let disabled = find_attr_expr(attributes, "disabled").unwrap_or_else(|| {
+            Expr::Lit(Lit::Bool(Bool {
+                span: DUMMY_SP,
+                value: false,
+            }))
+        });
+
+        let mut teleport_buf = SsrBuffer::new(buf.is_slot);
+        self.generate_ssr_children(&element_node.children, &mut teleport_buf);
+        let content = Expr::Arrow(ssr_arrow(vec![ident_pat("_push")], teleport_buf.finish()));
+
+        let render_teleport = self.ssr_call(
+            VueSsrImports::SsrRenderTeleport,
+            vec![
+                ident_expr(fervid_atom!("_push")),
+                content,
+                to,
+                disabled,
+                ident_expr(fervid_atom!("_parent")),
+            ],
+        );
+        buf.push_stmt(expr_stmt(render_teleport));
+    }
+
+    /// Generates `_ssrRenderSuspense(_push, { default: () => { /* children */ }, _: 1 })`.
+    /// The `#fallback` slot is never rendered on the server.
+    fn generate_ssr_suspense(&mut self, element_node: &ElementNode, buf: &mut SsrBuffer) {
+        let mut default_children: Vec<&Node> = Vec::new();
+        for child in element_node.children.iter() {
+            let slot_name = match child {
+                Node::Element(ElementNode { starting_tag, .. })
+                    if starting_tag.tag_name == "template" =>
+                {
+                    starting_tag
+                        .directives
+                        .as_ref()
+                        .and_then(|d| d.v_slot.as_ref())
+                        .map(|v_slot| match v_slot.slot_name {
+                            Some(StrOrExpr::Str(ref name)) => name.to_owned(),
+                            Some(StrOrExpr::Expr(_)) => fervid_atom!(""),
+                            None => fervid_atom!("default"),
+                        })
+                }
+                _ => None,
+            };
+
+            match (slot_name, child) {
+                (Some(name), Node::Element(template)) if name == "default" => {
+                    default_children.extend(template.children.iter())
+                }
+                (Some(_), _) => {}
+                (None, _) => default_children.push(child),
+            }
+        }
+
+        let mut suspense_buf = SsrBuffer::new(buf.is_slot);
+        for child in default_children {
+            self.generate_ssr_node(child, &mut suspense_buf, false);
+        }
+
+        let slots = Expr::Object(ObjectLit {
+            span: DUMMY_SP,
+            props: vec![
+                PropOrSpread::Prop(Box::new(Prop::KeyValue(KeyValueProp {
+                    key: str_or_expr_to_propname(StrOrExpr::Str(fervid_atom!("default")), DUMMY_SP),
+                    value: Box::new(Expr::Arrow(ssr_arrow(vec![], suspense_buf.finish()))),
+                }))),
+                PropOrSpread::Prop(Box::new(Prop::KeyValue(KeyValueProp {
+                    key: str_or_expr_to_propname(StrOrExpr::Str(fervid_atom!("_")), DUMMY_SP),
+                    value: Box::new(Expr::Lit(Lit::Num(Number {
+                        span: DUMMY_SP,
+                        value: 1.0,
+                        raw: None,
+                    }))),
+                }))),
+            ],
+        });
+
+        let render_suspense = self.ssr_call(
+            VueSsrImports::SsrRenderSuspense,
+            vec![ident_expr(fervid_atom!("_push")), slots],
+        );
+        buf.push_stmt(expr_stmt(render_suspense));
+    }
+
+    /// `<TransitionGroup tag="ul">` renders as `<ul>`, otherwise as a fragment
+    fn generate_ssr_transition_group(&mut self, element_node: &ElementNode, buf: &mut SsrBuffer) {
+        let attributes = &element_node.starting_tag.attributes;
+
+        let static_tag = attributes.iter().find_map(|attr| match attr {
+            AttributeOrBinding::RegularAttribute { name, value, .. } if name == "tag" => {
+                Some(value.to_owned())
+            }
+            _ => None,
+        });
+
+        let Some(tag) = static_tag else {
+            buf.push_str("<!--[-->");
+            self.generate_ssr_children(&element_node.children, buf);
+            buf.push_str("<!--]-->");
+            return;
+        };
+
+        // Render as a plain element without the transition-specific props
+        let mut as_element = element_node.to_owned();
+        as_element.starting_tag.tag_name = tag;
+        as_element.starting_tag.attributes.retain(|attr| {
+            !TRANSITION_GROUP_PROPS
+                .iter()
+                .any(|prop| check_attribute_name(attr, prop))
+                && !matches!(
+                    attr,
+                    AttributeOrBinding::RegularAttribute { name, .. }
+                    | AttributeOrBinding::VBind(VBindDirective {
+                        argument: Some(StrOrExpr::Str(name)),
+                        ..
+                    }) if name.ends_with("-class")
+                )
+        });
+        self.generate_ssr_element(&as_element, buf, false);
+    }
+}
+
+/// Finds a static (`foo="bar"`) or bound (`:foo="bar"`) attribute value
+fn find_attr_expr(attributes: &[AttributeOrBinding], expected: &str) -> Option<Expr> {
+    attributes.iter().find_map(|attr| match attr {
+        AttributeOrBinding::RegularAttribute { name, value, .. } if name == expected => {
+            Some(str_expr(value, DUMMY_SP))
+        }
+        AttributeOrBinding::VBind(VBindDirective {
+            argument: Some(StrOrExpr::Str(name)),
+            value,
+            ..
+        }) if name == expected => Some((**value).to_owned()),
+        _ => None,
+    })
+}
+
+fn expr_stmt(expr: Expr) -> Stmt {
+    Stmt::Expr(swc_core::ecma::ast::ExprStmt {
+        span: DUMMY_SP,
+        expr: Box::new(expr),
+    })
+}
@@ -0,0 +1,192 @@
+use fervid_core::{ConditionalNodeSequence, ElementNode, VForDirective, VueSsrImports};
+use swc_core::{
+    common::DUMMY_SP,
+    ecma::ast::{BlockStmt, Expr, ExprStmt, IfStmt, Pat, Stmt},
+};
+
+use super::{ssr_arrow, SsrBuffer};
+use crate::context::CodegenContext;
+
+impl CodegenContext {
+    /// Generates `v-if`/`v-else-if`/`v-else` as an `if` statement chain:
+    /// ```js
+    /// if (cond) {
+    ///   _push(`<div>if</div>`)
+    /// } else {
+    ///   _push(`<!---->`)
+    /// }
+    /// ```
+    pub fn generate_ssr_conditional_seq(
+        &mut self,
+        conditional_seq: &ConditionalNodeSequence,
+        buf: &mut SsrBuffer,
+        is_root: bool,
+    ) {
+        // The `else` branch or a comment placeholder
+        let else_stmts = match conditional_seq.else_node {
+            Some(ref else_node) => self.generate_ssr_branch(else_node, buf.is_slot, is_root),
+            None => {
+                let mut comment_buf = SsrBuffer::new(buf.is_slot);
+                comment_buf.push_str("<!---->");
+                comment_buf.finish()
+            }
+        };
+        let mut alt = Stmt::Block(BlockStmt {
+            span: DUMMY_SP,
+            stmts: else_stmts,
+        });
+
+        // Fold the branches from the back
+        let branches = std::iter::once(conditional_seq.if_node.as_ref())
+            .chain(conditional_seq.else_if_nodes.iter())
+            .collect::<Vec<_>>();
+
+        for conditional in branches.into_iter().rev() {
+            let cons_stmts = self.generate_ssr_branch(&conditional.node, buf.is_slot, is_root);
+            alt = Stmt::If(IfStmt {
+                span: DUMMY_SP,
+                test: Box::new(conditional.condition.to_owned()),
+                cons: Box::new(Stmt::Block(BlockStmt {
+                    span: DUMMY_SP,
+                    stmts: cons_stmts,
+                })),
+                alt: Some(Box::new(alt)),
+            });
+        }
+
+        buf.push_stmt(alt);
+    }
+
+    /// Generates `v-for` as a list render wrapped in fragment markers:
+    /// ```js
+    /// _push(`<!--[-->`)
+    /// _ssrRenderList(list, (item) => {
+    ///   _push(`<div>${_ssrInterpolate(item)}</div>`)
+    /// })
+    /// _push(`<!--]-->`)
+    /// ```
+    pub fn generate_ssr_v_for(
+        &mut self,
+        v_for: &VForDirective,
+        element_node: &ElementNode,
+        buf: &mut SsrBuffer,
+    ) {
+        let mut item_buf = SsrBuffer::new(buf.is_slot);
+        self.generate_ssr_element_kind(element_node, &mut item_buf, false);
+
+        let render_arrow = ssr_arrow(vec![Pat::Expr(v_for.itervar.to_owned())], item_buf.finish());
+
+        let render_list = self.ssr_call(
+            VueSsrImports::SsrRenderList,
+            vec![(*v_for.iterable).to_owned(), Expr::Arrow(render_arrow)],
+        );
+
+        buf.push_str("<!--[-->");
+        buf.push_stmt(Stmt::Expr(ExprStmt {
+            span: DUMMY_SP,
+            expr: Box::new(render_list),
+        }));
+        buf.push_str("<!--]-->");
+    }
+
+    /// Generates a conditional branch into a separate list of statements
+    fn generate_ssr_branch(
+        &mut self,
+        element_node: &ElementNode,
+        is_slot: bool,
+        is_root: bool,
+    ) -> Vec<Stmt> {
+        let mut branch_buf = SsrBuffer::new(is_slot);
+        self.generate_ssr_element_or_component(element_node, &mut branch_buf, is_root);
+        branch_buf.finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use fervid_core::{Conditional, Node, PatchFlags, VueDirectives};
+
+    use super::*;
+    use crate::test_utils::{element, interpolation, js};
+
+    #[test]
+    fn it_generates_v_if_else() {
+        // <div v-if="ok">yes</div><span v-else>no</span>
+        let conditional_seq = ConditionalNodeSequence {
+            if_node: Box::new(Conditional {
+                condition: *js("ok"),
+                node: element("div", vec![], vec![Node::Text("yes".into(), DUMMY_SP)]),
+            }),
+            else_if_nodes: vec![],
+            else_node: Some(Box::new(element(
+                "span",
+                vec![],
+                vec![Node::Text("no".into(), DUMMY_SP)],
+            ))),
+        };
+
+        let mut ctx = CodegenContext::default();
+        let mut buf = SsrBuffer::new(false);
+        ctx.generate_ssr_conditional_seq(&conditional_seq, &mut buf, false);
+
+        assert_eq!(
+            stringify(buf),
+            "if(ok){_push(`<div>yes</div>`);}else{_push(`<span>no</span>`);}"
+        );
+    }
+
+    #[test]
+    fn it_generates_v_if_without_else() {
+        // <div v-if="ok">yes</div>
+        let conditional_seq = ConditionalNodeSequence {
+            if_node: Box::new(Conditional {
+                condition: *js("ok"),
+                node: element("div", vec![], vec![Node::Text("yes".into(), DUMMY_SP)]),
+            }),
+            else_if_nodes: vec![],
+            else_node: None,
+        };
+
+        let mut ctx = CodegenContext::default();
+        let mut buf = SsrBuffer::new(false);
+        ctx.generate_ssr_conditional_seq(&conditional_seq, &mut buf, false);
+
+        assert_eq!(
+            stringify(buf),
+            "if(ok){_push(`<div>yes</div>`);}else{_push(`<!---->`);}"
+        );
+    }
+
+    #[test]
+    fn it_generates_v_for() {
+        // <li v-for="item in items">{{ item }}</li>
+        let mut li = element("li", vec![], vec![interpolation("item")]);
+        li.starting_tag.directives = Some(Box::new(VueDirectives {
+            v_for: Some(VForDirective {
+                iterable: js("items"),
+                itervar: js("item"),
+                patch_flags: PatchFlags::UnkeyedFragment.into(),
+                fragment_key: None,
+                span: DUMMY_SP,
+            }),
+            ..Default::default()
+        }));
+
+        let mut ctx = CodegenContext::default();
+        let mut buf = SsrBuffer::new(false);
+        ctx.generate_ssr_element_or_component(&li, &mut buf, false);
+
+        assert_eq!(
+            stringify(buf),
+            "_push(`<!--[-->`);_ssrRenderList(items,(item)=>{_push(`<li>${_ssrInterpolate(item)}</li>`);});_push(`<!--]-->`);"
+        );
+    }
+
+    fn stringify(buf: SsrBuffer) -> String {
+        buf.finish()
+            .into_iter()
+            .map(crate::test_utils::to_str)
+            .collect::<Vec<_>>()
+            .join("")
+    }
+}
@@ -0,0 +1,645 @@
+use fervid_core::{
+    fervid_atom, is_boolean_attr, is_void_tag, AttributeOrBinding, ElementKind, ElementNode,
+    FervidAtom, StrOrExpr, VBindDirective, VModelDirective, VueImports, VueSsrImports,
+};
+use swc_core::{
+    common::DUMMY_SP,
+    ecma::ast::{
+        ArrayLit, BinExpr, BinaryOp, CallExpr, Callee, CondExpr, Expr, ExprOrSpread, KeyValueProp,
+        MemberExpr, MemberProp, ObjectLit, Prop, PropName, PropOrSpread,
+    },
+};
+
+use super::{call_expr, ident_expr, null_expr, str_expr, SsrBuffer};
+use crate::{context::CodegenContext, utils::str_to_propname};
+
+impl CodegenContext {
+    /// Generates an element, component or a builtin.
+    /// `v-for` is handled here, because it wraps all kinds of nodes.
+    pub fn generate_ssr_element_or_component(
+        &mut self,
+        element_node: &ElementNode,
+        buf: &mut SsrBuffer,
+        is_root: bool,
+    ) {
+        if let Some(v_for) = element_node
+            .starting_tag
+            .directives
+            .as_ref()
+            .and_then(|directives| directives.v_for.as_ref())
+        {
+            self.generate_ssr_v_for(v_for, element_node, buf);
+            return;
+        }
+
+        self.generate_ssr_element_kind(element_node, buf, is_root);
+    }
+
+    /// Generates the node depending on its [`ElementKind`], ignoring `v-for`
+    pub(crate) fn generate_ssr_element_kind(
+        &mut self,
+        element_node: &ElementNode,
+        buf: &mut SsrBuffer,
+        is_root: bool,
+    ) {
+        match element_node.kind {
+            ElementKind::Builtin(builtin_type) => {
+                self.generate_ssr_builtin(element_node, builtin_type, buf, is_root)
+            }
+            ElementKind::Element => self.generate_ssr_element(element_node, buf, is_root),
+            ElementKind::Component => self.generate_ssr_component(element_node, buf, is_root),
+        }
+    }
+
+    /// Generates a plain HTML element, e.g. `<div class="foo">{{ bar }}</div>`
+    pub fn generate_ssr_element(
+        &mut self,
+        element_node: &ElementNode,
+        buf: &mut SsrBuffer,
+        is_root: bool,
+    ) {
+        let tag_name = &element_node.starting_tag.tag_name;
+
+        // `<template>` only renders its children
+        if tag_name == "template" {
+            self.generate_ssr_fragment(&element_node.children, buf);
+            return;
+        }
+
+        buf.push_str("<");
+        buf.push_str(tag_name);
+
+        let content = self.generate_ssr_element_attributes(element_node, buf, is_root);
+
+        if let Some(ref scope_id) = self.scope_id {
+            buf.push_str(" ");
+            buf.push_str(scope_id);
+        }
+        if buf.is_slot {
+            buf.push_expr(ident_expr(fervid_atom!("_scopeId")));
+        }
+
+        buf.push_str(">");
+
+        if is_void_tag(tag_name) {
+            return;
+        }
+
+        // `v-html`, `v-text` and `<textarea>` value replace the children
+        match content {
+            Some(content) => buf.push_expr(content),
+            None => self.generate_ssr_children(&element_node.children, buf),
+        }
+
+        buf.push_str("</");
+        buf.push_str(tag_name);
+        buf.push_str(">");
+    }
+
+    /// Generates the children, surrounding them with `<!--[-->` and `<!--]-->`
+    /// when there is more than one child.
+    pub(crate) fn generate_ssr_fragment(
+        &mut self,
+        children: &[fervid_core::Node],
+        buf: &mut SsrBuffer,
+    ) {
+        if children.len() == 1 {
+            self.generate_ssr_node(&children[0], buf, false);
+            return;
+        }
+
+        buf.push_str("<!--[-->");
+        self.generate_ssr_children(children, buf);
+        buf.push_str("<!--]-->");
+    }
+
+    /// Generates the element attributes into the buffer.
+    ///
+    /// Returns the expression which should be used instead of element children,
+    /// e.g. for `v-html`, `v-text` or `<textarea v-model>`.
+    fn generate_ssr_element_attributes(
+        &mut self,
+        element_node: &ElementNode,
+        buf: &mut SsrBuffer,
+        is_root: bool,
+    ) -> Option<Expr> {
+        let starting_tag = &element_node.starting_tag;
+        let directives = starting_tag.directives.as_deref();
+
+        let mut content: Option<Expr> = None;
+
+        // `v-html` and `v-text`
+        if let Some(v_html) = directives.and_then(|d| d.v_html.as_ref()) {
+            content = Some(nullish_to_empty((**v_html).to_owned()));
+        } else if let Some(v_text) = directives.and_then(|d| d.v_text.as_ref()) {
+            content =
+                Some(self.ssr_call(VueSsrImports::SsrInterpolate, vec![(**v_text).to_owned()]));
+        }
+
+        // When object spread, custom directives or fallthrough attributes are present,
+        // everything must go through `ssrRenderAttrs(mergeProps(...))` at runtime
+        let has_v_bind_object = starting_tag
+            .attributes
+            .iter()
+            .any(|attr| matches!(attr, AttributeOrBinding::VBind(v) if v.argument.is_none()));
+        let has_custom_directives = directives.map_or(false, |d| !d.custom.is_empty());
+
+        if is_root || has_v_bind_object || has_custom_directives {
+            let content_override = self.generate_ssr_merged_attributes(element_node, buf, is_root);
+            return content.or(content_override);
+        }
+
+        let mut class_regular: Option<&FervidAtom> = None;
+        let mut class_bound: Option<&Expr> = None;
+        let mut style_regular: Option<&FervidAtom> = None;
+        let mut style_bound: Option<&Expr> = None;
+
+        for attr in starting_tag.attributes.iter() {
+            match attr {
+                AttributeOrBinding::RegularAttribute { name, value, .. } if name == "class" => {
+                    class_regular = Some(value);
+                }
+                AttributeOrBinding::RegularAttribute { name, value, .. } if name == "style" => {
+                    style_regular = Some(value);
+                }
+                AttributeOrBinding::RegularAttribute { name, value, .. } => {
+                    push_static_attr(buf, name, value);
+                }
+
+                AttributeOrBinding::VBind(VBindDirective {
+                    argument: Some(StrOrExpr::Str(name)),
+                    value,
+                    ..
+                }) => {
+                    match name.as_ref() {
+                        "class" => class_bound = Some(value),
+                        "style" => style_bound = Some(value),
+                        "key" | "ref" => {}
+                        "innerHTML" => content = Some(nullish_to_empty((**value).to_owned())),
+                        "textContent" => {
+                            content = Some(self.ssr_call(
+                                VueSsrImports::SsrInterpolate,
+                                vec![(**value).to_owned()],
+                            ))
+                        }
+                        "value" if starting_tag.tag_name == "textarea" => {
+                            content = Some(self.ssr_call(
+                                VueSsrImports::SsrInterpolate,
+                                vec![(**value).to_owned()],
+                            ))
+                        }
+                        _ if is_boolean_attr(name) => {
+                            // `${_ssrIncludeBooleanAttr(value) ? " name" : ""}`
+                            let test = self.ssr_call(
+                                VueSsrImports::SsrIncludeBooleanAttr,
+                                vec![(**value).to_owned()],
+                            );
+                            let mut attr_str = String::with_capacity(name.len() + 1);
+                            attr_str.push(' ');
+                            attr_str.push_str(name);
+                            buf.push_expr(Expr::Cond(CondExpr {
+                                span: DUMMY_SP,
+                                test: Box::new(test),
+                                cons: Box::new(str_expr(&attr_str, DUMMY_SP)),
+                                alt: Box::new(str_expr("", DUMMY_SP)),
+                            }));
+                        }
+                        _ => {
+                            let expr = self.ssr_call(
+                                VueSsrImports::SsrRenderAttr,
+                                vec![str_expr(name, DUMMY_SP), (**value).to_owned()],
+                            );
+                            buf.push_expr(expr);
+                        }
+                    }
+                }
+
+                // `:[name]="value"`
+                AttributeOrBinding::VBind(VBindDirective {
+                    argument: Some(StrOrExpr::Expr(name_expr)),
+                    value,
+                    ..
+                }) => {
+                    let expr = self.ssr_call(
+                        VueSsrImports::SsrRenderDynamicAttr,
+                        vec![(**name_expr).to_owned(), (**value).to_owned()],
+                    );
+                    buf.push_expr(expr);
+                }
+
+                // Event listeners are not rendered on the server.
+                // Object `v-bind` is handled by the merged branch above.
+                AttributeOrBinding::VBind(_) | AttributeOrBinding::VOn(_) => {}
+            }
+        }
+
+        // `class`
+        match (class_regular, class_bound) {
+            (Some(regular), None) => push_static_attr(buf, "class", regular),
+            (regular, Some(bound)) => {
+                let arg = match regular {
+                    Some(regular) => {
+                        array_expr(vec![str_expr(regular, DUMMY_SP), bound.to_owned()])
+                    }
+                    None => bound.to_owned(),
+                };
+                buf.push_str(" class=\"");
+                let expr = self.ssr_call(VueSsrImports::SsrRenderClass, vec![arg]);
+                buf.push_expr(expr);
+                buf.push_str("\"");
+            }
+            (None, None) => {}
+        }
+
+        // `style` and `v-show`
+        let v_show = directives
+            .and_then(|d| d.v_show.as_ref())
+            .map(|v_show| v_show_style(v_show));
+        match (style_regular, style_bound, v_show) {
+            (None, None, None) => {}
+            (Some(regular), None, None) => push_static_attr(buf, "style", regular),
+            (regular, bound, v_show) => {
+                let mut style_parts = Vec::with_capacity(3);
+                if let Some(regular) = regular {
+                    style_parts.push(str_expr(regular, DUMMY_SP));
+                }
+                if let Some(bound) = bound {
+                    style_parts.push(bound.to_owned());
+                }
+                if let Some(v_show) = v_show {
+                    style_parts.push(v_show);
+                }
+                let arg = if style_parts.len() == 1 {
+                    style_parts.pop().expect("Style parts is not empty")
+                } else {
+                    array_expr(style_parts)
+                };
+                buf.push_str(" style=\"");
+                let expr = self.ssr_call(VueSsrImports::SsrRenderStyle, vec![arg]);
+                buf.push_expr(expr);
+                buf.push_str("\"");
+            }
+        }
+
+        // `v-model`
+        if let Some(directives) = directives {
+            for v_model in directives.v_model.iter() {
+                if let Some(model_content) =
+                    self.generate_ssr_element_v_model(element_node, v_model, buf)
+                {
+                    content = Some(model_content);
+                }
+            }
+        }
+
+        content
+    }
+
+    /// Generates `${_ssrRenderAttrs(_mergeProps({ /* attrs */ }, _attrs))}`.
+    /// Used when attributes cannot be known at compile time.
+    fn generate_ssr_merged_attributes(
+        &mut self,
+        element_node: &ElementNode,
+        buf: &mut SsrBuffer,
+        is_root: bool,
+    ) -> Option<Expr> {
+        let starting_tag = &element_node.starting_tag;
+        let mut content: Option<Expr> = None;
+
+        // Event listeners are not needed on the server
+        let attributes: Vec<AttributeOrBinding> = starting_tag
+            .attributes
+            .iter()
+            .filter(|attr| !matches!(attr, AttributeOrBinding::VOn(_)))
+            .cloned()
+            .collect();
+
+        let mut props = Vec::with_capacity(attributes.len());
+        let hints = self.generate_attributes(&attributes, &mut props);
+        let v_bind_object = hints.v_bind_no_arg.map(|v_bind| (*v_bind.value).to_owned());
+
+        let mut merge_args: Vec<Expr> = Vec::with_capacity(4);
+
+        if let Some(ref directives) = starting_tag.directives {
+            // `v-show` contributes to `style`
+            if let Some(ref v_show) = directives.v_show {
+                merge_args.push(Expr::Object(ObjectLit {
+                    span: DUMMY_SP,
+                    props: vec![key_value("style", v_show_style(v_show))],
+                }));
+            }
+
+            // `v-model` contributes to `value` or `checked`
+            for v_model in directives.v_model.iter() {
+                match model_kind(element_node) {
+                    ModelKind::Textarea => {
+                        content = Some(self.ssr_call(
+                            VueSsrImports::SsrInterpolate,
+                            vec![(*v_model.value).to_owned()],
+                        ))
+                    }
+                    ModelKind::Checkbox => {
+                        let checked = self.ssr_checkbox_checked(element_node, v_model);
+                        props.push(key_value("checked", checked));
+                    }
+                    ModelKind::Radio => {
+                        let checked = self.ssr_radio_checked(element_node, v_model);
+                        props.push(key_value("checked", checked));
+                    }
+                    ModelKind::Text => props.push(key_value("value", (*v_model.value).to_owned())),
+                    ModelKind::Dynamic | ModelKind::Unsupported => {}
+                }
+            }
+
+            // `_ssrGetDirectiveProps(_ctx, _directive_foo, value, arg, modifiers)`
+            for custom_directive in directives.custom.iter() {
+                let directive_ident =
+                    self.get_custom_directive_ident(&custom_directive.name, DUMMY_SP);
+                let Expr::Array(directive_arr) = self.generate_directive_from_parts(
+                    directive_ident,
+                    custom_directive.value.as_deref(),
+                    custom_directive.argument.as_ref(),
+                    &custom_directive.modifiers,
+                    DUMMY_SP,
+                ) else {
+                    unreachable!("Directive parts are always an array")
+                };
+
+                let mut args = Vec::with_capacity(directive_arr.elems.len() + 1);
+                args.push(ident_expr(fervid_atom!("_ctx")));
+                args.extend(
+                    directive_arr
+                        .elems
+                        .into_iter()
+                        .flatten()
+                        .map(|expr_or_spread| *expr_or_spread.expr),
+                );
+                merge_args.push(self.ssr_call(VueSsrImports::SsrGetDirectiveProps, args));
+            }
+        }
+
+        // Order matters: static object, `v-bind="obj"`, directives, then `_attrs`
+        let mut all_args = Vec::with_capacity(merge_args.len() + 3);
+        if !props.is_empty() {
+            all_args.push(Expr::Object(ObjectLit {
+                span: DUMMY_SP,
+                props,
+            }));
+        }
+        if let Some(v_bind_object) = v_bind_object {
+            all_args.push(v_bind_object);
+        }
+        all_args.extend(merge_args);
+        if is_root {
+            all_args.push(ident_expr(fervid_atom!("_attrs")));
+        }
+
+        let attrs_expr = if all_args.len() == 1 {
+            all_args.pop().expect("Args are not empty")
+        } else {
+            call_expr(
+                self.get_and_add_import_ident(VueImports::MergeProps),
+                all_args,
+            )
+        };
+
+        let expr = self.ssr_call(VueSsrImports::SsrRenderAttrs, vec![attrs_expr]);
+        buf.push_expr(expr);
+
+        content
+    }
+
+    /// Generates `v-model` for an element when attributes are known at compile time.
+    /// Returns content for `<textarea>`.
+    fn generate_ssr_element_v_model(
+        &mut self,
+        element_node: &ElementNode,
+        v_model: &VModelDirective,
+        buf: &mut SsrBuffer,
+    ) -> Option<Expr> {
+        match model_kind(element_node) {
+            ModelKind::Text => {
+                let expr = self.ssr_call(
+                    VueSsrImports::SsrRenderAttr,
+                    vec![str_expr("value", DUMMY_SP), (*v_model.value).to_owned()],
+                );
+                buf.push_expr(expr);
+                None
+            }
+            ModelKind::Checkbox => {
+                let checked = self.ssr_checkbox_checked(element_node, v_model);
+                buf.push_expr(checked_attr(checked));
+                None
+            }
+            ModelKind::Radio => {
+                let checked = self.ssr_radio_checked(element_node, v_model);
+                buf.push_expr(checked_attr(checked));
+                None
+            }
+            ModelKind::Dynamic => {
+                let type_expr = find_bound_attr(element_node, "type")
+                    .cloned()
+                    .unwrap_or_else(null_expr);
+                let value_expr = self.ssr_input_value(element_node);
+                let expr = self.ssr_call(
+                    VueSsrImports::SsrRenderDynamicModel,
+                    vec![type_expr, (*v_model.value).to_owned(), value_expr],
+                );
+                buf.push_expr(expr);
+                None
+            }
+            ModelKind::Textarea => Some(self.ssr_call(
+                VueSsrImports::SsrInterpolate,
+                vec![(*v_model.value).to_owned()],
+            )),
+            // `<select v-model>` selects its options at runtime during hydration
+            ModelKind::Unsupported => None,
+        }
+    }
+
+    /// `Array.isArray(model) ? _ssrLooseContain(model, value) : model`
+    fn ssr_checkbox_checked(
+        &mut self,
+        element_node: &ElementNode,
+        v_model: &VModelDirective,
+    ) -> Expr {
+        let value_expr = self.ssr_input_value(element_node);
+        let model = (*v_model.value).to_owned();
+
+        let is_array = Expr::Call(CallExpr {
+            span: DUMMY_SP,
+            callee: Callee::Expr(Box::new(Expr::Member(MemberExpr {
+                span: DUMMY_SP,
+                obj: Box::new(ident_expr(fervid_atom!("Array"))),
+                prop: MemberProp::Ident(swc_core::ecma::ast::Ident {
+                    span: DUMMY_SP,
+                    sym: fervid_atom!("isArray"),
+                    optional: false,
+                }),
+            }))),
+            args: vec![ExprOrSpread {
+                spread: None,
+                expr: Box::new(model.to_owned()),
+            }],
+            type_args: None,
+        });
+
+        let contain = self.ssr_call(
+            VueSsrImports::SsrLooseContain,
+            vec![model.to_owned(), value_expr],
+        );
+
+        Expr::Cond(CondExpr {
+            span: DUMMY_SP,
+            test: Box::new(is_array),
+            cons: Box::new(contain),
+            alt: Box::new(model),
+        })
+    }
+
+    /// `_ssrLooseEqual(model, value)`
+    fn ssr_radio_checked(&mut self, element_node: &ElementNode, v_model: &VModelDirective) -> Expr {
+        let value_expr = self.ssr_input_value(element_node);
+        self.ssr_call(
+            VueSsrImports::SsrLooseEqual,
+            vec![(*v_model.value).to_owned(), value_expr],
+        )
+    }
+
+    /// The `value` of an `<input>`, either static, bound or `null`
+    fn ssr_input_value(&mut self, element_node: &ElementNode) -> Expr {
+        for attr in element_node.starting_tag.attributes.iter() {
+            match attr {
+                AttributeOrBinding::RegularAttribute { name, value, .. } if name == "value" => {
+                    return str_expr(value, DUMMY_SP);
+                }
+                AttributeOrBinding::VBind(VBindDirective {
+                    argument: Some(StrOrExpr::Str(name)),
+                    value,
+                    ..
+                }) if name == "value" => return (**value).to_owned(),
+                _ => {}
+            }
+        }
+
+        null_expr()
+    }
+}
+
+/// How `v-model` is rendered on an element
+enum ModelKind {
+    Text,
+    Checkbox,
+    Radio,
+    Dynamic,
+    Textarea,
+    Unsupported,
+}
+
+fn model_kind(element_node: &ElementNode) -> ModelKind {
+    match element_node.starting_tag.tag_name.as_ref() {
+        "input" => {
+            for attr in element_node.starting_tag.attributes.iter() {
+                match attr {
+                    AttributeOrBinding::RegularAttribute { name, value, .. } if name == "type" => {
+                        return match value.as_ref() {
+                            "checkbox" => ModelKind::Checkbox,
+                            "radio" => ModelKind::Radio,
+                            _ => ModelKind::Text,
+                        };
+                    }
+                    AttributeOrBinding::VBind(VBindDirective {
+                        argument: Some(StrOrExpr::Str(name)),
+                        ..
+                    }) if name == "type" => return ModelKind::Dynamic,
+                    _ => {}
+                }
+            }
+            ModelKind::Text
+        }
+        "textarea" => ModelKind::Textarea,
+        _ => ModelKind::Unsupported,
+    }
+}
+
+fn find_bound_attr<'e>(element_node: &'e ElementNode, expected: &str) -> Option<&'e Expr> {
+    element_node
+        .starting_tag
+        .attributes
+        .iter()
+        .find_map(|attr| match attr {
+            AttributeOrBinding::VBind(VBindDirective {
+                argument: Some(StrOrExpr::Str(name)),
+                value,
+                ..
+            }) if name == expected => Some(value.as_ref()),
+            _ => None,
+        })
+}
+
+/// Pushes ` name="value"` or ` name` for empty values
+fn push_static_attr(buf: &mut SsrBuffer, name: &str, value: &str) {
+    buf.push_str(" ");
+    buf.push_str(name);
+    if !value.is_empty() {
+        buf.push_str("=\"");
+        buf.push_str(&super::escape_html(value));
+        buf.push_str("\"");
+    }
+}
+
+/// `checked ? " checked" : ""`
+fn checked_attr(checked: Expr) -> Expr {
+    Expr::Cond(CondExpr {
+        span: DUMMY_SP,
+        test: Box::new(checked),
+        cons: Box::new(str_expr(" checked", DUMMY_SP)),
+        alt: Box::new(str_expr("", DUMMY_SP)),
+    })
+}
+
+/// `v-show` as a style: `cond ? null : { display: "none" }`
+fn v_show_style(v_show: &Expr) -> Expr {
+    Expr::Cond(CondExpr {
+        span: DUMMY_SP,
+        test: Box::new(v_show.to_owned()),
+        cons: Box::new(null_expr()),
+        alt: Box::new(Expr::Object(ObjectLit {
+            span: DUMMY_SP,
+            props: vec![key_value("display", str_expr("none", DUMMY_SP))],
+        })),
+    })
+}
+
+/// `expr ?? ""`
+fn nullish_to_empty(expr: Expr) -> Expr {
+    Expr::Bin(BinExpr {
+        span: DUMMY_SP,
+        op: BinaryOp::NullishCoalescing,
+        left: Box::new(expr),
+        right: Box::new(str_expr("", DUMMY_SP)),
+    })
+}
+
+fn key_value(key: &str, value: Expr) -> PropOrSpread {
+    let key: PropName = str_to_propname(key, DUMMY_SP);
+    PropOrSpread::Prop(Box::new(Prop::KeyValue(KeyValueProp {
+        key,
+        value: Box::new(value),
+    })))
+}
+
+fn array_expr(elems: Vec<Expr>) -> Expr {
+    Expr::Array(ArrayLit {
+        span: DUMMY_SP,
+        elems: elems
+            .into_iter()
+            .map(|expr| {
+                Some(ExprOrSpread {
+                    spread: None,
+                    expr: Box::new(expr),
+                })
+            })
+            .collect(),
+    })
+}
@@ -0,0 +1,494 @@
+//! Server-side rendering code generation.
+//!
+//! Instead of building a VNode tree, SSR code generation produces an
+//! `ssrRender(_ctx, _push, _parent, _attrs)` function which pushes HTML strings
+//! into a buffer using the `@vue/server-renderer` helpers.
+
+use fervid_core::{fervid_atom, FervidAtom, Node, SfcTemplateBlock, VueSsrImports};
+use swc_core::{
+    common::{Span, DUMMY_SP},
+    ecma::ast::{
+        ArrowExpr, BindingIdent, BlockStmt, BlockStmtOrExpr, CallExpr, Callee, Decl, Expr,
+        ExprOrSpread, ExprStmt, Function, Ident, KeyValueProp, Lit, Null, ObjectLit, Param, Pat,
+        Prop, PropName, PropOrSpread, Stmt, Str, Tpl, TplElement, VarDecl, VarDeclKind,
+        VarDeclarator,
+    },
+};
+
+use crate::context::CodegenContext;
+
+mod components;
+mod control_flow;
+mod elements;
+
+/// Accumulates the HTML produced by SSR code generation.
+///
+/// Static strings and interpolated expressions are merged into a single
+/// template literal until a statement (e.g. `if` or a component render) is needed,
+/// at which point the pending template literal is flushed as `_push(`...`)`.
+pub struct SsrBuffer {
+    stmts: Vec<Stmt>,
+    quasis: Vec<String>,
+    exprs: Vec<Expr>,
+    /// Whether the code is generated inside a slot function.
+    /// Elements inside slots receive the `_scopeId` of the parent.
+    pub(crate) is_slot: bool,
+}
+
+impl SsrBuffer {
+    pub fn new(is_slot: bool) -> SsrBuffer {
+        SsrBuffer {
+            stmts: Vec::new(),
+            quasis: vec![String::new()],
+            exprs: Vec::new(),
+            is_slot,
+        }
+    }
+
+    /// Adds a static string. The string must already be escaped for HTML.
+    pub fn push_str(&mut self, s: &str) {
+        if let Some(last) = self.quasis.last_mut() {
+            last.push_str(s);
+        }
+    }
+
+    /// Adds an expression to be interpolated into the HTML as-is
+    pub fn push_expr(&mut self, expr: Expr) {
+        self.exprs.push(expr);
+        self.quasis.push(String::new());
+    }
+
+    /// Adds a statement after flushing the pending HTML
+    pub fn push_stmt(&mut self, stmt: Stmt) {
+        self.flush();
+        self.stmts.push(stmt);
+    }
+
+    /// Adds `_push(expr)` after flushing the pending HTML
+    pub fn push_call(&mut self, expr: Expr) {
+        self.flush();
+        self.stmts.push(push_stmt(expr));
+    }
+
+    /// Converts the pending HTML into a `_push(`...`)` statement
+    pub fn flush(&mut self) {
+        let is_empty = self.exprs.is_empty() && self.quasis.iter().all(|q| q.is_empty());
+        if is_empty {
+            return;
+        }
+
+        let quasis_len = self.quasis.len();
+        let quasis = self
+            .quasis
+            .drain(..)
+            .enumerate()
+            .map(|(idx, quasi)| TplElement {
+                span: DUMMY_SP,
+                tail: idx + 1 == quasis_len,
+                cooked: Some(FervidAtom::from(quasi.as_str())),
+                raw: FervidAtom::from(escape_template_raw(&quasi)),
+            })
+            .collect();
+
+        let tpl = Expr::Tpl(Tpl {
+            span: DUMMY_SP,
+            exprs: self.exprs.drain(..).map(Box::new).collect(),
+            quasis,
+        });
+
+        self.quasis.push(String::new());
+        self.stmts.push(push_stmt(tpl));
+    }
+
+    /// Flushes the pending HTML and returns all the statements
+    pub fn finish(mut self) -> Vec<Stmt> {
+        self.flush();
+        self.stmts
+    }
+}
+
+impl CodegenContext {
+    /// Generates the body of the `ssrRender` function for the template.
+    ///
+    /// The result is an arrow `(_ctx, _push, _parent, _attrs) => { /*...*/ }`,
+    /// which is later completed with resolves by [`CodegenContext::generate_module`].
+    pub fn generate_ssr_sfc_template(&mut self, sfc_template: &SfcTemplateBlock) -> Option<Expr> {
+        if sfc_template.roots.is_empty() {
+            return None;
+        }
+
+        let mut buf = SsrBuffer::new(false);
+
+        // `const _cssVars = { style: { /* vars */ } }`, bound to the roots by the transform
+        if let Some(ssr_css_vars) = self.ssr_css_vars.take() {
+            buf.push_stmt(css_vars_decl(ssr_css_vars));
+        }
+
+        // Fallthrough attributes only apply to a single root
+        let is_single_root = sfc_template.roots.len() == 1;
+        if !is_single_root {
+            buf.push_str("<!--[-->");
+        }
+        for root in sfc_template.roots.iter() {
+            self.generate_ssr_node(root, &mut buf, is_single_root);
+        }
+        if !is_single_root {
+            buf.push_str("<!--]-->");
+        }
+
+        Some(Expr::Arrow(ssr_arrow(
+            vec![
+                ident_pat("_ctx"),
+                ident_pat("_push"),
+                ident_pat("_parent"),
+                ident_pat("_attrs"),
+            ],
+            buf.finish(),
+        )))
+    }
+
+    /// Completes the SSR render arrow produced by [`CodegenContext::generate_ssr_sfc_template`]
+    /// with component and directive resolves. Used in the inline mode.
+    pub fn generate_ssr_render_arrow(&mut self, template_expr: Expr) -> ArrowExpr {
+        let (params, mut stmts) = unwrap_ssr_arrow(template_expr);
+        if let Some(resolves) = self.generate_ssr_resolves() {
+            stmts.insert(0, resolves);
+        }
+
+        ssr_arrow(params, stmts)
+    }
+
+    /// Converts the SSR render arrow produced by [`CodegenContext::generate_ssr_sfc_template`]
+    /// into `ssrRender(_ctx, _push, _parent, _attrs, $props, $setup, $data, $options) { /*...*/ }`.
+    pub fn generate_ssr_render_fn(&mut self, template_expr: Expr) -> Function {
+        let (_, mut stmts) = unwrap_ssr_arrow(template_expr);
+        if let Some(resolves) = self.generate_ssr_resolves() {
+            stmts.insert(0, resolves);
+        }
+
+        let params = [
+            "_ctx", "_push", "_parent", "_attrs", "$props", "$setup", "$data", "$options",
+        ]
+        .into_iter()
+        .map(|param| Param {
+            span: DUMMY_SP,
+            decorators: vec![],
+            pat: ident_pat(param),
+        })
+        .collect();
+
+        Function {
+            params,
+            decorators: vec![],
+            span: DUMMY_SP,
+            body: Some(BlockStmt {
+                span: DUMMY_SP,
+                stmts,
+            }),
+            is_generator: false,
+            is_async: false,
+            type_params: None,
+            return_type: None,
+        }
+    }
+
+    /// `const _component_foo = _resolveComponent("foo"), ...`
+    fn generate_ssr_resolves(&mut self) -> Option<Stmt> {
+        let mut resolves = self.generate_component_resolves();
+        resolves.extend(self.generate_directive_resolves());
+        if resolves.is_empty() {
+            return None;
+        }
+
+        Some(Stmt::Decl(Decl::Var(Box::new(VarDecl {
+            span: DUMMY_SP,
+            kind: VarDeclKind::Const,
+            declare: false,
+            decls: resolves,
+        }))))
+    }
+
+    /// Generates any node into the buffer.
+    /// `is_root` means that the node should receive the fallthrough `_attrs`.
+    pub fn generate_ssr_node(&mut self, node: &Node, buf: &mut SsrBuffer, is_root: bool) {
+        match node {
+            Node::Text(contents, _) => buf.push_str(&escape_html(contents)),
+
+            Node::Interpolation(interpolation) => {
+                let expr = self.ssr_call(
+                    VueSsrImports::SsrInterpolate,
+                    vec![(*interpolation.value).to_owned()],
+                );
+                buf.push_expr(expr);
+            }
+
+            Node::Comment(comment, _) => {
+                buf.push_str("<!--");
+                buf.push_str(comment);
+                buf.push_str("-->");
+            }
+
+            Node::Element(element_node) => {
+                self.generate_ssr_element_or_component(element_node, buf, is_root)
+            }
+
+            Node::ConditionalSeq(conditional_seq) => {
+                self.generate_ssr_conditional_seq(conditional_seq, buf, is_root)
+            }
+        }
+    }
+
+    /// Generates a sequence of nodes into the buffer
+    pub fn generate_ssr_children(&mut self, children: &[Node], buf: &mut SsrBuffer) {
+        for child in children.iter() {
+            self.generate_ssr_node(child, buf, false);
+        }
+    }
+
+    /// Generates `_ssrHelper(args)` and remembers the import
+    pub(crate) fn ssr_call(&mut self, helper: VueSsrImports, args: Vec<Expr>) -> Expr {
+        let sym = self.get_and_add_ssr_import_ident(helper);
+        call_expr(sym, args)
+    }
+}
+
+/// Splits the SSR render arrow into its parameters and body statements
+fn unwrap_ssr_arrow(template_expr: Expr) -> (Vec<Pat>, Vec<Stmt>) {
+    match template_expr {
+        Expr::Arrow(arrow) => match *arrow.body {
+            BlockStmtOrExpr::BlockStmt(block) => (arrow.params, block.stmts),
+            BlockStmtOrExpr::Expr(expr) => (arrow.params, vec![push_stmt(*expr)]),
+        },
+        // Should not happen, but treat any other expression as HTML to push
+        expr => (
+            vec![
+                ident_pat("_ctx"),
+                ident_pat("_push"),
+                ident_pat("_parent"),
+                ident_pat("_attrs"),
+            ],
+            vec![push_stmt(expr)],
+        ),
+    }
+}
+
+/// Escapes the text to be safely placed in HTML text or a double-quoted attribute
+pub fn escape_html(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    for ch in s.chars() {
+        match ch {
+            '"' => out.push_str("&quot;"),
+            '&' => out.push_str("&amp;"),
+            '\'' => out.push_str("&#39;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            _ => out.push(ch),
+        }
+    }
+    out
+}
+
+/// Escapes the characters which have a special meaning in a template literal
+fn escape_template_raw(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    let mut chars = s.chars().peekable();
+    while let Some(ch) = chars.next() {
+        match ch {
+            '\\' => out.push_str("\\\\"),
+            '`' => out.push_str("\\`"),
+            '$' if chars.peek() == Some(&'{') => out.push_str("\\$"),
+            _ => out.push(ch),
+        }
+    }
+    out
+}
+
+/// `_push(expr)`
+fn css_vars_decl(ssr_css_vars: Box<Expr>) -> Stmt {
+    let style = PropOrSpread::Prop(Box::new(Prop::KeyValue(KeyValueProp {
+        key: PropName::Ident(Ident {
+            span: DUMMY_SP,
+            sym: fervid_atom!("style"),
+            optional: false,
+        }),
+        value: ssr_css_vars,
+    })));
+
+    Stmt::Decl(Decl::Var(Box::new(VarDecl {
+        span: DUMMY_SP,
+        kind: VarDeclKind::Const,
+        declare: false,
+        decls: vec![VarDeclarator {
+            span: DUMMY_SP,
+            name: ident_pat("_cssVars"),
+            init: Some(Box::new(Expr::Object(ObjectLit {
+                span: DUMMY_SP,
+                props: vec![style],
+            }))),
+            definite: false,
+        }],
+    })))
+}
+
+fn push_stmt(expr: Expr) -> Stmt {
+    Stmt::Expr(ExprStmt {
+        span: DUMMY_SP,
+        expr: Box::new(call_expr(FervidAtom::from("_push"), vec![expr])),
+    })
+}
+
+pub(crate) fn call_expr(callee: FervidAtom, args: Vec<Expr>) -> Expr {
+    Expr::Call(CallExpr {
+        span: DUMMY_SP,
+        callee: Callee::Expr(Box::new(ident_expr(callee))),
+        args: args
+            .into_iter()
+            .map(|expr| ExprOrSpread {
+                spread: None,
+                expr: Box::new(expr),
+            })
+            .collect(),
+        type_args: None,
+    })
+}
+
+pub(crate) fn ident_expr(sym: FervidAtom) -> Expr {
+    Expr::Ident(Ident {
+        span: DUMMY_SP,
+        sym,
+        optional: false,
+    })
+}
+
+pub(crate) fn ident_pat(sym: &str) -> Pat {
+    Pat::Ident(BindingIdent {
+        id: Ident {
+            span: DUMMY_SP,
+            sym: FervidAtom::from(sym),
+            optional: false,
+        },
+        type_ann: None,
+    })
+}
+
+#[inline]
+pub(crate) fn null_expr() -> Expr {
+    Expr::Lit(Lit::Null(Null { span: DUMMY_SP }))
+}
+
+pub(crate) fn str_expr(value: &str, span: Span) -> Expr {
+    Expr::Lit(Lit::Str(Str {
+        span,
+        value: FervidAtom::from(value),
+        raw: None,
+    }))
+}
+
+/// `(params) => { stmts }`
+pub(crate) fn ssr_arrow(params: Vec<Pat>, stmts: Vec<Stmt>) -> ArrowExpr {
+    ArrowExpr {
+        span: DUMMY_SP,
+        params,
+        body: Box::new(BlockStmtOrExpr::BlockStmt(BlockStmt {
+            span: DUMMY_SP,
+            stmts,
+        })),
+        is_async: false,
+        is_generator: false,
+        type_params: None,
+        return_type: None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use fervid_core::{AttributeOrBinding, VBindDirective};
+
+    use super::*;
+    use crate::test_utils::{
+        element, interpolation, js, regular_attribute, v_bind_attribute, v_on_attribute,
+    };
+
+    #[test]
+    fn it_generates_root_element_with_attrs() {
+        // <div foo="bar" @click="handle">hello {{ name }}</div>
+        test_out(
+            vec![Node::Element(element(
+                "div",
+                vec![
+                    regular_attribute("foo", "bar"),
+                    v_on_attribute("onClick", "handle"),
+                ],
+                vec![
+                    Node::Text("hello ".into(), DUMMY_SP),
+                    interpolation("name"),
+                ],
+            ))],
+            "(_ctx,_push,_parent,_attrs)=>{_push(`<div${_ssrRenderAttrs(_mergeProps({foo:\"bar\"},_attrs))}>hello ${_ssrInterpolate(name)}</div>`);}",
+        );
+    }
+
+    #[test]
+    fn it_generates_multiple_roots_as_fragment() {
+        // <span :title="t" class="a"></span><br>`text`
+        test_out(
+            vec![
+                Node::Element(element(
+                    "span",
+                    vec![
+                        v_bind_attribute("title", "t"),
+                        regular_attribute("class", "a"),
+                    ],
+                    vec![],
+                )),
+                Node::Element(element("br", vec![], vec![])),
+                Node::Text("`a<b`".into(), DUMMY_SP),
+            ],
+            "(_ctx,_push,_parent,_attrs)=>{_push(`<!--[--><span${_ssrRenderAttr(\"title\",t)} class=\"a\"></span><br>\\`a&lt;b\\`<!--]-->`);}",
+        );
+    }
+
+    #[test]
+    fn it_generates_css_vars() {
+        // <div v-bind="_cssVars"></div>
+        let mut ctx = CodegenContext {
+            ssr_css_vars: Some(js("{ \"--abcd1234-color\": _ctx.color }")),
+            ..Default::default()
+        };
+        let out = ctx
+            .generate_ssr_sfc_template(&SfcTemplateBlock {
+                lang: "html".into(),
+                roots: vec![Node::Element(element(
+                    "div",
+                    vec![AttributeOrBinding::VBind(VBindDirective {
+                        argument: None,
+                        value: js("_cssVars"),
+                        is_camel: false,
+                        is_prop: false,
+                        is_attr: false,
+                        span: DUMMY_SP,
+                    })],
+                    vec![],
+                ))],
+                span: DUMMY_SP,
+            })
+            .expect("Template should not be empty");
+
+        assert_eq!(
+            crate::test_utils::to_str(out),
+            "(_ctx,_push,_parent,_attrs)=>{const _cssVars={style:{\"--abcd1234-color\":_ctx.color}};_push(`<div${_ssrRenderAttrs(_mergeProps(_cssVars,_attrs))}></div>`);}"
+        );
+    }
+
+    fn test_out(roots: Vec<Node>, expected: &str) {
+        let mut ctx = CodegenContext::default();
+        let out = ctx
+            .generate_ssr_sfc_template(&SfcTemplateBlock {
+                lang: "html".into(),
+                roots,
+                span: DUMMY_SP,
+            })
+            .expect("Template should not be empty");
+        assert_eq!(crate::test_utils::to_str(out), expected)
+    }
+}
@@ -1,4 +1,7 @@
-use fervid_core::{AttributeOrBinding, VBindDirective, VOnDirective};
+use fervid_core::{
+    AttributeOrBinding, ElementKind, ElementNode, Interpolation, Node as TemplateNode, StartingTag,
+    VBindDirective, VOnDirective,
+};
 use swc_core::{
     common::{SourceMap, DUMMY_SP},
     ecma::ast::Expr,
@@ -62,6 +65,40 @@ pub fn v_on_attribute(name: &str, value: &str) -> AttributeOrBinding {
         event: Some(name.into()),
         handler: Some(js(value)),
         modifiers: vec![],
+        key_modifiers: vec![],
+        is_handler_cacheable: false,
+        span: DUMMY_SP,
+    })
+}
+
+/// TEST ONLY
+#[inline]
+pub fn element(
+    tag_name: &str,
+    attributes: Vec<AttributeOrBinding>,
+    children: Vec<TemplateNode>,
+) -> ElementNode {
+    ElementNode {
+        starting_tag: StartingTag {
+            tag_name: tag_name.into(),
+            attributes,
+            directives: None,
+        },
+        children,
+        template_scope: 0,
+        kind: ElementKind::Element,
+        patch_hints: Default::default(),
+        span: DUMMY_SP,
+    }
+}
+
+/// TEST ONLY
+#[inline]
+pub fn interpolation(value: &str) -> TemplateNode {
+    TemplateNode::Interpolation(Interpolation {
+        value: js(value),
+        template_scope: 0,
+        patch_flag: true,
         span: DUMMY_SP,
     })
 }
@@ -8,29 +8,7 @@ use crate::context::CodegenContext;
 
 impl CodegenContext {
     pub fn generate_text_node(&mut self, contents: &str, span: Span) -> Expr {
-        let has_start_whitespace = contents.starts_with(char::is_whitespace);
-        let has_end_whitespace = contents.ends_with(char::is_whitespace);
-        let needs_shortening = has_start_whitespace || has_end_whitespace;
-
-        let value = if needs_shortening {
-            let trimmed = contents.trim();
-            let new_len =
-                trimmed.len() + (has_start_whitespace as usize) + (has_end_whitespace as usize);
-
-            // Re-create a string with all start and end whitespace replaced by a single space
-            let mut shortened = String::with_capacity(new_len);
-            if has_start_whitespace {
-                shortened.push(' ');
-            }
-            shortened.push_str(trimmed);
-            if has_end_whitespace && trimmed.len() > 0 {
-                shortened.push(' ');
-            }
-
-            FervidAtom::from(shortened)
-        } else {
-            FervidAtom::from(contents)
-        };
+        let value = condense_whitespace(contents);
 
         Expr::Lit(Lit::Str(Str {
             span,
@@ -39,3 +17,30 @@ impl CodegenContext {
         }))
     }
 }
+
+/// Replaces all the leading and trailing whitespace of a text node with a single space
+pub(crate) fn condense_whitespace(contents: &str) -> FervidAtom {
+    let has_start_whitespace = contents.starts_with(char::is_whitespace);
+    let has_end_whitespace = contents.ends_with(char::is_whitespace);
+    let needs_shortening = has_start_whitespace || has_end_whitespace;
+
+    if needs_shortening {
+        let trimmed = contents.trim();
+        let new_len =
+            trimmed.len() + (has_start_whitespace as usize) + (has_end_whitespace as usize);
+
+        // Re-create a string with all start and end whitespace replaced by a single space
+        let mut shortened = String::with_capacity(new_len);
+        if has_start_whitespace {
+            shortened.push(' ');
+        }
+        shortened.push_str(trimmed);
+        if has_end_whitespace && !trimmed.is_empty() {
+            shortened.push(' ');
+        }
+
+        FervidAtom::from(shortened)
+    } else {
+        FervidAtom::from(contents)
+    }
+}
@@ -4,6 +4,28 @@ pub fn is_html_tag(tag: &str) -> bool {
     TAGS.contains(tag)
 }
 
+/// Checks whether the tag is a void element, i.e. it never has a closing tag
+pub fn is_void_tag(tag: &str) -> bool {
+    VOID_TAGS.contains(tag)
+}
+
+/// Checks whether the attribute is a boolean HTML attribute, e.g. `disabled`.
+/// These are rendered without a value when truthy and omitted otherwise.
+pub fn is_boolean_attr(attr: &str) -> bool {
+    BOOLEAN_ATTRS.contains(attr)
+}
+
+/// Checks whether the tag is an SVG element, e.g. `path`
+pub fn is_svg_tag(tag: &str) -> bool {
+    SVG_TAGS.contains(tag)
+}
+
+/// Checks whether the attribute is a known HTML attribute,
+/// i.e. it is always set as an attribute and never as a DOM property
+pub fn is_known_html_attr(attr: &str) -> bool {
+    KNOWN_HTML_ATTRS.contains(attr)
+}
+
 static TAGS: phf::Set<&'static str> = phf_set! {
     "a",
     "abbr",
@@ -172,3 +194,257 @@ static TAGS: phf::Set<&'static str> = phf_set! {
     "wbr",
     "xmp",
 };
+
+static VOID_TAGS: phf::Set<&'static str> = phf_set! {
+    "area",
+    "base",
+    "br",
+    "col",
+    "embed",
+    "hr",
+    "img",
+    "input",
+    "link",
+    "meta",
+    "param",
+    "source",
+    "track",
+    "wbr",
+};
+
+/// https://github.com/vuejs/core/blob/main/packages/shared/src/domAttrConfig.ts
+static BOOLEAN_ATTRS: phf::Set<&'static str> = phf_set! {
+    "allowfullscreen",
+    "async",
+    "autofocus",
+    "autoplay",
+    "checked",
+    "controls",
+    "default",
+    "defer",
+    "disabled",
+    "formnovalidate",
+    "hidden",
+    "inert",
+    "ismap",
+    "itemscope",
+    "loop",
+    "multiple",
+    "muted",
+    "nomodule",
+    "novalidate",
+    "open",
+    "readonly",
+    "required",
+    "reversed",
+    "scoped",
+    "seamless",
+    "selected",
+};
+
+/// https://github.com/vuejs/core/blob/main/packages/shared/src/domTagConfig.ts
+static SVG_TAGS: phf::Set<&'static str> = phf_set! {
+    "svg",
+    "animate",
+    "animateMotion",
+    "animateTransform",
+    "circle",
+    "clipPath",
+    "color-profile",
+    "defs",
+    "desc",
+    "discard",
+    "ellipse",
+    "feBlend",
+    "feColorMatrix",
+    "feComponentTransfer",
+    "feComposite",
+    "feConvolveMatrix",
+    "feDiffuseLighting",
+    "feDisplacementMap",
+    "feDistantLight",
+    "feDropShadow",
+    "feFlood",
+    "feFuncA",
+    "feFuncB",
+    "feFuncG",
+    "feFuncR",
+    "feGaussianBlur",
+    "feImage",
+    "feMerge",
+    "feMergeNode",
+    "feMorphology",
+    "feOffset",
+    "fePointLight",
+    "feSpecularLighting",
+    "feSpotLight",
+    "feTile",
+    "feTurbulence",
+    "filter",
+    "foreignObject",
+    "g",
+    "hatch",
+    "hatchpath",
+    "image",
+    "line",
+    "linearGradient",
+    "marker",
+    "mask",
+    "mesh",
+    "meshgradient",
+    "meshpatch",
+    "meshrow",
+    "metadata",
+    "mpath",
+    "path",
+    "pattern",
+    "polygon",
+    "polyline",
+    "radialGradient",
+    "rect",
+    "set",
+    "solidcolor",
+    "stop",
+    "switch",
+    "symbol",
+    "text",
+    "textPath",
+    "title",
+    "tspan",
+    "unknown",
+    "use",
+    "view",
+};
+
+/// https://github.com/vuejs/core/blob/main/packages/shared/src/domAttrConfig.ts
+static KNOWN_HTML_ATTRS: phf::Set<&'static str> = phf_set! {
+    "accept",
+    "accept-charset",
+    "accesskey",
+    "action",
+    "align",
+    "allow",
+    "alt",
+    "async",
+    "autocapitalize",
+    "autocomplete",
+    "autofocus",
+    "autoplay",
+    "background",
+    "bgcolor",
+    "border",
+    "buffered",
+    "capture",
+    "challenge",
+    "charset",
+    "checked",
+    "cite",
+    "class",
+    "code",
+    "codebase",
+    "color",
+    "cols",
+    "colspan",
+    "content",
+    "contenteditable",
+    "contextmenu",
+    "controls",
+    "coords",
+    "crossorigin",
+    "csp",
+    "data",
+    "datetime",
+    "decoding",
+    "default",
+    "defer",
+    "dir",
+    "dirname",
+    "disabled",
+    "download",
+    "draggable",
+    "dropzone",
+    "enctype",
+    "enterkeyhint",
+    "for",
+    "form",
+    "formaction",
+    "formenctype",
+    "formmethod",
+    "formnovalidate",
+    "formtarget",
+    "headers",
+    "height",
+    "hidden",
+    "high",
+    "href",
+    "hreflang",
+    "http-equiv",
+    "icon",
+    "id",
+    "importance",
+    "inert",
+    "integrity",
+    "ismap",
+    "itemprop",
+    "keytype",
+    "kind",
+    "label",
+    "lang",
+    "language",
+    "loading",
+    "list",
+    "loop",
+    "low",
+    "manifest",
+    "max",
+    "maxlength",
+    "minlength",
+    "media",
+    "min",
+    "multiple",
+    "muted",
+    "name",
+    "novalidate",
+    "open",
+    "optimum",
+    "pattern",
+    "ping",
+    "placeholder",
+    "poster",
+    "preload",
+    "radiogroup",
+    "readonly",
+    "referrerpolicy",
+    "rel",
+    "required",
+    "reversed",
+    "rows",
+    "rowspan",
+    "sandbox",
+    "scope",
+    "scoped",
+    "selected",
+    "shape",
+    "size",
+    "sizes",
+    "slot",
+    "span",
+    "spellcheck",
+    "src",
+    "srcdoc",
+    "srclang",
+    "srcset",
+    "start",
+    "step",
+    "style",
+    "summary",
+    "tabindex",
+    "target",
+    "title",
+    "translate",
+    "type",
+    "usemap",
+    "value",
+    "width",
+    "wrap",
+};
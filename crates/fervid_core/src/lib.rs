@@ -8,11 +8,13 @@ mod vue_builtins;
 mod vue_imports;
 mod utils;
 
-pub use all_html_tags::is_html_tag;
+pub use all_html_tags::{
+    is_boolean_attr, is_html_tag, is_known_html_attr, is_svg_tag, is_void_tag,
+};
 pub use bindings::*;
 pub use sfc::*;
 pub use structs::*;
 pub use template::is_from_default_slot;
 pub use vue_builtins::VUE_BUILTINS;
-pub use vue_imports::{VueImports, VueImportsSet};
+pub use vue_imports::{VueImports, VueImportsSet, VueSsrImports, VueSsrImportsSet};
 pub use utils::*;
@@ -1,4 +1,7 @@
-use swc_core::{common::Span, ecma::ast::Module};
+use swc_core::{
+    common::Span,
+    ecma::ast::{Module, TsTypeParamDecl},
+};
 
 use crate::{FervidAtom, Node, StartingTag};
 
@@ -9,6 +12,10 @@ pub struct SfcDescriptor {
     pub script_setup: Option<SfcScriptBlock>,
     pub styles: Vec<SfcStyleBlock>,
     pub custom_blocks: Vec<SfcCustomBlock>,
+    /// Whether any of the `<style scoped>` blocks uses `:slotted()` or `::v-slotted()`
+    pub slotted: bool,
+    /// Files the blocks are imported from, e.g. `./foo.css` in `<style src="./foo.css">`
+    pub src_imports: Vec<FervidAtom>,
 }
 
 #[derive(Clone, Debug)]
@@ -23,6 +30,8 @@ pub struct SfcScriptBlock {
     pub content: Box<Module>,
     pub lang: SfcScriptLang,
     pub is_setup: bool,
+    /// Type parameters from `<script setup generic="T extends Foo">`
+    pub generic: Option<Box<TsTypeParamDecl>>,
     pub span: Span,
 }
 
@@ -32,6 +41,8 @@ pub struct SfcStyleBlock {
     pub content: FervidAtom,
     pub is_scoped: bool,
     pub is_module: bool,
+    /// Name from `<style module="name">`, `None` means the default `$style`
+    pub module_name: Option<FervidAtom>,
     pub span: Span,
 }
 
@@ -303,6 +303,8 @@ pub struct VForDirective {
     /// `foo` in `v-for="foo in bar"`
     pub itervar: Box<Expr>,
     pub patch_flags: PatchFlagsSet,
+    /// Key of the `Fragment` when `v-for` is a branch of `v-if`, e.g. `{ key: 0 }`
+    pub fragment_key: Option<u32>,
     pub span: Span
 }
 
@@ -313,8 +315,15 @@ pub struct VOnDirective {
     pub event: Option<StrOrExpr>,
     /// What is the handler to use. If None, `modifiers` must not be empty.
     pub handler: Option<Box<Expr>>,
-    /// A list of modifiers after the dot, e.g. `stop` and `prevent` in `@click.stop.prevent="handleClick"`
+    /// A list of modifiers after the dot, e.g. `stop` and `prevent` in `@click.stop.prevent="handleClick"`.
+    /// After the transform, only the modifiers for `withModifiers` are left here.
     pub modifiers: Vec<FervidAtom>,
+    /// Key modifiers for `withKeys`, e.g. `enter` in `@keyup.enter="submit"`.
+    /// These are separated from `modifiers` by the transform.
+    pub key_modifiers: Vec<FervidAtom>,
+    /// Whether the handler can be cached in `_cache`, i.e. it is not a constant
+    /// and does not reference the template scope variables (e.g. from `v-for` or `v-slot`)
+    pub is_handler_cacheable: bool,
     /// Byte location in source
     pub span: Span
 }
@@ -347,6 +356,9 @@ pub struct VModelDirective {
     pub update_handler: Option<Box<Expr>>,
     /// `lazy` and `trim` in `v-model.lazy.trim`
     pub modifiers: Vec<FervidAtom>,
+    /// Whether the `update_handler` can be cached in `_cache`,
+    /// i.e. it does not reference the template scope variables (e.g. from `v-for` or `v-slot`)
+    pub is_handler_cacheable: bool,
     pub span: Span
 }
 
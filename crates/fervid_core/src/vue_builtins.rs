@@ -3,6 +3,7 @@ use phf::phf_map;
 use crate::BuiltinType;
 
 pub static VUE_BUILTINS: phf::Map<&'static str, BuiltinType> = phf_map! {
+    "component" => BuiltinType::Component,
     "keep-alive" => BuiltinType::KeepAlive,
     "KeepAlive" => BuiltinType::KeepAlive,
     "slot" => BuiltinType::Slot,
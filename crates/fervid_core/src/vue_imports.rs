@@ -13,6 +13,12 @@ flags! {
         CreateElementBlock,
         #[strum(serialize = "_createElementVNode")]
         CreateElementVNode,
+        #[strum(serialize = "_createPropsRestProxy")]
+        CreatePropsRestProxy,
+        #[strum(serialize = "_createSlots")]
+        CreateSlots,
+        #[strum(serialize = "_createStaticVNode")]
+        CreateStaticVNode,
         #[strum(serialize = "_createTextVNode")]
         CreateTextVNode,
         #[strum(serialize = "_createVNode")]
@@ -27,14 +33,22 @@ flags! {
         IsRef,
         #[strum(serialize = "_KeepAlive")]
         KeepAlive,
+        #[strum(serialize = "_mergeDefaults")]
+        MergeDefaults,
         #[strum(serialize = "_mergeModels")]
         MergeModels,
+        #[strum(serialize = "_mergeProps")]
+        MergeProps,
         #[strum(serialize = "_normalizeClass")]
         NormalizeClass,
         #[strum(serialize = "_normalizeStyle")]
         NormalizeStyle,
         #[strum(serialize = "_openBlock")]
         OpenBlock,
+        #[strum(serialize = "_popScopeId")]
+        PopScopeId,
+        #[strum(serialize = "_pushScopeId")]
+        PushScopeId,
         #[strum(serialize = "_renderList")]
         RenderList,
         #[strum(serialize = "_renderSlot")]
@@ -53,12 +67,16 @@ flags! {
         Teleport,
         #[strum(serialize = "_toDisplayString")]
         ToDisplayString,
+        #[strum(serialize = "_toHandlerKey")]
+        ToHandlerKey,
         #[strum(serialize = "_Transition")]
         Transition,
         #[strum(serialize = "_TransitionGroup")]
         TransitionGroup,
         #[strum(serialize = "_unref")]
         Unref,
+        #[strum(serialize = "_useCssVars")]
+        UseCssVars,
         #[strum(serialize = "_useModel")]
         UseModel,
         #[strum(serialize = "_useSlots")]
@@ -75,10 +93,14 @@ flags! {
         VModelText,
         #[strum(serialize = "_vShow")]
         VShow,
+        #[strum(serialize = "_withAsyncContext")]
+        WithAsyncContext,
         #[strum(serialize = "_withCtx")]
         WithCtx,
         #[strum(serialize = "_withDirectives")]
         WithDirectives,
+        #[strum(serialize = "_withKeys")]
+        WithKeys,
         #[strum(serialize = "_withMemo")]
         WithMemo,
         #[strum(serialize = "_withModifiers")]
@@ -99,3 +121,58 @@ impl VueImports {
 }
 
 pub type VueImportsSet = FlagSet<VueImports>;
+
+flags! {
+    /// Helpers from `vue/server-renderer` used by the SSR code generation
+    #[derive(AsRefStr, EnumString, IntoStaticStr)]
+    pub enum VueSsrImports: u32 {
+        #[strum(serialize = "_ssrGetDirectiveProps")]
+        SsrGetDirectiveProps,
+        #[strum(serialize = "_ssrIncludeBooleanAttr")]
+        SsrIncludeBooleanAttr,
+        #[strum(serialize = "_ssrInterpolate")]
+        SsrInterpolate,
+        #[strum(serialize = "_ssrLooseContain")]
+        SsrLooseContain,
+        #[strum(serialize = "_ssrLooseEqual")]
+        SsrLooseEqual,
+        #[strum(serialize = "_ssrRenderAttr")]
+        SsrRenderAttr,
+        #[strum(serialize = "_ssrRenderAttrs")]
+        SsrRenderAttrs,
+        #[strum(serialize = "_ssrRenderClass")]
+        SsrRenderClass,
+        #[strum(serialize = "_ssrRenderComponent")]
+        SsrRenderComponent,
+        #[strum(serialize = "_ssrRenderDynamicAttr")]
+        SsrRenderDynamicAttr,
+        #[strum(serialize = "_ssrRenderDynamicModel")]
+        SsrRenderDynamicModel,
+        #[strum(serialize = "_ssrRenderList")]
+        SsrRenderList,
+        #[strum(serialize = "_ssrRenderSlot")]
+        SsrRenderSlot,
+        #[strum(serialize = "_ssrRenderStyle")]
+        SsrRenderStyle,
+        #[strum(serialize = "_ssrRenderSuspense")]
+        SsrRenderSuspense,
+        #[strum(serialize = "_ssrRenderTeleport")]
+        SsrRenderTeleport,
+        #[strum(serialize = "_ssrRenderVNode")]
+        SsrRenderVNode,
+    }
+}
+
+impl VueSsrImports {
+    #[inline]
+    pub fn as_str(self) -> &'static str {
+        self.into()
+    }
+
+    #[inline]
+    pub fn as_atom(self) -> FervidAtom {
+        self.as_str().into()
+    }
+}
+
+pub type VueSsrImportsSet = FlagSet<VueSsrImports>;
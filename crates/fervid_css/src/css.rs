@@ -1,21 +1,83 @@
 mod codegen;
+mod compat;
 mod error;
+mod minify;
+mod modules;
 mod parse;
+mod source_map;
 mod transform;
+mod vars;
 
 use fervid_core::error::Severity;
+use preset_env_base::query::{targets_to_versions, Query, Targets};
 use swc_core::common::Span;
 use swc_css_parser::parser::ParserConfig;
+use swc_css_prefixer::{options::Options as PrefixerOptions, prefixer};
+use swc_css_visit::VisitMutWith;
 
-pub use codegen::{stringify, StringifyOptions};
-pub use error::CssError;
+pub use codegen::{stringify, stringify_with_mappings, StringifyOptions};
+pub use compat::lower_stylesheet;
+pub use error::{CssError, CssErrorKind};
+pub use minify::CssMinifier;
+pub use modules::{
+    generate_css_module_name, CssModuleExports, CssModulesMode, CssModulesOptions,
+    CssModulesTransformer, DEFAULT_CSS_MODULES_PATTERN,
+};
 pub use parse::parse_stylesheet;
+pub use source_map::CssSourceMapOptions;
 pub use transform::ScopedTransformer;
+pub use vars::{
+    escape_css_var_name, generate_css_var_name, parse_css_vars, CssVar, CssVarsOptions,
+    CssVarsTransformer,
+};
+
+/// Browser versions to lower and prefix the styles for
+pub type CssTargets = preset_env_base::Versions;
+
+/// Parses a browserslist query, e.g. `chrome 80, safari 13` or `defaults`
+pub fn parse_css_targets(query: &str) -> Result<CssTargets, String> {
+    targets_to_versions(Some(Targets::Query(Query::Single(query.to_owned()))))
+        .map_err(|e| e.to_string())
+}
+
+/// Post-processing of the transformed styles
+#[derive(Debug, Default, Clone, Copy)]
+pub struct CssOutputOptions {
+    /// Removes duplicate declarations, merges shorthands and compresses colors and numbers
+    pub minify: bool,
+    /// When present, the modern syntax is lowered and prefixed for these browsers
+    pub targets: Option<CssTargets>,
+    /// Generates a source map for each of the blocks
+    pub source_map: bool,
+}
+
+impl CssOutputOptions {
+    /// Whether any of the post-processing is enabled
+    pub fn is_enabled(&self) -> bool {
+        self.minify || self.targets.is_some() || self.source_map
+    }
+}
 
 #[derive(Default)]
-pub struct TransformCssConfig {
+pub struct TransformCssConfig<'c> {
     pub parse: ParserConfig,
     pub stringify: StringifyOptions,
+    /// When present, `v-bind()` is rewritten to `var(--name)`
+    pub css_vars: Option<CssVarsOptions<'c>>,
+    /// When present, local names are renamed as in CSS Modules
+    pub css_modules: Option<CssModulesOptions<'c>>,
+    /// Minification and lowering for the browser targets
+    pub output: CssOutputOptions,
+    /// When present, a source map into the original file is generated
+    pub source_map: Option<CssSourceMapOptions<'c>>,
+}
+
+pub struct TransformCssResult {
+    pub code: String,
+    /// Original to generated names when `css_modules` was enabled
+    pub exports: Option<CssModuleExports>,
+    /// Source map when `source_map` was enabled
+    pub map: Option<String>,
 }
 
 /// Transforms raw CSS, also handles the scopes.
@@ -25,10 +87,14 @@ pub fn transform_css(
     scope: Option<&str>,
     errors: &mut Vec<CssError>,
     config: TransformCssConfig,
-) -> Option<String> {
+) -> Option<TransformCssResult> {
     // Parse and collect errors
     let mut parse_errors = Vec::new();
-    let parse_result = parse_stylesheet(content, span, config.parse, &mut parse_errors);
+    let parse_config = ParserConfig {
+        css_modules: config.parse.css_modules || config.css_modules.is_some(),
+        ..config.parse
+    };
+    let parse_result = parse_stylesheet(content, span, parse_config, &mut parse_errors);
     let is_recoverable = parse_result.is_ok();
     errors.extend(parse_errors.into_iter().map(|e| {
         CssError::from_parse_error(e, is_recoverable, false)
@@ -39,14 +105,43 @@ pub fn transform_css(
     };
 
     // Transform and check for unrecoverable errors
+    if let Some(css_vars) = config.css_vars {
+        let mut transformer = CssVarsTransformer::new(content, span, css_vars);
+        transformer.transform(&mut stylesheet);
+    }
+    let exports = config.css_modules.map(|css_modules| {
+        let mut transformer = CssModulesTransformer::new(css_modules);
+        transformer.transform(&mut stylesheet);
+        transformer.take_exports()
+    });
     if let Some(scope) = scope {
         let mut transformer = ScopedTransformer::new(scope);
         transformer.transform(&mut stylesheet);
         errors.append(&mut transformer.take_errors());
     }
+    if let Some(targets) = config.output.targets {
+        lower_stylesheet(&mut stylesheet, targets);
+        stylesheet.visit_mut_with(&mut prefixer(PrefixerOptions {
+            env: Some(Targets::Versions(targets)),
+        }));
+    }
+    if config.output.minify {
+        CssMinifier.transform(&mut stylesheet);
+    }
     if errors.iter().any(Severity::is_unrecoverable_error) {
         return None;
     }
 
-    Some(stringify(&stylesheet, config.stringify))
+    let Some(source_map) = config.source_map else {
+        return Some(TransformCssResult {
+            code: stringify(&stylesheet, config.stringify),
+            exports,
+            map: None,
+        });
+    };
+
+    let (code, mappings) = stringify_with_mappings(&stylesheet, config.stringify);
+    let map = source_map::build_source_map(&mappings, content, span, source_map);
+
+    Some(TransformCssResult { code, exports, map })
 }
@@ -1,3 +1,4 @@
+use swc_core::common::{BytePos, LineCol};
 use swc_css_ast::{PseudoClassSelectorChildren, PseudoElementSelectorChildren, Stylesheet};
 use swc_css_codegen::{
     writer::basic::{BasicCssWriter, BasicCssWriterConfig},
@@ -33,6 +34,25 @@ pub fn stringify(node: &Stylesheet, options: StringifyOptions) -> String {
     buf
 }
 
+/// Stringifies the [`Stylesheet`] and collects the generated positions of its spans
+pub fn stringify_with_mappings(
+    node: &Stylesheet,
+    options: StringifyOptions,
+) -> (String, Vec<(BytePos, LineCol)>) {
+    let mut buf = String::new();
+    let mut mappings = Vec::new();
+    let writer = BasicCssWriter::new(&mut buf, Some(&mut mappings), options.basic_css_writer);
+    let mut codegen = CodeGenerator::new(
+        writer,
+        CodegenConfig {
+            minify: options.minify,
+        },
+    );
+    let _ = codegen.emit(&node);
+
+    (buf, mappings)
+}
+
 pub fn stringify_pseudo_class_selector_children(nodes: Vec<PseudoClassSelectorChildren>) -> String {
     let mut result = String::new();
     let writer = BasicCssWriter::new(&mut result, None, BasicCssWriterConfig::default());
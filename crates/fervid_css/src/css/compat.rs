@@ -0,0 +1,156 @@
+//! Lowering of the modern CSS syntax for the browser targets.
+//!
+//! E.g. nested rules are flattened and `#rrggbbaa` becomes `rgba()`
+//! when at least one of the targets does not support them natively.
+
+use preset_env_base::version::should_enable;
+use swc_css_ast::Stylesheet;
+use swc_css_compat::{
+    compiler::{Compiler, Config},
+    feature::Features,
+};
+use swc_css_visit::VisitMutWith;
+
+use super::CssTargets;
+
+/// First browser versions supporting a feature natively.
+/// The browsers which are not listed are assumed to lack the support.
+const FEATURE_SUPPORT: &[(Features, &[(&str, &str)])] = &[
+    (
+        Features::NESTING,
+        &[
+            ("chrome", "120"),
+            ("edge", "120"),
+            ("firefox", "117"),
+            ("safari", "17.2"),
+            ("ios", "17.2"),
+            ("opera", "106"),
+            ("samsung", "25"),
+        ],
+    ),
+    // Not supported by any browser yet
+    (Features::CUSTOM_MEDIA, &[]),
+    (
+        Features::MEDIA_QUERY_RANGES,
+        &[
+            ("chrome", "104"),
+            ("edge", "104"),
+            ("firefox", "63"),
+            ("safari", "16.4"),
+            ("ios", "16.4"),
+            ("opera", "91"),
+            ("samsung", "20"),
+        ],
+    ),
+    (
+        Features::COLOR_HEX_ALPHA,
+        &[
+            ("chrome", "62"),
+            ("edge", "79"),
+            ("firefox", "49"),
+            ("safari", "10"),
+            ("ios", "10"),
+            ("opera", "49"),
+            ("samsung", "8"),
+        ],
+    ),
+    (
+        Features::COLOR_ALPHA_PARAMETER
+            .union(Features::COLOR_SPACE_SEPARATED_PARAMETERS)
+            .union(Features::COLOR_LEGACY_RGB_AND_HSL),
+        &[
+            ("chrome", "65"),
+            ("edge", "79"),
+            ("firefox", "52"),
+            ("safari", "12.1"),
+            ("ios", "12.2"),
+            ("opera", "52"),
+            ("samsung", "9"),
+        ],
+    ),
+    (
+        Features::COLOR_HWB,
+        &[
+            ("chrome", "101"),
+            ("edge", "101"),
+            ("firefox", "96"),
+            ("safari", "15"),
+            ("ios", "15"),
+            ("opera", "87"),
+            ("samsung", "19"),
+        ],
+    ),
+    (
+        Features::SELECTOR_NOT,
+        &[
+            ("chrome", "88"),
+            ("edge", "88"),
+            ("firefox", "84"),
+            ("safari", "9"),
+            ("ios", "9"),
+            ("opera", "74"),
+            ("samsung", "15"),
+        ],
+    ),
+];
+
+/// Lowers the syntax which is not supported by some of the `targets`
+pub fn lower_stylesheet(stylesheet: &mut Stylesheet, targets: CssTargets) {
+    let mut features = features_for_targets(targets);
+
+    // The nesting pass does not visit the rules it flattens, thus the separate pass
+    if features.contains(Features::NESTING) {
+        stylesheet.visit_mut_with(&mut Compiler::new(Config {
+            process: Features::NESTING,
+        }));
+        features.remove(Features::NESTING);
+    }
+
+    if !features.is_empty() {
+        stylesheet.visit_mut_with(&mut Compiler::new(Config { process: features }));
+    }
+}
+
+/// Collects the features which need to be lowered for the `targets`
+fn features_for_targets(targets: CssTargets) -> Features {
+    let mut features = Features::empty();
+
+    for (feature, support) in FEATURE_SUPPORT {
+        let mut versions = CssTargets::default();
+        for (browser, version) in support.iter() {
+            versions.insert(browser, version.parse().ok());
+        }
+
+        if should_enable(targets, versions, true) {
+            features |= *feature;
+        }
+    }
+
+    features
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::css::parse_css_targets;
+
+    use super::*;
+
+    #[test]
+    fn it_lowers_only_unsupported_features() {
+        let features = |query: &str| features_for_targets(parse_css_targets(query).unwrap());
+
+        let old = features("chrome 60");
+        assert!(old.contains(Features::NESTING));
+        assert!(old.contains(Features::COLOR_HEX_ALPHA));
+        assert!(old.contains(Features::SELECTOR_NOT));
+
+        let modern = features("chrome 120, firefox 120, safari 17.2");
+        assert!(!modern.contains(Features::NESTING));
+        assert!(!modern.contains(Features::COLOR_HEX_ALPHA));
+        assert!(!modern.contains(Features::COLOR_HWB));
+        assert!(modern.contains(Features::CUSTOM_MEDIA));
+
+        // The oldest browser wins
+        assert!(features("chrome 120, safari 16").contains(Features::NESTING));
+    }
+}
@@ -14,6 +14,10 @@ pub enum CssErrorKind {
     ParseUnrecoverable(ParseErrorKind),
     ParseDeepRecoverable(ParseErrorKind),
     ParseDeepUnrecoverable(ParseErrorKind),
+    /// `StylePreprocessor` failed for `<style lang="...">`, e.g. because of a syntax error
+    PreprocessError(String),
+    /// Browser targets query could not be parsed
+    InvalidTargets(String),
     // MinifyError(Error<MinifyErrorKind>),
     // PrinterError(Error<PrinterErrorKind>),
 }
@@ -43,6 +47,8 @@ impl Severity for CssError {
             CssErrorKind::ParseUnrecoverable(_) => SeverityLevel::UnrecoverableError,
             CssErrorKind::ParseDeepRecoverable(_) => SeverityLevel::RecoverableError,
             CssErrorKind::ParseDeepUnrecoverable(_) => SeverityLevel::UnrecoverableError,
+            CssErrorKind::PreprocessError(_) => SeverityLevel::UnrecoverableError,
+            CssErrorKind::InvalidTargets(_) => SeverityLevel::RecoverableError,
         }
     }
 }
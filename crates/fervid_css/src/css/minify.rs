@@ -0,0 +1,206 @@
+//! Minification of `<style>` blocks.
+//!
+//! Whitespace is already removed by the codegen, `swc_css_minifier` takes care of
+//! colors, numbers, units and `calc()`. On top of it, this removes duplicate declarations
+//! and merges the longhands into a shorthand, which `swc_css_minifier` does not do.
+
+use swc_core::common::{EqIgnoreSpan, Globals, DUMMY_SP, GLOBALS};
+use swc_css_ast::{
+    AbsoluteColorBase, Color, ComponentValue, Declaration, DeclarationName, Ident, SimpleBlock,
+    Stylesheet,
+};
+use swc_css_minifier::{minify, options::MinifyOptions};
+use swc_css_visit::{VisitMut, VisitMutWith};
+
+/// Shorthands which are merged from `-top`, `-right`, `-bottom` and `-left` longhands
+const BOX_SHORTHANDS: [&str; 2] = ["margin", "padding"];
+const BOX_SIDES: [&str; 4] = ["top", "right", "bottom", "left"];
+
+/// Minifies the [`Stylesheet`] in-place
+pub struct CssMinifier;
+
+impl CssMinifier {
+    pub fn transform(&mut self, stylesheet: &mut Stylesheet) {
+        // The minifier creates spans, which needs the globals of `swc_common`
+        if GLOBALS.is_set() {
+            minify(stylesheet, MinifyOptions::default());
+        } else {
+            GLOBALS.set(&Globals::new(), || {
+                minify(stylesheet, MinifyOptions::default())
+            });
+        }
+
+        // Values are already normalized, e.g. `#f00` and `red` are the same
+        stylesheet.visit_mut_with(self);
+    }
+}
+
+impl VisitMut for CssMinifier {
+    fn visit_mut_simple_block(&mut self, simple_block: &mut SimpleBlock) {
+        simple_block.visit_mut_children_with(self);
+
+        remove_duplicate_declarations(&mut simple_block.value);
+        for shorthand in BOX_SHORTHANDS {
+            merge_box_shorthand(&mut simple_block.value, shorthand);
+        }
+    }
+}
+
+/// Removes the declarations which are overridden later in the same block,
+/// e.g. `color: red; color: blue` -> `color: blue`.
+/// Fallbacks like `display: -webkit-box; display: flex` are kept.
+fn remove_duplicate_declarations(values: &mut Vec<ComponentValue>) {
+    let mut idx = 0;
+    while idx < values.len() {
+        let ComponentValue::Declaration(ref declaration) = values[idx] else {
+            idx += 1;
+            continue;
+        };
+
+        let is_overridden = values[(idx + 1)..].iter().any(|later| {
+            let ComponentValue::Declaration(later) = later else {
+                return false;
+            };
+
+            let is_same_name = get_declaration_name(declaration).is_some()
+                && get_declaration_name(declaration) == get_declaration_name(later);
+            let is_not_weaker = later.important.is_some() || declaration.important.is_none();
+            let is_same_value = declaration.value.eq_ignore_span(&later.value)
+                && declaration.important.is_some() == later.important.is_some();
+
+            is_same_name
+                && (is_same_value
+                    || (is_not_weaker
+                        && is_plain_value(&declaration.value)
+                        && is_plain_value(&later.value)))
+        });
+
+        if is_overridden {
+            values.remove(idx);
+        } else {
+            idx += 1;
+        }
+    }
+}
+
+/// Merges e.g. `margin-top`, `margin-right`, `margin-bottom` and `margin-left` into `margin`.
+/// Only done when there are no other declarations of the same family (like `margin-inline`),
+/// because their relative order matters.
+fn merge_box_shorthand(values: &mut Vec<ComponentValue>, shorthand: &str) {
+    let mut longhands: [Option<usize>; 4] = [None; 4];
+
+    for (idx, value) in values.iter().enumerate() {
+        let ComponentValue::Declaration(declaration) = value else {
+            continue;
+        };
+        let Some(name) = get_declaration_name(declaration) else {
+            continue;
+        };
+        let Some(side) = name.strip_prefix(shorthand) else {
+            continue;
+        };
+
+        let Some(side_idx) = side
+            .strip_prefix('-')
+            .and_then(|side| BOX_SIDES.iter().position(|s| *s == side))
+        else {
+            // E.g. `margin` itself or `margin-inline`
+            return;
+        };
+
+        if longhands[side_idx].is_some() {
+            return;
+        }
+        longhands[side_idx] = Some(idx);
+    }
+
+    let [Some(top), Some(right), Some(bottom), Some(left)] = longhands else {
+        return;
+    };
+
+    // All the longhands must be equally important and have exactly one value
+    let get = |idx: usize| match values[idx] {
+        ComponentValue::Declaration(ref declaration) => declaration,
+        _ => unreachable!(),
+    };
+    let important = get(top).important.to_owned();
+    let can_merge = [top, right, bottom, left].iter().all(|idx| {
+        let declaration = get(*idx);
+        declaration.important.is_some() == important.is_some()
+            && declaration.value.len() == 1
+            && is_plain_value(&declaration.value)
+    });
+    if !can_merge {
+        return;
+    }
+
+    let mut shorthand_value: Vec<ComponentValue> = [top, right, bottom, left]
+        .iter()
+        .map(|idx| get(*idx).value[0].to_owned())
+        .collect();
+    collapse_box_values(&mut shorthand_value);
+
+    // The shorthand takes the place of the first longhand
+    let first = top.min(right).min(bottom).min(left);
+    values[first] = ComponentValue::Declaration(Box::new(Declaration {
+        span: DUMMY_SP,
+        name: DeclarationName::Ident(Ident {
+            span: DUMMY_SP,
+            value: shorthand.into(),
+            raw: None,
+        }),
+        value: shorthand_value,
+        important,
+    }));
+
+    let mut to_remove = [top, right, bottom, left];
+    to_remove.sort_unstable();
+    for idx in to_remove.into_iter().rev().filter(|idx| *idx != first) {
+        values.remove(idx);
+    }
+}
+
+/// Removes the redundant values of a box shorthand, e.g. `0 1px 0 1px` -> `0 1px`
+fn collapse_box_values(values: &mut Vec<ComponentValue>) {
+    // `top right bottom left`: `left` defaults to `right`
+    if values.len() == 4 && values[3].eq_ignore_span(&values[1]) {
+        values.pop();
+    }
+    // `top right bottom`: `bottom` defaults to `top`
+    if values.len() == 3 && values[2].eq_ignore_span(&values[0]) {
+        values.pop();
+    }
+    // `top right`: `right` defaults to `top`
+    if values.len() == 2 && values[1].eq_ignore_span(&values[0]) {
+        values.pop();
+    }
+}
+
+/// Lowercase name of a declaration, `None` for custom properties
+fn get_declaration_name(declaration: &Declaration) -> Option<String> {
+    match declaration.name {
+        DeclarationName::Ident(ref ident) => Some(ident.value.as_ref().to_ascii_lowercase()),
+        DeclarationName::DashedIdent(_) => None,
+    }
+}
+
+/// Value without functions (e.g. `var()` or `calc()`) and vendor prefixes,
+/// meaning that it cannot be a fallback for another value
+fn is_plain_value(values: &[ComponentValue]) -> bool {
+    values.iter().all(|value| match value {
+        ComponentValue::Ident(ident) => !ident.value.starts_with('-'),
+        ComponentValue::Color(color) => matches!(
+            **color,
+            Color::AbsoluteColorBase(AbsoluteColorBase::HexColor(_))
+                | Color::AbsoluteColorBase(AbsoluteColorBase::NamedColorOrTransparent(_))
+        ),
+        ComponentValue::Str(_)
+        | ComponentValue::Number(_)
+        | ComponentValue::Integer(_)
+        | ComponentValue::Percentage(_)
+        | ComponentValue::Dimension(_)
+        | ComponentValue::LengthPercentage(_)
+        | ComponentValue::Delimiter(_) => true,
+        _ => false,
+    })
+}
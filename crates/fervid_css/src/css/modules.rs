@@ -0,0 +1,373 @@
+//! Support for `<style module>`.
+//!
+//! Local class names, ids and `@keyframes` are renamed to unique names,
+//! e.g. `.red` becomes `.Comp_red_1a2b3c4d`, and the mapping is returned
+//! to be exposed on the component as `$style` (or the module name).
+
+use std::hash::Hasher;
+
+use fervid_core::FervidAtom;
+use fxhash::FxHasher32;
+use swc_css_ast::{
+    AtRule, AtRulePrelude, ComplexSelector, ComplexSelectorChildren, ComponentValue,
+    CompoundSelector, CustomIdent, Declaration, DeclarationName, KeyframesName,
+    PseudoClassSelectorChildren, Stylesheet, SubclassSelector,
+};
+use swc_css_visit::{VisitMut, VisitMutWith};
+
+use super::transform::is_keyframes;
+
+/// Default pattern for the generated names
+pub const DEFAULT_CSS_MODULES_PATTERN: &str = "[name]_[local]_[hash]";
+
+/// Original name to the generated name, in the order of appearance
+pub type CssModuleExports = Vec<(FervidAtom, FervidAtom)>;
+
+/// Whether the names are local (renamed) unless wrapped in `:global`,
+/// or global (kept as is) unless wrapped in `:local`
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum CssModulesMode {
+    #[default]
+    Local,
+    Global,
+}
+
+/// Options for generating the CSS Modules names
+#[derive(Debug, Clone, Copy)]
+pub struct CssModulesOptions<'c> {
+    /// Pattern supporting `[name]`, `[local]` and `[hash]` placeholders
+    pub pattern: &'c str,
+    /// Default mode of the selectors
+    pub mode: CssModulesMode,
+    /// Replaces `[name]`, usually the component file name without extension
+    pub name: &'c str,
+    /// Component id (usually a file hash) which is used for `[hash]`
+    pub id: &'c str,
+}
+
+/// Generates a unique name for a local class, id or keyframes
+pub fn generate_css_module_name(local: &str, options: &CssModulesOptions) -> String {
+    let mut hasher = FxHasher32::default();
+    hasher.write(options.id.as_bytes());
+    hasher.write(local.as_bytes());
+    let hash = format!("{:x}", hasher.finish());
+
+    options
+        .pattern
+        .replace("[name]", options.name)
+        .replace("[local]", local)
+        .replace("[hash]", &hash)
+}
+
+/// Renames the local names and collects the exports
+pub struct CssModulesTransformer<'s> {
+    options: CssModulesOptions<'s>,
+    exports: CssModuleExports,
+    /// Local `@keyframes` names, used for rewriting `animation` and `animation-name`
+    keyframes: Vec<FervidAtom>,
+}
+
+impl<'s> CssModulesTransformer<'s> {
+    pub fn new(options: CssModulesOptions<'s>) -> Self {
+        Self {
+            options,
+            exports: Vec::new(),
+            keyframes: Vec::new(),
+        }
+    }
+
+    /// The [`Stylesheet`] must be parsed with `css_modules: true`,
+    /// otherwise `:global(...)` and `:local(...)` are not recognized
+    pub fn transform(&mut self, stylesheet: &mut Stylesheet) {
+        // `@keyframes` may be defined after they are used in `animation`
+        let mut collector = KeyframesCollector {
+            mode: self.options.mode,
+            keyframes: &mut self.keyframes,
+        };
+        stylesheet.visit_mut_with(&mut collector);
+
+        stylesheet.visit_mut_with(self);
+    }
+
+    pub fn take_exports(&mut self) -> CssModuleExports {
+        std::mem::take(&mut self.exports)
+    }
+
+    /// Renames a local name and remembers it in the exports
+    fn rename(&mut self, local: &FervidAtom) -> FervidAtom {
+        if let Some((_, generated)) = self.exports.iter().find(|(name, _)| name == local) {
+            return generated.to_owned();
+        }
+
+        let generated: FervidAtom = generate_css_module_name(local, &self.options).into();
+        self.exports.push((local.to_owned(), generated.to_owned()));
+        generated
+    }
+
+    fn transform_complex_selector(&mut self, complex_selector: &mut ComplexSelector) {
+        let mut mode = self.options.mode;
+        let old_children = std::mem::take(&mut complex_selector.children);
+        let mut new_children = Vec::with_capacity(old_children.len());
+
+        for child in old_children {
+            let ComplexSelectorChildren::CompoundSelector(compound_selector) = child else {
+                new_children.push(child);
+                continue;
+            };
+
+            self.transform_compound_selector(compound_selector, &mut mode, &mut new_children);
+        }
+
+        remove_dangling_combinators(&mut new_children);
+        complex_selector.children = new_children;
+    }
+
+    /// Renames the selectors according to `mode`.
+    /// Bare `:global` and `:local` switch the mode for the rest of the selector,
+    /// while `:global(...)` and `:local(...)` are unwrapped in-place.
+    fn transform_compound_selector(
+        &mut self,
+        mut compound_selector: CompoundSelector,
+        mode: &mut CssModulesMode,
+        out: &mut Vec<ComplexSelectorChildren>,
+    ) {
+        let old_subclass_selectors = std::mem::take(&mut compound_selector.subclass_selectors);
+
+        for mut subclass_selector in old_subclass_selectors {
+            match subclass_selector {
+                SubclassSelector::Class(ref mut class) if *mode == CssModulesMode::Local => {
+                    class.text.value = self.rename(&class.text.value);
+                    class.text.raw = None;
+                }
+
+                SubclassSelector::Id(ref mut id) if *mode == CssModulesMode::Local => {
+                    id.text.value = self.rename(&id.text.value);
+                    id.text.raw = None;
+                }
+
+                SubclassSelector::PseudoClass(ref mut pseudo_class) => {
+                    let pseudo_mode = match &*pseudo_class.name.value {
+                        "global" => CssModulesMode::Global,
+                        "local" => CssModulesMode::Local,
+                        _ => {
+                            // E.g. `:not(.foo)`
+                            pseudo_class.visit_mut_children_with(self);
+                            compound_selector.subclass_selectors.push(subclass_selector);
+                            continue;
+                        }
+                    };
+
+                    let Some(children) = pseudo_class.children.take() else {
+                        *mode = pseudo_mode;
+                        continue;
+                    };
+
+                    for child in children {
+                        let PseudoClassSelectorChildren::ComplexSelector(mut inner) = child else {
+                            continue;
+                        };
+
+                        // Transform the inner selector using the mode of the pseudo-class
+                        let parent_mode = std::mem::replace(&mut self.options.mode, pseudo_mode);
+                        self.transform_complex_selector(&mut inner);
+                        self.options.mode = parent_mode;
+
+                        // The first compound is merged, e.g. `.a:global(.b .c)` -> `.a_hash.b .c`
+                        let mut inner_children = inner.children.into_iter();
+                        if let Some(ComplexSelectorChildren::CompoundSelector(first)) =
+                            inner_children.next()
+                        {
+                            if compound_selector.type_selector.is_none() {
+                                compound_selector.type_selector = first.type_selector;
+                            }
+                            if compound_selector.nesting_selector.is_none() {
+                                compound_selector.nesting_selector = first.nesting_selector;
+                            }
+                            compound_selector
+                                .subclass_selectors
+                                .extend(first.subclass_selectors);
+                        }
+
+                        // The rest of the selectors continue from the last compound
+                        let mut rest: Vec<_> = inner_children.collect();
+                        if let Some(ComplexSelectorChildren::CompoundSelector(last)) = rest.pop() {
+                            let previous = std::mem::replace(&mut compound_selector, last);
+                            push_compound_selector(out, previous);
+                            out.append(&mut rest);
+                        }
+                    }
+                    continue;
+                }
+
+                _ => {}
+            }
+
+            compound_selector.subclass_selectors.push(subclass_selector);
+        }
+
+        push_compound_selector(out, compound_selector);
+    }
+
+    /// Rewrites the names of local keyframes in `animation` and `animation-name`
+    fn transform_animation(&mut self, declaration: &mut Declaration) {
+        for value in declaration.value.iter_mut() {
+            let ComponentValue::Ident(ident) = value else {
+                continue;
+            };
+
+            if self.keyframes.contains(&ident.value) {
+                ident.value = self.rename(&ident.value);
+                ident.raw = None;
+            }
+        }
+    }
+}
+
+impl VisitMut for CssModulesTransformer<'_> {
+    fn visit_mut_complex_selector(&mut self, complex_selector: &mut ComplexSelector) {
+        self.transform_complex_selector(complex_selector);
+    }
+
+    fn visit_mut_at_rule(&mut self, at_rule: &mut AtRule) {
+        if is_keyframes(at_rule) {
+            if let Some(AtRulePrelude::KeyframesPrelude(name)) = at_rule.prelude.as_deref_mut() {
+                let (local, mode) = unwrap_keyframes_name(name, self.options.mode);
+                if let Some(mut local) = local {
+                    if mode == CssModulesMode::Local {
+                        local.value = self.rename(&local.value);
+                        local.raw = None;
+                    }
+                    *name = KeyframesName::CustomIdent(Box::new(local));
+                }
+            }
+        }
+
+        at_rule.visit_mut_children_with(self);
+    }
+
+    fn visit_mut_declaration(&mut self, declaration: &mut Declaration) {
+        let is_animation = matches!(
+            declaration.name,
+            DeclarationName::Ident(ref ident)
+                if ident.value.eq_ignore_ascii_case("animation")
+                    || ident.value.eq_ignore_ascii_case("animation-name")
+        );
+
+        if is_animation {
+            self.transform_animation(declaration);
+        }
+
+        declaration.visit_mut_children_with(self);
+    }
+}
+
+/// Collects the names of local `@keyframes`
+struct KeyframesCollector<'k> {
+    mode: CssModulesMode,
+    keyframes: &'k mut Vec<FervidAtom>,
+}
+
+impl VisitMut for KeyframesCollector<'_> {
+    fn visit_mut_at_rule(&mut self, at_rule: &mut AtRule) {
+        if is_keyframes(at_rule) {
+            if let Some(AtRulePrelude::KeyframesPrelude(name)) = at_rule.prelude.as_deref_mut() {
+                if let (Some(local), CssModulesMode::Local) = unwrap_keyframes_name(name, self.mode)
+                {
+                    self.keyframes.push(local.value);
+                }
+            }
+        }
+
+        at_rule.visit_mut_children_with(self);
+    }
+}
+
+/// Gets the name of `@keyframes name`, `@keyframes :global(name)` or `@keyframes :local name`
+fn unwrap_keyframes_name(
+    name: &KeyframesName,
+    mode: CssModulesMode,
+) -> (Option<CustomIdent>, CssModulesMode) {
+    match name {
+        KeyframesName::CustomIdent(custom_ident) => (Some((**custom_ident).to_owned()), mode),
+        KeyframesName::Str(_) => (None, mode),
+        KeyframesName::PseudoPrefix(prefix) => {
+            unwrap_pseudo_keyframes_name(&prefix.pseudo.value, &prefix.name)
+        }
+        KeyframesName::PseudoFunction(function) => {
+            unwrap_pseudo_keyframes_name(&function.pseudo.value, &function.name)
+        }
+    }
+}
+
+fn unwrap_pseudo_keyframes_name(
+    pseudo: &str,
+    name: &KeyframesName,
+) -> (Option<CustomIdent>, CssModulesMode) {
+    let mode = if pseudo.eq_ignore_ascii_case("global") {
+        CssModulesMode::Global
+    } else {
+        CssModulesMode::Local
+    };
+    unwrap_keyframes_name(name, mode)
+}
+
+/// Skips the compound selectors which became empty, e.g. bare `:global`
+fn push_compound_selector(
+    out: &mut Vec<ComplexSelectorChildren>,
+    compound_selector: CompoundSelector,
+) {
+    let is_empty = compound_selector.type_selector.is_none()
+        && compound_selector.nesting_selector.is_none()
+        && compound_selector.subclass_selectors.is_empty();
+
+    if !is_empty {
+        out.push(ComplexSelectorChildren::CompoundSelector(compound_selector));
+    }
+}
+
+/// Removes combinators which were left after removing the empty compound selectors,
+/// e.g. `:global .foo` -> ` .foo` -> `.foo`
+fn remove_dangling_combinators(children: &mut Vec<ComplexSelectorChildren>) {
+    let mut is_previous_combinator = true;
+    children.retain(|child| {
+        let is_combinator = matches!(child, ComplexSelectorChildren::Combinator(_));
+        let should_retain = !(is_combinator && is_previous_combinator);
+        if should_retain {
+            is_previous_combinator = is_combinator;
+        }
+        should_retain
+    });
+
+    if matches!(
+        children.last(),
+        Some(ComplexSelectorChildren::Combinator(_))
+    ) {
+        children.pop();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_generates_css_module_names() {
+        let options = CssModulesOptions {
+            pattern: DEFAULT_CSS_MODULES_PATTERN,
+            mode: CssModulesMode::Local,
+            name: "Comp",
+            id: "abcd1234",
+        };
+
+        let name = generate_css_module_name("red", &options);
+        assert!(name.starts_with("Comp_red_"));
+        assert_eq!(name, generate_css_module_name("red", &options));
+        assert_ne!(name, generate_css_module_name("blue", &options));
+
+        let options = CssModulesOptions {
+            pattern: "[local]",
+            ..options
+        };
+        assert_eq!(generate_css_module_name("red", &options), "red");
+    }
+}
@@ -0,0 +1,108 @@
+use sourcemap::{SourceMap, SourceMapBuilder};
+use swc_core::common::{BytePos, LineCol, Span};
+
+/// Options for the source map of the transformed CSS
+#[derive(Debug, Clone, Copy)]
+pub struct CssSourceMapOptions<'c> {
+    /// Original file the CSS `span` points into, e.g. the whole SFC.
+    /// Like in `fervid_parser`, the first byte of it is at `BytePos(1)`.
+    pub source: &'c str,
+    /// Name of the original file, e.g. `Comp.vue`
+    pub filename: &'c str,
+    /// Map of the preprocessor which produced the CSS from the original content of the span
+    pub input_map: Option<&'c str>,
+}
+
+/// Builds a JSON source map from the generated positions into the original file.
+/// Returns `None` when the `input_map` cannot be parsed.
+pub(crate) fn build_source_map(
+    mappings: &[(BytePos, LineCol)],
+    content: &str,
+    span: Span,
+    options: CssSourceMapOptions,
+) -> Option<String> {
+    let input_map = match options.input_map {
+        Some(input_map) => Some(SourceMap::from_slice(input_map.as_bytes()).ok()?),
+        None => None,
+    };
+
+    // Where the content starts in the original file
+    let start_offset = (span.lo.0 as usize).saturating_sub(1);
+    let (start_line, start_col) = LineIndex::new(options.source).line_col(start_offset)?;
+    let content_index = LineIndex::new(content);
+
+    let mut builder = SourceMapBuilder::new(None);
+    let src_id = builder.add_source(options.filename);
+    builder.set_source_contents(src_id, Some(options.source));
+
+    for (pos, generated) in mappings {
+        let Some(offset) = pos.0.checked_sub(span.lo.0) else {
+            continue;
+        };
+        let Some((mut line, mut col)) = content_index.line_col(offset as usize) else {
+            continue;
+        };
+
+        // Go through the preprocessor back to the original content
+        if let Some(ref input_map) = input_map {
+            let Some(token) = input_map
+                .lookup_token(line, col)
+                .filter(|t| t.get_dst_line() == line)
+            else {
+                continue;
+            };
+            line = token.get_src_line();
+            col = token.get_src_col();
+        }
+
+        // Positions inside the content are relative to its start
+        if line == 0 {
+            col += start_col;
+        }
+        line += start_line;
+
+        builder.add_raw(
+            generated.line,
+            generated.col,
+            line,
+            col,
+            Some(src_id),
+            None,
+            false,
+        );
+    }
+
+    let mut buf = Vec::new();
+    builder.into_sourcemap().to_writer(&mut buf).ok()?;
+    String::from_utf8(buf).ok()
+}
+
+/// Byte offsets of the line starts
+struct LineIndex<'i> {
+    input: &'i str,
+    line_starts: Vec<usize>,
+}
+
+impl<'i> LineIndex<'i> {
+    fn new(input: &'i str) -> Self {
+        let line_starts = std::iter::once(0)
+            .chain(input.match_indices('\n').map(|(idx, _)| idx + 1))
+            .collect();
+
+        Self { input, line_starts }
+    }
+
+    /// Gets zero-based line and UTF-16 column of the byte `offset`
+    fn line_col(&self, offset: usize) -> Option<(u32, u32)> {
+        let line = self.line_starts.partition_point(|&start| start <= offset) - 1;
+        let line_start = self.line_starts[line];
+        let col: usize = self
+            .input
+            .get(line_start..offset)?
+            .chars()
+            .map(char::len_utf16)
+            .sum();
+
+        Some((line as u32, col as u32))
+    }
+}
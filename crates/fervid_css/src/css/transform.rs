@@ -1,10 +1,14 @@
+use fervid_core::FervidAtom;
 use swc_core::common::{Span, Spanned, DUMMY_SP};
 use swc_css_ast::{
-    AtRule, AttributeSelector, Combinator, ComplexSelector, ComplexSelectorChildren,
-    ComponentValue, Ident, ListOfComponentValues, PseudoClassSelectorChildren,
-    PseudoElementSelectorChildren, QualifiedRulePrelude, Rule, SelectorList, SimpleBlock,
-    Stylesheet, SubclassSelector, WqName,
+    AtRule, AtRuleName, AtRulePrelude, AttributeSelector, Combinator, ComplexSelector,
+    ComplexSelectorChildren, ComponentValue, CompoundSelector, Declaration, DeclarationName,
+    ForgivingComplexSelector, Ident, KeyframesName, ListOfComponentValues,
+    PseudoClassSelectorChildren, PseudoElementSelectorChildren, QualifiedRule,
+    QualifiedRulePrelude, RelativeSelectorList, Rule, SelectorList, SimpleBlock, Stylesheet,
+    SubclassSelector, TypeSelector, WqName,
 };
+use swc_css_visit::{VisitMut, VisitMutWith};
 
 use super::{
     codegen::{
@@ -17,39 +21,56 @@ use super::{
 pub struct ScopedTransformer<'s> {
     scope: &'s str,
     errors: Vec<CssError>,
+    /// Whether a parent rule has `:deep`, so that the nested rules are not scoped
+    is_inside_deep: bool,
+    /// Names of `@keyframes` in this stylesheet, used for rewriting `animation` and `animation-name`
+    keyframes: Vec<FervidAtom>,
 }
 
-impl<'i, 's> ScopedTransformer<'s> {
+impl<'s> ScopedTransformer<'s> {
     pub fn new(scope: &'s str) -> Self {
         Self {
             scope,
             errors: vec![],
+            is_inside_deep: false,
+            keyframes: Vec::new(),
         }
     }
 
     pub fn transform(&mut self, stylesheet: &mut Stylesheet) {
-        for rule in stylesheet.rules.iter_mut() {
+        // `@keyframes` may be defined after they are used in `animation`
+        let mut collector = KeyframesCollector {
+            keyframes: &mut self.keyframes,
+        };
+        stylesheet.visit_mut_with(&mut collector);
+
+        let old_rules = std::mem::take(&mut stylesheet.rules);
+        stylesheet.rules.reserve(old_rules.len());
+
+        for mut rule in old_rules {
+            let mut hoisted_rules = Vec::new();
+
             match rule {
-                Rule::QualifiedRule(qualified_rule) => match qualified_rule.prelude {
-                    QualifiedRulePrelude::SelectorList(ref mut selector_list) => {
-                        self.transform_selector_list(selector_list);
-                    }
-                    QualifiedRulePrelude::RelativeSelectorList(_) => {}
-                    QualifiedRulePrelude::ListOfComponentValues(
-                        ref mut list_of_component_values,
-                    ) => {
-                        self.transform_list_of_component_values(list_of_component_values);
-                    }
-                },
+                Rule::QualifiedRule(ref mut qualified_rule) => {
+                    hoisted_rules = self.take_global_rules(&mut qualified_rule.block);
+                    self.transform_qualified_rule(qualified_rule);
+                }
 
-                Rule::AtRule(at_rule) => {
+                Rule::AtRule(ref mut at_rule) => {
                     self.transform_at_rule(at_rule);
                 }
 
-                Rule::ListOfComponentValues(list_of_component_values) => {
+                Rule::ListOfComponentValues(ref mut list_of_component_values) => {
                     self.transform_list_of_component_values(list_of_component_values);
                 }
             }
+
+            stylesheet.rules.push(rule);
+            stylesheet.rules.extend(
+                hoisted_rules
+                    .into_iter()
+                    .map(|hoisted| Rule::QualifiedRule(Box::new(hoisted))),
+            );
         }
     }
 
@@ -59,132 +80,85 @@ impl<'i, 's> ScopedTransformer<'s> {
 
     /// This is the meat of the scoped transform
     pub fn transform_complex_selector(&mut self, complex_selector: &mut ComplexSelector) {
-        let mut deep_idx: Option<usize> = None;
-        let mut deep_children: Option<ComplexSelector> = None;
-        let mut is_deep_alone = false;
-        let mut previous_compound_selector_idx = 0;
-
-        // 1.
-        // Search phase. This would find the `:deep` or `::v-deep`,
-        // take its contents and also rewrite when it is not alone in `CompoundSelector`
-        for (idx, complex_selector_child) in complex_selector.children.iter_mut().enumerate() {
-            let ComplexSelectorChildren::CompoundSelector(compound_selector) =
-                complex_selector_child
-            else {
-                continue;
-            };
-
-            let compound_selector_len = compound_selector.subclass_selectors.len()
-                + compound_selector.type_selector.is_some() as usize // 1 if Some
-                + compound_selector.nesting_selector.is_some() as usize; // 1 if Some
-
-            // Find the `:deep`
-            let deep = compound_selector
-                .subclass_selectors
-                .iter_mut()
-                .find(|sel| match sel {
-                    SubclassSelector::PseudoClass(pseudo) if pseudo.name.value == "deep" => true,
-                    SubclassSelector::PseudoElement(pseudo) if pseudo.name.value == "v-deep" => {
-                        true
-                    }
-                    _ => false,
-                });
+        // `:root`, `html` and `body` are never inside the component, thus not scoped
+        if is_root_only(complex_selector) {
+            return;
+        }
 
-            // Remember the `CompoundSelector` idx if no `:deep` or it is not alone.
-            // This is for correctly inserting the `AttributeSelector`.
-            if deep.is_none() || compound_selector_len != 1 {
-                previous_compound_selector_idx = idx;
+        // `:deep()`, `:slotted()` and `:global()` replace the usual scoping
+        match find_scoped_pseudo(complex_selector) {
+            Some((idx, ScopedPseudo::Deep)) => {
+                self.transform_deep(complex_selector, idx);
             }
-
-            let Some(deep) = deep else {
-                continue;
-            };
-
-            // Alone means there are no other selectors in this `CompoundSelector`
-            is_deep_alone = compound_selector_len == 1;
-
-            // Take `children` from `:deep` or `::v-deep`
-            match deep {
-                SubclassSelector::PseudoClass(deep_pseudo_class) => {
-                    if let Some(children) = deep_pseudo_class.children.take() {
-                        deep_children = process_pseudo_class_children(children, &mut self.errors);
-                    }
-                }
-
-                SubclassSelector::PseudoElement(deep_pseudo_element) => {
-                    if let Some(children) = deep_pseudo_element.children.take() {
-                        deep_children = process_pseudo_element_children(children, &mut self.errors);
-                    }
-                }
-
-                _ => unreachable!(),
+            Some((idx, ScopedPseudo::Slotted)) => {
+                self.transform_slotted(complex_selector, idx);
+            }
+            Some((idx, ScopedPseudo::Global)) => {
+                self.transform_global(complex_selector, idx);
+            }
+            None => {
+                let attribute = self.get_subclass_selector_to_add();
+                insert_scope_attribute(&mut complex_selector.children, &attribute);
             }
-
-            // Rewrite deep with `AttributeSelector` (e.g. `[data-v-abcd]`)
-            *deep = self.get_subclass_selector_to_add();
-
-            deep_idx = Some(idx);
-            break;
         }
+    }
 
-        // 2.
-        // Check that we actually found `:deep`.
-        // If not, just add to the `previous_compound_selector_idx`
-        let Some(deep_idx) = deep_idx else {
-            if let Some(ComplexSelectorChildren::CompoundSelector(last_compound_selector)) =
-                complex_selector
-                    .children
-                    .get_mut(previous_compound_selector_idx)
-            {
-                last_compound_selector
-                    .subclass_selectors
-                    .push(self.get_subclass_selector_to_add());
-            }
+    /// Rewrites `:deep(.bar)` or `::v-deep(.bar)` so that only the part before it is scoped,
+    /// e.g. `.foo :deep(.bar)` -> `.foo[data-v-abcd1234] .bar`
+    fn transform_deep(&mut self, complex_selector: &mut ComplexSelector, idx: usize) {
+        let Some(ComplexSelectorChildren::CompoundSelector(compound_selector)) =
+            complex_selector.children.get_mut(idx)
+        else {
+            return;
+        };
 
+        let Some(deep_idx) = compound_selector
+            .subclass_selectors
+            .iter()
+            .position(|sel| matches!(get_scoped_pseudo(sel), Some(ScopedPseudo::Deep)))
+        else {
             return;
         };
 
-        // Special case: deep is the only selector in `ComplexSelector`
-        let is_deep_really_alone = complex_selector.children.len() == 1;
+        let mut deep = compound_selector.subclass_selectors.remove(deep_idx);
+        let deep_children = take_pseudo_children(&mut deep, &mut self.errors);
 
-        // 3.
-        // Cut the array after the `deep_idx`
+        // Alone means there are no other selectors in this `CompoundSelector`
+        let is_deep_alone = compound_selector.subclass_selectors.is_empty()
+            && compound_selector.type_selector.is_none()
+            && compound_selector.nesting_selector.is_none();
+
+        // Cut the array after the `:deep`
         let mut selectors_after_deep: Vec<ComplexSelectorChildren> =
-            complex_selector.children.drain((deep_idx + 1)..).collect();
-
-        // 4.
-        // Check if deep we found is alone.
-        // In this case we need to add `[data-v]` to the previous `CompoundSelector`.
-        if is_deep_alone && !is_deep_really_alone {
-            // Remove the lonely `:deep` or `::v-deep` (which was already transformed to `[data-v]`)
-            let Some(ComplexSelectorChildren::CompoundSelector(mut deep_alone)) =
-                complex_selector.children.pop()
-            else {
-                unreachable!()
-            };
+            complex_selector.children.drain((idx + 1)..).collect();
 
-            let Some(subclass_selector) = deep_alone.subclass_selectors.pop() else {
-                unreachable!()
-            };
+        // The lonely `:deep` is removed, and `[data-v]` goes to the previous `CompoundSelector`
+        if is_deep_alone {
+            complex_selector.children.pop();
+        }
 
-            // Add to the previously found `CompoundSelector`
-            let Some(ComplexSelectorChildren::CompoundSelector(previous_compound_selector)) =
-                complex_selector
-                    .children
-                    .get_mut(previous_compound_selector_idx)
-            else {
-                unreachable!()
-            };
+        let attribute = self.get_subclass_selector_to_add();
+        let needs_combinator = if complex_selector.children.is_empty() {
+            // Special case: deep is the only selector in `ComplexSelector`,
+            // e.g. `:deep(.bar)` -> `[data-v-abcd1234] .bar`
+            complex_selector
+                .children
+                .push(ComplexSelectorChildren::CompoundSelector(
+                    CompoundSelector {
+                        span: DUMMY_SP,
+                        nesting_selector: None,
+                        type_selector: None,
+                        subclass_selectors: vec![attribute],
+                    },
+                ));
+            true
+        } else {
+            insert_scope_attribute(&mut complex_selector.children, &attribute);
+            !is_deep_alone
+        };
 
-            previous_compound_selector
-                .subclass_selectors
-                .push(subclass_selector);
-        } else if deep_children.is_some()
-            && (is_deep_alone && is_deep_really_alone || !is_deep_alone)
-        {
-            // Add descendant Combinator (` `) when deep is:
-            // - part of other `CompoundSelector`, or
-            // - a single selector inside `ComplexSelector` and has children.
+        // Add descendant `Combinator` (` `) when there is no combinator before `:deep` contents
+        if needs_combinator && deep_children.is_some() {
             complex_selector
                 .children
                 .push(ComplexSelectorChildren::Combinator(Combinator {
@@ -193,7 +167,6 @@ impl<'i, 's> ScopedTransformer<'s> {
                 }));
         }
 
-        // 5.
         // Add children of deep
         if let Some(mut deep_children_parsed) = deep_children {
             complex_selector
@@ -201,48 +174,209 @@ impl<'i, 's> ScopedTransformer<'s> {
                 .append(&mut deep_children_parsed.children);
         }
 
-        // 6.
         // Put back the remaining parts
         complex_selector.children.append(&mut selectors_after_deep);
     }
 
+    /// Rewrites `:slotted(.foo)` or `::v-slotted(.foo)` to `.foo[data-v-abcd1234-s]`.
+    /// The rest of the selector is not scoped, because the slotted attribute already scopes it.
+    fn transform_slotted(&mut self, complex_selector: &mut ComplexSelector, idx: usize) {
+        let Some(ComplexSelectorChildren::CompoundSelector(compound_selector)) =
+            complex_selector.children.get_mut(idx)
+        else {
+            return;
+        };
+
+        let Some(slotted_idx) = compound_selector
+            .subclass_selectors
+            .iter()
+            .position(|sel| matches!(get_scoped_pseudo(sel), Some(ScopedPseudo::Slotted)))
+        else {
+            return;
+        };
+
+        let mut slotted = compound_selector.subclass_selectors.remove(slotted_idx);
+        let Some(slotted_children) = take_pseudo_children(&mut slotted, &mut self.errors) else {
+            return;
+        };
+
+        // Add `[data-v-abcd1234-s]` to the `:slotted` contents
+        let mut slotted_children = slotted_children.children;
+        let attribute = self.get_slotted_subclass_selector_to_add();
+        insert_scope_attribute(&mut slotted_children, &attribute);
+
+        // The first `CompoundSelector` is merged, e.g. `.foo:slotted(.bar)` -> `.foo.bar[data-v-abcd1234-s]`
+        let mut slotted_children = slotted_children.into_iter();
+        if let Some(ComplexSelectorChildren::CompoundSelector(first)) = slotted_children.next() {
+            if compound_selector.type_selector.is_none() {
+                compound_selector.type_selector = first.type_selector;
+            }
+            compound_selector
+                .subclass_selectors
+                .extend(first.subclass_selectors);
+        }
+
+        // And the rest is put after it
+        complex_selector
+            .children
+            .splice((idx + 1)..(idx + 1), slotted_children);
+    }
+
+    /// Rewrites the whole selector containing `:global(.foo)` or `::v-global(.foo)` to `.foo`
+    fn transform_global(&mut self, complex_selector: &mut ComplexSelector, idx: usize) {
+        let Some(ComplexSelectorChildren::CompoundSelector(compound_selector)) =
+            complex_selector.children.get_mut(idx)
+        else {
+            return;
+        };
+
+        let Some(global) = compound_selector
+            .subclass_selectors
+            .iter_mut()
+            .find(|sel| matches!(get_scoped_pseudo(sel), Some(ScopedPseudo::Global)))
+        else {
+            return;
+        };
+
+        if let Some(global_children) = take_pseudo_children(global, &mut self.errors) {
+            complex_selector.children = global_children.children;
+        }
+    }
+
+    /// Takes out the nested rules with `:global()` selectors, so that they are not prefixed by the parent rule.
+    /// E.g. `.foo { :global(.bar) {} }` -> `.foo {} .bar {}`
+    fn take_global_rules(&mut self, block: &mut SimpleBlock) -> Vec<QualifiedRule> {
+        let mut result = Vec::new();
+
+        let old_values = std::mem::take(&mut block.value);
+        for component_value in old_values {
+            let ComponentValue::QualifiedRule(mut nested_rule) = component_value else {
+                block.value.push(component_value);
+                continue;
+            };
+
+            let mut nested_hoisted_rules = self.take_global_rules(&mut nested_rule.block);
+
+            // All the selectors must be global, otherwise hoisting changes the meaning
+            let is_all_global = match nested_rule.prelude {
+                QualifiedRulePrelude::RelativeSelectorList(ref relative_selector_list) => {
+                    relative_selector_list.children.iter().all(|relative| {
+                        relative.combinator.is_none()
+                            && matches!(
+                                find_scoped_pseudo(&relative.selector),
+                                Some((_, ScopedPseudo::Global))
+                            )
+                    })
+                }
+                _ => false,
+            };
+
+            if !is_all_global {
+                block.value.push(ComponentValue::QualifiedRule(nested_rule));
+                result.append(&mut nested_hoisted_rules);
+                continue;
+            }
+
+            let QualifiedRulePrelude::RelativeSelectorList(ref mut relative_selector_list) =
+                nested_rule.prelude
+            else {
+                unreachable!()
+            };
+
+            let mut selector_list = SelectorList {
+                span: relative_selector_list.span,
+                children: relative_selector_list
+                    .children
+                    .drain(..)
+                    .map(|relative| relative.selector)
+                    .collect(),
+            };
+            self.transform_selector_list(&mut selector_list);
+            nested_rule.prelude = QualifiedRulePrelude::SelectorList(selector_list);
+
+            result.push(*nested_rule);
+            result.append(&mut nested_hoisted_rules);
+        }
+
+        result
+    }
+
     /// 0. Prepare what selector to add.
     /// It is always an attribute selector, e.g. `[data-v-abcd1234]`
     fn get_subclass_selector_to_add(&self) -> SubclassSelector {
-        SubclassSelector::Attribute(Box::new(AttributeSelector {
-            span: DUMMY_SP,
-            name: WqName {
-                span: DUMMY_SP,
-                prefix: None,
-                value: Ident {
-                    span: DUMMY_SP,
-                    value: self.scope.into(),
-                    raw: None,
-                },
-            },
-            matcher: None,
-            value: None,
-            modifier: None,
-        }))
+        create_attribute_selector(self.scope.into())
+    }
+
+    /// Same as [`ScopedTransformer::get_subclass_selector_to_add`], but for slots, e.g. `[data-v-abcd1234-s]`
+    fn get_slotted_subclass_selector_to_add(&self) -> SubclassSelector {
+        create_attribute_selector(format!("{}-s", self.scope).into())
+    }
+
+    /// Scoped keyframes get the scope suffix, e.g. `@keyframes fade` -> `@keyframes fade-abcd1234`
+    fn get_scoped_keyframes_name(&self, name: &str) -> FervidAtom {
+        let id = self.scope.strip_prefix("data-v-").unwrap_or(self.scope);
+        format!("{name}-{id}").into()
     }
 
     fn transform_at_rule(&mut self, at_rule: &mut AtRule) {
+        if is_keyframes(at_rule) {
+            if let Some(AtRulePrelude::KeyframesPrelude(KeyframesName::CustomIdent(
+                ref mut custom_ident,
+            ))) = at_rule.prelude.as_deref_mut()
+            {
+                custom_ident.value = self.get_scoped_keyframes_name(&custom_ident.value);
+                custom_ident.raw = None;
+            }
+
+            // Keyframe blocks have no selectors to scope
+            return;
+        }
+
         if let Some(ref mut at_rule_block) = at_rule.block {
             self.transform_simple_block(at_rule_block);
         };
     }
 
-    fn transform_component_value(&mut self, component_value: &mut ComponentValue) {
-        match component_value {
-            ComponentValue::QualifiedRule(qual) => match qual.prelude {
+    /// Transforms the rule selectors and the nested rules, e.g. `.foo { & .bar {} }`
+    fn transform_qualified_rule(&mut self, qualified_rule: &mut QualifiedRule) {
+        let has_deep = match qualified_rule.prelude {
+            QualifiedRulePrelude::SelectorList(ref selector_list) => {
+                selector_list.children.iter().any(has_deep)
+            }
+            QualifiedRulePrelude::RelativeSelectorList(ref relative_selector_list) => {
+                relative_selector_list
+                    .children
+                    .iter()
+                    .any(|relative| has_deep(&relative.selector))
+            }
+            QualifiedRulePrelude::ListOfComponentValues(_) => false,
+        };
+
+        if !self.is_inside_deep {
+            match qualified_rule.prelude {
                 QualifiedRulePrelude::SelectorList(ref mut selector_list) => {
                     self.transform_selector_list(selector_list);
                 }
-                QualifiedRulePrelude::RelativeSelectorList(_) => {}
+                QualifiedRulePrelude::RelativeSelectorList(ref mut relative_selector_list) => {
+                    self.transform_relative_selector_list(relative_selector_list);
+                }
                 QualifiedRulePrelude::ListOfComponentValues(ref mut list_of_component_values) => {
                     self.transform_list_of_component_values(list_of_component_values);
                 }
-            },
+            }
+        }
+
+        let was_inside_deep = self.is_inside_deep;
+        self.is_inside_deep |= has_deep;
+        self.transform_simple_block(&mut qualified_rule.block);
+        self.is_inside_deep = was_inside_deep;
+    }
+
+    fn transform_component_value(&mut self, component_value: &mut ComponentValue) {
+        match component_value {
+            ComponentValue::QualifiedRule(qualified_rule) => {
+                self.transform_qualified_rule(qualified_rule);
+            }
 
             ComponentValue::ComplexSelector(complex_selector) => {
                 self.transform_complex_selector(complex_selector);
@@ -260,10 +394,39 @@ impl<'i, 's> ScopedTransformer<'s> {
                 self.transform_list_of_component_values(list_of_component_values);
             }
 
+            ComponentValue::Declaration(declaration) => {
+                self.transform_declaration(declaration);
+            }
+
             _ => {}
         }
     }
 
+    /// Rewrites the names of scoped keyframes in `animation` and `animation-name`,
+    /// including the vendor-prefixed ones, e.g. `-webkit-animation`
+    fn transform_declaration(&mut self, declaration: &mut Declaration) {
+        let DeclarationName::Ident(ref name) = declaration.name else {
+            return;
+        };
+
+        let name = name.value.to_ascii_lowercase();
+        let name = strip_vendor_prefix(&name);
+        if name != "animation" && name != "animation-name" {
+            return;
+        }
+
+        for value in declaration.value.iter_mut() {
+            let ComponentValue::Ident(ident) = value else {
+                continue;
+            };
+
+            if self.keyframes.contains(&ident.value) {
+                ident.value = self.get_scoped_keyframes_name(&ident.value);
+                ident.raw = None;
+            }
+        }
+    }
+
     fn transform_list_of_component_values(
         &mut self,
         list_of_component_values: &mut ListOfComponentValues,
@@ -279,11 +442,300 @@ impl<'i, 's> ScopedTransformer<'s> {
         }
     }
 
+    fn transform_relative_selector_list(
+        &mut self,
+        relative_selector_list: &mut RelativeSelectorList,
+    ) {
+        for relative_selector in relative_selector_list.children.iter_mut() {
+            self.transform_complex_selector(&mut relative_selector.selector);
+        }
+    }
+
     fn transform_simple_block(&mut self, simple_block: &mut SimpleBlock) {
-        for component_value in simple_block.value.iter_mut() {
-            self.transform_component_value(component_value);
+        let old_values = std::mem::take(&mut simple_block.value);
+        simple_block.value.reserve(old_values.len());
+
+        for mut component_value in old_values {
+            // Global rules are taken before the transform, otherwise `:global` is already gone
+            let hoisted_rules = match component_value {
+                ComponentValue::QualifiedRule(ref mut qualified_rule) => {
+                    self.take_global_rules(&mut qualified_rule.block)
+                }
+                _ => Vec::new(),
+            };
+
+            self.transform_component_value(&mut component_value);
+
+            simple_block.value.push(component_value);
+            simple_block.value.extend(
+                hoisted_rules
+                    .into_iter()
+                    .map(|hoisted| ComponentValue::QualifiedRule(Box::new(hoisted))),
+            );
+        }
+    }
+}
+
+/// Collects the names of `@keyframes`
+struct KeyframesCollector<'k> {
+    keyframes: &'k mut Vec<FervidAtom>,
+}
+
+impl VisitMut for KeyframesCollector<'_> {
+    fn visit_mut_at_rule(&mut self, at_rule: &mut AtRule) {
+        if is_keyframes(at_rule) {
+            if let Some(AtRulePrelude::KeyframesPrelude(KeyframesName::CustomIdent(
+                ref custom_ident,
+            ))) = at_rule.prelude.as_deref()
+            {
+                self.keyframes.push(custom_ident.value.to_owned());
+            }
+        }
+
+        at_rule.visit_mut_children_with(self);
+    }
+}
+
+/// Checks for `@keyframes`, including the vendor-prefixed ones, e.g. `@-webkit-keyframes`
+pub(crate) fn is_keyframes(at_rule: &AtRule) -> bool {
+    let AtRuleName::Ident(ref ident) = at_rule.name else {
+        return false;
+    };
+
+    strip_vendor_prefix(&ident.value.to_ascii_lowercase()) == "keyframes"
+}
+
+/// E.g. `-webkit-keyframes` -> `keyframes`
+fn strip_vendor_prefix(name: &str) -> &str {
+    name.strip_prefix('-')
+        .and_then(|name| name.split_once('-'))
+        .map_or(name, |(_, unprefixed)| unprefixed)
+}
+
+/// Pseudo-classes and pseudo-elements which change how the selector is scoped
+enum ScopedPseudo {
+    /// `:deep()` or `::v-deep()`
+    Deep,
+    /// `:slotted()` or `::v-slotted()`
+    Slotted,
+    /// `:global()` or `::v-global()`
+    Global,
+}
+
+fn get_scoped_pseudo(subclass_selector: &SubclassSelector) -> Option<ScopedPseudo> {
+    let (name, has_children) = match subclass_selector {
+        SubclassSelector::PseudoClass(pseudo_class) => (
+            pseudo_class.name.value.as_ref(),
+            pseudo_class.children.is_some(),
+        ),
+        SubclassSelector::PseudoElement(pseudo_element) => {
+            let Some(name) = pseudo_element.name.value.strip_prefix("v-") else {
+                return None;
+            };
+            (name, pseudo_element.children.is_some())
+        }
+        _ => return None,
+    };
+
+    match name {
+        "deep" => Some(ScopedPseudo::Deep),
+        "slotted" if has_children => Some(ScopedPseudo::Slotted),
+        "global" if has_children => Some(ScopedPseudo::Global),
+        _ => None,
+    }
+}
+
+fn has_deep(complex_selector: &ComplexSelector) -> bool {
+    matches!(
+        find_scoped_pseudo(complex_selector),
+        Some((_, ScopedPseudo::Deep))
+    )
+}
+
+/// Checks that the selector only consists of `:root`, `html` and `body`, e.g. `html body`
+fn is_root_only(complex_selector: &ComplexSelector) -> bool {
+    complex_selector.children.iter().all(|child| {
+        let ComplexSelectorChildren::CompoundSelector(compound_selector) = child else {
+            return true;
+        };
+
+        let is_root_type = match compound_selector.type_selector.as_deref() {
+            Some(TypeSelector::TagName(tag_name)) => {
+                matches!(&*tag_name.name.value.value, "html" | "body")
+            }
+            Some(TypeSelector::Universal(_)) => false,
+            None => !compound_selector.subclass_selectors.is_empty(),
+        };
+
+        is_root_type
+            && compound_selector.nesting_selector.is_none()
+            && compound_selector.subclass_selectors.iter().all(|sel| {
+                matches!(sel, SubclassSelector::PseudoClass(pseudo_class) if pseudo_class.name.value == "root")
+            })
+    })
+}
+
+/// Inserts the scope attribute after the last selector which is not a pseudo-class or a pseudo-element,
+/// e.g. `.foo:hover::before` -> `.foo[data-v-abcd1234]:hover::before`.
+/// When there are only pseudos, the arguments of `:is()` and `:where()` get scoped instead,
+/// e.g. `:is(.foo, .bar)` -> `:is(.foo[data-v-abcd1234], .bar[data-v-abcd1234])`
+fn insert_scope_attribute(children: &mut [ComplexSelectorChildren], attribute: &SubclassSelector) {
+    let anchor = children.iter().enumerate().rev().find_map(|(idx, child)| {
+        let ComplexSelectorChildren::CompoundSelector(compound_selector) = child else {
+            return None;
+        };
+
+        if let Some(pos) = compound_selector
+            .subclass_selectors
+            .iter()
+            .rposition(|sel| !is_pseudo(sel))
+        {
+            return Some((idx, pos + 1));
+        }
+
+        let has_type_or_nesting = compound_selector.type_selector.is_some()
+            || compound_selector.nesting_selector.is_some();
+        has_type_or_nesting.then_some((idx, 0))
+    });
+
+    if let Some((idx, pos)) = anchor {
+        let Some(ComplexSelectorChildren::CompoundSelector(compound_selector)) =
+            children.get_mut(idx)
+        else {
+            return;
+        };
+
+        // `&` refers to the parent rule which is already scoped
+        if compound_selector.nesting_selector.is_none() {
+            compound_selector
+                .subclass_selectors
+                .insert(pos, attribute.clone());
+        }
+        return;
+    }
+
+    if scope_is_where_arguments(children, attribute) {
+        return;
+    }
+
+    // Only pseudos, e.g. `::before` -> `[data-v-abcd1234]::before`
+    if let Some(ComplexSelectorChildren::CompoundSelector(first_compound_selector)) = children
+        .iter_mut()
+        .find(|child| matches!(child, ComplexSelectorChildren::CompoundSelector(_)))
+    {
+        first_compound_selector
+            .subclass_selectors
+            .insert(0, attribute.clone());
+    }
+}
+
+/// Scopes the arguments of the first `:is()` or `:where()`, returns `false` if there is none
+fn scope_is_where_arguments(
+    children: &mut [ComplexSelectorChildren],
+    attribute: &SubclassSelector,
+) -> bool {
+    let Some(pseudo_children) = children
+        .iter_mut()
+        .filter_map(|child| match child {
+            ComplexSelectorChildren::CompoundSelector(compound_selector) => {
+                Some(compound_selector.subclass_selectors.iter_mut())
+            }
+            ComplexSelectorChildren::Combinator(_) => None,
+        })
+        .flatten()
+        .find_map(|subclass_selector| match subclass_selector {
+            SubclassSelector::PseudoClass(pseudo_class)
+                if matches!(&*pseudo_class.name.value, "is" | "where") =>
+            {
+                pseudo_class.children.as_mut()
+            }
+            _ => None,
+        })
+    else {
+        return false;
+    };
+
+    for pseudo_child in pseudo_children.iter_mut() {
+        match pseudo_child {
+            PseudoClassSelectorChildren::ForgivingSelectorList(selector_list) => {
+                for forgiving in selector_list.children.iter_mut() {
+                    if let ForgivingComplexSelector::ComplexSelector(complex_selector) = forgiving {
+                        insert_scope_attribute(&mut complex_selector.children, attribute);
+                    }
+                }
+            }
+            PseudoClassSelectorChildren::SelectorList(selector_list) => {
+                for complex_selector in selector_list.children.iter_mut() {
+                    insert_scope_attribute(&mut complex_selector.children, attribute);
+                }
+            }
+            _ => {}
         }
     }
+
+    true
+}
+
+fn is_pseudo(subclass_selector: &SubclassSelector) -> bool {
+    matches!(
+        subclass_selector,
+        SubclassSelector::PseudoClass(_) | SubclassSelector::PseudoElement(_)
+    )
+}
+
+/// Finds the first scoped pseudo and the index of its `CompoundSelector`
+fn find_scoped_pseudo(complex_selector: &ComplexSelector) -> Option<(usize, ScopedPseudo)> {
+    complex_selector
+        .children
+        .iter()
+        .enumerate()
+        .find_map(|(idx, child)| {
+            let ComplexSelectorChildren::CompoundSelector(compound_selector) = child else {
+                return None;
+            };
+
+            compound_selector
+                .subclass_selectors
+                .iter()
+                .find_map(get_scoped_pseudo)
+                .map(|scoped_pseudo| (idx, scoped_pseudo))
+        })
+}
+
+/// Takes and parses the contents of a pseudo-class or a pseudo-element
+fn take_pseudo_children(
+    subclass_selector: &mut SubclassSelector,
+    errors: &mut Vec<CssError>,
+) -> Option<ComplexSelector> {
+    match subclass_selector {
+        SubclassSelector::PseudoClass(pseudo_class) => pseudo_class
+            .children
+            .take()
+            .and_then(|children| process_pseudo_class_children(children, errors)),
+        SubclassSelector::PseudoElement(pseudo_element) => pseudo_element
+            .children
+            .take()
+            .and_then(|children| process_pseudo_element_children(children, errors)),
+        _ => None,
+    }
+}
+
+fn create_attribute_selector(name: FervidAtom) -> SubclassSelector {
+    SubclassSelector::Attribute(Box::new(AttributeSelector {
+        span: DUMMY_SP,
+        name: WqName {
+            span: DUMMY_SP,
+            prefix: None,
+            value: Ident {
+                span: DUMMY_SP,
+                value: name,
+                raw: None,
+            },
+        },
+        matcher: None,
+        value: None,
+        modifier: None,
+    }))
 }
 
 // Processes contents of `:deep`
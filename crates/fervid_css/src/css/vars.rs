@@ -0,0 +1,336 @@
+//! Support for `v-bind()` inside `<style>`.
+//!
+//! `v-bind(color)` gets rewritten to `var(--<id>-color)`,
+//! and the component is expected to provide the value using `useCssVars`.
+
+use std::hash::Hasher;
+
+use fxhash::FxHasher32;
+use swc_core::common::{BytePos, Span, DUMMY_SP};
+use swc_css_ast::{ComponentValue, Function, FunctionName, Ident, Stylesheet};
+use swc_css_visit::{VisitMut, VisitMutWith};
+
+const V_BIND: &str = "v-bind";
+
+/// An expression found inside `v-bind()`, e.g. `color` in `v-bind(color)`
+#[derive(Debug, Clone, PartialEq)]
+pub struct CssVar {
+    /// Expression without the surrounding quotes, e.g. `theme.primary` for `v-bind('theme.primary')`
+    pub expr: String,
+    pub span: Span,
+}
+
+/// Options for rewriting `v-bind()` to CSS custom properties
+#[derive(Debug, Clone, Copy)]
+pub struct CssVarsOptions<'c> {
+    /// Component id (usually a file hash) to make the variable names unique
+    pub id: &'c str,
+    /// Production mode uses hashes instead of readable variable names
+    pub is_prod: bool,
+}
+
+/// Finds all the distinct expressions used in `v-bind()` in the raw style content.
+/// Comments are skipped.
+pub fn parse_css_vars(content: &str, span: Span) -> Vec<CssVar> {
+    let bytes = content.as_bytes();
+    let len = bytes.len();
+    let mut result: Vec<CssVar> = Vec::new();
+
+    let mut idx = 0;
+    while idx < len {
+        let rest = &bytes[idx..];
+
+        // Skip comments
+        if rest.starts_with(b"/*") {
+            idx = content[idx + 2..]
+                .find("*/")
+                .map_or(len, |end| idx + 2 + end + 2);
+            continue;
+        }
+
+        if !rest.starts_with(V_BIND.as_bytes()) {
+            idx += 1;
+            continue;
+        }
+
+        // Allow whitespace between `v-bind` and `(`
+        idx += V_BIND.len();
+        while idx < len && bytes[idx].is_ascii_whitespace() {
+            idx += 1;
+        }
+        if bytes.get(idx) != Some(&b'(') {
+            continue;
+        }
+
+        let start = idx + 1;
+        let Some(end) = lex_binding(bytes, start) else {
+            break;
+        };
+        idx = end + 1;
+
+        let expr = normalize_expression(&content[start..end]);
+        if expr.is_empty() || result.iter().any(|v| v.expr == expr) {
+            continue;
+        }
+
+        result.push(CssVar {
+            expr: expr.to_owned(),
+            span: Span::new(
+                span.lo + BytePos(start as u32),
+                span.lo + BytePos(end as u32),
+                span.ctxt,
+            ),
+        });
+    }
+
+    result
+}
+
+/// Generates the name of a CSS custom property (without the leading `--`).
+///
+/// In development, the name is readable, e.g. `abcd1234-theme.primary`.
+/// The name is not escaped, use [`escape_css_var_name`] when emitting it as-is.
+/// In production, it is a hash of component id and the expression.
+pub fn generate_css_var_name(id: &str, expr: &str, is_prod: bool) -> String {
+    if is_prod {
+        let mut hasher = FxHasher32::default();
+        hasher.write(id.as_bytes());
+        hasher.write(expr.as_bytes());
+        return format!("{:x}", hasher.finish());
+    }
+
+    format!("{}-{}", id, expr)
+}
+
+/// Escapes the symbols which are not allowed in a CSS identifier with a backslash,
+/// e.g. `abcd1234-theme.primary` becomes `abcd1234-theme\.primary`.
+/// Port of `getEscapedCssVarName` from `@vue/shared`.
+pub fn escape_css_var_name(name: &str) -> String {
+    let mut escaped = String::with_capacity(name.len());
+    for c in name.chars() {
+        if matches!(
+            c,
+            ' ' | '!'
+                | '"'
+                | '#'
+                | '$'
+                | '%'
+                | '&'
+                | '\''
+                | '('
+                | ')'
+                | '*'
+                | '+'
+                | ','
+                | '.'
+                | '/'
+                | ':'
+                | ';'
+                | '<'
+                | '='
+                | '>'
+                | '?'
+                | '@'
+                | '['
+                | '\\'
+                | ']'
+                | '^'
+                | '`'
+                | '{'
+                | '|'
+                | '}'
+                | '~'
+        ) {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+/// Rewrites `v-bind(expr)` to `var(--name)` in the declaration values
+pub struct CssVarsTransformer<'s> {
+    source: &'s str,
+    source_start: BytePos,
+    options: CssVarsOptions<'s>,
+}
+
+impl<'s> CssVarsTransformer<'s> {
+    /// `source` and `span` must be the same as the ones used for parsing the [`Stylesheet`]
+    pub fn new(source: &'s str, span: Span, options: CssVarsOptions<'s>) -> Self {
+        Self {
+            source,
+            source_start: span.lo,
+            options,
+        }
+    }
+
+    pub fn transform(&mut self, stylesheet: &mut Stylesheet) {
+        stylesheet.visit_mut_with(self);
+    }
+
+    /// Gets the normalized expression of `v-bind()` from the original source
+    fn get_expression(&self, function: &Function) -> Option<&'s str> {
+        let lo = (function.span.lo - self.source_start).0 as usize;
+        let hi = (function.span.hi - self.source_start).0 as usize;
+        let raw = self.source.get(lo..hi)?;
+
+        let args_start = raw.find('(')? + 1;
+        let args = raw.get(args_start..)?.strip_suffix(')')?;
+
+        Some(normalize_expression(args))
+    }
+}
+
+impl VisitMut for CssVarsTransformer<'_> {
+    fn visit_mut_function(&mut self, function: &mut Function) {
+        function.visit_mut_children_with(self);
+
+        let is_v_bind =
+            matches!(function.name, FunctionName::Ident(ref ident) if ident.value == V_BIND);
+        if !is_v_bind {
+            return;
+        }
+
+        let Some(expr) = self.get_expression(function) else {
+            return;
+        };
+
+        let var_name = generate_css_var_name(self.options.id, expr, self.options.is_prod);
+
+        // `var(--name)`
+        function.name = FunctionName::Ident(Ident {
+            span: DUMMY_SP,
+            value: "var".into(),
+            raw: None,
+        });
+        function.value = vec![ComponentValue::Ident(Box::new(Ident {
+            span: DUMMY_SP,
+            value: format!("--{}", var_name).into(),
+            raw: None,
+        }))];
+    }
+}
+
+/// Finds the closing `)` of `v-bind(`, respecting quotes and nested parens
+fn lex_binding(bytes: &[u8], start: usize) -> Option<usize> {
+    let mut paren_depth = 0usize;
+    let mut quote: Option<u8> = None;
+    let mut is_escaped = false;
+
+    for (idx, &c) in bytes.iter().enumerate().skip(start) {
+        if let Some(q) = quote {
+            if is_escaped {
+                is_escaped = false;
+            } else if c == b'\\' {
+                is_escaped = true;
+            } else if c == q {
+                quote = None;
+            }
+            continue;
+        }
+
+        match c {
+            b'\'' | b'"' => quote = Some(c),
+            b'(' => paren_depth += 1,
+            b')' if paren_depth == 0 => return Some(idx),
+            b')' => paren_depth -= 1,
+            _ => {}
+        }
+    }
+
+    None
+}
+
+/// Trims the expression and removes the surrounding quotes
+fn normalize_expression(raw: &str) -> &str {
+    let trimmed = raw.trim();
+    let bytes = trimmed.as_bytes();
+
+    if bytes.len() >= 2 {
+        let first = bytes[0];
+        if (first == b'\'' || first == b'"') && bytes[bytes.len() - 1] == first {
+            return &trimmed[1..trimmed.len() - 1];
+        }
+    }
+
+    trimmed
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_parses_css_vars() {
+        let input = "
+            .foo { color: v-bind(color); font-size: v-bind ( 'font.size' ) }
+            /* v-bind(commented) */
+            .bar { background: v-bind(\"theme.bg\"); color: v-bind(color) }
+            .baz { width: v-bind(`${w}px`); height: v-bind(fn(a, ')')) }";
+
+        let vars: Vec<String> = parse_css_vars(input, span(input))
+            .into_iter()
+            .map(|v| v.expr)
+            .collect();
+
+        assert_eq!(
+            vars,
+            vec!["color", "font.size", "theme.bg", "`${w}px`", "fn(a, ')')"]
+        );
+    }
+
+    #[test]
+    fn it_generates_css_var_names() {
+        assert_eq!(
+            generate_css_var_name("abcd1234", "color", false),
+            "abcd1234-color"
+        );
+        assert_eq!(
+            generate_css_var_name("abcd1234", "theme.primary", false),
+            "abcd1234-theme.primary"
+        );
+        assert_eq!(
+            generate_css_var_name("abcd1234", "color", true),
+            generate_css_var_name("abcd1234", "color", true)
+        );
+        assert_ne!(
+            generate_css_var_name("abcd1234", "color", true),
+            generate_css_var_name("abcd1234", "bg", true)
+        );
+    }
+
+    #[test]
+    fn it_keeps_css_var_names_distinct() {
+        assert_ne!(
+            generate_css_var_name("abcd1234", "theme.primary", false),
+            generate_css_var_name("abcd1234", "theme_primary", false)
+        );
+        assert_ne!(
+            generate_css_var_name("abcd1234", "a + b", false),
+            generate_css_var_name("abcd1234", "a - b", false)
+        );
+    }
+
+    #[test]
+    fn it_escapes_css_var_names() {
+        assert_eq!(escape_css_var_name("abcd1234-color"), "abcd1234-color");
+        assert_eq!(
+            escape_css_var_name("abcd1234-theme.primary"),
+            "abcd1234-theme\\.primary"
+        );
+        assert_eq!(
+            escape_css_var_name("abcd1234-a + b['c']"),
+            "abcd1234-a\\ \\+\\ b\\[\\'c\\'\\]"
+        );
+        assert_eq!(escape_css_var_name("a\\b"), "a\\\\b");
+    }
+
+    fn span(input: &str) -> Span {
+        Span::new(
+            BytePos(1),
+            BytePos(1 + input.len() as u32),
+            Default::default(),
+        )
+    }
+}
@@ -21,7 +21,7 @@
 //! let result = fervid_css::transform_css(input, span, Some("data-v-abcd1234"), &mut errors, Default::default());
 //!
 //! if let Some(transformed_css) = result {
-//!     assert_eq!(".example[data-v-abcd1234]{background:#ff0}", transformed_css);
+//!     assert_eq!(".example[data-v-abcd1234]{background:#ff0}", transformed_css.code);
 //! }
 //! ```
 
@@ -54,7 +54,7 @@ mod tests {
             );
             let mut errors = Vec::new();
             let out = css::transform_css($input, span, Some("data-v-abcd1234"), &mut errors, Default::default());
-            assert_eq!(out.ok_or(()), $expected);
+            assert_eq!(out.map(|r| r.code).ok_or(()), $expected);
         };
     }
 
@@ -196,4 +196,445 @@ mod tests {
             minify_yes!()
         );
     }
+
+    #[test]
+    fn transform_style_pseudos() {
+        test_ok!(
+            ".foo::before { color: red }",
+            ".foo[data-v-abcd1234]::before{color:red}",
+            minify_yes!()
+        );
+
+        test_ok!(
+            ".foo:hover::after, .bar .baz:first-child { color: red }",
+            ".foo[data-v-abcd1234]:hover::after,.bar .baz[data-v-abcd1234]:first-child{color:red}",
+            minify_yes!()
+        );
+
+        test_ok!(
+            "::selection { color: red }",
+            "[data-v-abcd1234]::selection{color:red}",
+            minify_yes!()
+        );
+
+        test_ok!(
+            ".foo:not(.bar), .foo:has(> .bar) { color: red }",
+            ".foo[data-v-abcd1234]:not(.bar),.foo[data-v-abcd1234]:has(>.bar){color:red}",
+            minify_yes!()
+        );
+
+        test_ok!(
+            ":is(.foo, .bar) { color: red }",
+            ":is(.foo[data-v-abcd1234],.bar[data-v-abcd1234]){color:red}",
+            minify_yes!()
+        );
+
+        test_ok!(
+            ":where(.foo) .bar, .baz:is(:hover) { color: red }",
+            ":where(.foo) .bar[data-v-abcd1234],.baz[data-v-abcd1234]:is(:hover){color:red}",
+            minify_yes!()
+        );
+
+        test_ok!(
+            ".foo:hover :deep(.bar) { color: red }",
+            ".foo[data-v-abcd1234]:hover .bar{color:red}",
+            minify_yes!()
+        );
+    }
+
+    #[test]
+    fn transform_style_root() {
+        test_ok!(
+            ":root, html, body, html body { color: red }",
+            ":root,html,body,html body{color:red}",
+            minify_yes!()
+        );
+
+        test_ok!(
+            "body .foo, html.dark { color: red }",
+            "body .foo[data-v-abcd1234],html.dark[data-v-abcd1234]{color:red}",
+            minify_yes!()
+        );
+    }
+
+    #[test]
+    fn transform_style_nesting() {
+        test_ok!(
+            ".foo { color: red; &:hover { color: blue } & .bar { color: green } > .baz { color: red } }",
+            ".foo[data-v-abcd1234]{color:red;&:hover{color:blue}& .bar[data-v-abcd1234]{color:green}>.baz[data-v-abcd1234]{color:red}}",
+            minify_yes!()
+        );
+
+        test_ok!(
+            ".foo { @media print { .bar { color: red } } }",
+            ".foo[data-v-abcd1234]{@media print{.bar[data-v-abcd1234]{color:red}}}",
+            minify_yes!()
+        );
+
+        test_ok!(
+            ".foo :deep(.bar) { .baz { color: red } }",
+            ".foo[data-v-abcd1234] .bar{.baz{color:red}}",
+            minify_yes!()
+        );
+    }
+
+    #[test]
+    fn transform_style_keyframes() {
+        test_ok!(
+            ".foo { animation: fade 1s, spin 2s } @keyframes fade { from { opacity: 0 } }",
+            ".foo[data-v-abcd1234]{animation:fade-abcd1234 1s,spin 2s}@keyframes fade-abcd1234{from{opacity:0}}",
+            minify_yes!()
+        );
+
+        test_ok!(
+            "@-webkit-keyframes fade { to { opacity: 1 } } @media print { .foo { -webkit-animation-name: fade } }",
+            "@-webkit-keyframes fade-abcd1234{to{opacity:1}}@media print{.foo[data-v-abcd1234]{-webkit-animation-name:fade-abcd1234}}",
+            minify_yes!()
+        );
+    }
+
+    #[test]
+    fn transform_style_slotted() {
+        test_ok!(
+            ":slotted(.foo) { color: red }",
+            ".foo[data-v-abcd1234-s]{color:red}",
+            minify_yes!()
+        );
+
+        test_ok!(
+            "::v-slotted(.foo .bar) { color: red }",
+            ".foo .bar[data-v-abcd1234-s]{color:red}",
+            minify_yes!()
+        );
+
+        test_ok!(
+            ".baz :slotted(.foo) { color: red }",
+            ".baz .foo[data-v-abcd1234-s]{color:red}",
+            minify_yes!()
+        );
+
+        test_ok!(
+            ".baz:slotted(.foo), .qux { color: red }",
+            ".baz.foo[data-v-abcd1234-s],.qux[data-v-abcd1234]{color:red}",
+            minify_yes!()
+        );
+    }
+
+    #[test]
+    fn transform_style_global() {
+        test_ok!(
+            ":global(.foo) { color: red }",
+            ".foo{color:red}",
+            minify_yes!()
+        );
+
+        test_ok!(
+            ".baz ::v-global(.foo .bar) { color: red }",
+            ".foo .bar{color:red}",
+            minify_yes!()
+        );
+
+        test_ok!(
+            ".baz { color: red; :global(.foo) { color: blue } .qux { color: green } }",
+            ".baz[data-v-abcd1234]{color:red;.qux[data-v-abcd1234]{color:green}}.foo{color:blue}",
+            minify_yes!()
+        );
+
+        test_ok!(
+            "@media print { .baz { :global(.foo) { color: blue } } }",
+            "@media print{.baz[data-v-abcd1234]{}.foo{color:blue}}",
+            minify_yes!()
+        );
+    }
+
+    #[test]
+    fn transform_css_vars() {
+        let input = ".foo { color: v-bind(color); margin: calc(v-bind('theme.gap') * 2) }";
+        let span = Span::new(
+            BytePos(1),
+            BytePos(1 + input.len() as u32),
+            Default::default(),
+        );
+        let mut errors = Vec::new();
+        let out = css::transform_css(
+            input,
+            span,
+            Some("data-v-abcd1234"),
+            &mut errors,
+            css::TransformCssConfig {
+                css_vars: Some(css::CssVarsOptions {
+                    id: "abcd1234",
+                    is_prod: false,
+                }),
+                ..Default::default()
+            },
+        );
+
+        assert_eq!(
+            out.map(|r| r.code),
+            Some(String::from(
+                ".foo[data-v-abcd1234]{color:var(--abcd1234-color);margin:calc(var(--abcd1234-theme\\.gap)*2)}"
+            ))
+        );
+    }
+
+    #[test]
+    fn transform_css_minify() {
+        let minify = |input: &str| {
+            let span = Span::new(
+                BytePos(1),
+                BytePos(1 + input.len() as u32),
+                Default::default(),
+            );
+            let mut errors = Vec::new();
+            css::transform_css(
+                input,
+                span,
+                None,
+                &mut errors,
+                css::TransformCssConfig {
+                    output: css::CssOutputOptions {
+                        minify: true,
+                        ..Default::default()
+                    },
+                    ..Default::default()
+                },
+            )
+            .map(|r| r.code)
+        };
+
+        assert_eq!(
+            minify(".foo { color: #FF0000; background: #ffffff; border-color: rgb(0, 0, 128); outline-color: rgba(0, 0, 0, .5) }").as_deref(),
+            Some(".foo{color:red;background:#fff;border-color:navy;outline-color:rgba(0,0,0,.5)}")
+        );
+        assert_eq!(
+            minify(".foo { margin: 0px 0px; padding: 2px 2px 2px 2px; width: calc(100% - 1px); opacity: 0.50 }").as_deref(),
+            Some(".foo{margin:0;padding:2px;width:calc(100% - 1px);opacity:.5}")
+        );
+
+        // Overridden declarations are removed, but the duplicates which may be fallbacks are kept
+        assert_eq!(
+            minify(".foo { color: #f00; color: red; width: 1px; width: 2px }").as_deref(),
+            Some(".foo{color:red;width:2px}")
+        );
+        assert_eq!(
+            minify(".foo { color: red !important; color: blue; display: -webkit-box; display: flex }").as_deref(),
+            Some(".foo{color:red!important;color:blue;display:-webkit-box;display:flex}")
+        );
+        assert_eq!(
+            minify(".foo { width: 1px; width: calc(100% - 1px) }").as_deref(),
+            Some(".foo{width:1px;width:calc(100% - 1px)}")
+        );
+
+        // Longhands are merged into a shorthand, unless other longhands depend on the order
+        assert_eq!(
+            minify(".foo { padding-top: 2px; padding-right: 4px; padding-bottom: 2px; padding-left: 4px }").as_deref(),
+            Some(".foo{padding:2px 4px}")
+        );
+        assert_eq!(
+            minify(".foo { margin-top: 0; margin-right: 0; margin-bottom: 0; margin-left: 0; margin-inline-start: 1px }").as_deref(),
+            Some(".foo{margin-top:0;margin-right:0;margin-bottom:0;margin-left:0;margin-inline-start:1px}")
+        );
+
+        // Keywords in shorthands, names and strings are not colors
+        assert_eq!(
+            minify(".foo { border: 1px solid white; background: url(navy.png) no-repeat, white }").as_deref(),
+            Some(".foo{border:1px solid white;background:url(navy.png)no-repeat,white}")
+        );
+        assert_eq!(
+            minify(".foo { font: italic 12px/1.5 Black, serif; animation: white 1s; grid-area: red }").as_deref(),
+            Some(".foo{font:italic 12px/1.5 Black,serif;animation:white 1s;grid-area:red}")
+        );
+        assert_eq!(
+            minify(".foo { color: red; /* white */ content: \"white  ;\"; }").as_deref(),
+            Some(".foo{color:red;content:\"white  ;\"}")
+        );
+    }
+
+    #[test]
+    fn transform_css_targets() {
+        let input = ".foo { user-select: none } .foo::placeholder { color: red }";
+        let span = Span::new(
+            BytePos(1),
+            BytePos(1 + input.len() as u32),
+            Default::default(),
+        );
+        let mut errors = Vec::new();
+        let out = css::transform_css(
+            input,
+            span,
+            Some("data-v-abcd1234"),
+            &mut errors,
+            css::TransformCssConfig {
+                output: css::CssOutputOptions {
+                    minify: false,
+                    targets: Some(css::parse_css_targets("safari 12").expect("valid query")),
+                    ..Default::default()
+                },
+                ..Default::default()
+            },
+        )
+        .expect("should transform");
+
+        assert!(out.code.contains("-webkit-user-select:none"));
+        assert!(out.code.contains(".foo[data-v-abcd1234]::placeholder"));
+        assert!(css::parse_css_targets("not a browser 1").is_err());
+    }
+
+    #[test]
+    fn transform_css_targets_lowering() {
+        let lower = |input: &str, query: &str| {
+            let span = Span::new(
+                BytePos(1),
+                BytePos(1 + input.len() as u32),
+                Default::default(),
+            );
+            let mut errors = Vec::new();
+            css::transform_css(
+                input,
+                span,
+                None,
+                &mut errors,
+                css::TransformCssConfig {
+                    output: css::CssOutputOptions {
+                        targets: Some(css::parse_css_targets(query).expect("valid query")),
+                        ..Default::default()
+                    },
+                    ..Default::default()
+                },
+            )
+            .map(|r| r.code)
+        };
+
+        let nested = ".foo { color: red; & .bar { color: blue } &:hover { color: green } }";
+        assert_eq!(
+            lower(nested, "chrome 100").as_deref(),
+            Some(".foo{color:red}.foo .bar{color:blue}.foo:hover{color:green}")
+        );
+        assert_eq!(
+            lower(nested, "chrome 120").as_deref(),
+            Some(".foo{color:red;& .bar{color:blue}&:hover{color:green}}")
+        );
+        assert_eq!(
+            lower(".foo { color: #ff000080; & .bar { color: hwb(0 0% 0%) } }", "chrome 50").as_deref(),
+            Some(".foo{color:rgba(255,0,0,.5)}.foo .bar{color:rgb(255,0,0)}")
+        );
+    }
+
+    #[test]
+    fn transform_css_source_map() {
+        let transform = |source: &str, content: &str, input_map: Option<&str>| {
+            // Content starts right after `<style>`
+            let style_start = source.find("<style").expect("has a style block");
+            let lo = (style_start + source[style_start..].find('>').unwrap_or(0)) as u32 + 2;
+            let span = Span::new(
+                BytePos(lo),
+                BytePos(lo + content.len() as u32),
+                Default::default(),
+            );
+            let mut errors = Vec::new();
+            let out = css::transform_css(
+                content,
+                span,
+                Some("data-v-abcd1234"),
+                &mut errors,
+                css::TransformCssConfig {
+                    source_map: Some(css::CssSourceMapOptions {
+                        source,
+                        filename: "Comp.vue",
+                        input_map,
+                    }),
+                    ..Default::default()
+                },
+            )
+            .expect("should transform");
+            let map = out.map.expect("should have a map");
+            (out.code, sourcemap::SourceMap::from_slice(map.as_bytes()).expect("valid map"))
+        };
+
+        // Positions are in the SFC
+        let source = "<template></template>\n<style scoped>\n  .foo { color: red }\n</style>";
+        let (code, map) = transform(source, "\n  .foo { color: red }\n", None);
+        assert_eq!(code, ".foo[data-v-abcd1234]{color:red}");
+        assert_eq!(map.get_source(0), Some("Comp.vue"));
+        assert_eq!(map.get_source_contents(0), Some(source));
+        let token = map.lookup_token(0, 0).expect("has a token");
+        assert_eq!((token.get_src_line(), token.get_src_col()), (2, 2));
+        let token = map.lookup_token(0, 28).expect("has a token");
+        assert_eq!((token.get_src_line(), token.get_src_col()), (2, 16));
+
+        // Chained with the preprocessor
+        let source = "<style lang=\"scss\">$c: red; .foo { color: $c }</style>";
+        let mut input_map = sourcemap::SourceMapBuilder::new(None);
+        input_map.add(0, 0, 0, 9, Some("Comp.vue"), None, false);
+        input_map.add(0, 7, 0, 16, Some("Comp.vue"), None, false);
+        let mut input_map_json = Vec::new();
+        input_map
+            .into_sourcemap()
+            .to_writer(&mut input_map_json)
+            .expect("serializes");
+        let input_map_json = String::from_utf8(input_map_json).expect("utf-8");
+        let (_, map) = transform(source, ".foo { color: red }", Some(&input_map_json));
+        let token = map.lookup_token(0, 0).expect("has a token");
+        assert_eq!((token.get_src_line(), token.get_src_col()), (0, 28));
+        let token = map.lookup_token(0, 28).expect("has a token");
+        assert_eq!((token.get_src_line(), token.get_src_col()), (0, 35));
+    }
+
+    #[test]
+    fn transform_css_modules() {
+        let input = "
+            .red { color: red; animation: fade 1s }
+            .red:hover, #main > .blue { color: blue }
+            :global(.title) .red, :global .title .blue { margin: 0 }
+            .a:global(.b .c):not(.d) { margin: 0 }
+            @keyframes fade { from { opacity: 0 } }
+            @keyframes :global(spin) { from { opacity: 0 } }";
+        let span = Span::new(
+            BytePos(1),
+            BytePos(1 + input.len() as u32),
+            Default::default(),
+        );
+        let mut errors = Vec::new();
+        let out = css::transform_css(
+            input,
+            span,
+            None,
+            &mut errors,
+            css::TransformCssConfig {
+                css_modules: Some(css::CssModulesOptions {
+                    pattern: "[local]_x",
+                    mode: css::CssModulesMode::Local,
+                    name: "Comp",
+                    id: "abcd1234",
+                }),
+                ..Default::default()
+            },
+        )
+        .expect("should transform");
+
+        assert_eq!(
+            out.code,
+            ".red_x{color:red;animation:fade_x 1s}.red_x:hover,#main_x>.blue_x{color:blue}.title .red_x,.title .blue{margin:0}.a_x.b .c:not(.d_x){margin:0}@keyframes fade_x{from{opacity:0}}@keyframes spin{from{opacity:0}}"
+        );
+
+        let exports: Vec<(&str, &str)> = out
+            .exports
+            .as_ref()
+            .expect("should have exports")
+            .iter()
+            .map(|(local, generated)| (&**local, &**generated))
+            .collect();
+        assert_eq!(
+            exports,
+            vec![
+                ("red", "red_x"),
+                ("fade", "fade_x"),
+                ("main", "main_x"),
+                ("blue", "blue_x"),
+                ("a", "a_x"),
+                ("d", "d_x"),
+            ]
+        );
+    }
 }
@@ -9,7 +9,7 @@ use farmfe_core::{
     parking_lot::Mutex,
     plugin::{Plugin, PluginLoadHookResult, PluginTransformHookResult},
 };
-use fervid::CompileOptions;
+use fervid::{CompileOptions, NativeTypeResolveFs, TypeScopeCache};
 use fxhash::FxHashMap;
 
 use farmfe_macro_plugin::farm_plugin;
@@ -17,12 +17,14 @@ use farmfe_macro_plugin::farm_plugin;
 #[farm_plugin]
 pub struct FarmPluginVueFervid {
     virtual_modules: Mutex<FxHashMap<String, PluginLoadHookResult>>,
+    type_scope_cache: TypeScopeCache,
 }
 
 impl FarmPluginVueFervid {
     fn new(_config: &Config, _options: String) -> Self {
         Self {
             virtual_modules: Default::default(),
+            type_scope_cache: Default::default(),
         }
     }
 }
@@ -88,7 +90,17 @@ impl Plugin for FarmPluginVueFervid {
                 id: param.module_id.clone().into(),
                 is_prod: Some(true),
                 ssr: None,
+                transform_asset_urls: None,
                 gen_default_as: None,
+                hoist_static: None,
+                css_modules_pattern: None,
+                css_modules_mode: None,
+                style_preprocessor: None,
+                css_minify: None,
+                css_targets: None,
+                type_resolve_fs: Some(&NativeTypeResolveFs),
+                type_scope_cache: Some(&self.type_scope_cache),
+                global_type_files: None,
                 source_map: None
             },
         );
@@ -117,7 +129,7 @@ impl Plugin for FarmPluginVueFervid {
                     content: style.code,
                     // TODO Determine based on style lang
                     module_type: ModuleType::Css,
-                    source_map: None,
+                    source_map: style.source_map,
                 });
             }
         }
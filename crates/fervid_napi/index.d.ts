@@ -34,10 +34,30 @@ export interface FervidJsCompilerOptions {
    */
   customElement?: undefined
 }
-export interface FervidJsCompilerOptionsTemplate {}
+export interface FervidJsCompilerOptionsTemplate {
+  /**
+   * Transform asset urls, e.g. `<img src="./logo.png">`, into imports.
+   * Default: same as in `@vue/compiler-sfc`
+   */
+  transformAssetUrls?: FervidJsCompilerOptionsAssetUrls
+}
+export interface FervidJsCompilerOptionsAssetUrls {
+  /** Relative urls are prefixed with `base` instead of being imported */
+  base?: string
+  /**
+   * Also import absolute urls, e.g. `/logo.png`.
+   * Default: false
+   */
+  includeAbsolute?: boolean
+  /**
+   * Tags and their attributes to transform, e.g. `{ img: ['src'] }`.
+   * `*` matches any tag, `{}` disables the transform.
+   * Default: same as in `@vue/compiler-sfc`
+   */
+  tags?: Record<string, Array<string>>
+}
 export interface FervidJsCompilerOptionsScript {
   /**
-   * Ignored
    * Hoist <script setup> static constants.
    * - Only enabled when one `<script setup>` exists.
    * Default: true
@@ -45,10 +65,37 @@ export interface FervidJsCompilerOptionsScript {
   hoistStatic?: boolean
   /** Produce source maps */
   sourceMap?: boolean
+  /**
+   * Files with the global types used in `defineProps<T>()` and `defineEmits<T>()`,
+   * e.g. `['/project/src/global.d.ts']`.
+   * Default: none
+   */
+  globalTypeFiles?: Array<string>
 }
 export interface FervidJsCompilerOptionsStyle {
   /** Ignored */
   trim?: boolean
+  /**
+   * Pattern for the names in `<style module>`.
+   * Supports `[name]`, `[local]` and `[hash]`.
+   * Default: `[name]_[local]_[hash]`
+   */
+  modulesPattern?: string
+  /**
+   * Whether names in `<style module>` are `local` or `global` by default.
+   * Default: `local`
+   */
+  modulesMode?: string
+  /**
+   * Minify the styles: remove duplicate declarations, merge shorthands and compress colors and numbers.
+   * Default: false
+   */
+  minify?: boolean
+  /**
+   * Browserslist query to lower and prefix the styles for, e.g. `chrome 80, safari 13`.
+   * Default: none
+   */
+  targets?: string
 }
 export interface FervidCompileOptions {
   /** Scope ID for prefixing injected CSS variables */
@@ -57,6 +104,16 @@ export interface FervidCompileOptions {
   filename: string
   /** Generate a const instead of default export */
   genDefaultAs?: string
+  /**
+   * Converts `<style lang="scss">` and others to CSS, so that fervid can scope them.
+   * Only supported by `compileSync`, `compileAsync` reports an error for such blocks
+   */
+  stylePreprocessor?: (lang: string, content: string, filename: string) => StylePreprocessorResult
+}
+/** Returned from the `stylePreprocessor` callback */
+export interface StylePreprocessorResult {
+  code: string
+  map?: string
 }
 export interface CompileResult {
   code: string
@@ -64,12 +121,18 @@ export interface CompileResult {
   errors: Array<SerializedError>
   customBlocks: Array<CustomBlock>
   sourceMap?: string
+  /**
+   * Files other than the SFC the result depends on, e.g. the imported prop types.
+   * Recompile the SFC when any of them changes.
+   */
+  deps: Array<string>
 }
 export interface Style {
   code: string
   isCompiled: boolean
   lang: string
   isScoped: boolean
+  sourceMap?: string
 }
 export interface CustomBlock {
   content: string
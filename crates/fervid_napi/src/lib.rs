@@ -4,13 +4,19 @@
 #[global_allocator]
 static ALLOC: mimalloc_rust::GlobalMiMalloc = mimalloc_rust::GlobalMiMalloc;
 
-use std::borrow::Cow;
+use std::{borrow::Cow, sync::Arc};
 
 use napi::bindgen_prelude::*;
 use napi_derive::napi;
 
-use fervid::{compile, CompileOptions};
-use structs::{CompileResult, FervidCompileOptions, FervidJsCompiler, FervidJsCompilerOptions};
+use fervid::{
+    compile, AssetUrlOptions, CompileOptions, CssModulesMode, NativeTypeResolveFs,
+    StylePreprocessor,
+};
+use structs::{
+    AsyncStylePreprocessor, CompileResult, FervidCompileOptions, FervidJsCompiler,
+    FervidJsCompilerOptions, JsStylePreprocessor,
+};
 
 mod structs;
 
@@ -19,16 +25,33 @@ impl FervidJsCompiler {
     #[napi(constructor)]
     pub fn new(options: Option<FervidJsCompilerOptions>) -> Self {
         let options = options.unwrap_or_else(Default::default);
-        FervidJsCompiler { options }
+        FervidJsCompiler {
+            options,
+            type_scope_cache: Arc::default(),
+        }
     }
 
     #[napi]
     pub fn compile_sync(
         &self,
+        env: Env,
         source: String,
-        options: FervidCompileOptions,
+        mut options: FervidCompileOptions,
     ) -> Result<CompileResult> {
-        self.compile_and_convert(&source, &options)
+        let style_preprocessor = options
+            .style_preprocessor
+            .take()
+            .map(|callback| JsStylePreprocessor { env, callback });
+
+        self.compile_and_convert(
+            &source,
+            &options.filename,
+            &options.id,
+            options.gen_default_as.as_deref(),
+            style_preprocessor
+                .as_ref()
+                .map(|p| p as &dyn StylePreprocessor),
+        )
     }
 
     #[napi]
@@ -41,7 +64,10 @@ impl FervidJsCompiler {
         let task = CompileTask {
             compiler: self.to_owned(),
             input: source,
-            options,
+            filename: options.filename,
+            id: options.id,
+            gen_default_as: options.gen_default_as,
+            has_style_preprocessor: options.style_preprocessor.is_some(),
         };
         AsyncTask::with_optional_signal(task, signal)
     }
@@ -49,15 +75,59 @@ impl FervidJsCompiler {
     fn compile_and_convert(
         &self,
         source: &str,
-        options: &FervidCompileOptions,
+        filename: &str,
+        id: &str,
+        gen_default_as: Option<&str>,
+        style_preprocessor: Option<&dyn StylePreprocessor>,
     ) -> Result<CompileResult> {
         // Normalize options to the ones defined in fervid
+        let script_options = self.options.script.as_ref();
+        let style_options = self.options.style.as_ref();
+        let asset_urls_options = self
+            .options
+            .template
+            .as_ref()
+            .and_then(|t| t.transform_asset_urls.as_ref());
         let compile_options = CompileOptions {
-            filename: Cow::Borrowed(&options.filename),
-            id: Cow::Borrowed(&options.id),
+            filename: Cow::Borrowed(filename),
+            id: Cow::Borrowed(id),
             is_prod: self.options.is_production,
             ssr: self.options.ssr,
-            gen_default_as: options.gen_default_as.as_ref().map(|v| Cow::Borrowed(v.as_str())),
+            transform_asset_urls: asset_urls_options.map(|asset_urls| AssetUrlOptions {
+                base: asset_urls.base.as_deref().map(Into::into),
+                include_absolute: asset_urls.include_absolute.unwrap_or_default(),
+                tags: match asset_urls.tags {
+                    Some(ref tags) => tags
+                        .iter()
+                        .map(|(tag, attrs)| {
+                            let attrs = attrs.iter().map(|attr| attr.as_str().into()).collect();
+                            (tag.as_str().into(), attrs)
+                        })
+                        .collect(),
+                    None => AssetUrlOptions::default().tags,
+                },
+            }),
+            gen_default_as: gen_default_as.map(Cow::Borrowed),
+            hoist_static: script_options.and_then(|s| s.hoist_static),
+            css_modules_pattern: style_options
+                .and_then(|s| s.modules_pattern.as_deref())
+                .map(Cow::Borrowed),
+            css_modules_mode: style_options
+                .and_then(|s| s.modules_mode.as_deref())
+                .map(|mode| match mode {
+                    "global" => CssModulesMode::Global,
+                    _ => CssModulesMode::Local,
+                }),
+            style_preprocessor,
+            css_minify: style_options.and_then(|s| s.minify),
+            css_targets: style_options
+                .and_then(|s| s.targets.as_deref())
+                .map(Cow::Borrowed),
+            type_resolve_fs: Some(&NativeTypeResolveFs),
+            type_scope_cache: Some(&self.type_scope_cache),
+            global_type_files: script_options
+                .and_then(|s| s.global_type_files.as_deref())
+                .map(Cow::Borrowed),
             source_map: self.options.source_map
         };
 
@@ -67,6 +137,7 @@ impl FervidJsCompiler {
         Ok(CompileResult {
             code: native_compile_result.code,
             source_map: native_compile_result.source_map,
+            deps: native_compile_result.deps,
             custom_blocks: native_compile_result
                 .other_assets
                 .into_iter()
@@ -89,7 +160,11 @@ impl FervidJsCompiler {
 pub struct CompileTask {
     compiler: FervidJsCompiler,
     input: String,
-    options: FervidCompileOptions,
+    filename: String,
+    id: String,
+    gen_default_as: Option<String>,
+    /// The callback itself is left behind, because it is not `Send`
+    has_style_preprocessor: bool,
 }
 
 #[napi]
@@ -98,7 +173,19 @@ impl Task for CompileTask {
     type Output = CompileResult;
 
     fn compute(&mut self) -> napi::Result<Self::Output> {
-        self.compiler.compile_and_convert(&self.input, &self.options)
+        // JS preprocessors cannot be called outside of the main thread,
+        // report the non-CSS blocks instead of silently leaving them as is
+        let style_preprocessor = self
+            .has_style_preprocessor
+            .then_some(&AsyncStylePreprocessor as &dyn StylePreprocessor);
+
+        self.compiler.compile_and_convert(
+            &self.input,
+            &self.filename,
+            &self.id,
+            self.gen_default_as.as_deref(),
+            style_preprocessor,
+        )
     }
 
     fn resolve(&mut self, _env: Env, result: Self::Output) -> napi::Result<Self::JsValue> {
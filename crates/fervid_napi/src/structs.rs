@@ -1,3 +1,7 @@
+use std::{collections::HashMap, sync::Arc};
+
+use fervid::{PreprocessedStyle, StylePreprocessor, TypeScopeCache};
+use napi::{Env, JsFunction, JsObject};
 use napi_derive::napi;
 use swc_core::common::Spanned;
 
@@ -6,6 +10,8 @@ use swc_core::common::Spanned;
 #[derive(Clone)]
 pub struct FervidJsCompiler {
     pub options: FervidJsCompilerOptions,
+    /// Parsed type files shared by the compilations, e.g. the imported props interfaces
+    pub(crate) type_scope_cache: Arc<TypeScopeCache>,
 }
 
 /// Raw options passed from the Node.js side
@@ -15,7 +21,6 @@ pub struct FervidJsCompilerOptions {
     /// Apply production optimizations. Default: false
     pub is_production: Option<bool>,
 
-    /// TODO Support SSR
     /// Enable SSR. Default: false
     pub ssr: Option<bool>,
 
@@ -49,18 +54,39 @@ pub struct FervidJsCompilerOptions {
 
 #[napi(object)]
 #[derive(Clone)]
-pub struct FervidJsCompilerOptionsTemplate {}
+pub struct FervidJsCompilerOptionsTemplate {
+    /// Transform asset urls, e.g. `<img src="./logo.png">`, into imports.
+    /// Default: same as in `@vue/compiler-sfc`
+    pub transform_asset_urls: Option<FervidJsCompilerOptionsAssetUrls>,
+}
+
+#[napi(object)]
+#[derive(Clone)]
+pub struct FervidJsCompilerOptionsAssetUrls {
+    /// Relative urls are prefixed with `base` instead of being imported
+    pub base: Option<String>,
+    /// Also import absolute urls, e.g. `/logo.png`.
+    /// Default: false
+    pub include_absolute: Option<bool>,
+    /// Tags and their attributes to transform, e.g. `{ img: ['src'] }`.
+    /// `*` matches any tag, `{}` disables the transform.
+    /// Default: same as in `@vue/compiler-sfc`
+    pub tags: Option<HashMap<String, Vec<String>>>,
+}
 
 #[napi(object)]
 #[derive(Clone)]
 pub struct FervidJsCompilerOptionsScript {
-    /// Ignored
     /// Hoist <script setup> static constants.
     /// - Only enabled when one `<script setup>` exists.
     /// Default: true
     pub hoist_static: Option<bool>,
     /// Produce source maps
-    pub source_map: Option<bool>
+    pub source_map: Option<bool>,
+    /// Files with the global types used in `defineProps<T>()` and `defineEmits<T>()`,
+    /// e.g. `['/project/src/global.d.ts']`.
+    /// Default: none
+    pub global_type_files: Option<Vec<String>>,
 }
 
 #[napi(object)]
@@ -68,10 +94,22 @@ pub struct FervidJsCompilerOptionsScript {
 pub struct FervidJsCompilerOptionsStyle {
     /// Ignored
     pub trim: Option<bool>,
+    /// Pattern for the names in `<style module>`.
+    /// Supports `[name]`, `[local]` and `[hash]`.
+    /// Default: `[name]_[local]_[hash]`
+    pub modules_pattern: Option<String>,
+    /// Whether names in `<style module>` are `local` or `global` by default.
+    /// Default: `local`
+    pub modules_mode: Option<String>,
+    /// Minify the styles: remove duplicate declarations, merge shorthands and compress colors and numbers.
+    /// Default: false
+    pub minify: Option<bool>,
+    /// Browserslist query to lower and prefix the styles for, e.g. `chrome 80, safari 13`.
+    /// Default: none
+    pub targets: Option<String>,
 }
 
 #[napi(object)]
-#[derive(Clone)]
 pub struct FervidCompileOptions {
     /// Scope ID for prefixing injected CSS variables
     pub id: String,
@@ -79,6 +117,74 @@ pub struct FervidCompileOptions {
     pub filename: String,
     /// Generate a const instead of default export
     pub gen_default_as: Option<String>,
+    /// Converts `<style lang="scss">` and others to CSS, so that fervid can scope them.
+    /// Only supported by `compileSync`, `compileAsync` reports an error for such blocks
+    #[napi(
+        ts_type = "(lang: string, content: string, filename: string) => StylePreprocessorResult"
+    )]
+    pub style_preprocessor: Option<JsFunction>,
+}
+
+/// Returned from the `stylePreprocessor` callback
+#[napi(object)]
+pub struct StylePreprocessorResult {
+    pub code: String,
+    pub map: Option<String>,
+}
+
+/// Calls the JS callback for `<style lang="scss">` and other non-CSS blocks
+pub struct JsStylePreprocessor {
+    pub env: Env,
+    pub callback: JsFunction,
+}
+
+impl StylePreprocessor for JsStylePreprocessor {
+    fn preprocess(
+        &self,
+        lang: &str,
+        content: &str,
+        filename: &str,
+    ) -> Result<PreprocessedStyle, String> {
+        let call = || -> napi::Result<PreprocessedStyle> {
+            let args = [
+                self.env.create_string(lang)?,
+                self.env.create_string(content)?,
+                self.env.create_string(filename)?,
+            ];
+            let result: JsObject = self.callback.call(None, &args)?.coerce_to_object()?;
+
+            let Some(code) = result.get::<_, String>("code")? else {
+                return Err(napi::Error::from_reason(
+                    "Style preprocessor did not return `code`",
+                ));
+            };
+
+            Ok(PreprocessedStyle {
+                code,
+                map: result.get::<_, String>("map")?,
+            })
+        };
+
+        call().map_err(|e| e.reason)
+    }
+}
+
+/// Used by `compileAsync` in place of the JS callback,
+/// which cannot be called outside of the main thread
+pub struct AsyncStylePreprocessor;
+
+impl StylePreprocessor for AsyncStylePreprocessor {
+    fn preprocess(
+        &self,
+        lang: &str,
+        _content: &str,
+        _filename: &str,
+    ) -> Result<PreprocessedStyle, String> {
+        Err(format!(
+            "`stylePreprocessor` is not supported by `compileAsync`, use `compileSync` to compile `<style lang=\"{}\">`",
+            lang
+        ))
+    }
 }
 
 #[napi(object)]
@@ -87,7 +193,10 @@ pub struct CompileResult {
     pub styles: Vec<Style>,
     pub errors: Vec<SerializedError>,
     pub custom_blocks: Vec<CustomBlock>,
-    pub source_map: Option<String>
+    pub source_map: Option<String>,
+    /// Files other than the SFC the result depends on, e.g. the imported prop types.
+    /// Recompile the SFC when any of them changes.
+    pub deps: Vec<String>,
 }
 
 #[napi(object)]
@@ -96,6 +205,7 @@ pub struct Style {
     pub is_compiled: bool,
     pub lang: String,
     pub is_scoped: bool,
+    pub source_map: Option<String>,
 }
 
 #[napi(object)]
@@ -120,6 +230,7 @@ impl From<fervid::CompileEmittedStyle> for Style {
             is_compiled: value.is_compiled,
             lang: value.lang,
             is_scoped: value.is_scoped,
+            source_map: value.source_map,
         }
     }
 }
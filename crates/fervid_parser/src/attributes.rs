@@ -298,6 +298,9 @@ impl SfcParser<'_, '_, '_> {
                     event: argument,
                     handler,
                     modifiers,
+                    key_modifiers: vec![],
+                    // Decided by the transform
+                    is_handler_cacheable: false,
                     span,
                 }));
             }
@@ -334,6 +337,7 @@ impl SfcParser<'_, '_, '_> {
                                     iterable,
                                     itervar,
                                     patch_flags: Default::default(),
+                                    fragment_key: None,
                                     span
                                 }
                             );
@@ -361,6 +365,7 @@ impl SfcParser<'_, '_, '_> {
                             value: model_binding,
                             update_handler: None,
                             modifiers,
+                            is_handler_cacheable: false,
                             span,
                         });
                     }
@@ -130,6 +130,18 @@ font-weight: bold;
         assert!(styles[1].lang == "css" && !styles[1].is_scoped && styles[1].is_module);
         assert!(styles[2].lang == "css" && styles[2].is_scoped && !styles[2].is_module);
         assert!(styles[3].lang == "css" && styles[3].is_scoped && !styles[3].is_module);
+        assert!(styles[1].module_name.is_none());
+    }
+
+    #[test]
+    fn style_block_named_module() {
+        let (mut src, _) = padding();
+        src.push_str("<style module=\"classes\">\n.red { color: red }\n</style>\n");
+
+        let styles = parse(&src).styles;
+        assert_eq!(1, styles.len());
+        assert!(styles[0].is_module);
+        assert_eq!(styles[0].module_name.as_deref(), Some("classes"));
     }
 
     #[test]
@@ -346,13 +358,19 @@ h1 { color: red }
 
     #[test]
     fn slotted_detection() {
-        // TODO Implement :slotted and ::v-slotted detection
-        let _descriptor1 = parse("<template>hi</template>");
-        let _descriptor2 = parse("<template>hi</template><style>h1{color:red;}</style>");
-        let _descriptor3 =
+        let descriptor1 = parse("<template>hi</template>");
+        let descriptor2 = parse("<template>hi</template><style>h1{color:red;}</style>");
+        let descriptor3 =
             parse("<template>hi</template><style scoped>:slotted(h1){color:red;}</style>");
-        let _descriptor4 =
+        let descriptor4 =
             parse("<template>hi</template><style scoped>::v-slotted(h1){color:red;}</style>");
+        let descriptor5 = parse("<template>hi</template><style>:slotted(h1){color:red;}</style>");
+
+        assert!(!descriptor1.slotted);
+        assert!(!descriptor2.slotted);
+        assert!(descriptor3.slotted);
+        assert!(descriptor4.slotted);
+        assert!(!descriptor5.slotted);
     }
 
     #[test]
@@ -420,6 +438,42 @@ h1 { color: red }
             .any(|e| matches!(&e.kind, ParseErrorKind::MissingTemplateOrScript)));
     }
 
+    #[test]
+    fn it_records_src_imports() {
+        let (descriptor, errors) = parse_with_errors(
+            r#"<template src="./comp.html"></template><script setup>console.log(1)</script><style src="./comp.css"></style><docs src="./docs.md"></docs>"#,
+        );
+        assert!(errors.is_empty());
+        assert_eq!(
+            descriptor.src_imports,
+            vec!["./comp.html", "./comp.css", "./docs.md"]
+        );
+    }
+
+    #[test]
+    fn it_parses_script_setup_generic() {
+        let (descriptor, errors) = parse_with_errors(
+            r#"<script setup lang="ts" generic="T extends string, U = number">const a = 1</script>"#,
+        );
+        assert!(errors.is_empty());
+
+        let generic = descriptor
+            .script_setup
+            .and_then(|s| s.generic)
+            .expect("generic should be parsed");
+        assert_eq!(generic.params.len(), 2);
+        assert_eq!(&generic.params[0].name.sym, "T");
+        assert!(generic.params[0].constraint.is_some());
+        assert_eq!(&generic.params[1].name.sym, "U");
+        assert!(generic.params[1].default.is_some());
+
+        // Malformed `generic` is reported
+        let (descriptor, errors) =
+            parse_with_errors(r#"<script setup lang="ts" generic="T extends">const a = 1</script>"#);
+        assert!(!errors.is_empty());
+        assert!(descriptor.script_setup.is_some_and(|s| s.generic.is_none()));
+    }
+
     fn parse(source: &str) -> SfcDescriptor {
         let mut errors = Vec::new();
         let mut parser = SfcParser::new(source, &mut errors);
@@ -1,7 +1,7 @@
 use fervid_core::{SfcScriptBlock, SfcScriptLang};
 use swc_core::{
     common::Span,
-    ecma::ast::{Expr, Module, Pat},
+    ecma::ast::{Expr, Module, Pat, TsTypeParamDecl},
 };
 use swc_ecma_parser::{lexer::Lexer, EsConfig, Parser, StringInput, Syntax, TsConfig};
 use swc_html_ast::{Child, Element};
@@ -19,6 +19,7 @@ impl SfcParser<'_, '_, '_> {
         let mut is_setup_seen = false;
         let mut is_lang_seen = false;
         let mut lang = SfcScriptLang::Es;
+        let mut generic_attr = None;
         for attr in element.attributes.iter() {
             match attr.name.as_str() {
                 "setup" => {
@@ -50,10 +51,19 @@ impl SfcParser<'_, '_, '_> {
                         }
                     }
                 }
+                "generic" => generic_attr = attr.value.as_ref().map(|v| (v.clone(), attr.span)),
                 _ => {}
             }
         }
 
+        // `generic` only makes sense for `<script setup lang="ts">`
+        let generic = match generic_attr {
+            Some((ref value, span)) if is_setup && matches!(lang, SfcScriptLang::Typescript) => {
+                self.parse_ts_type_params(value, span)
+            }
+            _ => None,
+        };
+
         // `<script>` should always have a single `Text` child
         let script_content = match element.children.get(0) {
             Some(Child::Text(t)) => t,
@@ -76,6 +86,7 @@ impl SfcParser<'_, '_, '_> {
                     }),
                     lang,
                     is_setup,
+                    generic,
                     span: element.span,
                 }));
             }
@@ -100,6 +111,7 @@ impl SfcParser<'_, '_, '_> {
             content: Box::new(module_content),
             lang,
             is_setup,
+            generic,
             span: element.span,
         }))
     }
@@ -171,4 +183,22 @@ impl SfcParser<'_, '_, '_> {
 
         parse_result.map_err(From::from)
     }
+
+    /// Parses the value of the `generic` attribute, e.g. `T extends string, U`.
+    /// The value is wrapped into an arrow function `<T extends string, U>() => {}`
+    /// and its type parameters are taken.
+    pub fn parse_ts_type_params(&mut self, raw: &str, span: Span) -> Option<Box<TsTypeParamDecl>> {
+        let wrapped = format!("<{}>() => {{}}", raw);
+
+        match self.parse_expr(&wrapped, Syntax::Typescript(TsConfig::default()), span) {
+            Ok(expr) => match *expr {
+                Expr::Arrow(arrow) => arrow.type_params,
+                _ => None,
+            },
+            Err(e) => {
+                self.report_error(e);
+                None
+            }
+        }
+    }
 }
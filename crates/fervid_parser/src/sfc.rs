@@ -51,6 +51,16 @@ impl SfcParser<'_, '_, '_> {
             let tag_name = &root_element.tag_name;
             let root_node_span = root_element.span;
 
+            // Content of the block is in another file
+            let src = root_element
+                .attributes
+                .iter()
+                .find(|attr| attr.name.eq("src"))
+                .and_then(|attr| attr.value.to_owned());
+            if let Some(src) = src {
+                sfc_descriptor.src_imports.push(src);
+            }
+
             if tag_name.eq("template") {
                 // Check duplicate
                 if sfc_descriptor.template.is_some() {
@@ -92,6 +102,9 @@ impl SfcParser<'_, '_, '_> {
                 }
             } else if tag_name.eq("style") {
                 if let Some(style_block) = self.parse_sfc_style_element(root_element) {
+                    sfc_descriptor.slotted |= style_block.is_scoped
+                        && (style_block.content.contains(":slotted(")
+                            || style_block.content.contains("::v-slotted("));
                     sfc_descriptor.styles.push(style_block);
                 }
             } else {
@@ -9,6 +9,7 @@ impl SfcParser<'_, '_, '_> {
         let mut lang = fervid_atom!("css");
         let mut is_scoped = false;
         let mut is_module = false;
+        let mut module_name = None;
 
         for attr in element.attributes.into_iter() {
             if attr.name.eq("lang") {
@@ -21,6 +22,7 @@ impl SfcParser<'_, '_, '_> {
                 is_scoped = true;
             } else if attr.name.eq("module") {
                 is_module = true;
+                module_name = attr.value.filter(|v| !v.is_empty());
             }
         }
 
@@ -43,6 +45,7 @@ impl SfcParser<'_, '_, '_> {
                     content: fervid_atom!(""),
                     is_scoped,
                     is_module,
+                    module_name,
                     span: element.span,
                 });
             }
@@ -58,6 +61,7 @@ impl SfcParser<'_, '_, '_> {
             content: style_content.data,
             is_scoped,
             is_module,
+            module_name,
             span: style_content.span,
         })
     }
@@ -28,13 +28,19 @@ lazy_static! {
     pub static ref DEFINE_SLOTS: FervidAtom = fervid_atom!("defineSlots");
     pub static ref REACTIVE: FervidAtom = fervid_atom!("reactive");
     pub static ref REF: FervidAtom = fervid_atom!("ref");
+    pub static ref TO_REF: FervidAtom = fervid_atom!("toRef");
+    pub static ref WATCH: FervidAtom = fervid_atom!("watch");
+    pub static ref WITH_DEFAULTS: FervidAtom = fervid_atom!("withDefaults");
 
     // Helper atoms
+    pub static ref DEFAULT_HELPER: FervidAtom = fervid_atom!("__default__");
     pub static ref EMIT: FervidAtom = fervid_atom!("emit");
     pub static ref EMIT_HELPER: FervidAtom = fervid_atom!("__emit");
     pub static ref EXPOSE_HELPER: FervidAtom = fervid_atom!("__expose");
     pub static ref MERGE_MODELS_HELPER: FervidAtom = fervid_atom!("_mergeModels");
     pub static ref MODEL_VALUE: FervidAtom = fervid_atom!("modelValue");
     pub static ref PROPS_HELPER: FervidAtom = fervid_atom!("__props");
+    pub static ref RESTORE_HELPER: FervidAtom = fervid_atom!("__restore");
+    pub static ref TEMP_HELPER: FervidAtom = fervid_atom!("__temp");
     pub static ref USE_MODEL_HELPER: FervidAtom = fervid_atom!("_useModel");
 }
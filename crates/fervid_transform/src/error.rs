@@ -17,10 +17,27 @@ pub struct ScriptError {
 pub enum ScriptErrorKind {
     /// A compiler macro was imported, but it didn't need to
     CompilerMacroImport,
+    /// Expression inside `v-bind()` in `<style>` could not be parsed
+    CssVarsMalformedExpression,
     /// `defineEmits` called with 0 type arguments (e.g. `defineEmits<>()`)
     DefineEmitsMalformed,
     /// `defineEmits` was called with both runtime and type arguments
     DefineEmitsTypeAndNonTypeArguments,
+    /// Computed key in `defineProps` destructure, e.g. `const { [foo]: bar } = defineProps()`
+    DefinePropsDestructureComputedKey,
+    /// Assignment to a destructured prop, e.g. `foo = 1` or `foo++`
+    DefinePropsDestructureCannotAssign,
+    /// Nested pattern in `defineProps` destructure, e.g. `const { foo: { bar } } = defineProps()`
+    DefinePropsDestructureNestedPattern,
+    /// A destructured prop was passed directly to `watch()` or `toRef()`,
+    /// a getter should be passed instead, e.g. `watch(() => foo)`
+    DefinePropsDestructurePassedDirectly,
+    /// `defineProps` or `withDefaults` references a variable declared in `<script setup>`,
+    /// e.g. `withDefaults(defineProps<Props>(), defaults)` with `const defaults = { foo: 1 }`.
+    /// Props are declared outside of `setup()`, where such variables do not exist
+    DefinePropsLocalReference,
+    /// `defineProps` was called with both runtime and type arguments
+    DefinePropsTypeAndNonTypeArguments,
     /// "defineEmits() type cannot mixed call signature and property syntax"
     DefineEmitsMixedCallAndPropertySyntax,
     /// Duplicate `defineEmits` call
@@ -33,11 +50,17 @@ pub enum ScriptErrorKind {
     /// A type param was not provided,
     /// e.g. `ExtractPropTypes<>`
     ResolveTypeMissingTypeParam,
+    /// Types are imported from another file, but no file system access was provided
+    ResolveTypeNoFs,
     /// Type parameters were not provided,
     /// e.g. `ExtractPropTypes`
     ResolveTypeMissingTypeParams,
     /// A type both not supported and not planned to be supported during type resolution
     ResolveTypeUnresolvable,
+    /// "Failed to resolve import source"
+    ResolveTypeUnresolvableImportSource,
+    /// A file with the imported types could not be parsed
+    ResolveTypeUnparsableFile,
     /// "Failed to resolve index type into finite keys"
     ResolveTypeUnresolvableIndexType,
     /// An unsupported construction during type resolution
@@ -48,6 +71,9 @@ pub enum ScriptErrorKind {
     ResolveTypeUnsupportedComputedKey,
     /// Disallow non-type exports inside `<script setup>`
     SetupExport,
+    /// `withDefaults` was not called with a type-based `defineProps` as the first argument,
+    /// e.g. `withDefaults(defineProps(['foo']), { foo: 1 })`
+    WithDefaultsWithoutTypeBasedDefineProps,
 }
 
 impl From<CssError> for TransformError {
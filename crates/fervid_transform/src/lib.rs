@@ -1,9 +1,17 @@
 use error::TransformError;
 use fervid_core::{SfcDescriptor, SfcScriptBlock, SfcScriptLang};
 use misc::infer_name;
-use script::transform_and_record_scripts;
-use style::{attach_scope_id, create_style_scope, transform_style_blocks};
-use template::transform_and_record_template;
+use script::{
+    resolve_type::{resolve_relative_path, TypeResolveContext},
+    transform_and_record_scripts,
+};
+use fervid_css::{CssModulesOptions, CssVarsOptions, DEFAULT_CSS_MODULES_PATTERN};
+use style::{
+    attach_css_modules, attach_scope_id, collect_css_vars, create_style_scope, css_modules_name,
+    inject_css_vars, inject_ssr_css_vars, preprocess_style_blocks, transform_style_blocks,
+    StyleSourceMapOptions,
+};
+use template::{transform_and_record_template, transform_asset_urls};
 
 #[macro_use]
 extern crate lazy_static;
@@ -45,10 +53,16 @@ pub fn transform_sfc<'o>(
             .as_ref()
             .map_or(false, recognize_lang);
 
+    // Hoisting is only safe when `<script setup>` is the only script
+    bindings_helper.hoist_static = options.hoist_static && sfc_descriptor.script_legacy.is_none();
+
     // Transform the scripts
+    let mut type_resolve_ctx =
+        TypeResolveContext::new(options.filename.to_owned(), options.type_resolve);
     let mut transform_result = transform_and_record_scripts(
         sfc_descriptor.script_setup,
         sfc_descriptor.script_legacy,
+        &mut type_resolve_ctx,
         &mut bindings_helper,
         errors,
     );
@@ -57,23 +71,87 @@ pub fn transform_sfc<'o>(
     let mut template_block = None;
     if let Some(mut template) = sfc_descriptor.template {
         transform_and_record_template(&mut template, &mut bindings_helper);
+        if let Some(asset_urls) = options.transform_asset_urls {
+            transform_asset_urls(&mut template, asset_urls, &mut bindings_helper);
+        }
         if !template.roots.is_empty() {
             template_block = Some(template);
         }
     }
 
-    // Transform scoped CSS
+    // Convert `<style lang="scss">` and others to CSS
     let mut style_blocks = sfc_descriptor.styles;
+    let style_input_maps = match options.style_preprocessor {
+        Some(style_preprocessor) => preprocess_style_blocks(
+            &mut style_blocks,
+            style_preprocessor,
+            options.filename,
+            errors,
+        ),
+        None => Vec::new(),
+    };
+
+    // Inject `v-bind()`s used in `<style>`
+    let css_vars_options = CssVarsOptions {
+        id: options.scope_id,
+        is_prod: options.is_prod,
+    };
+    let css_vars = collect_css_vars(&style_blocks);
+    inject_css_vars(
+        &css_vars,
+        css_vars_options,
+        &mut transform_result,
+        &mut bindings_helper,
+        errors,
+    );
+    let ssr_css_vars = match template_block {
+        Some(ref mut template) if options.is_ssr => {
+            inject_ssr_css_vars(&css_vars, css_vars_options, template, &mut bindings_helper)
+        }
+        _ => None,
+    };
+
+    // Transform scoped CSS and CSS Modules
     let scope = create_style_scope(&options.scope_id);
-    let had_scoped_blocks = transform_style_blocks(&mut style_blocks, &scope, errors);
-    if had_scoped_blocks {
+    let css_modules_name = css_modules_name(options.filename);
+    let css_modules_options = CssModulesOptions {
+        pattern: options
+            .css_modules_pattern
+            .unwrap_or(DEFAULT_CSS_MODULES_PATTERN),
+        mode: options.css_modules_mode,
+        name: &css_modules_name,
+        id: options.scope_id,
+    };
+    let style_result = transform_style_blocks(
+        &mut style_blocks,
+        &scope,
+        css_vars_options,
+        css_modules_options,
+        options.css_output,
+        StyleSourceMapOptions {
+            source: options.source,
+            filename: options.filename,
+            input_maps: &style_input_maps,
+        },
+        errors,
+    );
+    if style_result.had_scoped_blocks {
         attach_scope_id(&mut transform_result, &scope);
     }
+    attach_css_modules(&mut transform_result, &style_result.css_modules);
 
     // Augment with some metadata
     let mut exported_obj = transform_result.export_obj;
     infer_name(&mut exported_obj, &options.filename);
 
+    // Files the SFC depends on, e.g. for the bundlers to recompile it when they change
+    let mut deps = type_resolve_ctx.deps;
+    for src in sfc_descriptor.src_imports.iter() {
+        if src.starts_with('.') || src.starts_with('/') {
+            deps.insert(resolve_relative_path(options.filename, src));
+        }
+    }
+
     TransformSfcResult {
         bindings_helper,
         exported_obj,
@@ -81,6 +159,10 @@ pub fn transform_sfc<'o>(
         setup_fn: transform_result.setup_fn,
         template_block,
         style_blocks,
+        style_source_maps: style_result.source_maps,
+        style_compiled: style_result.compiled,
+        ssr_css_vars,
         custom_blocks: sfc_descriptor.custom_blocks,
+        deps: deps.into_iter().collect(),
     }
 }
@@ -3,13 +3,21 @@
 use fervid_core::{SfcScriptBlock, TemplateGenerationMode};
 use swc_core::{
     common::DUMMY_SP,
-    ecma::ast::{Function, Module, ObjectLit},
+    ecma::ast::{
+        BindingIdent, Decl, ExportNamedSpecifier, ExportSpecifier, Expr, Function, Ident,
+        ImportDecl, ImportNamedSpecifier, ImportSpecifier, Module, ModuleDecl, ModuleExportName,
+        ModuleItem, ObjectLit, Pat, PropOrSpread, SpreadElement, Stmt, Str, VarDecl, VarDeclKind,
+        VarDeclarator,
+    },
 };
 
-use crate::{error::TransformError, structs::TransformScriptsResult, BindingsHelper};
+use crate::{
+    atoms::DEFAULT_HELPER, error::TransformError, structs::TransformScriptsResult, BindingsHelper,
+};
 
 use self::{
     imports::process_imports,
+    resolve_type::TypeResolveContext,
     options_api::{transform_and_record_script_options_api, AnalyzeOptions},
     setup::{merge_sfc_helper, transform_and_record_script_setup},
 };
@@ -17,7 +25,7 @@ use self::{
 pub mod common;
 mod imports;
 mod options_api;
-mod resolve_type;
+pub mod resolve_type;
 mod setup;
 pub mod utils;
 
@@ -30,12 +38,26 @@ pub mod utils;
 /// - Variable bindings (from `<script setup>` and from Options API);
 /// - Import bindings;
 /// - (TODO) Imported `.vue` component bindings;
+///
+/// Types declared and imported in both scripts are recorded to the [`TypeResolveContext`].
 pub fn transform_and_record_scripts(
     script_setup: Option<SfcScriptBlock>,
     mut script_legacy: Option<SfcScriptBlock>,
+    ctx: &mut TypeResolveContext,
     bindings_helper: &mut BindingsHelper,
     errors: &mut Vec<TransformError>,
 ) -> TransformScriptsResult {
+    // Record the types before any transformation, they are needed by the macros
+    ctx.record_scope(
+        script_legacy
+            .iter()
+            .chain(script_setup.iter())
+            .flat_map(|script| script.content.body.iter()),
+        script_setup
+            .as_ref()
+            .and_then(|script_setup| script_setup.generic.as_deref()),
+    );
+
     // Set inline flag in `BindingsHelper`
     if bindings_helper.is_prod && script_setup.is_some() {
         bindings_helper.template_generation_mode = TemplateGenerationMode::Inline;
@@ -92,7 +114,15 @@ pub fn transform_and_record_scripts(
     let mut setup_fn: Option<Box<Function>> = None;
     if let Some(script_setup) = script_setup {
         let setup_transform_result =
-            transform_and_record_script_setup(script_setup, bindings_helper, errors);
+            transform_and_record_script_setup(script_setup, ctx, bindings_helper, errors);
+
+        // The default export of `<script>` is preserved as `const __default__`
+        // and is later spread into the exported object: `{ ...__default__, setup }`
+        if !export_obj.props.is_empty() {
+            hoist_default_export(&mut module, &mut export_obj);
+        } else {
+            hoist_named_default_export(&mut module, &mut export_obj);
+        }
 
         // TODO Push imports at module top or bottom? Or smart merge?
         // TODO Merge Vue imports produced by module transformation
@@ -106,8 +136,8 @@ pub fn transform_and_record_scripts(
             &mut export_obj.props,
         );
 
-        // TODO Adding bindings to `setup()` in Options API will get overwritten in `<script setup>`
-        // https://play.vuejs.org/#eNp9U01v2zAM/SuELm6BNFmTm5F22IYetsM2bMUudTEYNp2okyVDklMPQf77SNpunS7txTQfH/n4Ye/Vh6aZ71pUqVpHrBuTR7zOLAB5IV4Urm7EFaAPw+5CV1eZir7FTA1RgMq5gbg4KnScGYyLKVGf0rb6ZBa7z/pDQ//rB2qA7cvs7ZJYaAL21CqnV6KKXS+2y4G1GljX/CB8NWqVekehynlK/g3awipTBBRtiK7mMbbucVJ3vaCEMZdHBJvXSAQ2pRAYPTFJL3F2pwm7nAGb5T1ZW2J3zsJGh0gF9nuJXcLhcDQr16OYa6J2NlB0kNC2aSPVr12JhhTE/soNnwzS+Lfh7qR9eA9JxC4mkEJSUtVERp3ujetg7Qi4o9PdC+BswfovmlmHwusmQsDY8uF03TgfgW/5iU4Jlaf1JXM5Ln92CScV1HmE25FzBQnBtDEpNS1L79hJwRKrvDUR9jysiJ2d9w6AJ9fb0YNxNynIBysgbUkesq1ePifddxNZNVMxUKjSm/lDcJZ+EKmYKf4mtUH/ra+bqXTUylRujHv8IhirzUa82GLx5wT+EDrGMvXdY0C/o2U/xWLuN0i35/DNz690okmQ7tkaYr8R/IHBmZZ77GkfW1tS2xOedPtZTqTt5jbcdBFtGIca13UQfqboXHyf10Z/bnc1X437VYd/HFh0XQ==
+        // `setup()` of `<script>` is overwritten by the one of `<script setup>`,
+        // because it goes after the `...__default__` spread (same as in the official compiler)
         setup_fn = setup_transform_result.setup_fn;
     }
 
@@ -118,13 +148,171 @@ pub fn transform_and_record_scripts(
     }
 }
 
+/// Moves the `<script>` default export to a `const __default__` declaration
+/// and replaces the exported object with `{ ...__default__ }`.
+fn hoist_default_export(module: &mut Module, export_obj: &mut ObjectLit) {
+    let span = export_obj.span;
+    let mut props = std::mem::take(&mut export_obj.props);
+
+    // `export default defineComponent({})` is already `{ ...defineComponent({}) }`,
+    // in this case the spread expression is used as is
+    let default_expr = match props.as_slice() {
+        [PropOrSpread::Spread(_)] => {
+            let Some(PropOrSpread::Spread(spread)) = props.pop() else {
+                unreachable!()
+            };
+            spread.expr
+        }
+        _ => Box::new(Expr::Object(ObjectLit { span, props })),
+    };
+
+    let default_ident = Ident {
+        span: DUMMY_SP,
+        sym: DEFAULT_HELPER.to_owned(),
+        optional: false,
+    };
+
+    module
+        .body
+        .push(ModuleItem::Stmt(Stmt::Decl(Decl::Var(Box::new(VarDecl {
+            span: DUMMY_SP,
+            kind: VarDeclKind::Const,
+            declare: false,
+            decls: vec![VarDeclarator {
+                span: DUMMY_SP,
+                name: Pat::Ident(BindingIdent {
+                    id: default_ident.to_owned(),
+                    type_ann: None,
+                }),
+                init: Some(default_expr),
+                definite: false,
+            }],
+        })))));
+
+    export_obj.props.push(PropOrSpread::Spread(SpreadElement {
+        dot3_token: DUMMY_SP,
+        expr: Box::new(Expr::Ident(default_ident)),
+    }));
+}
+
+/// Moves `export { foo as default }` of `<script>` to a `const __default__ = foo` declaration,
+/// and `export { foo as default } from './foo'` to `import { foo as __default__ } from './foo'`.
+/// The exported object becomes `{ ...__default__ }` when such an export is found.
+fn hoist_named_default_export(module: &mut Module, export_obj: &mut ObjectLit) {
+    let mut found: Option<(ModuleExportName, Option<Box<Str>>)> = None;
+
+    module.body.retain_mut(|module_item| {
+        let ModuleItem::ModuleDecl(ModuleDecl::ExportNamed(named_export)) = module_item else {
+            return true;
+        };
+        if named_export.type_only || found.is_some() {
+            return true;
+        }
+
+        let default_idx = named_export.specifiers.iter().position(|specifier| {
+            let ExportSpecifier::Named(ExportNamedSpecifier {
+                orig,
+                exported,
+                is_type_only: false,
+                ..
+            }) = specifier
+            else {
+                return false;
+            };
+            matches!(
+                exported.as_ref().unwrap_or(orig),
+                ModuleExportName::Ident(Ident { sym, .. }) if sym == "default"
+            )
+        });
+        let Some(default_idx) = default_idx else {
+            return true;
+        };
+
+        let ExportSpecifier::Named(specifier) = named_export.specifiers.remove(default_idx) else {
+            unreachable!()
+        };
+        found = Some((specifier.orig, named_export.src.take()));
+
+        // Keep the other exports, the source is only needed for the default one
+        if !named_export.specifiers.is_empty() {
+            named_export.src = found.as_ref().and_then(|(_, src)| src.to_owned());
+            return true;
+        }
+        false
+    });
+
+    let Some((orig, src)) = found else {
+        return;
+    };
+
+    let default_ident = Ident {
+        span: DUMMY_SP,
+        sym: DEFAULT_HELPER.to_owned(),
+        optional: false,
+    };
+
+    match src {
+        // `import { foo as __default__ } from './foo'`
+        Some(src) => module.body.insert(
+            0,
+            ModuleItem::ModuleDecl(ModuleDecl::Import(ImportDecl {
+                span: DUMMY_SP,
+                specifiers: vec![ImportSpecifier::Named(ImportNamedSpecifier {
+                    span: DUMMY_SP,
+                    local: default_ident.to_owned(),
+                    imported: Some(orig),
+                    is_type_only: false,
+                })],
+                src,
+                type_only: false,
+                with: None,
+                phase: Default::default(),
+            })),
+        ),
+
+        // `const __default__ = foo`
+        None => {
+            let init = match orig {
+                ModuleExportName::Ident(ident) => Expr::Ident(ident),
+                ModuleExportName::Str(_) => return,
+            };
+            module
+                .body
+                .push(ModuleItem::Stmt(Stmt::Decl(Decl::Var(Box::new(VarDecl {
+                    span: DUMMY_SP,
+                    kind: VarDeclKind::Const,
+                    declare: false,
+                    decls: vec![VarDeclarator {
+                        span: DUMMY_SP,
+                        name: Pat::Ident(BindingIdent {
+                            id: default_ident.to_owned(),
+                            type_ann: None,
+                        }),
+                        init: Some(Box::new(init)),
+                        definite: false,
+                    }],
+                })))));
+        }
+    }
+
+    export_obj.props.push(PropOrSpread::Spread(SpreadElement {
+        dot3_token: DUMMY_SP,
+        expr: Box::new(Expr::Ident(default_ident)),
+    }));
+}
+
 #[cfg(test)]
 mod tests {
     use swc_core::common::{sync::Lrc, SourceMap, Span};
     use swc_ecma_codegen::{text_writer::JsWriter, Emitter, Node};
 
     use super::*;
-    use crate::test_utils::parser::parse_javascript_module;
+    use fervid_core::{fervid_atom, BindingTypes};
+
+    use crate::{
+        test_utils::{parser::parse_typescript_module, to_str},
+        SetupBinding,
+    };
 
     /// https://github.com/vuejs/core/blob/c0c9432b64091fa15fd8619cfb06828735356a42/packages/compiler-sfc/__tests__/compileScript.spec.ts#L261-L275
     #[test]
@@ -152,11 +340,165 @@ mod tests {
         );
     }
 
+    #[test]
+    fn it_spreads_default_export_of_normal_script() {
+        let (res, bindings_helper) = transform_scripts(
+            "
+            export const foo = 1
+            export enum Color { Red, Green }
+            export default {
+                inheritAttrs: false,
+            }",
+            "
+            const count = ref(0)",
+        );
+
+        assert_eq!(
+            "export const foo=1;export enum Color{Red,Green}const __default__={inheritAttrs:false};",
+            to_str(&res.module)
+        );
+        assert_eq!("{...__default__}", to_str(&res.export_obj));
+
+        // Bindings of `<script>` are visible to the template
+        let options_api_bindings = bindings_helper
+            .options_api_bindings
+            .expect("Options API bindings must be present");
+        assert_eq!(
+            vec![
+                SetupBinding(fervid_atom!("foo"), BindingTypes::LiteralConst),
+                SetupBinding(fervid_atom!("Color"), BindingTypes::LiteralConst),
+            ],
+            options_api_bindings.setup
+        );
+    }
+
+    #[test]
+    fn it_spreads_non_object_default_export() {
+        let (res, _) = transform_scripts("export default createOptions()", "const foo = 1");
+
+        assert_eq!(
+            "const __default__=createOptions();",
+            to_str(&res.module)
+        );
+        assert_eq!("{...__default__}", to_str(&res.export_obj));
+    }
+
+    #[test]
+    fn it_does_not_hoist_without_default_export() {
+        let (res, _) = transform_scripts("export const foo = 1", "const bar = 1");
+
+        assert_eq!("export const foo=1;", to_str(&res.module));
+        assert_eq!("{}", to_str(&res.export_obj));
+    }
+
+    #[test]
+    fn it_spreads_named_default_export() {
+        let (res, bindings_helper) = transform_scripts(
+            "
+            const opts = { name: 'Foo' }
+            export { opts as default }",
+            "const bar = 1",
+        );
+
+        assert_eq!(
+            "const opts={name:\"Foo\"};const __default__=opts;",
+            to_str(&res.module)
+        );
+        assert_eq!("{...__default__}", to_str(&res.export_obj));
+
+        // `default` is not a binding
+        let options_api_bindings = bindings_helper
+            .options_api_bindings
+            .expect("Options API bindings must be present");
+        assert_eq!(
+            vec![SetupBinding(fervid_atom!("opts"), BindingTypes::SetupConst)],
+            options_api_bindings.setup
+        );
+
+        // Re-export from another module becomes an import
+        let (res, _) = transform_scripts("export { default, x } from './opts'", "const bar = 1");
+
+        assert_eq!(
+            "import{default as __default__}from\"./opts\";export{x}from\"./opts\";",
+            to_str(&res.module)
+        );
+        assert_eq!("{...__default__}", to_str(&res.export_obj));
+    }
+
+    #[test]
+    fn it_deduplicates_vue_imports() {
+        check_import_dedupe(
+            "
+            import { ref } from 'vue'",
+            "
+            import { ref, computed } from 'vue'
+            const foo = ref()
+            const bar = computed(() => foo.value)",
+            "import { ref } from 'vue';\nimport { computed } from 'vue';\n",
+        );
+    }
+
+    #[test]
+    fn it_exposes_imports_shared_by_both_scripts_from_setup() {
+        let (res, bindings_helper) = transform_scripts(
+            "
+            import Foo from './Foo.vue'",
+            "
+            import Foo from './Foo.vue'",
+        );
+
+        assert_eq!("import Foo from\"./Foo.vue\";", to_str(&res.module));
+
+        // Same as if `Foo` was only imported in `<script setup>`
+        assert_eq!(
+            vec![SetupBinding(fervid_atom!("Foo"), BindingTypes::Imported)],
+            bindings_helper.setup_bindings
+        );
+        let options_api_bindings = bindings_helper
+            .options_api_bindings
+            .expect("Options API bindings must be present");
+        assert!(options_api_bindings.imports.is_empty());
+    }
+
     fn check_import_dedupe(script_content: &str, script_setup_content: &str, expected: &str) {
+        let (res, _) = transform_scripts(script_content, script_setup_content);
+
+        // Emitting the result requires some setup with SWC
+        let cm: Lrc<SourceMap> = Default::default();
+        let mut buff: Vec<u8> = Vec::with_capacity(128);
+        let writer: JsWriter<&mut Vec<u8>> = JsWriter::new(cm.clone(), "\n", &mut buff, None);
+
+        // For possible errors, otherwise SWC does not like it
+        let mut source = String::from(script_content);
+        source.push_str(script_setup_content);
+        cm.new_source_file(swc_core::common::FileName::Anon, source);
+
+        let mut emitter_cfg = swc_ecma_codegen::Config::default();
+        emitter_cfg.minify = false;
+        emitter_cfg.omit_last_semi = false;
+
+        let mut emitter = Emitter {
+            cfg: emitter_cfg,
+            comments: None,
+            wr: writer,
+            cm,
+        };
+
+        let _ = res.module.emit_with(&mut emitter);
+
+        let stringified = String::from_utf8(buff).unwrap();
+
+        assert_eq!(expected, stringified);
+    }
+
+    fn transform_scripts(
+        script_content: &str,
+        script_setup_content: &str,
+    ) -> (TransformScriptsResult, BindingsHelper) {
         macro_rules! ts {
             ($input: expr) => {
                 Box::new(
-                    parse_javascript_module($input, 0, Default::default())
+                    parse_typescript_module($input, 0, Default::default())
                         .expect("analyze_ts expects the input to be parseable")
                         .0,
                 )
@@ -167,6 +509,7 @@ mod tests {
             content: ts!(script_content),
             lang: fervid_core::SfcScriptLang::Typescript,
             is_setup: false,
+            generic: None,
             span: Span {
                 lo: swc_core::common::BytePos(1),
                 hi: swc_core::common::BytePos(script_content.len() as u32 + 1),
@@ -177,6 +520,7 @@ mod tests {
             content: ts!(script_setup_content),
             lang: fervid_core::SfcScriptLang::Typescript,
             is_setup: true,
+            generic: None,
             span: Span {
                 lo: swc_core::common::BytePos(script_content.len() as u32 + 2),
                 hi: swc_core::common::BytePos(script_setup_content.len() as u32 + 1),
@@ -187,38 +531,15 @@ mod tests {
         // Do work
         let mut bindings_helper = BindingsHelper::default();
         let mut errors = Vec::new();
+        let mut ctx = TypeResolveContext::new("anonymous.vue".into(), Default::default());
         let res = transform_and_record_scripts(
             Some(script_setup),
             Some(script),
+            &mut ctx,
             &mut bindings_helper,
             &mut errors,
         );
 
-        // Emitting the result requires some setup with SWC
-        let cm: Lrc<SourceMap> = Default::default();
-        let mut buff: Vec<u8> = Vec::with_capacity(128);
-        let writer: JsWriter<&mut Vec<u8>> = JsWriter::new(cm.clone(), "\n", &mut buff, None);
-
-        // For possible errors, otherwise SWC does not like it
-        let mut source = String::from(script_content);
-        source.push_str(script_setup_content);
-        cm.new_source_file(swc_core::common::FileName::Anon, source);
-
-        let mut emitter_cfg = swc_ecma_codegen::Config::default();
-        emitter_cfg.minify = false;
-        emitter_cfg.omit_last_semi = false;
-
-        let mut emitter = Emitter {
-            cfg: emitter_cfg,
-            comments: None,
-            wr: writer,
-            cm,
-        };
-
-        let _ = res.module.emit_with(&mut emitter);
-
-        let stringified = String::from_utf8(buff).unwrap();
-
-        assert_eq!(expected, stringified);
+        (res, bindings_helper)
     }
 }
@@ -5,7 +5,9 @@ use swc_core::ecma::{
 };
 
 use crate::{
-    atoms::{COMPUTED, DEFINE_EMITS, DEFINE_EXPOSE, DEFINE_PROPS, REACTIVE, REF, VUE},
+    atoms::{
+        COMPUTED, DEFINE_EMITS, DEFINE_EXPOSE, DEFINE_PROPS, REACTIVE, REF, TO_REF, VUE, WATCH,
+    },
     error::{ScriptError, ScriptErrorKind, TransformError },
     structs::VueResolvedImports,
     BindingsHelper, ImportBinding, SetupBinding,
@@ -68,6 +70,7 @@ pub fn register_import(
     errors: &mut Vec<TransformError>,
 ) -> bool {
     let mut binding_type = BindingTypes::Imported;
+    let mut vue_import_id: Option<Id> = None;
 
     let (local, imported, span) = match import_specifier {
         // e.g. `import * as foo from 'mod.js'`
@@ -121,12 +124,7 @@ pub fn register_import(
                     return false;
                 }
 
-                collect_vue_import(
-                    imported_word,
-                    imported_as.to_id(),
-                    &mut bindings_helper.vue_resolved_imports,
-                );
-                return true;
+                vue_import_id = Some(imported_as.to_id());
             } else if is_dot_vue_import && imported_word == "default" {
                 // Only `import { default as Smth }` is supported.
                 // `import { default }` is invalid, and SWC will catch that
@@ -142,18 +140,50 @@ pub fn register_import(
     };
 
     // Check duplicates
-    if let Some(existing) = bindings_helper.user_imports.get(&local) {
+    if let Some(existing) = bindings_helper.user_imports.get_mut(&local) {
         // Not exact duplicate means some local name has been used twice
         if existing.source != *source || existing.imported != imported {
             errors.push(TransformError::ScriptError(ScriptError {
                 span,
                 kind: ScriptErrorKind::DuplicateImport,
             }));
+            return false;
+        }
+
+        // Same import in `<script>` and `<script setup>` is exposed from `setup()`
+        // as if it was imported only in `<script setup>`
+        if is_from_setup && !existing.is_from_setup && !is_from_vue_import {
+            existing.is_from_setup = true;
+            if let Some(ref mut options_api_bindings) = bindings_helper.options_api_bindings {
+                options_api_bindings.imports.retain(|it| it.0 != local);
+            }
+            bindings_helper
+                .setup_bindings
+                .push(SetupBinding(local, BindingTypes::Imported));
         }
 
         return false;
     }
 
+    // Named imports from `vue` are tracked to be deduplicated, but are not bindings
+    if let Some(vue_import_id) = vue_import_id {
+        collect_vue_import(
+            &imported,
+            vue_import_id,
+            &mut bindings_helper.vue_resolved_imports,
+        );
+        bindings_helper.user_imports.insert(
+            local.to_owned(),
+            ImportBinding {
+                source: source.to_owned(),
+                imported,
+                local,
+                is_from_setup,
+            },
+        );
+        return true;
+    }
+
     if is_from_setup {
         bindings_helper
             .setup_bindings
@@ -188,6 +218,10 @@ fn collect_vue_import(imported_word: &JsWord, used_as: Id, vue_imports: &mut Vue
         vue_imports.computed = Some(used_as)
     } else if *imported_word == *REACTIVE {
         vue_imports.reactive = Some(used_as)
+    } else if *imported_word == *TO_REF {
+        vue_imports.to_ref = Some(used_as)
+    } else if *imported_word == *WATCH {
+        vue_imports.watch = Some(used_as)
     }
 }
 
@@ -256,7 +290,9 @@ mod tests {
                 vue_user_imports: VueResolvedImports {
                     ref_import: Some((fervid_atom!("ref"), SyntaxContext::default())),
                     computed: Some((fervid_atom!("computed"), SyntaxContext::default())),
-                    reactive: Some((fervid_atom!("reactive"), SyntaxContext::default()))
+                    reactive: Some((fervid_atom!("reactive"), SyntaxContext::default())),
+                    to_ref: None,
+                    watch: None
                 },
                 ..Default::default()
             }
@@ -271,7 +307,9 @@ mod tests {
                 vue_user_imports: VueResolvedImports {
                     ref_import: Some((fervid_atom!("foo"), SyntaxContext::default())),
                     computed: Some((fervid_atom!("bar"), SyntaxContext::default())),
-                    reactive: Some((fervid_atom!("baz"), SyntaxContext::default()))
+                    reactive: Some((fervid_atom!("baz"), SyntaxContext::default())),
+                    to_ref: None,
+                    watch: None
                 },
                 ..Default::default()
             }
@@ -325,7 +363,9 @@ mod tests {
                 vue_user_imports: VueResolvedImports {
                     ref_import: Some((fervid_atom!("ref"), SyntaxContext::default())),
                     computed: Some((fervid_atom!("computed"), SyntaxContext::default())),
-                    reactive: Some((fervid_atom!("reactive"), SyntaxContext::default()))
+                    reactive: Some((fervid_atom!("reactive"), SyntaxContext::default())),
+                    to_ref: None,
+                    watch: None
                 },
                 ..Default::default()
             }
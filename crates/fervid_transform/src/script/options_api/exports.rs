@@ -37,6 +37,11 @@ pub fn collect_exports_named(named: &NamedExport, out: &mut Vec<SetupBinding>) {
                     .as_ref()
                     .unwrap_or(&named_export_specifier.orig);
 
+                // `export { foo as default }` is the component itself, not a binding
+                if matches!(exported, ModuleExportName::Ident(ident) if ident.sym == "default") {
+                    continue;
+                }
+
                 collect_module_export_name(exported, out, binding_type)
             }
         }
@@ -1,24 +1,34 @@
 //! Adapted from https://github.com/vuejs/core/blob/main/packages/compiler-sfc/src/script/resolveType.ts
 
-use std::rc::Rc;
+use std::sync::Arc;
 
 use fervid_core::{fervid_atom, FervidAtom};
-use fxhash::FxHashMap;
+use fxhash::{FxBuildHasher, FxHashMap};
+use indexmap::{IndexMap, IndexSet};
 use itertools::Itertools;
 use phf::{phf_set, Set};
 use swc_core::{
     common::{pass::Either, Span, Spanned, DUMMY_SP},
     ecma::ast::{
-        BinExpr, BinaryOp, Expr, Ident, Lit, Tpl, TsCallSignatureDecl, TsEntityName,
-        TsFnOrConstructorType, TsFnType, TsGetterSignature, TsIndexedAccessType,
+        BinExpr, BinaryOp, Expr, Ident, Lit, ModuleItem, Tpl, TsCallSignatureDecl, TsEntityName,
+        TsFnOrConstructorType, TsFnParam, TsFnType, TsGetterSignature, TsIndexedAccessType,
         TsIntersectionType, TsKeywordType, TsKeywordTypeKind, TsLit, TsLitType, TsMappedType,
         TsQualifiedName, TsTplLitType, TsType, TsTypeAnn, TsTypeElement, TsTypeLit,
-        TsTypeQueryExpr, TsTypeRef, TsUnionOrIntersectionType, TsUnionType,
+        TsTypeOperatorOp, TsTypeParamDecl, TsTypeQueryExpr, TsTypeRef, TsUnionOrIntersectionType,
+        TsUnionType,
     },
 };
 
 use crate::error::{ScriptError, ScriptErrorKind};
 
+use self::fs::{global_type_file_to_scope, import_source_to_scope};
+
+mod fs;
+mod scope;
+
+pub use fs::{NativeTypeResolveFs, TypeResolveFs, TypeScopeCache};
+pub(crate) use fs::resolve_relative_path;
+
 static SUPPORTED_BUILTINS_SET: Set<&'static str> = phf_set! {
     "Partial",
     "Required",
@@ -27,27 +37,49 @@ static SUPPORTED_BUILTINS_SET: Set<&'static str> = phf_set! {
     "Omit",
 };
 
+/// Runtime type used when the actual type cannot be inferred
+pub const UNKNOWN_TYPE: &str = "Unknown";
+
 type ResolutionResult<T> = Result<T, ScriptError>;
 
 #[derive(Default)]
 pub struct ResolvedElements {
-    pub props: FxHashMap<FervidAtom, TsTypeElement>,
+    pub props: IndexMap<FervidAtom, ResolvedProp, FxBuildHasher>,
     pub calls: Vec<Either<TsFnType, TsCallSignatureDecl>>,
 }
 
-type ScopeTypeNode = TsType;
+/// A type element of the resolved props along with the scope it was declared in
+pub struct ResolvedProp {
+    pub element: TsTypeElement,
+    pub owner_scope: Arc<TypeScope>,
+}
 
+type ScopeTypeNode = Arc<TsType>;
+
+#[derive(Debug)]
 pub struct TypeScope {
     filename: String,
     // source: String,
     // offset: usize,
-    imports: FxHashMap<FervidAtom, ()>,
+    imports: FxHashMap<FervidAtom, TypeScopeImport>,
     types: FxHashMap<FervidAtom, ScopeTypeNode>,
     declares: FxHashMap<FervidAtom, ScopeTypeNode>,
     is_generic_scope: bool,
+    /// Type parameters of `<script setup generic="T">`.
+    /// These shadow the other types and have no runtime type.
+    generic_params: Vec<FervidAtom>,
     // resolved_import_sources: FxHashMap<FervidAtom, String>,
     exported_types: FxHashMap<FervidAtom, ScopeTypeNode>,
     exported_declares: FxHashMap<FervidAtom, ScopeTypeNode>,
+    /// Sources of `export * from './foo'`, looked up when an exported type is not found
+    exported_all: Vec<FervidAtom>,
+}
+
+#[derive(Debug)]
+struct TypeScopeImport {
+    source: FervidAtom,
+    /// `default` for default imports and `*` for namespace imports
+    imported: FervidAtom,
 }
 
 impl TypeScope {
@@ -58,24 +90,73 @@ impl TypeScope {
             types: Default::default(),
             declares: Default::default(),
             is_generic_scope: false,
+            generic_params: Default::default(),
             exported_types: Default::default(),
             exported_declares: Default::default(),
+            exported_all: Default::default(),
         }
     }
 }
 
+/// A type node found by a reference along with the scope it was declared in
+struct ResolvedTypeReference {
+    ts_type: ScopeTypeNode,
+    owner_scope: Arc<TypeScope>,
+}
+
 enum MergeElementsAs {
     Union,
     Intersection,
 }
 
-pub struct TypeResolveContext {
+/// Options for resolving the types used in macros, e.g. `defineProps<Props>()`
+#[derive(Debug, Default, Clone, Copy)]
+pub struct TypeResolveOptions<'o> {
+    /// File system access, required for the types imported from other files
+    pub fs: Option<&'o dyn TypeResolveFs>,
+    /// Parsed type files shared between compilations.
+    /// When `None`, the files are parsed once per compilation.
+    pub cache: Option<&'o TypeScopeCache>,
+    /// Files with the global types, e.g. `src/global.d.ts`.
+    /// These are looked up when a type is not found in the SFC.
+    pub global_type_files: &'o [String],
+}
+
+pub struct TypeResolveContext<'o> {
     pub filename: String,
-    pub scope: Rc<TypeScope>,
+    pub scope: Arc<TypeScope>,
+    /// Files the resolved types come from, e.g. `/src/types.ts` for `import { Props } from './types'`
+    pub deps: IndexSet<String, FxBuildHasher>,
+    options: TypeResolveOptions<'o>,
+    /// Used when no cache is provided in the `options`
+    own_cache: TypeScopeCache,
+    /// Files currently searched through `export *`, to not go in circles
+    export_all_stack: Vec<String>,
+    /// Scopes of the `global_type_files`, loaded on first use
+    global_scopes: Option<Vec<Arc<TypeScope>>>,
 }
 
-impl TypeResolveContext {
-    pub fn new(filename: String) -> TypeResolveContext {
+impl<'o> TypeResolveContext<'o> {
+    pub fn new(filename: String, options: TypeResolveOptions<'o>) -> TypeResolveContext<'o> {
+        let scope = Arc::from(TypeScope::new(filename.to_owned()));
+        TypeResolveContext {
+            filename,
+            scope,
+            deps: Default::default(),
+            options,
+            own_cache: Default::default(),
+            export_all_stack: Vec::new(),
+            global_scopes: None,
+        }
+    }
+
+    /// Records the imports and types of the SFC scripts, e.g. of `<script>` and `<script setup>`,
+    /// along with the type parameters from `<script setup generic="...">`
+    pub fn record_scope<'m>(
+        &mut self,
+        module_items: impl IntoIterator<Item = &'m ModuleItem>,
+        generic: Option<&TsTypeParamDecl>,
+    ) {
         // function ctxToScope(ctx: TypeResolveContext): TypeScope {
         //     if (ctx.scope) {
         //       return ctx.scope
@@ -99,8 +180,22 @@ impl TypeResolveContext {
 
         //     return (ctx.scope = scope)
         //   }
-        let scope = Rc::from(TypeScope::new(filename.to_owned()));
-        TypeResolveContext { filename, scope }
+        let module_items = module_items.into_iter().collect_vec();
+        let mut scope = TypeScope::from_module_items(self.filename.to_owned(), &module_items);
+
+        if let Some(generic) = generic {
+            scope.generic_params = generic
+                .params
+                .iter()
+                .map(|param| param.name.sym.to_owned())
+                .collect();
+        }
+
+        self.scope = Arc::new(scope);
+    }
+
+    fn cache(&self) -> &TypeScopeCache {
+        self.options.cache.unwrap_or(&self.own_cache)
     }
 }
 
@@ -118,7 +213,7 @@ pub fn resolve_type_elements(
 fn resolve_type_elements_impl(
     ctx: &mut TypeResolveContext,
     ts_type: &TsType,
-    scope: &TypeScope,
+    scope: &Arc<TypeScope>,
 ) -> ResolutionResult<ResolvedElements> {
     // TODO Implementing a check for `@vue-ignore` requires access to comments
     // if (
@@ -129,7 +224,7 @@ fn resolve_type_elements_impl(
     // }
 
     match ts_type {
-        TsType::TsTypeLit(type_lit) => type_elements_to_map(&type_lit.members),
+        TsType::TsTypeLit(type_lit) => type_elements_to_map(&type_lit.members, scope),
         TsType::TsParenthesizedType(paren) => {
             resolve_type_elements_impl(ctx, &paren.type_ann, scope)
         }
@@ -216,8 +311,9 @@ fn resolve_type_elements_impl(
                 }
             }
 
-            let resolved = resolve_type_reference(ctx, ts_type, scope);
-            if let Some(_resolved) = resolved {
+            let resolved = resolve_type_reference(ctx, ts_type, scope)?;
+            if let Some(resolved) = resolved {
+                // TODO typeParameters
                 // let typeParams: Record<string, Node> | undefined
                 // if (
                 //     (resolved.type === 'TSTypeAliasDeclaration' ||
@@ -238,7 +334,7 @@ fn resolve_type_elements_impl(
                 //     resolved._ownerScope,
                 //     typeParams,
                 // )
-                todo!()
+                return resolve_type_elements_impl(ctx, &resolved.ts_type, &resolved.owner_scope);
             }
 
             if type_name_single == "" {
@@ -265,8 +361,8 @@ fn resolve_type_elements_impl(
                     return Err(error(ScriptErrorKind::ResolveTypeMissingTypeParam, type_params.span));
                 };
 
-                if let Some(ret) = resolve_return_type(ctx, first_type_param, scope) {
-                    return resolve_type_elements_impl(ctx, ret, scope);
+                if let Some(ret) = resolve_return_type(ctx, first_type_param, scope)? {
+                    return resolve_type_elements_impl(ctx, &ret.ts_type, &ret.owner_scope);
                 }
             }
 
@@ -289,24 +385,21 @@ fn resolve_type_elements_impl(
                 }
             }
 
-            // TODO
-            // const sourceScope = importSourceToScope(
-            // ctx,
-            // node.argument,
-            // scope,
-            // node.argument.value,
-            // )
-            // const resolved = resolveTypeReference(ctx, node, sourceScope)
-            // if (resolved) {
-            // return resolveTypeElements(ctx, resolved, resolved._ownerScope)
-            // }
-            // break
-            todo!()
+            let source_scope =
+                import_source_to_scope(ctx, &import_type.arg.value, scope, import_type.span)?;
+            let name = get_reference_name(ts_type);
+            if let Some(resolved) =
+                inner_resolve_type_reference(ctx, ts_type, &source_scope, &name, true)?
+            {
+                return resolve_type_elements_impl(ctx, &resolved.ts_type, &resolved.owner_scope);
+            }
+
+            Err(error(ScriptErrorKind::ResolveTypeUnresolvable, import_type.span))
         }
 
         TsType::TsTypeQuery(type_query) => {
-            if let Some(resolved) = resolve_type_reference(ctx, ts_type, scope) {
-                resolve_type_elements_impl(ctx, resolved, scope)
+            if let Some(resolved) = resolve_type_reference(ctx, ts_type, scope)? {
+                resolve_type_elements_impl(ctx, &resolved.ts_type, &resolved.owner_scope)
             } else {
                 Err(error(ScriptErrorKind::ResolveTypeUnresolvable, type_query.span))
             }
@@ -327,21 +420,27 @@ fn resolve_type_elements_impl(
     }
 }
 
-fn type_elements_to_map(elements: &Vec<TsTypeElement>) -> ResolutionResult<ResolvedElements> {
+fn type_elements_to_map(
+    elements: &Vec<TsTypeElement>,
+    scope: &Arc<TypeScope>,
+) -> ResolutionResult<ResolvedElements> {
     let mut result = ResolvedElements::default();
 
     for ts_type_element in elements.iter() {
+        let resolved_prop = || ResolvedProp {
+            element: ts_type_element.to_owned(),
+            owner_scope: scope.to_owned(),
+        };
+
         macro_rules! implementation {
             ($signature: ident) => {
-                // TODO No scopes supported
-
                 let name = get_id(&$signature.key);
                 if let (Some(name), false) = (name, $signature.computed) {
-                    result.props.insert(name, ts_type_element.to_owned());
+                    result.props.insert(name, resolved_prop());
                 } else if let Expr::Tpl(tpl) = $signature.key.as_ref() {
                     let keys = resolve_template_keys(&tpl)?;
                     for key in keys {
-                        result.props.insert(key, ts_type_element.to_owned());
+                        result.props.insert(key, resolved_prop());
                     }
                 } else {
                     return Err(error(ScriptErrorKind::ResolveTypeUnsupportedComputedKey, $signature.span));
@@ -389,7 +488,10 @@ fn merge_elements(
                     result.props.insert(key, new_value);
                 }
 
-                Some(existing_value) => {
+                Some(ResolvedProp {
+                    element: existing_value,
+                    ..
+                }) => {
                     let optional = match existing_value {
                         TsTypeElement::TsPropertySignature(s) => s.optional,
                         TsTypeElement::TsGetterSignature(s) => s.optional,
@@ -402,15 +504,9 @@ fn merge_elements(
                             // TODO Validate. Probably just pushing to existing is better?
                             let types: Vec<Box<TsType>> = vec![
                                 // existing
-                                Box::new(TsType::TsTypeLit(TsTypeLit {
-                                    span: DUMMY_SP,
-                                    members: vec![existing_value.to_owned()],
-                                })),
+                                type_element_to_type(existing_value),
                                 // new
-                                Box::new(TsType::TsTypeLit(TsTypeLit {
-                                    span: DUMMY_SP,
-                                    members: vec![new_value.to_owned()],
-                                })),
+                                type_element_to_type(&new_value.element),
                             ];
 
                             let union_or_intersection = match merge_as {
@@ -436,13 +532,18 @@ fn merge_elements(
                                 $signature.optional || optional,
                             );
 
-                            result
-                                .props
-                                .insert(key, TsTypeElement::TsGetterSignature(property));
+                            let owner_scope = new_value.owner_scope.to_owned();
+                            result.props.insert(
+                                key,
+                                ResolvedProp {
+                                    element: TsTypeElement::TsGetterSignature(property),
+                                    owner_scope,
+                                },
+                            );
                         };
                     }
 
-                    match new_value {
+                    match new_value.element {
                         TsTypeElement::TsMethodSignature(ref signature) => {
                             implementation!(signature);
                         }
@@ -470,10 +571,27 @@ fn merge_elements(
     result
 }
 
+/// Gets the type of a property, e.g. `string` for `foo: string`
+fn type_element_to_type(type_element: &TsTypeElement) -> Box<TsType> {
+    let type_ann = match type_element {
+        TsTypeElement::TsPropertySignature(s) => s.type_ann.as_ref(),
+        TsTypeElement::TsGetterSignature(s) => s.type_ann.as_ref(),
+        _ => None,
+    };
+
+    match type_ann {
+        Some(type_ann) => type_ann.type_ann.to_owned(),
+        None => Box::new(TsType::TsTypeLit(TsTypeLit {
+            span: DUMMY_SP,
+            members: vec![type_element.to_owned()],
+        })),
+    }
+}
+
 fn resolve_mapped_type(
     ctx: &mut TypeResolveContext,
     mapped_type: &TsMappedType,
-    scope: &TypeScope,
+    scope: &Arc<TypeScope>,
 ) -> ResolutionResult<ResolvedElements> {
     let mut result = ResolvedElements::default();
 
@@ -503,9 +621,13 @@ fn resolve_mapped_type(
             mapped_type.optional.is_some(),
         );
 
-        result
-            .props
-            .insert(key, TsTypeElement::TsGetterSignature(property));
+        result.props.insert(
+            key,
+            ResolvedProp {
+                element: TsTypeElement::TsGetterSignature(property),
+                owner_scope: scope.to_owned(),
+            },
+        );
     }
 
     Ok(result)
@@ -514,7 +636,7 @@ fn resolve_mapped_type(
 fn resolve_index_type(
     ctx: &mut TypeResolveContext,
     index_type: &TsIndexedAccessType,
-    scope: &TypeScope,
+    scope: &Arc<TypeScope>,
 ) -> ResolutionResult<Vec<TsType>> {
     let TsIndexedAccessType {
         obj_type,
@@ -527,8 +649,7 @@ fn resolve_index_type(
         ..
     }) = index_type.as_ref()
     {
-        return resolve_array_element_type(ctx, &obj_type, scope)
-            .map(|v| v.into_iter().cloned().collect_vec());
+        return resolve_array_element_type(ctx, &obj_type, scope);
     }
 
     let resolved = resolve_type_elements_impl(ctx, &obj_type, scope)?;
@@ -537,7 +658,7 @@ fn resolve_index_type(
 
     macro_rules! implementation {
         ($value: ident) => {
-            let target_type = match $value {
+            let target_type = match $value.element {
                 TsTypeElement::TsPropertySignature(ref s) => &s.type_ann,
                 TsTypeElement::TsGetterSignature(ref s) => &s.type_ann,
                 TsTypeElement::TsMethodSignature(ref s) => &s.type_ann,
@@ -556,13 +677,13 @@ fn resolve_index_type(
     }) = index_type.as_ref()
     {
         // Values of the map
-        for (_key, value) in props.drain() {
+        for (_key, value) in props.drain(..) {
             implementation!(value);
         }
     } else {
         // Values of the string type
         for key in resolve_string_type(ctx, &index_type, scope)? {
-            let Some(value) = props.remove(&key) else {
+            let Some(value) = props.shift_remove(&key) else {
                 continue;
             };
 
@@ -573,20 +694,20 @@ fn resolve_index_type(
     Ok(types)
 }
 
-fn resolve_array_element_type<'t>(
+fn resolve_array_element_type(
     ctx: &mut TypeResolveContext,
-    array_element_type: &'t TsType,
-    scope: &'t TypeScope,
-) -> ResolutionResult<Vec<&'t TsType>> {
+    array_element_type: &TsType,
+    scope: &Arc<TypeScope>,
+) -> ResolutionResult<Vec<TsType>> {
     match array_element_type {
         // type[]
-        TsType::TsArrayType(array_type) => Ok(vec![&array_type.elem_type]),
+        TsType::TsArrayType(array_type) => Ok(vec![*array_type.elem_type.to_owned()]),
 
         // tuple
         TsType::TsTupleType(tuple_type) => Ok(tuple_type
             .elem_types
             .iter()
-            .map(|t| t.ty.as_ref())
+            .map(|t| *t.ty.to_owned())
             .collect_vec()),
 
         TsType::TsTypeRef(ref type_ref) => {
@@ -602,10 +723,10 @@ fn resolve_array_element_type<'t>(
                 Ok(type_params
                     .params
                     .iter()
-                    .map(|it| it.as_ref())
+                    .map(|it| *it.to_owned())
                     .collect_vec())
-            } else if let Some(resolved) = resolve_type_reference(ctx, array_element_type, scope) {
-                resolve_array_element_type(ctx, resolved, scope)
+            } else if let Some(resolved) = resolve_type_reference(ctx, array_element_type, scope)? {
+                resolve_array_element_type(ctx, &resolved.ts_type, &resolved.owner_scope)
             } else {
                 Err(error(ScriptErrorKind::ResolveTypeElementType, type_ref.span))
             }
@@ -669,35 +790,42 @@ fn qualified_name_to_path(qual_name: &TsQualifiedName) -> Vec<FervidAtom> {
         .collect_vec()
 }
 
-fn resolve_global_scope(ctx: &mut TypeResolveContext) -> Result<Option<Vec<TypeScope>>, ()> {
-    // function resolveGlobalScope(ctx: TypeResolveContext): TypeScope[] | undefined {
-    //     if (ctx.options.globalTypeFiles) {
-    //       const fs = resolveFS(ctx)
-    //       if (!fs) {
-    //         throw new Error('[vue/compiler-sfc] globalTypeFiles requires fs access.')
-    //       }
-    //       return ctx.options.globalTypeFiles.map(file =>
-    //         fileToScope(ctx, normalizePath(file), true),
-    //       )
-    //     }
-    //   }
+/// Gets the scopes of the `global_type_files`, which are parsed once per context
+fn resolve_global_scope(
+    ctx: &mut TypeResolveContext,
+    span: Span,
+) -> ResolutionResult<Vec<Arc<TypeScope>>> {
+    if let Some(ref global_scopes) = ctx.global_scopes {
+        return Ok(global_scopes.to_owned());
+    }
 
-    // TODO: Implement when FS access is ready
-    Ok(None)
+    let global_type_files = ctx.options.global_type_files;
+    let mut global_scopes = Vec::with_capacity(global_type_files.len());
+    for global_type_file in global_type_files.iter() {
+        global_scopes.push(global_type_file_to_scope(ctx, global_type_file, span)?);
+    }
+
+    ctx.global_scopes = Some(global_scopes.to_owned());
+    Ok(global_scopes)
 }
 
-fn resolve_type_from_import<'t>(
+fn resolve_type_from_import(
     ctx: &mut TypeResolveContext,
-    ts_type: &'t TsType,
-    name: &str,
-    scope: &TypeScope,
-) -> Option<&'t ScopeTypeNode> {
+    ts_type: &TsType,
+    import: &TypeScopeImport,
+    scope: &Arc<TypeScope>,
+) -> ResolutionResult<Option<ResolvedTypeReference>> {
     // const { source, imported } = scope.imports[name]
     // const sourceScope = importSourceToScope(ctx, node, scope, source)
     // return resolveTypeReference(ctx, node, sourceScope, imported, true)
-
-    // TODO: Implement when FS access is ready
-    None
+    let source_scope = import_source_to_scope(ctx, &import.source, scope, ts_type.span())?;
+    inner_resolve_type_reference(
+        ctx,
+        ts_type,
+        &source_scope,
+        &[import.imported.to_owned()],
+        true,
+    )
 }
 
 fn resolve_template_keys(tpl: &Tpl) -> ResolutionResult<Vec<FervidAtom>> {
@@ -745,7 +873,7 @@ fn resolve_template_keys(tpl: &Tpl) -> ResolutionResult<Vec<FervidAtom>> {
 fn resolve_template_keys_ts(
     ctx: &mut TypeResolveContext,
     tpl: &TsTplLitType,
-    scope: &TypeScope,
+    scope: &Arc<TypeScope>,
 ) -> ResolutionResult<Vec<FervidAtom>> {
     struct StackItem {
         expr_idx: usize,
@@ -791,7 +919,7 @@ fn resolve_template_keys_ts(
 fn resolve_string_type(
     ctx: &mut TypeResolveContext,
     ts_type: &TsType,
-    scope: &TypeScope,
+    scope: &Arc<TypeScope>,
 ) -> ResolutionResult<Vec<FervidAtom>> {
     match ts_type {
         TsType::TsLitType(lit_type) => match lit_type.lit {
@@ -811,9 +939,9 @@ fn resolve_string_type(
         }
 
         TsType::TsTypeRef(type_ref) => {
-            let resolved = resolve_type_reference(ctx, ts_type, scope);
+            let resolved = resolve_type_reference(ctx, ts_type, scope)?;
             if let Some(resolved) = resolved {
-                return resolve_string_type(ctx, resolved, scope);
+                return resolve_string_type(ctx, &resolved.ts_type, &resolved.owner_scope);
             }
 
             let TsEntityName::Ident(ref type_name_ident) = type_ref.type_name else {
@@ -901,7 +1029,7 @@ fn resolve_builtin(
     ctx: &mut TypeResolveContext,
     type_ref: &TsTypeRef,
     name: &str,
-    scope: &TypeScope,
+    scope: &Arc<TypeScope>,
 ) -> ResolutionResult<ResolvedElements> {
     let Some(ref type_params) = type_ref.type_params else {
         return Err(error(ScriptErrorKind::ResolveTypeMissingTypeParams, type_ref.span));
@@ -911,18 +1039,18 @@ fn resolve_builtin(
         return Err(error(ScriptErrorKind::ResolveTypeMissingTypeParam, type_params.span));
     };
 
-    let mut t = resolve_type_elements(ctx, &first_type_param)?;
+    let mut t = resolve_type_elements_impl(ctx, &first_type_param, scope)?;
 
     match name {
         "Partial" | "Required" => {
             let is_optional = name == "Partial";
 
             for prop in t.props.values_mut() {
-                match prop {
-                    TsTypeElement::TsPropertySignature(s) => s.optional = is_optional,
-                    TsTypeElement::TsGetterSignature(s) => s.optional = is_optional,
-                    TsTypeElement::TsSetterSignature(s) => s.optional = is_optional,
-                    TsTypeElement::TsMethodSignature(s) => s.optional = is_optional,
+                match prop.element {
+                    TsTypeElement::TsPropertySignature(ref mut s) => s.optional = is_optional,
+                    TsTypeElement::TsGetterSignature(ref mut s) => s.optional = is_optional,
+                    TsTypeElement::TsSetterSignature(ref mut s) => s.optional = is_optional,
+                    TsTypeElement::TsMethodSignature(ref mut s) => s.optional = is_optional,
                     _ => {}
                 }
             }
@@ -973,53 +1101,363 @@ fn find_static_property_type<'t>(ts_type: &'t TsTypeLit, key: &str) -> Option<&'
     })
 }
 
-fn resolve_return_type<'t>(
+fn resolve_return_type(
     ctx: &mut TypeResolveContext,
-    ts_type: &'t TsType,
-    scope: &'t TypeScope,
-) -> Option<&'t TsType> {
-    let mut resolved = Option::<&TsType>::None;
+    ts_type: &TsType,
+    scope: &Arc<TypeScope>,
+) -> ResolutionResult<Option<ResolvedTypeReference>> {
+    let mut resolved = None;
     if matches!(
         ts_type,
         TsType::TsTypeRef(_) | TsType::TsTypeQuery(_) | TsType::TsImportType(_)
     ) {
-        resolved = resolve_type_reference(ctx, &ts_type, scope);
+        resolved = resolve_type_reference(ctx, &ts_type, scope)?;
     }
 
     let Some(resolved) = resolved else {
-        return None;
+        return Ok(None);
     };
 
-    match resolved {
+    match resolved.ts_type.as_ref() {
         TsType::TsFnOrConstructorType(TsFnOrConstructorType::TsFnType(fn_type)) => {
-            Some(&fn_type.type_ann.type_ann)
+            Ok(Some(ResolvedTypeReference {
+                ts_type: Arc::new(*fn_type.type_ann.type_ann.to_owned()),
+                owner_scope: resolved.owner_scope,
+            }))
         }
 
+        _ => Ok(None),
+    }
+}
+
+/// Infers the runtime types of a type element, e.g. `String` for `foo: string`.
+/// Returns `Unknown` when the type cannot be checked at runtime.
+pub fn infer_runtime_type_element(
+    ctx: &mut TypeResolveContext,
+    type_element: &TsTypeElement,
+    scope: &Arc<TypeScope>,
+) -> Vec<FervidAtom> {
+    let type_ann = match type_element {
+        TsTypeElement::TsPropertySignature(s) => s.type_ann.as_ref(),
+        TsTypeElement::TsGetterSignature(s) => s.type_ann.as_ref(),
+        TsTypeElement::TsMethodSignature(_) => return vec![fervid_atom!("Function")],
         _ => None,
+    };
+
+    match type_ann {
+        Some(type_ann) => infer_runtime_type(ctx, &type_ann.type_ann, scope),
+        None => vec![FervidAtom::from(UNKNOWN_TYPE)],
+    }
+}
+
+/// Infers the runtime types of a type, e.g. `String` for `string` or `null` for `null`.
+/// Returns `Unknown` when the type cannot be checked at runtime.
+///
+/// Adapted from `inferRuntimeType` of the official compiler
+pub fn infer_runtime_type(
+    ctx: &mut TypeResolveContext,
+    ts_type: &TsType,
+    scope: &Arc<TypeScope>,
+) -> Vec<FervidAtom> {
+    infer_runtime_type_impl(ctx, ts_type, scope, false)
+}
+
+/// When `is_key_of` is `true`, infers the runtime types of the keys of a type,
+/// e.g. `String` for `keyof { foo: string }`
+fn infer_runtime_type_impl(
+    ctx: &mut TypeResolveContext,
+    ts_type: &TsType,
+    scope: &Arc<TypeScope>,
+    is_key_of: bool,
+) -> Vec<FervidAtom> {
+    macro_rules! runtime_type {
+        ($($name: literal),+) => {
+            vec![$(fervid_atom!($name)),+]
+        };
+    }
+
+    macro_rules! unknown {
+        () => {
+            vec![FervidAtom::from(UNKNOWN_TYPE)]
+        };
+    }
+
+    match ts_type {
+        TsType::TsKeywordType(keyword_type) => match keyword_type.kind {
+            TsKeywordTypeKind::TsStringKeyword => runtime_type!("String"),
+            TsKeywordTypeKind::TsNumberKeyword => runtime_type!("Number"),
+            TsKeywordTypeKind::TsBooleanKeyword => runtime_type!("Boolean"),
+            TsKeywordTypeKind::TsObjectKeyword => runtime_type!("Object"),
+            TsKeywordTypeKind::TsNullKeyword => runtime_type!("null"),
+            TsKeywordTypeKind::TsSymbolKeyword => runtime_type!("Symbol"),
+            TsKeywordTypeKind::TsAnyKeyword if is_key_of => {
+                runtime_type!("String", "Number", "Symbol")
+            }
+            _ => unknown!(),
+        },
+
+        TsType::TsTypeLit(type_lit) => {
+            let mut types = Vec::with_capacity(1);
+            for member in type_lit.members.iter() {
+                let runtime_type = if is_key_of {
+                    match member {
+                        TsTypeElement::TsPropertySignature(s)
+                            if matches!(*s.key, Expr::Lit(Lit::Num(_))) =>
+                        {
+                            fervid_atom!("Number")
+                        }
+
+                        // `[key: number]: string`
+                        TsTypeElement::TsIndexSignature(s) => {
+                            let param_type = s.params.first().and_then(|param| match param {
+                                TsFnParam::Ident(ident) => ident.type_ann.as_ref(),
+                                _ => None,
+                            });
+                            let Some(param_type) = param_type else {
+                                continue;
+                            };
+
+                            let runtime_type =
+                                infer_runtime_type_impl(ctx, &param_type.type_ann, scope, false)
+                                    .into_iter()
+                                    .next();
+                            match runtime_type {
+                                Some(t) if t != UNKNOWN_TYPE => t,
+                                _ => return unknown!(),
+                            }
+                        }
+
+                        _ => fervid_atom!("String"),
+                    }
+                } else {
+                    match member {
+                        TsTypeElement::TsCallSignatureDecl(_)
+                        | TsTypeElement::TsConstructSignatureDecl(_) => fervid_atom!("Function"),
+                        _ => fervid_atom!("Object"),
+                    }
+                };
+
+                if !types.contains(&runtime_type) {
+                    types.push(runtime_type);
+                }
+            }
+
+            if types.is_empty() {
+                if is_key_of {
+                    return unknown!();
+                }
+                types.push(fervid_atom!("Object"));
+            }
+            types
+        }
+
+        TsType::TsFnOrConstructorType(TsFnOrConstructorType::TsFnType(_)) => {
+            runtime_type!("Function")
+        }
+
+        TsType::TsArrayType(_) | TsType::TsTupleType(_) => runtime_type!("Array"),
+
+        TsType::TsLitType(lit_type) => match lit_type.lit {
+            TsLit::Str(_) | TsLit::Tpl(_) => runtime_type!("String"),
+            TsLit::Bool(_) => runtime_type!("Boolean"),
+            TsLit::Number(_) | TsLit::BigInt(_) => runtime_type!("Number"),
+        },
+
+        TsType::TsTypeRef(type_ref) => {
+            match resolve_type_reference(ctx, ts_type, scope) {
+                Ok(Some(resolved)) => {
+                    return infer_runtime_type_impl(
+                        ctx,
+                        &resolved.ts_type,
+                        &resolved.owner_scope,
+                        is_key_of,
+                    );
+                }
+                Ok(None) => {}
+                Err(_) => return unknown!(),
+            }
+
+            let TsEntityName::Ident(ref type_name) = type_ref.type_name else {
+                return unknown!();
+            };
+
+            let type_param = |idx: usize| {
+                type_ref
+                    .type_params
+                    .as_ref()
+                    .and_then(|type_params| type_params.params.get(idx))
+            };
+
+            if is_key_of {
+                match type_name.sym.as_str() {
+                    "String"
+                    | "Array"
+                    | "ArrayLike"
+                    | "Parameters"
+                    | "ConstructorParameters"
+                    | "ReadonlyArray" => runtime_type!("String", "Number"),
+
+                    // TS built-in utility types
+                    "Record" | "Partial" | "Required" | "Readonly" => match type_param(0) {
+                        Some(t) => infer_runtime_type_impl(ctx, t, scope, true),
+                        None => unknown!(),
+                    },
+                    "Pick" | "Extract" => match type_param(1) {
+                        Some(t) => infer_runtime_type_impl(ctx, t, scope, false),
+                        None => unknown!(),
+                    },
+
+                    "Function" | "Object" | "Set" | "Map" | "WeakSet" | "WeakMap" | "Date"
+                    | "Promise" | "Error" | "Uppercase" | "Lowercase" | "Capitalize"
+                    | "Uncapitalize" | "ReadonlyMap" | "ReadonlySet" => runtime_type!("String"),
+
+                    _ => unknown!(),
+                }
+            } else {
+                match type_name.sym.as_str() {
+                    "Array" | "Function" | "Object" | "Set" | "Map" | "WeakSet" | "WeakMap"
+                    | "Date" | "Promise" | "Error" => vec![type_name.sym.to_owned()],
+
+                    // TS built-in utility types
+                    // https://www.typescriptlang.org/docs/handbook/utility-types.html
+                    "Partial" | "Required" | "Readonly" | "Record" | "Pick" | "Omit"
+                    | "InstanceType" => runtime_type!("Object"),
+                    "Uppercase" | "Lowercase" | "Capitalize" | "Uncapitalize" => {
+                        runtime_type!("String")
+                    }
+                    "Parameters" | "ConstructorParameters" | "ReadonlyArray" => {
+                        runtime_type!("Array")
+                    }
+                    "ReadonlyMap" => runtime_type!("Map"),
+                    "ReadonlySet" => runtime_type!("Set"),
+
+                    "NonNullable" => match type_param(0) {
+                        Some(t) => {
+                            let mut types = infer_runtime_type_impl(ctx, t, scope, false);
+                            types.retain(|t| t != "null");
+                            types
+                        }
+                        None => unknown!(),
+                    },
+                    "Extract" => match type_param(1) {
+                        Some(t) => infer_runtime_type_impl(ctx, t, scope, false),
+                        None => unknown!(),
+                    },
+                    "Exclude" | "OmitThisParameter" => match type_param(0) {
+                        Some(t) => infer_runtime_type_impl(ctx, t, scope, false),
+                        None => unknown!(),
+                    },
+
+                    // Cannot infer, e.g. `ThisParameterType`
+                    _ => unknown!(),
+                }
+            }
+        }
+
+        TsType::TsParenthesizedType(paren) => {
+            infer_runtime_type_impl(ctx, &paren.type_ann, scope, false)
+        }
+
+        TsType::TsUnionOrIntersectionType(union_or_intersection) => {
+            let (types, is_intersection) = match union_or_intersection {
+                TsUnionOrIntersectionType::TsUnionType(u) => (&u.types, false),
+                TsUnionOrIntersectionType::TsIntersectionType(i) => (&i.types, true),
+            };
+
+            let mut result = flatten_types(ctx, types.iter().map(Box::as_ref), scope, is_key_of);
+            if is_intersection {
+                result.retain(|t| t != UNKNOWN_TYPE);
+            }
+            result
+        }
+
+        TsType::TsIndexedAccessType(indexed_access_type) => {
+            match resolve_index_type(ctx, indexed_access_type, scope) {
+                Ok(types) => flatten_types(ctx, types.iter(), scope, is_key_of),
+                Err(_) => unknown!(),
+            }
+        }
+
+        TsType::TsImportType(import_type) => {
+            let Ok(source_scope) =
+                import_source_to_scope(ctx, &import_type.arg.value, scope, import_type.span)
+            else {
+                return unknown!();
+            };
+
+            let name = get_reference_name(ts_type);
+            match inner_resolve_type_reference(ctx, ts_type, &source_scope, &name, true) {
+                Ok(Some(resolved)) => {
+                    infer_runtime_type_impl(ctx, &resolved.ts_type, &resolved.owner_scope, false)
+                }
+                _ => unknown!(),
+            }
+        }
+
+        // `typeof foo` only supports an identifier in the local scope
+        TsType::TsTypeQuery(type_query) => match type_query.expr_name {
+            TsTypeQueryExpr::TsEntityName(TsEntityName::Ident(ref ident)) => {
+                match scope.declares.get(&ident.sym) {
+                    Some(declared) => {
+                        let declared = declared.to_owned();
+                        infer_runtime_type_impl(ctx, &declared, scope, is_key_of)
+                    }
+                    None => unknown!(),
+                }
+            }
+            _ => unknown!(),
+        },
+
+        // e.g. `readonly string[]` or `keyof Foo`
+        TsType::TsTypeOperator(type_operator) => infer_runtime_type_impl(
+            ctx,
+            &type_operator.type_ann,
+            scope,
+            matches!(type_operator.op, TsTypeOperatorOp::KeyOf),
+        ),
+
+        _ => unknown!(),
     }
 }
 
-pub fn resolve_union_type<'t>(
+/// Infers the runtime types of several types, skipping the duplicates
+fn flatten_types<'t>(
     ctx: &mut TypeResolveContext,
-    ts_type: &'t TsType,
-    scope: &'t TypeScope,
-) -> Vec<&'t TsType> {
+    types: impl Iterator<Item = &'t TsType>,
+    scope: &Arc<TypeScope>,
+    is_key_of: bool,
+) -> Vec<FervidAtom> {
     let mut result = Vec::new();
-    resolve_union_type_impl(ctx, ts_type, scope, &mut result);
+    for t in types {
+        for runtime_type in infer_runtime_type_impl(ctx, t, scope, is_key_of) {
+            if !result.contains(&runtime_type) {
+                result.push(runtime_type);
+            }
+        }
+    }
     result
 }
 
+pub fn resolve_union_type(
+    ctx: &mut TypeResolveContext,
+    ts_type: &TsType,
+    scope: &Arc<TypeScope>,
+) -> ResolutionResult<Vec<TsType>> {
+    let mut result = Vec::new();
+    resolve_union_type_impl(ctx, ts_type, scope, &mut result)?;
+    Ok(result)
+}
+
 /// Adapted from https://github.com/vuejs/core/blob/0ac0f2e338f6f8f0bea7237db539c68bfafb88ae/packages/compiler-sfc/src/script/resolveType.ts#L1922-L1940
-fn resolve_union_type_impl<'t>(
+fn resolve_union_type_impl(
     ctx: &mut TypeResolveContext,
-    ts_type: &'t TsType,
-    scope: &'t TypeScope,
-    out: &mut Vec<&'t TsType>,
-) {
-    let mut ts_type = ts_type;
+    ts_type: &TsType,
+    scope: &Arc<TypeScope>,
+    out: &mut Vec<TsType>,
+) -> ResolutionResult<()> {
     if let TsType::TsTypeRef(_) = ts_type {
-        if let Some(resolved) = resolve_type_reference(ctx, &ts_type, scope) {
-            ts_type = resolved;
+        if let Some(resolved) = resolve_type_reference(ctx, ts_type, scope)? {
+            return resolve_union_type_impl(ctx, &resolved.ts_type, &resolved.owner_scope, out);
         }
     }
 
@@ -1027,22 +1465,20 @@ fn resolve_union_type_impl<'t>(
         ts_type
     {
         for union_type_child in union_type.types.iter() {
-            resolve_union_type_impl(ctx, &union_type_child, scope, out);
+            resolve_union_type_impl(ctx, union_type_child, scope, out)?;
         }
     } else {
-        out.push(ts_type);
+        out.push(ts_type.to_owned());
     }
+
+    Ok(())
 }
 
-fn resolve_type_reference<'t>(
+fn resolve_type_reference(
     ctx: &mut TypeResolveContext,
-    ts_type: &'t TsType,
-    scope: &'t TypeScope,
-) -> Option<&'t TsType> {
-    // TODO No type resolution is implemented yet
-    // TODO Implementing this requires scopes
-    // TODO It also requires a FS layer to work the same way as in official compiler
-
+    ts_type: &TsType,
+    scope: &Arc<TypeScope>,
+) -> ResolutionResult<Option<ResolvedTypeReference>> {
     // TODO No caching is supported
     //     const canCache = !scope?.isGenericScope
     //     if (canCache && node._resolvedReference) {
@@ -1053,24 +1489,29 @@ fn resolve_type_reference<'t>(
     inner_resolve_type_reference(ctx, ts_type, scope, &name, false)
 }
 
-fn inner_resolve_type_reference<'t>(
+fn inner_resolve_type_reference(
     ctx: &mut TypeResolveContext,
-    ts_type: &'t TsType,
-    scope: &'t TypeScope,
+    ts_type: &TsType,
+    scope: &Arc<TypeScope>,
     name: &[FervidAtom],
     only_exported: bool,
-) -> Option<&'t ScopeTypeNode> {
+) -> ResolutionResult<Option<ResolvedTypeReference>> {
     let name_single = if name.len() == 1 {
         Some(&name[0])
     } else if !name.is_empty() {
         None
     } else {
-        return None;
+        return Ok(None);
     };
 
     if let Some(name_single) = name_single {
-        if let Some(_) = scope.imports.get(name_single) {
-            return resolve_type_from_import(ctx, ts_type, &name_single, scope);
+        // Generic parameters are in scope, but cannot be resolved to a type
+        if scope.generic_params.contains(name_single) {
+            return Ok(None);
+        }
+
+        if let Some(import) = scope.imports.get(name_single) {
+            return resolve_type_from_import(ctx, ts_type, import, scope);
         }
 
         let lookup_source = match ts_type {
@@ -1081,31 +1522,59 @@ fn inner_resolve_type_reference<'t>(
         };
 
         if let Some(found) = lookup_source.get(name_single) {
-            return Some(found);
+            return Ok(Some(ResolvedTypeReference {
+                ts_type: found.to_owned(),
+                owner_scope: scope.to_owned(),
+            }));
         }
 
-        // fallback to global
-        let global_scopes = resolve_global_scope(ctx);
-        if let Ok(Some(global_scopes)) = global_scopes {
-            for s in global_scopes {
-                let src = if matches!(ts_type, TsType::TsTypeQuery(_)) {
-                    &s.declares
-                } else {
-                    &s.types
-                };
-                if let Some(_found) = src.get(name_single) {
-                    // TODO ;(ctx.deps || (ctx.deps = new Set())).add(s.filename)
-                    // return Some(found);
-                    todo!();
+        // Types re-exported using `export * from './foo'`
+        if only_exported {
+            for source in scope.exported_all.iter() {
+                let source_scope = import_source_to_scope(ctx, source, scope, ts_type.span())?;
+                if ctx.export_all_stack.contains(&source_scope.filename) {
+                    continue;
                 }
+
+                ctx.export_all_stack.push(source_scope.filename.to_owned());
+                let found = inner_resolve_type_reference(ctx, ts_type, &source_scope, name, true);
+                ctx.export_all_stack.pop();
+
+                if let Some(found) = found? {
+                    return Ok(Some(found));
+                }
+            }
+        }
+
+        // fallback to global
+        for global_scope in resolve_global_scope(ctx, ts_type.span())? {
+            let src = if matches!(ts_type, TsType::TsTypeQuery(_)) {
+                &global_scope.declares
+            } else {
+                &global_scope.types
+            };
+            if let Some(found) = src.get(name_single) {
+                ctx.deps.insert(global_scope.filename.to_owned());
+                return Ok(Some(ResolvedTypeReference {
+                    ts_type: found.to_owned(),
+                    owner_scope: global_scope.to_owned(),
+                }));
             }
         }
 
         // Not found
-        return None;
+        return Ok(None);
     }
 
-    let ns = inner_resolve_type_reference(ctx, ts_type, scope, &name[0..1], only_exported);
+    // Namespace import, e.g. `Types.Props` from `import * as Types from './types'`
+    if let Some(import) = scope.imports.get(&name[0]) {
+        if import.imported == "*" {
+            let source_scope = import_source_to_scope(ctx, &import.source, scope, ts_type.span())?;
+            return inner_resolve_type_reference(ctx, ts_type, &source_scope, &name[1..], true);
+        }
+    }
+
+    let ns = inner_resolve_type_reference(ctx, ts_type, scope, &name[0..1], only_exported)?;
     if let Some(_ns) = ns {
         // TODO This is pretty much impossible to cover
         //   1: TSModuleDeclaration is not a part of TsType;
@@ -1127,7 +1596,7 @@ fn inner_resolve_type_reference<'t>(
         //         }
     }
 
-    None
+    Ok(None)
 }
 
 /// Support for the `ExtractPropTypes` helper - it's non-exhaustive, mostly
@@ -1139,7 +1608,7 @@ fn resolve_extract_prop_types(
     // Reuse the same object, so clear `calls` just to be compatible to the official compiler
     resolved_elements.calls.clear();
 
-    for raw in resolved_elements.props.values_mut() {
+    for ResolvedProp { element: raw, .. } in resolved_elements.props.values_mut() {
         let (key, type_ann) = match raw {
             TsTypeElement::TsPropertySignature(ref s) => {
                 (&s.key, s.type_ann.as_ref().map(|v| &v.type_ann))
@@ -1351,7 +1820,7 @@ fn error(kind: ScriptErrorKind, span: Span) -> ScriptError {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::test_utils::parser::parse_typescript_expr;
+    use crate::test_utils::parser::{parse_typescript_expr, parse_typescript_module};
 
     #[test]
     fn it_resolves_template_literal_keys() {
@@ -1395,6 +1864,419 @@ mod tests {
         assert_eq!(result, vec!["A", "B", "C"]);
     }
 
+    struct MemoryFs(FxHashMap<&'static str, &'static str>);
+
+    impl TypeResolveFs for MemoryFs {
+        fn file_exists(&self, path: &str) -> bool {
+            self.0.contains_key(path)
+        }
+
+        fn read_file(&self, path: &str) -> Option<String> {
+            self.0.get(path).map(|s| s.to_string())
+        }
+    }
+
+    fn memory_fs(files: &[(&'static str, &'static str)]) -> MemoryFs {
+        MemoryFs(files.iter().copied().collect())
+    }
+
+    /// Resolves `Props` in the `script` of `/src/Comp.vue` to the prop names and their runtime types
+    fn resolve_props(
+        script: &str,
+        options: TypeResolveOptions,
+    ) -> ResolutionResult<Vec<(FervidAtom, Vec<FervidAtom>)>> {
+        let module = parse_typescript_module(script, 0, Default::default())
+            .expect("Should parse")
+            .0;
+
+        let mut ctx = TypeResolveContext::new("/src/Comp.vue".into(), options);
+        ctx.record_scope(module.body.iter(), None);
+
+        let props_type = TsType::TsTypeRef(TsTypeRef {
+            span: DUMMY_SP,
+            type_name: TsEntityName::Ident(Ident::new("Props".into(), DUMMY_SP)),
+            type_params: None,
+        });
+
+        let resolved = resolve_type_elements(&mut ctx, &props_type)?;
+        Ok(resolved
+            .props
+            .into_iter()
+            .map(|(key, prop)| {
+                let types = infer_runtime_type_element(&mut ctx, &prop.element, &prop.owner_scope);
+                (key, types)
+            })
+            .collect())
+    }
+
+    #[test]
+    fn it_resolves_relative_imports() {
+        let fs = memory_fs(&[(
+            "/src/types.ts",
+            "export interface Props { foo: string; bar?: number | boolean }",
+        )]);
+        let options = TypeResolveOptions {
+            fs: Some(&fs),
+            ..Default::default()
+        };
+
+        let result =
+            resolve_props("import type { Props } from './types'", options).expect("Should resolve");
+        assert_eq!(
+            result,
+            vec![
+                (fervid_atom!("foo"), vec![fervid_atom!("String")]),
+                (
+                    fervid_atom!("bar"),
+                    vec![fervid_atom!("Number"), fervid_atom!("Boolean")]
+                ),
+            ]
+        );
+
+        // Aliased and re-exported
+        let fs = memory_fs(&[
+            (
+                "/src/types/index.ts",
+                "export { Base as Props } from '../base'",
+            ),
+            ("/src/base.d.ts", "export type Base = { baz: () => void }"),
+        ]);
+        let options = TypeResolveOptions {
+            fs: Some(&fs),
+            ..Default::default()
+        };
+        let result =
+            resolve_props("import { Props } from './types'", options).expect("Should resolve");
+        assert_eq!(
+            result,
+            vec![(fervid_atom!("baz"), vec![fervid_atom!("Function")])]
+        );
+    }
+
+    #[test]
+    fn it_resolves_export_all() {
+        let fs = memory_fs(&[
+            (
+                "/src/types/index.ts",
+                "export * from './other'\nexport * from './props'",
+            ),
+            ("/src/types/other.ts", "export type Other = string"),
+            (
+                "/src/types/props.ts",
+                "export interface Props { foo: boolean }",
+            ),
+        ]);
+        let options = TypeResolveOptions {
+            fs: Some(&fs),
+            ..Default::default()
+        };
+
+        let result =
+            resolve_props("import type { Props } from './types'", options).expect("Should resolve");
+        assert_eq!(
+            result,
+            vec![(fervid_atom!("foo"), vec![fervid_atom!("Boolean")])]
+        );
+    }
+
+    #[test]
+    fn it_resolves_ts_config_paths() {
+        let fs = memory_fs(&[
+            (
+                "/tsconfig.json",
+                r#"{
+                    // Comments and trailing commas are allowed
+                    "compilerOptions": {
+                        "baseUrl": ".",
+                        "paths": { "@/*": ["src/*"], },
+                    },
+                }"#,
+            ),
+            ("/src/types.ts", "export interface Props { foo: string }"),
+        ]);
+        let options = TypeResolveOptions {
+            fs: Some(&fs),
+            ..Default::default()
+        };
+
+        let result =
+            resolve_props("import type { Props } from '@/types'", options).expect("Should resolve");
+        assert_eq!(
+            result,
+            vec![(fervid_atom!("foo"), vec![fervid_atom!("String")])]
+        );
+    }
+
+    #[test]
+    fn it_resolves_vue_imports() {
+        let fs = memory_fs(&[(
+            "/src/Other.vue",
+            r#"<script lang="ts">export interface Props { foo: string[] }</script>"#,
+        )]);
+        let options = TypeResolveOptions {
+            fs: Some(&fs),
+            ..Default::default()
+        };
+
+        let result = resolve_props("import type { Props } from './Other.vue'", options)
+            .expect("Should resolve");
+        assert_eq!(
+            result,
+            vec![(fervid_atom!("foo"), vec![fervid_atom!("Array")])]
+        );
+    }
+
+    #[test]
+    fn it_reuses_cached_scopes() {
+        let fs = memory_fs(&[("/src/types.ts", "export interface Props { foo: string }")]);
+        let cache = TypeScopeCache::default();
+        let options = TypeResolveOptions {
+            fs: Some(&fs),
+            cache: Some(&cache),
+            ..Default::default()
+        };
+
+        let resolve_owner_scope = || {
+            let module = parse_typescript_module(
+                "import type { Props } from './types'",
+                0,
+                Default::default(),
+            )
+            .expect("Should parse")
+            .0;
+            let mut ctx = TypeResolveContext::new("/src/Comp.vue".into(), options);
+            ctx.record_scope(module.body.iter(), None);
+
+            let props_type = TsType::TsTypeRef(TsTypeRef {
+                span: DUMMY_SP,
+                type_name: TsEntityName::Ident(Ident::new("Props".into(), DUMMY_SP)),
+                type_params: None,
+            });
+            let resolved = resolve_type_elements(&mut ctx, &props_type).expect("Should resolve");
+            let (_, prop) = resolved
+                .props
+                .into_iter()
+                .next()
+                .expect("Should have a prop");
+            prop.owner_scope
+        };
+
+        let first = resolve_owner_scope();
+        let second = resolve_owner_scope();
+        assert!(Arc::ptr_eq(&first, &second));
+    }
+
+    #[test]
+    fn it_resolves_global_types() {
+        let fs = memory_fs(&[
+            ("/global.d.ts", "interface GlobalProps { foo: string }"),
+            (
+                "/types.d.ts",
+                "export {}\ndeclare global { type Props = GlobalProps & { bar?: number } }",
+            ),
+        ]);
+        let global_type_files = ["/global.d.ts".to_owned(), "/types.d.ts".to_owned()];
+        let options = TypeResolveOptions {
+            fs: Some(&fs),
+            global_type_files: &global_type_files,
+            ..Default::default()
+        };
+
+        let result = resolve_props("", options).expect("Should resolve");
+        assert_eq!(
+            result,
+            vec![
+                (fervid_atom!("foo"), vec![fervid_atom!("String")]),
+                (fervid_atom!("bar"), vec![fervid_atom!("Number")]),
+            ]
+        );
+
+        // Local types take precedence
+        let result =
+            resolve_props("type Props = { baz: boolean }", options).expect("Should resolve");
+        assert_eq!(
+            result,
+            vec![(fervid_atom!("baz"), vec![fervid_atom!("Boolean")])]
+        );
+
+        // Global files are not read without the file system
+        let result = resolve_props(
+            "",
+            TypeResolveOptions {
+                global_type_files: &global_type_files,
+                ..Default::default()
+            },
+        );
+        assert!(matches!(
+            result,
+            Err(ScriptError {
+                kind: ScriptErrorKind::ResolveTypeNoFs,
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn it_records_deps() {
+        let fs = memory_fs(&[
+            ("/global.d.ts", "type Base = { foo: string }"),
+            ("/unused.d.ts", "type Unused = { bar: string }"),
+            ("/src/types/index.ts", "export * from './props'"),
+            (
+                "/src/types/props.ts",
+                "export interface Props extends Base { baz: number }",
+            ),
+        ]);
+        let global_type_files = ["/global.d.ts".to_owned(), "/unused.d.ts".to_owned()];
+        let options = TypeResolveOptions {
+            fs: Some(&fs),
+            global_type_files: &global_type_files,
+            ..Default::default()
+        };
+
+        let module = parse_typescript_module(
+            "import type { Props } from './types'",
+            0,
+            Default::default(),
+        )
+        .expect("Should parse")
+        .0;
+        let mut ctx = TypeResolveContext::new("/src/Comp.vue".into(), options);
+        ctx.record_scope(module.body.iter(), None);
+
+        let props_type = TsType::TsTypeRef(TsTypeRef {
+            span: DUMMY_SP,
+            type_name: TsEntityName::Ident(Ident::new("Props".into(), DUMMY_SP)),
+            type_params: None,
+        });
+        resolve_type_elements(&mut ctx, &props_type).expect("Should resolve");
+
+        assert_eq!(
+            ctx.deps.into_iter().collect::<Vec<_>>(),
+            vec!["/src/types/index.ts", "/src/types/props.ts", "/global.d.ts"]
+        );
+    }
+
+    #[test]
+    fn it_errors_on_unresolvable_imports() {
+        let result = resolve_props("import type { Props } from './types'", Default::default());
+        assert!(matches!(
+            result,
+            Err(ScriptError {
+                kind: ScriptErrorKind::ResolveTypeNoFs,
+                ..
+            })
+        ));
+
+        let fs = memory_fs(&[]);
+        let options = TypeResolveOptions {
+            fs: Some(&fs),
+            ..Default::default()
+        };
+        let result = resolve_props("import type { Props } from './types'", options);
+        assert!(matches!(
+            result,
+            Err(ScriptError {
+                kind: ScriptErrorKind::ResolveTypeUnresolvableImportSource,
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn it_infers_runtime_types() {
+        let resolved = resolve_props(
+            r#"
+            enum Color { Red, Green }
+            enum Mixed { A = 'a', B = 1 }
+            class Foo {}
+            type Fn = () => void
+            interface Options { a: string; b?: number }
+            interface Keys { a: string; 1: number }
+            declare const opts: Options
+            interface Props {
+                string: string
+                number: number
+                boolean: boolean
+                object: object
+                literal: 'foo' | 'bar'
+                union: string | number
+                nullable: string | null
+                array: string[]
+                genericArray: Array<string>
+                tuple: [string, number]
+                record: Record<string, number>
+                fn: (a: number) => void
+                fnAlias: Fn
+                method(): void
+                date: Date
+                enum: Color
+                mixedEnum: Mixed
+                class: Foo
+                intersection: { a: string } & Unresolved
+                partial: Partial<Options>
+                pick: Pick<Options, 'a'>
+                omit: Omit<Options, 'a'>
+                readonly: Readonly<Options>
+                readonlyArray: readonly string[]
+                nonNullable: NonNullable<string | null>
+                extract: Extract<string | number, string>
+                exclude: Exclude<string | number, number>
+                uppercase: Uppercase<'foo'>
+                indexed: Options['a']
+                keyOf: keyof Keys
+                keyOfAny: keyof any
+                typeQuery: typeof opts
+                unknown: Unresolved
+            }
+            "#,
+            Default::default(),
+        )
+        .expect("Should resolve");
+
+        let expected: &[(&str, &[&str])] = &[
+            ("string", &["String"]),
+            ("number", &["Number"]),
+            ("boolean", &["Boolean"]),
+            ("object", &["Object"]),
+            ("literal", &["String"]),
+            ("union", &["String", "Number"]),
+            ("nullable", &["String", "null"]),
+            ("array", &["Array"]),
+            ("genericArray", &["Array"]),
+            ("tuple", &["Array"]),
+            ("record", &["Object"]),
+            ("fn", &["Function"]),
+            ("fnAlias", &["Function"]),
+            ("method", &["Function"]),
+            ("date", &["Date"]),
+            ("enum", &["Number"]),
+            ("mixedEnum", &["String", "Number"]),
+            ("class", &["Object"]),
+            ("intersection", &["Object"]),
+            ("partial", &["Object"]),
+            ("pick", &["Object"]),
+            ("omit", &["Object"]),
+            ("readonly", &["Object"]),
+            ("readonlyArray", &["Array"]),
+            ("nonNullable", &["String"]),
+            ("extract", &["String"]),
+            ("exclude", &["String", "Number"]),
+            ("uppercase", &["String"]),
+            ("indexed", &["String"]),
+            ("keyOf", &["String", "Number"]),
+            ("keyOfAny", &["String", "Number", "Symbol"]),
+            ("typeQuery", &["Object"]),
+            ("unknown", &[UNKNOWN_TYPE]),
+        ];
+
+        assert_eq!(resolved.len(), expected.len());
+        for ((key, types), (expected_key, expected_types)) in resolved.iter().zip(expected) {
+            assert_eq!(key, expected_key);
+            assert_eq!(types, expected_types, "runtime types of `{key}`");
+        }
+    }
+
     #[test]
     fn it_capitalizes() {
         let mut atoms = vec!["foo".into(), "bazBar".into(), "".into()];
@@ -0,0 +1,445 @@
+//! File system access and module resolution for the types imported from other files.
+//!
+//! Adapted from `importSourceToScope`, `resolveExt` and `fileToScope` of the official compiler.
+
+use std::{
+    hash::{Hash, Hasher},
+    sync::{Arc, Mutex},
+};
+
+use fervid_parser::SfcParser;
+use fxhash::{FxHashMap, FxHasher64};
+use swc_core::{
+    common::{BytePos, Span},
+    ecma::ast::{EsVersion, ModuleItem},
+};
+use swc_ecma_parser::{lexer::Lexer, Parser, StringInput, Syntax, TsConfig};
+
+use crate::error::ScriptErrorKind;
+
+use super::{error, ResolutionResult, TypeResolveContext, TypeScope};
+
+/// Access to the file system for resolving the types imported from other files,
+/// e.g. `Props` in `import type { Props } from './types'`.
+///
+/// Paths are always `/`-separated.
+pub trait TypeResolveFs {
+    /// Whether a file (not a directory) exists at `path`
+    fn file_exists(&self, path: &str) -> bool;
+    /// Reads the file at `path`, `None` when it cannot be read
+    fn read_file(&self, path: &str) -> Option<String>;
+}
+
+impl std::fmt::Debug for dyn TypeResolveFs + '_ {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("TypeResolveFs")
+    }
+}
+
+/// [`TypeResolveFs`] which uses `std::fs`
+#[derive(Debug, Default, Clone, Copy)]
+pub struct NativeTypeResolveFs;
+
+impl TypeResolveFs for NativeTypeResolveFs {
+    fn file_exists(&self, path: &str) -> bool {
+        std::path::Path::new(path).is_file()
+    }
+
+    fn read_file(&self, path: &str) -> Option<String> {
+        std::fs::read_to_string(path).ok()
+    }
+}
+
+/// Parsed scopes of the type files, which can be shared between compilations.
+/// A file is parsed again only when its contents change.
+#[derive(Debug, Default)]
+pub struct TypeScopeCache {
+    /// Keyed by the filename and whether the file is a global type file
+    scopes: Mutex<FxHashMap<(String, bool), CachedTypeScope>>,
+}
+
+#[derive(Debug)]
+struct CachedTypeScope {
+    source_hash: u64,
+    scope: Arc<TypeScope>,
+}
+
+impl TypeScopeCache {
+    fn get(&self, key: &(String, bool), source_hash: u64) -> Option<Arc<TypeScope>> {
+        let scopes = self.scopes.lock().ok()?;
+        scopes
+            .get(key)
+            .filter(|cached| cached.source_hash == source_hash)
+            .map(|cached| cached.scope.to_owned())
+    }
+
+    fn insert(&self, key: (String, bool), source_hash: u64, scope: Arc<TypeScope>) {
+        if let Ok(mut scopes) = self.scopes.lock() {
+            scopes.insert(key, CachedTypeScope { source_hash, scope });
+        }
+    }
+}
+
+/// Finds the file of an import `source` and gets its scope
+pub(super) fn import_source_to_scope(
+    ctx: &mut TypeResolveContext,
+    source: &str,
+    scope: &TypeScope,
+    span: Span,
+) -> ResolutionResult<Arc<TypeScope>> {
+    let Some(fs) = ctx.options.fs else {
+        return Err(error(ScriptErrorKind::ResolveTypeNoFs, span));
+    };
+
+    let resolved = if source.starts_with('.') {
+        resolve_ext(fs, &join_paths(dirname(&scope.filename), source))
+    } else {
+        resolve_with_ts_config(fs, source, &scope.filename)
+    };
+
+    let Some(resolved) = resolved else {
+        return Err(error(
+            ScriptErrorKind::ResolveTypeUnresolvableImportSource,
+            span,
+        ));
+    };
+
+    ctx.deps.insert(resolved.to_owned());
+    file_to_scope(ctx, fs, resolved, false, span)
+}
+
+/// Gets the scope of a file from `globalTypeFiles`
+pub(super) fn global_type_file_to_scope(
+    ctx: &mut TypeResolveContext,
+    filename: &str,
+    span: Span,
+) -> ResolutionResult<Arc<TypeScope>> {
+    let Some(fs) = ctx.options.fs else {
+        return Err(error(ScriptErrorKind::ResolveTypeNoFs, span));
+    };
+
+    file_to_scope(ctx, fs, normalize_path(filename), true, span)
+}
+
+fn file_to_scope(
+    ctx: &mut TypeResolveContext,
+    fs: &dyn TypeResolveFs,
+    filename: String,
+    as_global: bool,
+    span: Span,
+) -> ResolutionResult<Arc<TypeScope>> {
+    let Some(source) = fs.read_file(&filename) else {
+        return Err(error(
+            ScriptErrorKind::ResolveTypeUnresolvableImportSource,
+            span,
+        ));
+    };
+
+    let source_hash = {
+        let mut hasher = FxHasher64::default();
+        source.hash(&mut hasher);
+        hasher.finish()
+    };
+
+    let cache = ctx.cache();
+    let cache_key = (filename, as_global);
+    if let Some(cached) = cache.get(&cache_key, source_hash) {
+        return Ok(cached);
+    }
+
+    let scope = Arc::new(parse_file(
+        cache_key.0.to_owned(),
+        &source,
+        as_global,
+        span,
+    )?);
+    cache.insert(cache_key, source_hash, scope.to_owned());
+
+    Ok(scope)
+}
+
+fn parse_file(
+    filename: String,
+    source: &str,
+    as_global: bool,
+    span: Span,
+) -> ResolutionResult<TypeScope> {
+    let unparsable = || error(ScriptErrorKind::ResolveTypeUnparsableFile, span);
+    let to_scope = |filename: String, module_items: &[&ModuleItem]| {
+        if as_global {
+            TypeScope::from_global_module_items(filename, module_items)
+        } else {
+            TypeScope::from_module_items(filename, module_items)
+        }
+    };
+
+    if filename.ends_with(".vue") {
+        let mut errors = Vec::new();
+        let mut parser = SfcParser::new(source, &mut errors);
+        let sfc = parser.parse_sfc().map_err(|_| unparsable())?;
+
+        let module_items: Vec<&ModuleItem> = sfc
+            .script_legacy
+            .iter()
+            .chain(sfc.script_setup.iter())
+            .flat_map(|script| script.content.body.iter())
+            .collect();
+
+        return Ok(to_scope(filename, &module_items));
+    }
+
+    let lexer = Lexer::new(
+        Syntax::Typescript(TsConfig {
+            tsx: filename.ends_with(".tsx"),
+            dts: filename.ends_with(".d.ts"),
+            ..Default::default()
+        }),
+        EsVersion::EsNext,
+        StringInput::new(source, BytePos(1), BytePos(1 + source.len() as u32)),
+        None,
+    );
+    let mut parser = Parser::new_from(lexer);
+    let module = parser.parse_module().map_err(|_| unparsable())?;
+
+    let module_items: Vec<&ModuleItem> = module.body.iter().collect();
+    Ok(to_scope(filename, &module_items))
+}
+
+/// Tries the TypeScript extensions, e.g. `./types` may be `./types.ts` or `./types/index.d.ts`
+fn resolve_ext(fs: &dyn TypeResolveFs, filename: &str) -> Option<String> {
+    // TS may import `.js` files, which are `.ts` or `.d.ts` in reality
+    let filename = filename.strip_suffix(".js").unwrap_or(filename);
+
+    let try_resolve = |candidate: String| fs.file_exists(&candidate).then_some(candidate);
+
+    try_resolve(filename.to_owned())
+        .or_else(|| try_resolve(format!("{filename}.ts")))
+        .or_else(|| try_resolve(format!("{filename}.tsx")))
+        .or_else(|| try_resolve(format!("{filename}.d.ts")))
+        .or_else(|| try_resolve(join_paths(filename, "index.ts")))
+        .or_else(|| try_resolve(join_paths(filename, "index.tsx")))
+        .or_else(|| try_resolve(join_paths(filename, "index.d.ts")))
+}
+
+/// Resolves a non-relative import using `baseUrl` and `paths` of the nearest `tsconfig.json`
+fn resolve_with_ts_config(
+    fs: &dyn TypeResolveFs,
+    source: &str,
+    containing_file: &str,
+) -> Option<String> {
+    let mut dir = dirname(containing_file);
+    let ts_config_path = loop {
+        let candidate = join_paths(dir, "tsconfig.json");
+        if fs.file_exists(&candidate) {
+            break candidate;
+        }
+
+        let parent = dirname(dir);
+        if parent == dir || dir.is_empty() {
+            return None;
+        }
+        dir = parent;
+    };
+
+    let ts_config = load_ts_config(fs, &ts_config_path, 0)?;
+
+    if let Some((ref paths_dir, ref paths)) = ts_config.paths {
+        let base = ts_config.base_url.as_deref().unwrap_or(paths_dir);
+
+        // Exact match or the wildcard pattern with the longest prefix
+        let mut best_match: Option<(&str, &[String], &str)> = None;
+        for (pattern, targets) in paths.iter() {
+            let captured = match pattern.split_once('*') {
+                None if pattern == source => "",
+                None => continue,
+                Some((prefix, suffix)) => {
+                    if source.len() < prefix.len() + suffix.len()
+                        || !source.starts_with(prefix)
+                        || !source.ends_with(suffix)
+                    {
+                        continue;
+                    }
+                    &source[prefix.len()..source.len() - suffix.len()]
+                }
+            };
+
+            let prefix_len = pattern.find('*').unwrap_or(usize::MAX);
+            let is_better = match best_match {
+                None => true,
+                Some((best_pattern, _, _)) => {
+                    prefix_len > best_pattern.find('*').unwrap_or(usize::MAX)
+                }
+            };
+            if is_better {
+                best_match = Some((pattern, targets, captured));
+            }
+        }
+
+        if let Some((_, targets, captured)) = best_match {
+            for target in targets.iter() {
+                let target = target.replacen('*', captured, 1);
+                if let Some(resolved) = resolve_ext(fs, &join_paths(base, &target)) {
+                    return Some(resolved);
+                }
+            }
+        }
+    }
+
+    let base_url = ts_config.base_url?;
+    resolve_ext(fs, &join_paths(&base_url, source))
+}
+
+/// `compilerOptions.paths`, e.g. `{ "@/*": ["src/*"] }`
+type TsConfigPathPatterns = Vec<(String, Vec<String>)>;
+
+/// Module resolution options of a `tsconfig.json`
+#[derive(Default)]
+struct TsConfigPaths {
+    /// Absolute `compilerOptions.baseUrl`
+    base_url: Option<String>,
+    /// `compilerOptions.paths` along with the directory of the config which defines them
+    paths: Option<(String, TsConfigPathPatterns)>,
+}
+
+fn load_ts_config(fs: &dyn TypeResolveFs, path: &str, depth: usize) -> Option<TsConfigPaths> {
+    let content = fs.read_file(path)?;
+    let json: serde_json::Value = serde_json::from_str(&strip_json_comments(&content)).ok()?;
+    let dir = dirname(path);
+
+    // Relative `extends` only, packages are not supported
+    let mut result = match json.get("extends").and_then(|v| v.as_str()) {
+        Some(extends) if extends.starts_with('.') && depth < 8 => {
+            let mut extends_path = join_paths(dir, extends);
+            if !extends_path.ends_with(".json") {
+                extends_path.push_str(".json");
+            }
+            load_ts_config(fs, &extends_path, depth + 1).unwrap_or_default()
+        }
+        _ => TsConfigPaths::default(),
+    };
+
+    let Some(compiler_options) = json.get("compilerOptions") else {
+        return Some(result);
+    };
+
+    if let Some(base_url) = compiler_options.get("baseUrl").and_then(|v| v.as_str()) {
+        result.base_url = Some(join_paths(dir, base_url));
+    }
+
+    if let Some(paths) = compiler_options.get("paths").and_then(|v| v.as_object()) {
+        let paths = paths
+            .iter()
+            .map(|(pattern, targets)| {
+                let targets = targets
+                    .as_array()
+                    .map(|targets| {
+                        targets
+                            .iter()
+                            .filter_map(|t| t.as_str().map(ToOwned::to_owned))
+                            .collect()
+                    })
+                    .unwrap_or_default();
+                (pattern.to_owned(), targets)
+            })
+            .collect();
+        result.paths = Some((dir.to_owned(), paths));
+    }
+
+    Some(result)
+}
+
+/// `tsconfig.json` allows comments and trailing commas, which are not valid JSON
+fn strip_json_comments(input: &str) -> String {
+    let mut result = String::with_capacity(input.len());
+    let mut chars = input.chars().peekable();
+    let mut in_string = false;
+
+    while let Some(c) = chars.next() {
+        if in_string {
+            result.push(c);
+            match c {
+                '\\' => result.extend(chars.next()),
+                '"' => in_string = false,
+                _ => {}
+            }
+            continue;
+        }
+
+        match (c, chars.peek()) {
+            ('"', _) => {
+                in_string = true;
+                result.push(c);
+            }
+            ('/', Some('/')) => while chars.next_if(|c| *c != '\n').is_some() {},
+            ('/', Some('*')) => {
+                chars.next();
+                while let Some(c) = chars.next() {
+                    if c == '*' && chars.next_if_eq(&'/').is_some() {
+                        break;
+                    }
+                }
+            }
+            (',', _) => {
+                // Skip the comma if only whitespace is left before the closing bracket
+                let rest = chars.clone().find(|c| !c.is_whitespace());
+                if !matches!(rest, Some('}' | ']')) {
+                    result.push(c);
+                }
+            }
+            _ => result.push(c),
+        }
+    }
+
+    result
+}
+
+/// Resolves `source` relative to the directory of `filename`,
+/// e.g. `/src/foo.css` for `./foo.css` in `/src/Comp.vue`
+pub(crate) fn resolve_relative_path(filename: &str, source: &str) -> String {
+    join_paths(dirname(filename), source)
+}
+
+/// Directory of the `path`, empty when there is none
+fn dirname(path: &str) -> &str {
+    let path = path.trim_end_matches(['/', '\\']);
+    match path.rfind(['/', '\\']) {
+        Some(0) => "/",
+        Some(idx) => &path[..idx],
+        None => "",
+    }
+}
+
+/// Joins and normalizes the paths, `rel` may also be absolute
+fn join_paths(base: &str, rel: &str) -> String {
+    let is_absolute = rel.starts_with(['/', '\\']) || rel.get(1..2) == Some(":");
+    if is_absolute || base.is_empty() {
+        return normalize_path(rel);
+    }
+
+    normalize_path(&format!("{base}/{rel}"))
+}
+
+/// Converts to a `/`-separated path without `.` and `..` segments, where possible
+fn normalize_path(path: &str) -> String {
+    let path = path.replace('\\', "/");
+    let is_absolute = path.starts_with('/');
+
+    let mut segments = Vec::<&str>::new();
+    for segment in path.split('/') {
+        match segment {
+            "" | "." => {}
+            ".." if matches!(segments.last(), Some(last) if *last != "..") => {
+                segments.pop();
+            }
+            ".." if is_absolute => {}
+            _ => segments.push(segment),
+        }
+    }
+
+    let joined = segments.join("/");
+    if is_absolute {
+        format!("/{joined}")
+    } else {
+        joined
+    }
+}
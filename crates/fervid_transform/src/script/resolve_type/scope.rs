@@ -0,0 +1,479 @@
+//! Records the imports and the types declared in a module into a [`TypeScope`].
+//!
+//! Adapted from `recordImports` and `recordTypes` of the official compiler.
+
+use std::sync::Arc;
+
+use fervid_core::{fervid_atom, FervidAtom};
+use swc_core::{
+    common::DUMMY_SP,
+    ecma::ast::{
+        Decl, DefaultDecl, ExportSpecifier, Expr, Function, Ident, ImportDecl, ImportSpecifier,
+        Lit, ModuleDecl, ModuleExportName, ModuleItem, Number, Pat, Stmt, Str, TsEntityName,
+        TsEnumDecl, TsFnOrConstructorType, TsFnParam, TsFnType, TsInterfaceDecl,
+        TsIntersectionType, TsKeywordType, TsKeywordTypeKind, TsLit, TsLitType, TsNamespaceBody,
+        TsQualifiedName, TsType, TsTypeAnn, TsTypeLit, TsTypeRef, TsUnionOrIntersectionType,
+        TsUnionType,
+    },
+};
+
+use super::{TypeScope, TypeScopeImport};
+
+impl TypeScope {
+    /// Creates the scope of a module, e.g. of an imported `.ts` file
+    pub(super) fn from_module_items(filename: String, module_items: &[&ModuleItem]) -> TypeScope {
+        let mut scope = TypeScope::new(filename);
+
+        // Local names and the names they are exported as.
+        // These are resolved when all the types are known.
+        let mut exports = Vec::<(FervidAtom, FervidAtom)>::new();
+
+        for module_item in module_items {
+            let module_decl = match module_item {
+                ModuleItem::Stmt(Stmt::Decl(decl)) => {
+                    scope.record_decl(decl);
+                    continue;
+                }
+                ModuleItem::Stmt(_) => continue,
+                ModuleItem::ModuleDecl(module_decl) => module_decl,
+            };
+
+            match module_decl {
+                ModuleDecl::Import(import_decl) => scope.record_import(import_decl),
+
+                ModuleDecl::ExportDecl(export_decl) => {
+                    scope.record_decl(&export_decl.decl);
+                    exports.extend(decl_ids(&export_decl.decl).map(|id| (id.to_owned(), id)));
+                }
+
+                ModuleDecl::ExportNamed(named_export) => {
+                    for specifier in named_export.specifiers.iter() {
+                        let ExportSpecifier::Named(named) = specifier else {
+                            continue;
+                        };
+
+                        let local = module_export_name(&named.orig);
+                        let exported = named
+                            .exported
+                            .as_ref()
+                            .map_or_else(|| local.to_owned(), module_export_name);
+
+                        if let Some(ref src) = named_export.src {
+                            // Re-export, register an import and export it as a type reference
+                            scope.imports.insert(
+                                exported.to_owned(),
+                                TypeScopeImport {
+                                    source: src.value.to_owned(),
+                                    imported: local,
+                                },
+                            );
+                            scope
+                                .exported_types
+                                .insert(exported.to_owned(), Arc::new(type_ref(exported)));
+                        } else {
+                            exports.push((local, exported));
+                        }
+                    }
+                }
+
+                ModuleDecl::ExportAll(export_all) => {
+                    scope.exported_all.push(export_all.src.value.to_owned());
+                }
+
+                ModuleDecl::ExportDefaultDecl(export_default_decl) => {
+                    let ts_type = match export_default_decl.decl {
+                        DefaultDecl::TsInterfaceDecl(ref interface) => interface_to_type(interface),
+                        DefaultDecl::Class(_) => empty_type_lit(),
+                        DefaultDecl::Fn(_) => continue,
+                    };
+                    scope
+                        .exported_types
+                        .insert(fervid_atom!("default"), Arc::new(ts_type));
+                }
+
+                ModuleDecl::ExportDefaultExpr(export_default_expr) => {
+                    if let Expr::Ident(ref ident) = *export_default_expr.expr {
+                        exports.push((ident.sym.to_owned(), fervid_atom!("default")));
+                    }
+                }
+
+                _ => {}
+            }
+        }
+
+        for (local, exported) in exports {
+            if let Some(ts_type) = scope.types.get(&local) {
+                scope
+                    .exported_types
+                    .insert(exported.to_owned(), ts_type.to_owned());
+            } else if scope.imports.contains_key(&local) {
+                // Imported and exported again, e.g. `import { Foo } from './foo'; export { Foo }`
+                scope
+                    .exported_types
+                    .insert(exported.to_owned(), Arc::new(type_ref(local.to_owned())));
+            }
+
+            if let Some(declared) = scope.declares.get(&local) {
+                scope
+                    .exported_declares
+                    .insert(exported, declared.to_owned());
+            }
+        }
+
+        scope
+    }
+
+    /// Creates the scope of a global type file, e.g. `global.d.ts`.
+    /// Declarations of a file without imports and exports are all global,
+    /// otherwise only the ones inside `declare global { ... }` are.
+    pub(super) fn from_global_module_items(
+        filename: String,
+        module_items: &[&ModuleItem],
+    ) -> TypeScope {
+        let mut scope = TypeScope::new(filename);
+
+        let is_ambient = module_items
+            .iter()
+            .all(|module_item| matches!(module_item, ModuleItem::Stmt(_)));
+
+        for module_item in module_items {
+            let ModuleItem::Stmt(Stmt::Decl(decl)) = module_item else {
+                continue;
+            };
+
+            match decl {
+                Decl::TsModule(ts_module) if ts_module.global => {
+                    let Some(TsNamespaceBody::TsModuleBlock(ref block)) = ts_module.body else {
+                        continue;
+                    };
+
+                    for block_item in block.body.iter() {
+                        match block_item {
+                            ModuleItem::Stmt(Stmt::Decl(decl)) => scope.record_decl(decl),
+                            ModuleItem::ModuleDecl(ModuleDecl::ExportDecl(export_decl)) => {
+                                scope.record_decl(&export_decl.decl)
+                            }
+                            _ => {}
+                        }
+                    }
+                }
+
+                _ if is_ambient => scope.record_decl(decl),
+
+                _ => {}
+            }
+        }
+
+        scope
+    }
+
+    fn record_import(&mut self, import_decl: &ImportDecl) {
+        for specifier in import_decl.specifiers.iter() {
+            let (local, imported) = match specifier {
+                ImportSpecifier::Named(named) => (
+                    &named.local,
+                    named
+                        .imported
+                        .as_ref()
+                        .map_or_else(|| named.local.sym.to_owned(), module_export_name),
+                ),
+                ImportSpecifier::Default(default) => (&default.local, fervid_atom!("default")),
+                ImportSpecifier::Namespace(namespace) => (&namespace.local, fervid_atom!("*")),
+            };
+
+            self.imports.insert(
+                local.sym.to_owned(),
+                TypeScopeImport {
+                    source: import_decl.src.value.to_owned(),
+                    imported,
+                },
+            );
+        }
+    }
+
+    /// Records a type or an ambient declaration, e.g. `declare const foo: string`
+    fn record_decl(&mut self, decl: &Decl) {
+        match decl {
+            Decl::TsInterface(interface) => {
+                let id = interface.id.sym.to_owned();
+
+                // Declaration merging
+                if let Some(existing) = self.types.get_mut(&id) {
+                    merge_interface(Arc::make_mut(existing), interface);
+                } else {
+                    self.types
+                        .insert(id, Arc::new(interface_to_type(interface)));
+                }
+            }
+
+            Decl::TsTypeAlias(type_alias) => {
+                self.types.insert(
+                    type_alias.id.sym.to_owned(),
+                    Arc::new(*type_alias.type_ann.to_owned()),
+                );
+            }
+
+            Decl::TsEnum(ts_enum) => {
+                self.types
+                    .insert(ts_enum.id.sym.to_owned(), Arc::new(enum_to_type(ts_enum)));
+            }
+
+            Decl::Class(class_decl) => {
+                self.types
+                    .insert(class_decl.ident.sym.to_owned(), Arc::new(empty_type_lit()));
+            }
+
+            Decl::Fn(fn_decl) if fn_decl.declare => {
+                self.declares.insert(
+                    fn_decl.ident.sym.to_owned(),
+                    Arc::new(fn_to_type(&fn_decl.function)),
+                );
+            }
+
+            Decl::Var(var_decl) if var_decl.declare => {
+                for var_declarator in var_decl.decls.iter() {
+                    let Pat::Ident(ref binding) = var_declarator.name else {
+                        continue;
+                    };
+                    let Some(ref type_ann) = binding.type_ann else {
+                        continue;
+                    };
+
+                    self.declares.insert(
+                        binding.id.sym.to_owned(),
+                        Arc::new(*type_ann.type_ann.to_owned()),
+                    );
+                }
+            }
+
+            _ => {}
+        }
+    }
+}
+
+/// Names introduced by a declaration
+fn decl_ids(decl: &Decl) -> impl Iterator<Item = FervidAtom> + '_ {
+    let (single, var_decl) = match decl {
+        Decl::TsInterface(interface) => (Some(&interface.id), None),
+        Decl::TsTypeAlias(type_alias) => (Some(&type_alias.id), None),
+        Decl::TsEnum(ts_enum) => (Some(&ts_enum.id), None),
+        Decl::Class(class_decl) => (Some(&class_decl.ident), None),
+        Decl::Fn(fn_decl) => (Some(&fn_decl.ident), None),
+        Decl::Var(var_decl) => (None, Some(var_decl)),
+        _ => (None, None),
+    };
+
+    let var_ids = var_decl
+        .into_iter()
+        .flat_map(|var_decl| var_decl.decls.iter())
+        .filter_map(|var_declarator| var_declarator.name.as_ident().map(|b| &b.id));
+
+    single
+        .into_iter()
+        .chain(var_ids)
+        .map(|id| id.sym.to_owned())
+}
+
+fn module_export_name(name: &ModuleExportName) -> FervidAtom {
+    match name {
+        ModuleExportName::Ident(ident) => ident.sym.to_owned(),
+        ModuleExportName::Str(s) => s.value.to_owned(),
+    }
+}
+
+fn type_ref(name: FervidAtom) -> TsType {
+    TsType::TsTypeRef(TsTypeRef {
+        span: DUMMY_SP,
+        type_name: TsEntityName::Ident(Ident {
+            span: DUMMY_SP,
+            sym: name,
+            optional: false,
+        }),
+        type_params: None,
+    })
+}
+
+/// Classes are only recorded to be inferred as `Object`
+fn empty_type_lit() -> TsType {
+    TsType::TsTypeLit(TsTypeLit {
+        span: DUMMY_SP,
+        members: vec![],
+    })
+}
+
+/// Converts `interface Foo extends Bar { baz: string }` to `{ baz: string } & Bar`
+fn interface_to_type(interface: &TsInterfaceDecl) -> TsType {
+    let body = TsType::TsTypeLit(TsTypeLit {
+        span: interface.body.span,
+        members: interface.body.body.to_owned(),
+    });
+
+    if interface.extends.is_empty() {
+        return body;
+    }
+
+    let mut types = Vec::with_capacity(interface.extends.len() + 1);
+    types.push(Box::new(body));
+    types.extend(extends_to_types(interface));
+
+    TsType::TsUnionOrIntersectionType(TsUnionOrIntersectionType::TsIntersectionType(
+        TsIntersectionType {
+            span: interface.span,
+            types,
+        },
+    ))
+}
+
+/// Adds the members and the base types of a repeated `interface` declaration
+fn merge_interface(existing: &mut TsType, interface: &TsInterfaceDecl) {
+    match existing {
+        TsType::TsTypeLit(type_lit) if interface.extends.is_empty() => {
+            type_lit.members.extend(interface.body.body.iter().cloned());
+        }
+
+        TsType::TsUnionOrIntersectionType(TsUnionOrIntersectionType::TsIntersectionType(
+            intersection,
+        )) if matches!(
+            intersection.types.first().map(Box::as_ref),
+            Some(TsType::TsTypeLit(_))
+        ) =>
+        {
+            if let Some(TsType::TsTypeLit(type_lit)) =
+                intersection.types.first_mut().map(Box::as_mut)
+            {
+                type_lit.members.extend(interface.body.body.iter().cloned());
+            }
+            intersection.types.extend(extends_to_types(interface));
+        }
+
+        TsType::TsTypeLit(type_lit) => {
+            type_lit.members.extend(interface.body.body.iter().cloned());
+            let mut types = vec![Box::new(TsType::TsTypeLit(type_lit.to_owned()))];
+            types.extend(extends_to_types(interface));
+            *existing = TsType::TsUnionOrIntersectionType(
+                TsUnionOrIntersectionType::TsIntersectionType(TsIntersectionType {
+                    span: interface.span,
+                    types,
+                }),
+            );
+        }
+
+        // Not an interface, e.g. `type Foo = ...` with `interface Foo {}`, which is a TS error
+        _ => *existing = interface_to_type(interface),
+    }
+}
+
+fn extends_to_types(interface: &TsInterfaceDecl) -> impl Iterator<Item = Box<TsType>> + '_ {
+    interface.extends.iter().filter_map(|parent| {
+        let type_name = expr_to_entity_name(&parent.expr)?;
+
+        Some(Box::new(TsType::TsTypeRef(TsTypeRef {
+            span: parent.span,
+            type_name,
+            type_params: parent.type_args.to_owned(),
+        })))
+    })
+}
+
+/// Converts `Foo.Bar` in `interface Baz extends Foo.Bar` to an entity name
+fn expr_to_entity_name(expr: &Expr) -> Option<TsEntityName> {
+    match expr {
+        Expr::Ident(ident) => Some(TsEntityName::Ident(ident.to_owned())),
+        Expr::Member(member_expr) => {
+            let right = member_expr.prop.as_ident()?.to_owned();
+            let left = expr_to_entity_name(&member_expr.obj)?;
+            Some(TsEntityName::TsQualifiedName(Box::new(TsQualifiedName {
+                left,
+                right,
+            })))
+        }
+        _ => None,
+    }
+}
+
+/// Converts an enum to a union of its member values, e.g. `'foo' | 1`.
+/// Members with computed values are skipped.
+fn enum_to_type(ts_enum: &TsEnumDecl) -> TsType {
+    let mut types = Vec::with_capacity(ts_enum.members.len());
+    let mut next_value = Some(0.0);
+
+    for member in ts_enum.members.iter() {
+        let lit = match member.init.as_deref() {
+            None => next_value.map(|value| {
+                TsLit::Number(Number {
+                    span: member.span,
+                    value,
+                    raw: None,
+                })
+            }),
+            Some(Expr::Lit(Lit::Num(n))) => Some(TsLit::Number(n.to_owned())),
+            Some(Expr::Lit(Lit::Str(s))) => Some(TsLit::Str(s.to_owned())),
+            Some(Expr::Tpl(tpl)) if tpl.exprs.is_empty() => Some(TsLit::Str(Str {
+                span: tpl.span,
+                value: tpl
+                    .quasis
+                    .first()
+                    .and_then(|q| q.cooked.to_owned())
+                    .unwrap_or_default(),
+                raw: None,
+            })),
+            Some(_) => None,
+        };
+
+        next_value = match lit {
+            Some(TsLit::Number(ref n)) => Some(n.value + 1.0),
+            _ => None,
+        };
+
+        if let Some(lit) = lit {
+            types.push(Box::new(TsType::TsLitType(TsLitType {
+                span: member.span,
+                lit,
+            })));
+        }
+    }
+
+    if types.is_empty() {
+        return TsType::TsKeywordType(TsKeywordType {
+            span: ts_enum.span,
+            kind: TsKeywordTypeKind::TsNumberKeyword,
+        });
+    }
+
+    TsType::TsUnionOrIntersectionType(TsUnionOrIntersectionType::TsUnionType(TsUnionType {
+        span: ts_enum.span,
+        types,
+    }))
+}
+
+/// Converts `declare function foo(bar: string): number` to `(bar: string) => number`
+fn fn_to_type(function: &Function) -> TsType {
+    let params = function
+        .params
+        .iter()
+        .filter_map(|param| match param.pat {
+            Pat::Ident(ref ident) => Some(TsFnParam::Ident(ident.to_owned())),
+            Pat::Array(ref array) => Some(TsFnParam::Array(array.to_owned())),
+            Pat::Rest(ref rest) => Some(TsFnParam::Rest(rest.to_owned())),
+            Pat::Object(ref object) => Some(TsFnParam::Object(object.to_owned())),
+            _ => None,
+        })
+        .collect();
+
+    let type_ann = function.return_type.to_owned().unwrap_or_else(|| {
+        Box::new(TsTypeAnn {
+            span: DUMMY_SP,
+            type_ann: Box::new(TsType::TsKeywordType(TsKeywordType {
+                span: DUMMY_SP,
+                kind: TsKeywordTypeKind::TsAnyKeyword,
+            })),
+        })
+    });
+
+    TsType::TsFnOrConstructorType(TsFnOrConstructorType::TsFnType(TsFnType {
+        span: function.span,
+        params,
+        type_params: function.type_params.to_owned(),
+        type_ann,
+    }))
+}
@@ -1,10 +1,13 @@
-use fervid_core::{BindingTypes, SfcScriptBlock};
+use fervid_core::{BindingTypes, FervidAtom, SfcScriptBlock};
 use swc_core::{
     common::{Span, DUMMY_SP},
-    ecma::ast::{
-        BindingIdent, BlockStmt, Decl, ExprStmt, Function, Ident, KeyValuePatProp, KeyValueProp,
-        ModuleDecl, ModuleItem, ObjectPat, ObjectPatProp, Param, Pat, Prop, PropName, PropOrSpread,
-        Stmt, VarDeclKind,
+    ecma::{
+        ast::{
+            BindingIdent, BlockStmt, Decl, Expr, ExprStmt, Function, Ident, KeyValuePatProp,
+            KeyValueProp, ModuleDecl, ModuleItem, ObjectPat, ObjectPatProp, Param, Pat, Prop,
+            PropName, PropOrSpread, Stmt, VarDeclKind, VarDeclarator,
+        },
+        visit::{Visit, VisitWith},
     },
 };
 
@@ -16,6 +19,7 @@ use crate::{
             categorize_class, categorize_expr, categorize_fn_decl, enrich_binding_types,
             extract_variables_from_pat,
         },
+        resolve_type::TypeResolveContext,
         setup::macros::TransformMacroResult,
         utils::is_static,
     },
@@ -23,12 +27,19 @@ use crate::{
     BindingsHelper, SetupBinding,
 };
 
+mod async_context;
 mod await_detection;
 mod macros;
+mod props_destructure;
 
 use self::{
+    async_context::transform_async_context,
     await_detection::detect_await_module_item,
     macros::{postprocess_macros, transform_script_setup_macro_expr},
+    props_destructure::{
+        apply_props_destructure_defaults, is_props_destructure, record_props_destructure,
+        transform_destructured_props,
+    },
 };
 
 use super::imports::process_imports;
@@ -45,6 +56,7 @@ pub struct TransformScriptSetupResult {
 /// Transforms the `<script setup>` block and records its bindings
 pub fn transform_and_record_script_setup(
     mut script_setup: SfcScriptBlock,
+    ctx: &mut TypeResolveContext,
     bindings_helper: &mut BindingsHelper,
     errors: &mut Vec<TransformError>,
 ) -> TransformScriptSetupResult {
@@ -68,6 +80,19 @@ pub fn transform_and_record_script_setup(
         }
     }
 
+    // Declarations using `T` of `generic="T"` cannot be moved out of the generic `setup()`
+    let generic_params: Vec<FervidAtom> =
+        script_setup
+            .generic
+            .as_ref()
+            .map_or_else(Vec::new, |generic| {
+                generic
+                    .params
+                    .iter()
+                    .map(|param| param.name.sym.to_owned())
+                    .collect()
+            });
+
     // Go over the whole script setup: process all the statements and declarations
     for module_item in script_setup.content.body {
         let stmt = match module_item {
@@ -89,12 +114,17 @@ pub fn transform_and_record_script_setup(
             ModuleItem::Stmt(stmt) => stmt,
         };
 
+        // Literal constants are moved out of `setup()` to not be re-created for each instance
+        let should_hoist =
+            bindings_helper.hoist_static && is_hoistable_stmt(&stmt, &generic_params);
+
         let transformed = match stmt {
             Stmt::Expr(expr_stmt) => {
                 let span = expr_stmt.span;
 
                 let transform_macro_result = transform_script_setup_macro_expr(
                     &expr_stmt.expr,
+                    ctx,
                     bindings_helper,
                     &mut sfc_object_helper,
                     false,
@@ -119,21 +149,47 @@ pub fn transform_and_record_script_setup(
             }
 
             Stmt::Decl(decl) => {
-                transform_decl_stmt(decl, bindings_helper, &mut sfc_object_helper).map(Stmt::Decl)
+                transform_decl_stmt(decl, ctx, bindings_helper, &mut sfc_object_helper, errors)
+                    .map(Stmt::Decl)
             }
 
             // By default, just return the same statement
             _ => Some(stmt),
         };
 
-        if let Some(transformed_stmt) = transformed {
-            setup_body_stmts.push(transformed_stmt);
+        match transformed {
+            Some(transformed_stmt) if should_hoist => {
+                module_items.push(ModuleItem::Stmt(transformed_stmt));
+            }
+            Some(transformed_stmt) => setup_body_stmts.push(transformed_stmt),
+            None => {}
         }
     }
 
+    // Access the destructured props through `__props` and add their defaults
+    if !sfc_object_helper.props_destructure.is_empty() {
+        transform_destructured_props(
+            &mut setup_body_stmts,
+            bindings_helper,
+            &sfc_object_helper,
+            errors,
+        );
+        apply_props_destructure_defaults(bindings_helper, &mut sfc_object_helper);
+    }
+
+    // Props are declared at the module level and cannot use the variables of `setup()`
+    if let Some(ref props) = sfc_object_helper.props {
+        check_setup_references(props, &setup_body_stmts, errors);
+    }
+
     // Post-process macros, e.g. merge models to `props` and `emits`
     postprocess_macros(bindings_helper, &mut sfc_object_helper);
 
+    // Restore the component instance after the top-level `await`s
+    if sfc_object_helper.is_async_setup {
+        transform_async_context(&mut setup_body_stmts, bindings_helper);
+    }
+
     // Should we check that this function was not assigned anywhere else?
     let setup_fn = Some(Box::new(Function {
         params: get_setup_fn_params(&sfc_object_helper),
@@ -145,7 +201,7 @@ pub fn transform_and_record_script_setup(
         }),
         is_generator: false,
         is_async: sfc_object_helper.is_async_setup,
-        type_params: None,
+        type_params: script_setup.generic,
         return_type: None,
     }));
 
@@ -160,8 +216,10 @@ pub fn transform_and_record_script_setup(
 /// These are typically `var`/`let`/`const` declarations, function declarations, etc.
 fn transform_decl_stmt(
     decl: Decl,
+    ctx: &mut TypeResolveContext,
     bindings_helper: &mut BindingsHelper,
     sfc_object_helper: &mut SfcExportedObjectHelper,
+    errors: &mut Vec<TransformError>,
 ) -> Option<Decl> {
     /// Pushes the binding type and returns the same passed `Decl`
     macro_rules! push_return {
@@ -187,7 +245,19 @@ fn transform_decl_stmt(
             // Collected bindings cache
             let mut collected_bindings = Vec::<SetupBinding>::with_capacity(2);
 
-            for var_declarator in var_decl.as_mut().decls.iter_mut() {
+            var_decl.decls.retain_mut(|var_declarator| {
+                // Props destructure, e.g. `const { foo = 1 } = defineProps()`.
+                // The declarator is removed, unless there is a rest element.
+                if is_const && is_props_destructure(var_declarator) {
+                    return transform_props_destructure(
+                        var_declarator,
+                        ctx,
+                        bindings_helper,
+                        sfc_object_helper,
+                        errors,
+                    );
+                }
+
                 // LHS is just an identifier, e.g. in `const foo = 'bar'`
                 let is_ident = var_declarator.name.is_ident();
 
@@ -198,6 +268,7 @@ fn transform_decl_stmt(
                 if let Some(ref init_expr) = var_declarator.init {
                     let transform_macro_result = transform_script_setup_macro_expr(
                         init_expr,
+                        ctx,
                         bindings_helper,
                         sfc_object_helper,
                         true,
@@ -208,6 +279,8 @@ fn transform_decl_stmt(
                     {
                         // Macros always overwrite the RHS
                         var_declarator.init = transformed_expr;
+                    } else if let TransformMacroResult::Error(err) = transform_macro_result {
+                        errors.push(err);
                     } else if is_const && is_ident {
                         // Resolve only when this is a constant identifier.
                         // For destructures correct bindings are already assigned.
@@ -221,6 +294,12 @@ fn transform_decl_stmt(
                 bindings_helper
                     .setup_bindings
                     .extend(collected_bindings.drain(..));
+
+                true
+            });
+
+            if var_decl.decls.is_empty() {
+                return None;
             }
 
             Some(Decl::Var(var_decl))
@@ -274,6 +353,123 @@ pub fn merge_sfc_helper(sfc_helper: SfcExportedObjectHelper, dest: &mut Vec<Prop
     dest.extend(sfc_helper.untyped_fields);
 }
 
+/// Checks if a statement only declares literal constants and can be hoisted out of `setup()`,
+/// e.g. `const foo = 1, bar = 'baz'` or `enum Foo { Bar = 1 }`.
+/// Statements mentioning the `generic_params`, e.g. `const foo: T | null = null`, are not.
+fn is_hoistable_stmt(stmt: &Stmt, generic_params: &[FervidAtom]) -> bool {
+    if !generic_params.is_empty() {
+        let mut visitor = GenericParamsVisitor {
+            generic_params,
+            is_used: false,
+        };
+        stmt.visit_with(&mut visitor);
+        if visitor.is_used {
+            return false;
+        }
+    }
+
+    match stmt {
+        Stmt::Decl(Decl::Var(var_decl)) => {
+            matches!(var_decl.kind, VarDeclKind::Const)
+                && !var_decl.declare
+                && var_decl.decls.iter().all(|decl| {
+                    decl.name.is_ident() && decl.init.as_ref().map_or(false, |e| is_static(e))
+                })
+        }
+        Stmt::Decl(Decl::TsEnum(ts_enum)) => ts_enum
+            .members
+            .iter()
+            .all(|m| m.init.as_ref().map_or(true, |e| is_static(e))),
+        _ => false,
+    }
+}
+
+struct GenericParamsVisitor<'a> {
+    generic_params: &'a [FervidAtom],
+    is_used: bool,
+}
+
+impl Visit for GenericParamsVisitor<'_> {
+    fn visit_ident(&mut self, n: &Ident) {
+        self.is_used |= self.generic_params.contains(&n.sym);
+    }
+}
+
+/// Reports a reference to a variable declared in `setup()` from an expression
+/// which is moved out of `setup()`, e.g. `withDefaults(defineProps<Props>(), defaults)`.
+/// Hoisted literal constants are not in `setup_body_stmts` and can be referenced.
+fn check_setup_references(
+    expr: &Expr,
+    setup_body_stmts: &[Stmt],
+    errors: &mut Vec<TransformError>,
+) {
+    let mut locals = Vec::<SetupBinding>::new();
+    for stmt in setup_body_stmts {
+        match stmt {
+            Stmt::Decl(Decl::Var(var_decl)) => {
+                for var_declarator in var_decl.decls.iter() {
+                    extract_variables_from_pat(&var_declarator.name, &mut locals, true);
+                }
+            }
+            Stmt::Decl(Decl::Fn(fn_decl)) => locals.push(categorize_fn_decl(fn_decl)),
+            Stmt::Decl(Decl::Class(class)) => locals.push(categorize_class(class)),
+            Stmt::Decl(Decl::TsEnum(ts_enum)) => locals.push(SetupBinding(
+                ts_enum.id.sym.to_owned(),
+                BindingTypes::SetupConst,
+            )),
+            _ => {}
+        }
+    }
+
+    if locals.is_empty() {
+        return;
+    }
+
+    let mut visitor = SetupReferencesVisitor {
+        locals: &locals,
+        reference_span: None,
+    };
+    expr.visit_with(&mut visitor);
+
+    if let Some(span) = visitor.reference_span {
+        errors.push(TransformError::ScriptError(ScriptError {
+            span,
+            kind: ScriptErrorKind::DefinePropsLocalReference,
+        }));
+    }
+}
+
+struct SetupReferencesVisitor<'a> {
+    locals: &'a [SetupBinding],
+    reference_span: Option<Span>,
+}
+
+impl SetupReferencesVisitor<'_> {
+    fn check_ident(&mut self, ident: &Ident) {
+        if self.reference_span.is_none() && self.locals.iter().any(|binding| binding.0 == ident.sym)
+        {
+            self.reference_span = Some(ident.span);
+        }
+    }
+}
+
+impl Visit for SetupReferencesVisitor<'_> {
+    fn visit_expr(&mut self, n: &Expr) {
+        if let Expr::Ident(ident) = n {
+            self.check_ident(ident);
+        }
+        n.visit_children_with(self);
+    }
+
+    // Object keys and member properties are not references, but `{ foo }` is
+    fn visit_prop(&mut self, n: &Prop) {
+        if let Prop::Shorthand(ident) = n {
+            self.check_ident(ident);
+        }
+        n.visit_children_with(self);
+    }
+}
+
 /// Returns an error span if non-type export is used
 fn check_export(module_decl: &ModuleDecl) -> Option<Span> {
     match module_decl {
@@ -299,6 +495,48 @@ fn check_export(module_decl: &ModuleDecl) -> Option<Span> {
     }
 }
 
+/// Processes `const { foo = 1, ...rest } = defineProps()`.
+/// Returns `true` when the declarator should be kept, i.e. for a rest proxy:
+/// `const rest = _createPropsRestProxy(__props, ["foo"])`
+fn transform_props_destructure(
+    var_declarator: &mut VarDeclarator,
+    ctx: &mut TypeResolveContext,
+    bindings_helper: &mut BindingsHelper,
+    sfc_object_helper: &mut SfcExportedObjectHelper,
+    errors: &mut Vec<TransformError>,
+) -> bool {
+    let Pat::Object(ref obj_pat) = var_declarator.name else {
+        return true;
+    };
+
+    if let Some(ref init_expr) = var_declarator.init {
+        let transform_macro_result = transform_script_setup_macro_expr(
+            init_expr,
+            ctx,
+            bindings_helper,
+            sfc_object_helper,
+            true,
+        );
+
+        if let TransformMacroResult::Error(e) = transform_macro_result {
+            errors.push(e);
+        }
+    }
+
+    match record_props_destructure(obj_pat, bindings_helper, sfc_object_helper) {
+        Ok(Some((rest_pat, rest_proxy))) => {
+            var_declarator.name = rest_pat;
+            var_declarator.init = Some(rest_proxy);
+            true
+        }
+        Ok(None) => false,
+        Err(e) => {
+            errors.push(TransformError::ScriptError(e));
+            false
+        }
+    }
+}
+
 /// Used to populate the params to `setup()`, such as `__props`, `emit`, etc.
 fn get_setup_fn_params(sfc_object_helper: &SfcExportedObjectHelper) -> Vec<Param> {
     let has_ctx_param =
@@ -372,18 +610,31 @@ fn get_setup_fn_params(sfc_object_helper: &SfcExportedObjectHelper) -> Vec<Param
 mod tests {
     use crate::{
         error::{ScriptError, ScriptErrorKind, TransformError},
-        test_utils::parser::*,
+        test_utils::{parser::*, to_str},
         BindingsHelper, SetupBinding,
     };
-    use fervid_core::{fervid_atom, BindingTypes, SfcScriptBlock};
+    use fervid_core::{fervid_atom, BindingTypes, SfcScriptBlock, VueImports};
     use swc_core::common::DUMMY_SP;
 
-    use super::transform_and_record_script_setup;
+    use super::{
+        transform_and_record_script_setup, TransformScriptSetupResult, TypeResolveContext,
+    };
 
     fn analyze_bindings(script_setup: SfcScriptBlock) -> Vec<SetupBinding> {
+        let mut ctx = TypeResolveContext::new("anonymous.vue".into(), Default::default());
+        ctx.record_scope(
+            script_setup.content.body.iter(),
+            script_setup.generic.as_deref(),
+        );
+
         let mut bindings_helper = BindingsHelper::default();
         let mut errors = Vec::new();
-        transform_and_record_script_setup(script_setup, &mut bindings_helper, &mut errors);
+        transform_and_record_script_setup(
+            script_setup,
+            &mut ctx,
+            &mut bindings_helper,
+            &mut errors,
+        );
 
         bindings_helper.setup_bindings
     }
@@ -397,6 +648,7 @@ mod tests {
             content: Box::new(parsed),
             lang: fervid_core::SfcScriptLang::Es,
             is_setup: true,
+            generic: None,
             span: DUMMY_SP,
         })
     }
@@ -410,10 +662,62 @@ mod tests {
             content: Box::new(parsed),
             lang: fervid_core::SfcScriptLang::Typescript,
             is_setup: true,
+            generic: None,
             span: DUMMY_SP,
         })
     }
 
+    fn transform_ts(
+        input: &str,
+        is_prod: bool,
+    ) -> (
+        TransformScriptSetupResult,
+        BindingsHelper,
+        Vec<TransformError>,
+    ) {
+        transform_ts_with(
+            input,
+            BindingsHelper {
+                is_prod,
+                ..Default::default()
+            },
+        )
+    }
+
+    fn transform_ts_with(
+        input: &str,
+        mut bindings_helper: BindingsHelper,
+    ) -> (
+        TransformScriptSetupResult,
+        BindingsHelper,
+        Vec<TransformError>,
+    ) {
+        let parsed = parse_typescript_module(input, 0, Default::default())
+            .expect("transform_ts expects the input to be parseable")
+            .0;
+
+        let script_setup = SfcScriptBlock {
+            content: Box::new(parsed),
+            lang: fervid_core::SfcScriptLang::Typescript,
+            is_setup: true,
+            generic: None,
+            span: DUMMY_SP,
+        };
+
+        let mut ctx = TypeResolveContext::new("anonymous.vue".into(), Default::default());
+        ctx.record_scope(script_setup.content.body.iter(), None);
+
+        let mut errors = Vec::new();
+        let result = transform_and_record_script_setup(
+            script_setup,
+            &mut ctx,
+            &mut bindings_helper,
+            &mut errors,
+        );
+
+        (result, bindings_helper, errors)
+    }
+
     macro_rules! test_js_and_ts {
         ($input: expr, $expected: expr) => {
             assert_eq!(analyze_js_bindings($input), $expected);
@@ -671,10 +975,54 @@ mod tests {
         );
     }
 
+    // https://github.com/vuejs/core/blob/a41c5f1f4367a9f41bcdb8c4e02f54b2378e577d/packages/compiler-sfc/__tests__/compileScript.spec.ts#L942
     #[test]
     fn with_typescript_with_generic_attribute() {
-        // TODO Generics are not implemented yet
-        // https://github.com/vuejs/core/blob/a41c5f1f4367a9f41bcdb8c4e02f54b2378e577d/packages/compiler-sfc/__tests__/compileScript.spec.ts#L942
+        let mut parse_errors = Vec::new();
+        let mut parser = fervid_parser::SfcParser::new(
+            r#"<script setup lang="ts" generic="T extends Record<string, string>">
+            type T = string
+            type Props = { msg: T; list: T[] }
+            defineProps<Props>()
+            </script>"#,
+            &mut parse_errors,
+        );
+        let script_setup = parser
+            .parse_sfc()
+            .ok()
+            .and_then(|descriptor| descriptor.script_setup)
+            .expect("script setup should be parsed");
+
+        let mut ctx = TypeResolveContext::new("anonymous.vue".into(), Default::default());
+        ctx.record_scope(
+            script_setup.content.body.iter(),
+            script_setup.generic.as_deref(),
+        );
+
+        let mut bindings_helper = BindingsHelper::default();
+        let mut errors = Vec::new();
+        let result = transform_and_record_script_setup(
+            script_setup,
+            &mut ctx,
+            &mut bindings_helper,
+            &mut errors,
+        );
+        assert!(errors.is_empty());
+
+        // Generic parameters are not resolved, even when shadowing a type
+        let props = result
+            .sfc_object_helper
+            .props
+            .expect("props should be generated");
+        assert_eq!(
+            to_str(&props),
+            "{msg:{type:null,required:true},list:{type:Array,required:true}}"
+        );
+
+        // `setup` function is generic
+        let setup_fn = result.setup_fn.expect("setup should be generated");
+        let type_params = setup_fn.type_params.expect("setup should be generic");
+        assert_eq!(&type_params.params[0].name.sym, "T");
     }
 
     #[test]
@@ -703,6 +1051,282 @@ mod tests {
         );
     }
 
+    #[test]
+    fn works_for_type_only_define_props() {
+        assert_eq!(
+            analyze_ts_bindings(
+                r"
+                interface Base {
+                    foo: string
+                }
+                interface Props extends Base {
+                    'bar-baz'?: number
+                }
+                defineProps<Partial<Props>>()
+                "
+            ),
+            vec![
+                SetupBinding(fervid_atom!("bar-baz"), BindingTypes::Props),
+                SetupBinding(fervid_atom!("foo"), BindingTypes::Props),
+            ]
+        );
+    }
+
+    #[test]
+    fn with_typescript_with_defaults_static() {
+        let (result, bindings_helper, errors) = transform_ts(
+            r"
+            const props = withDefaults(defineProps<{
+                foo?: string
+                bar?: number
+                baz: boolean
+                qux?(): number
+                quux?: () => void
+                'foo-bar'?: string[]
+            }>(), {
+                foo: 'hi',
+                qux() { return 1 },
+                quux: () => {},
+                ['foo-bar']: () => ['a']
+            })
+            ",
+            false,
+        );
+        assert!(errors.is_empty());
+
+        let props = result
+            .sfc_object_helper
+            .props
+            .expect("props should be generated");
+        assert_eq!(
+            to_str(&props),
+            "{foo:{type:String,required:false,default:\"hi\"},bar:{type:Number,required:false},baz:{type:Boolean,required:true},qux:{type:Function,required:false,default(){return 1;}},quux:{type:Function,required:false,default:()=>{},skipFactory:true},\"foo-bar\":{type:Array,required:false,default:()=>[\"a\"]}}"
+        );
+
+        assert!(bindings_helper
+            .setup_bindings
+            .contains(&SetupBinding(fervid_atom!("foo-bar"), BindingTypes::Props)));
+    }
+
+    #[test]
+    fn with_typescript_with_defaults_dynamic() {
+        let (result, bindings_helper, errors) = transform_ts(
+            r"
+            withDefaults(defineProps<{ foo?: string; bar?: number }>(), { ...defaults, bar: 1 })
+            ",
+            false,
+        );
+        assert!(errors.is_empty());
+
+        let props = result
+            .sfc_object_helper
+            .props
+            .expect("props should be generated");
+        assert_eq!(
+            to_str(&props),
+            "_mergeDefaults({foo:{type:String,required:false},bar:{type:Number,required:false}},{...defaults,bar:1})"
+        );
+        assert!(bindings_helper
+            .vue_imports
+            .contains(VueImports::MergeDefaults));
+    }
+
+    #[test]
+    fn with_defaults_requires_type_based_define_props() {
+        for input in [
+            "withDefaults(defineProps(['foo']), { foo: 1 })",
+            "const props = withDefaults({ foo: 1 })",
+        ] {
+            let (_, _, errors) = transform_ts(input, false);
+            assert!(matches!(
+                errors.first(),
+                Some(TransformError::ScriptError(ScriptError {
+                    kind: ScriptErrorKind::WithDefaultsWithoutTypeBasedDefineProps,
+                    ..
+                }))
+            ));
+        }
+    }
+
+    #[test]
+    fn define_props_cannot_reference_setup_variables() {
+        for input in [
+            r"
+            const defaults = { foo: 'bar' }
+            withDefaults(defineProps<{ foo?: string }>(), defaults)
+            ",
+            r"
+            const { foo } = useFoo()
+            withDefaults(defineProps<{ foo?: string }>(), { foo: () => foo })
+            ",
+            r"
+            let foo = 'bar'
+            withDefaults(defineProps<{ foo?: string }>(), { foo })
+            ",
+            r"
+            const defaultFoo = ref('bar')
+            defineProps({ foo: { type: String, default: defaultFoo } })
+            ",
+        ] {
+            let (_, _, errors) = transform_ts(input, false);
+            assert!(matches!(
+                errors.first(),
+                Some(TransformError::ScriptError(ScriptError {
+                    kind: ScriptErrorKind::DefinePropsLocalReference,
+                    ..
+                }))
+            ));
+        }
+
+        // Hoisted literal constants and object keys are fine
+        let (result, _, errors) = transform_ts_with(
+            r"
+            const foo = 'bar'
+            const props = withDefaults(defineProps<{ foo?: string }>(), { ...{ foo } })
+            ",
+            BindingsHelper {
+                hoist_static: true,
+                ..Default::default()
+            },
+        );
+        assert!(errors.is_empty());
+        assert!(result.sfc_object_helper.props.is_some());
+    }
+
+    #[test]
+    fn with_typescript_runtime_props_in_prod() {
+        let (result, _, errors) = transform_ts(
+            r"
+            interface Props {
+                foo?: string
+                bar: boolean | number
+                fn?: () => void
+                fnWithDefault?: () => void
+                withDefault?: number
+            }
+            withDefaults(defineProps<Props>(), {
+                fnWithDefault: () => {},
+                withDefault: 1
+            })
+            ",
+            true,
+        );
+        assert!(errors.is_empty());
+
+        let props = result
+            .sfc_object_helper
+            .props
+            .expect("props should be generated");
+        assert_eq!(
+            to_str(&props),
+            "{foo:{},bar:{type:[Boolean,Number]},fn:{},fnWithDefault:{type:Function,default:()=>{},skipFactory:true},withDefault:{default:1}}"
+        );
+    }
+
+    #[test]
+    fn hoists_static_literal_constants() {
+        let (result, bindings_helper, errors) = transform_ts_with(
+            r"
+            const foo = 1, bar = true
+            const product = 2 * (3 + 4)
+            const count = ref(0)
+            const mixed = 1, obj = {}
+            let notConst = 1
+            enum Color { Red = 1, Green }
+            ",
+            BindingsHelper {
+                hoist_static: true,
+                ..Default::default()
+            },
+        );
+        assert!(errors.is_empty());
+
+        let hoisted: Vec<String> = result.module_items.iter().map(to_str).collect();
+        assert_eq!(
+            hoisted,
+            vec![
+                "const foo=1,bar=true;",
+                "const product=2*(3+4);",
+                "enum Color{Red=1,Green}",
+            ]
+        );
+
+        let setup_body = result
+            .setup_fn
+            .and_then(|setup_fn| setup_fn.body)
+            .expect("setup should have a body");
+        assert_eq!(
+            to_str(&setup_body),
+            "{const count=ref(0);const mixed=1,obj={};let notConst=1;}"
+        );
+
+        // Bindings are still recorded
+        assert!(bindings_helper.setup_bindings.contains(&SetupBinding(
+            fervid_atom!("foo"),
+            BindingTypes::LiteralConst
+        )));
+        assert!(bindings_helper.setup_bindings.contains(&SetupBinding(
+            fervid_atom!("Color"),
+            BindingTypes::LiteralConst
+        )));
+    }
+
+    #[test]
+    fn does_not_hoist_constants_using_generic_params() {
+        let mut parse_errors = Vec::new();
+        let mut parser = fervid_parser::SfcParser::new(
+            r#"<script setup lang="ts" generic="T, U extends string">
+            const foo: T | null = null
+            const bar = 'bar' as U
+            const baz: string = 'baz'
+            </script>"#,
+            &mut parse_errors,
+        );
+        let script_setup = parser
+            .parse_sfc()
+            .ok()
+            .and_then(|descriptor| descriptor.script_setup)
+            .expect("script setup should be parsed");
+
+        let mut ctx = TypeResolveContext::new("anonymous.vue".into(), Default::default());
+        ctx.record_scope(
+            script_setup.content.body.iter(),
+            script_setup.generic.as_deref(),
+        );
+
+        let mut bindings_helper = BindingsHelper {
+            hoist_static: true,
+            ..Default::default()
+        };
+        let mut errors = Vec::new();
+        let result = transform_and_record_script_setup(
+            script_setup,
+            &mut ctx,
+            &mut bindings_helper,
+            &mut errors,
+        );
+        assert!(errors.is_empty());
+
+        let hoisted: Vec<String> = result.module_items.iter().map(to_str).collect();
+        assert_eq!(hoisted, vec!["const baz:string=\"baz\";"]);
+
+        let setup_body = result
+            .setup_fn
+            .and_then(|setup_fn| setup_fn.body)
+            .expect("setup should have a body");
+        assert_eq!(
+            to_str(&setup_body),
+            "{const foo:T|null=null;const bar=\"bar\" as U;}"
+        );
+    }
+
+    #[test]
+    fn does_not_hoist_when_disabled() {
+        let (result, _, _) = transform_ts("const foo = 1", false);
+
+        assert!(result.module_items.is_empty());
+    }
+
     // https://github.com/vuejs/core/blob/140a7681cc3bba22f55d97fd85a5eafe97a1230f/packages/compiler-sfc/__tests__/compileScript.spec.ts#L871-L890
     #[test]
     fn non_type_named_exports() {
@@ -716,12 +1340,19 @@ mod tests {
                     content: Box::new(parsed),
                     lang: fervid_core::SfcScriptLang::Typescript,
                     is_setup: true,
+                    generic: None,
                     span: DUMMY_SP,
                 };
 
+                let mut ctx = TypeResolveContext::new("anonymous.vue".into(), Default::default());
                 let mut bindings_helper = BindingsHelper::default();
                 let mut errors = Vec::new();
-                transform_and_record_script_setup(script_setup, &mut bindings_helper, &mut errors);
+                transform_and_record_script_setup(
+                    script_setup,
+                    &mut ctx,
+                    &mut bindings_helper,
+                    &mut errors,
+                );
 
                 if $should_error {
                     let error = errors.first().expect("Should have error");
@@ -0,0 +1,373 @@
+//! Preserves the component instance across the top-level `await`s of `<script setup>`, e.g.
+//! `await foo()` -> `(([__temp, __restore] = _withAsyncContext(() => foo())), await __temp, __restore())`.
+//!
+//! Adapted from `processAwait` of the official compiler
+
+use fervid_core::{FervidAtom, VueImports};
+use swc_core::{
+    common::{util::take::Take, DUMMY_SP},
+    ecma::{
+        ast::{
+            ArrayPat, ArrowExpr, AssignExpr, AssignOp, AssignTarget, AssignTargetPat, AwaitExpr,
+            BindingIdent, BlockStmt, BlockStmtOrExpr, CallExpr, Callee, Class, Decl, Expr,
+            ExprOrSpread, ExprStmt, Function, Ident, ParenExpr, Pat, SeqExpr, SimpleAssignTarget,
+            Stmt, TsKeywordType, TsKeywordTypeKind, TsType, TsTypeAnn, VarDecl, VarDeclKind,
+            VarDeclarator,
+        },
+        visit::{VisitMut, VisitMutWith},
+    },
+};
+
+use crate::{
+    atoms::{RESTORE_HELPER, TEMP_HELPER},
+    BindingsHelper,
+};
+
+use super::await_detection::detect_await_expr;
+
+/// Wraps the `await` expressions and `for await` loops of the setup body in `_withAsyncContext`.
+/// `let __temp, __restore` is added to the beginning of the body when anything was wrapped.
+pub fn transform_async_context(stmts: &mut Vec<Stmt>, bindings_helper: &mut BindingsHelper) {
+    let mut visitor = AsyncContextVisitor { has_await: false };
+    for stmt in stmts.iter_mut() {
+        stmt.visit_mut_with(&mut visitor);
+    }
+
+    if !visitor.has_await {
+        return;
+    }
+
+    bindings_helper.vue_imports |= VueImports::WithAsyncContext;
+
+    // `let __temp, __restore` or `let __temp: any, __restore: any`
+    let is_ts = bindings_helper.is_ts;
+    let declarator = |sym: &FervidAtom| VarDeclarator {
+        span: DUMMY_SP,
+        name: Pat::Ident(BindingIdent {
+            id: ident(sym),
+            type_ann: if is_ts {
+                Some(Box::new(TsTypeAnn {
+                    span: DUMMY_SP,
+                    type_ann: Box::new(TsType::TsKeywordType(TsKeywordType {
+                        span: DUMMY_SP,
+                        kind: TsKeywordTypeKind::TsAnyKeyword,
+                    })),
+                }))
+            } else {
+                None
+            },
+        }),
+        init: None,
+        definite: false,
+    };
+
+    stmts.insert(
+        0,
+        Stmt::Decl(Decl::Var(Box::new(VarDecl {
+            span: DUMMY_SP,
+            kind: VarDeclKind::Let,
+            declare: false,
+            decls: vec![declarator(&TEMP_HELPER), declarator(&RESTORE_HELPER)],
+        }))),
+    );
+}
+
+struct AsyncContextVisitor {
+    has_await: bool,
+}
+
+impl AsyncContextVisitor {
+    /// Generates
+    /// ```js
+    /// (
+    ///   ([__temp, __restore] = _withAsyncContext(() => arg)),
+    ///   __temp = await __temp,
+    ///   __restore(),
+    ///   __temp
+    /// )
+    /// ```
+    /// When `is_statement` is `true`, the value of `__temp` is not needed.
+    fn wrap(&mut self, arg: Box<Expr>, is_statement: bool) -> Box<Expr> {
+        self.has_await = true;
+
+        // Nested awaits need an async function
+        let is_async = detect_await_expr(&arg);
+
+        // Object literal as the arrow body needs parentheses
+        let arg = if arg.is_object() {
+            Box::new(Expr::Paren(ParenExpr {
+                span: DUMMY_SP,
+                expr: arg,
+            }))
+        } else {
+            arg
+        };
+
+        self.wrap_getter(BlockStmtOrExpr::Expr(arg), is_async, is_statement)
+    }
+
+    fn wrap_getter(
+        &mut self,
+        getter_body: BlockStmtOrExpr,
+        is_async: bool,
+        is_statement: bool,
+    ) -> Box<Expr> {
+        // `[__temp, __restore] = _withAsyncContext(() => arg)`
+        let with_async_context = Expr::Assign(AssignExpr {
+            span: DUMMY_SP,
+            op: AssignOp::Assign,
+            left: AssignTarget::Pat(AssignTargetPat::Array(ArrayPat {
+                span: DUMMY_SP,
+                elems: vec![
+                    Some(Pat::Ident(BindingIdent::from(ident(&TEMP_HELPER)))),
+                    Some(Pat::Ident(BindingIdent::from(ident(&RESTORE_HELPER)))),
+                ],
+                optional: false,
+                type_ann: None,
+            })),
+            right: Box::new(Expr::Call(CallExpr {
+                span: DUMMY_SP,
+                callee: Callee::Expr(Box::new(Expr::Ident(ident(
+                    &VueImports::WithAsyncContext.as_atom(),
+                )))),
+                args: vec![ExprOrSpread {
+                    spread: None,
+                    expr: Box::new(Expr::Arrow(ArrowExpr {
+                        span: DUMMY_SP,
+                        params: vec![],
+                        body: Box::new(getter_body),
+                        is_async,
+                        is_generator: false,
+                        type_params: None,
+                        return_type: None,
+                    })),
+                }],
+                type_args: None,
+            })),
+        });
+
+        // `await __temp`
+        let await_temp = Box::new(Expr::Await(AwaitExpr {
+            span: DUMMY_SP,
+            arg: Box::new(Expr::Ident(ident(&TEMP_HELPER))),
+        }));
+
+        // `__restore()`
+        let restore = Box::new(Expr::Call(CallExpr {
+            span: DUMMY_SP,
+            callee: Callee::Expr(Box::new(Expr::Ident(ident(&RESTORE_HELPER)))),
+            args: vec![],
+            type_args: None,
+        }));
+
+        let mut exprs = Vec::with_capacity(4);
+        exprs.push(Box::new(Expr::Paren(ParenExpr {
+            span: DUMMY_SP,
+            expr: Box::new(with_async_context),
+        })));
+        if is_statement {
+            exprs.push(await_temp);
+            exprs.push(restore);
+        } else {
+            // `__temp = await __temp`
+            exprs.push(Box::new(Expr::Assign(AssignExpr {
+                span: DUMMY_SP,
+                op: AssignOp::Assign,
+                left: AssignTarget::Simple(SimpleAssignTarget::Ident(BindingIdent::from(ident(
+                    &TEMP_HELPER,
+                )))),
+                right: await_temp,
+            })));
+            exprs.push(restore);
+            exprs.push(Box::new(Expr::Ident(ident(&TEMP_HELPER))));
+        }
+
+        Box::new(Expr::Paren(ParenExpr {
+            span: DUMMY_SP,
+            expr: Box::new(Expr::Seq(SeqExpr {
+                span: DUMMY_SP,
+                exprs,
+            })),
+        }))
+    }
+}
+
+impl VisitMut for AsyncContextVisitor {
+    // `await` inside functions and classes is not top-level
+    fn visit_mut_function(&mut self, _n: &mut Function) {}
+
+    fn visit_mut_arrow_expr(&mut self, _n: &mut ArrowExpr) {}
+
+    fn visit_mut_class(&mut self, _n: &mut Class) {}
+
+    fn visit_mut_stmt(&mut self, n: &mut Stmt) {
+        n.visit_mut_children_with(self);
+
+        // `for await (const foo of bar) {}` is wrapped as a whole
+        let Stmt::ForOf(ref for_of_stmt) = n else {
+            return;
+        };
+        if !for_of_stmt.is_await {
+            return;
+        }
+
+        self.has_await = true;
+        let for_of_stmt = n.take();
+        let wrapped = self.wrap_getter(
+            BlockStmtOrExpr::BlockStmt(BlockStmt {
+                span: DUMMY_SP,
+                stmts: vec![for_of_stmt],
+            }),
+            true,
+            true,
+        );
+        *n = Stmt::Expr(ExprStmt {
+            span: DUMMY_SP,
+            expr: wrapped,
+        });
+    }
+
+    fn visit_mut_expr_stmt(&mut self, n: &mut ExprStmt) {
+        // `await foo` as a statement does not need the awaited value
+        let Expr::Await(ref mut await_expr) = *n.expr else {
+            n.visit_mut_children_with(self);
+            return;
+        };
+
+        await_expr.arg.visit_mut_with(self);
+        n.expr = self.wrap(await_expr.arg.take(), true);
+    }
+
+    fn visit_mut_expr(&mut self, n: &mut Expr) {
+        n.visit_mut_children_with(self);
+
+        if let Expr::Await(await_expr) = n {
+            *n = *self.wrap(await_expr.arg.take(), false);
+        }
+    }
+}
+
+fn ident(sym: &FervidAtom) -> Ident {
+    Ident {
+        span: DUMMY_SP,
+        sym: sym.to_owned(),
+        optional: false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use fervid_core::{SfcScriptBlock, VueImports};
+    use swc_core::common::DUMMY_SP;
+
+    use crate::{
+        script::{resolve_type::TypeResolveContext, setup::transform_and_record_script_setup},
+        test_utils::{parser::parse_typescript_module, to_str},
+        BindingsHelper,
+    };
+
+    /// Compiles the `<script setup>` and returns the setup body and whether `withAsyncContext` is used
+    fn compile(input: &str, is_ts: bool) -> (String, bool) {
+        let parsed = parse_typescript_module(input, 0, Default::default())
+            .expect("compile expects the input to be parseable")
+            .0;
+
+        let script_setup = SfcScriptBlock {
+            content: Box::new(parsed),
+            lang: fervid_core::SfcScriptLang::Typescript,
+            is_setup: true,
+            generic: None,
+            span: DUMMY_SP,
+        };
+
+        let mut ctx = TypeResolveContext::new("anonymous.vue".into(), Default::default());
+        let mut bindings_helper = BindingsHelper {
+            is_ts,
+            ..Default::default()
+        };
+        let mut errors = Vec::new();
+        let result = transform_and_record_script_setup(
+            script_setup,
+            &mut ctx,
+            &mut bindings_helper,
+            &mut errors,
+        );
+        assert!(errors.is_empty());
+
+        let setup_fn = result.setup_fn.expect("setup should be generated");
+        assert!(setup_fn.is_async);
+        let body = setup_fn.body.expect("setup should have a body");
+
+        (
+            to_str(&body),
+            bindings_helper
+                .vue_imports
+                .contains(VueImports::WithAsyncContext),
+        )
+    }
+
+    // https://github.com/vuejs/core/blob/main/packages/compiler-sfc/__tests__/compileScript.spec.ts
+    #[test]
+    fn it_wraps_expression_statements() {
+        let (body, uses_helper) = compile("await foo()", false);
+        assert_eq!(
+            body,
+            "{let __temp,__restore;(([__temp,__restore]=_withAsyncContext(()=>foo())),await __temp,__restore());}"
+        );
+        assert!(uses_helper);
+    }
+
+    #[test]
+    fn it_wraps_variable_declarations() {
+        let (body, _) = compile("const a = 1 + (await foo)", false);
+        assert_eq!(
+            body,
+            "{let __temp,__restore;const a=1+((([__temp,__restore]=_withAsyncContext(()=>foo)),__temp=await __temp,__restore(),__temp));}"
+        );
+    }
+
+    #[test]
+    fn it_wraps_nested_awaits() {
+        let (body, _) = compile("await (await foo)", false);
+        assert_eq!(
+            body,
+            "{let __temp,__restore;(([__temp,__restore]=_withAsyncContext(async()=>((([__temp,__restore]=_withAsyncContext(()=>foo)),__temp=await __temp,__restore(),__temp)))),await __temp,__restore());}"
+        );
+    }
+
+    #[test]
+    fn it_wraps_awaits_in_nested_statements() {
+        let (body, _) = compile("if (ok) { await foo } else { const a = await bar }", false);
+        assert_eq!(
+            body,
+            "{let __temp,__restore;if(ok){(([__temp,__restore]=_withAsyncContext(()=>foo)),await __temp,__restore());}else{const a=(([__temp,__restore]=_withAsyncContext(()=>bar)),__temp=await __temp,__restore(),__temp);}}"
+        );
+    }
+
+    #[test]
+    fn it_wraps_for_await() {
+        let (body, _) = compile("for await (const a of foo) { use(a) }", false);
+        assert_eq!(
+            body,
+            "{let __temp,__restore;(([__temp,__restore]=_withAsyncContext(async()=>{for await(const a of foo){use(a);}})),await __temp,__restore());}"
+        );
+    }
+
+    #[test]
+    fn it_ignores_await_inside_functions() {
+        let (body, _) = compile(
+            "await foo\nconst bar = async () => { await baz }\nasync function qux() { await baz }",
+            false,
+        );
+        assert_eq!(
+            body,
+            "{let __temp,__restore;(([__temp,__restore]=_withAsyncContext(()=>foo)),await __temp,__restore());const bar=async()=>{await baz;};async function qux(){await baz;}}"
+        );
+    }
+
+    #[test]
+    fn it_declares_helpers_as_any_in_typescript() {
+        let (body, _) = compile("await foo", true);
+        assert!(body.starts_with("{let __temp:any,__restore:any;"));
+    }
+}
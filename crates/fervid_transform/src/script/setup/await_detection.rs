@@ -1,5 +1,5 @@
 use swc_core::ecma::{
-    ast::{Expr, ModuleItem},
+    ast::{Expr, ForOfStmt, ModuleItem},
     visit::{Visit, VisitWith},
 };
 
@@ -10,6 +10,13 @@ pub fn detect_await_module_item(module_item: &ModuleItem) -> bool {
     await_detector.found
 }
 
+/// Detects usage of "await" inside an expression, not counting the nested functions
+pub fn detect_await_expr(expr: &Expr) -> bool {
+    let mut await_detector = AwaitDetector::default();
+    expr.visit_with(&mut await_detector);
+    await_detector.found
+}
+
 #[derive(Default)]
 struct AwaitDetector {
     found: bool,
@@ -20,6 +27,15 @@ impl Visit for AwaitDetector {
         self.found = true;
     }
 
+    fn visit_for_of_stmt(&mut self, n: &ForOfStmt) {
+        if n.is_await {
+            self.found = true;
+            return;
+        }
+
+        n.visit_children_with(self);
+    }
+
     fn visit_function(&mut self, n: &swc_core::ecma::ast::Function) {
         for param in n.params.iter() {
             if self.found {
@@ -164,6 +180,12 @@ mod tests {
         );
     }
 
+    #[test]
+    fn for_await() {
+        assert_await_detection!("for await (const a of foo) {}");
+        assert_await_detection!("if (ok) { for await (const a of foo) {} }");
+    }
+
     #[test]
     fn should_ignore_await_inside_functions() {
         // function declaration
@@ -177,6 +199,11 @@ mod tests {
             "const cls = class Foo { async method() { await bar }}",
             false
         );
+        // `for await` inside a function
+        assert_await_detection!(
+            "async function foo() { for await (const a of bar) {} }",
+            false
+        );
     }
 
     fn assert_await_detection_fn(input: &str, should_async: bool) {
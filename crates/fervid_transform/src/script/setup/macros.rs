@@ -4,8 +4,9 @@ use itertools::{Either, Itertools};
 use swc_core::{
     common::{Spanned, DUMMY_SP},
     ecma::ast::{
-        ArrayLit, Bool, CallExpr, Callee, Expr, ExprOrSpread, Ident, KeyValueProp, Lit, ObjectLit,
-        Prop, PropName, PropOrSpread, Str, TsFnOrConstructorType, TsFnParam, TsLit, TsType,
+        ArrayLit, Bool, CallExpr, Callee, Expr, ExprOrSpread, GetterProp, Ident, KeyValueProp, Lit,
+        MethodProp, Null, ObjectLit, Prop, PropName, PropOrSpread, SetterProp, Str,
+        TsFnOrConstructorType, TsFnParam, TsLit, TsType, TsTypeElement,
     },
 };
 
@@ -13,12 +14,13 @@ use crate::{
     atoms::{
         DEFINE_EMITS, DEFINE_EXPOSE, DEFINE_MODEL, DEFINE_OPTIONS, DEFINE_PROPS, DEFINE_SLOTS,
         EMIT_HELPER, EXPOSE_HELPER, MERGE_MODELS_HELPER, MODEL_VALUE, PROPS_HELPER,
-        USE_MODEL_HELPER,
+        USE_MODEL_HELPER, WITH_DEFAULTS,
     },
     error::{ScriptError, ScriptErrorKind, TransformError},
     script::{
         resolve_type::{
-            resolve_type_elements, resolve_union_type, ResolvedElements, TypeResolveContext,
+            infer_runtime_type_element, resolve_type_elements, resolve_union_type,
+            ResolvedElements, TypeResolveContext, UNKNOWN_TYPE,
         },
         utils::{collect_obj_fields, collect_string_arr},
     },
@@ -39,6 +41,7 @@ pub enum TransformMacroResult {
 /// See https://vuejs.org/api/sfc-script-setup.html#defineprops-defineemits
 pub fn transform_script_setup_macro_expr(
     expr: &Expr,
+    ctx: &mut TypeResolveContext,
     bindings_helper: &mut BindingsHelper,
     sfc_object_helper: &mut SfcExportedObjectHelper,
     is_var_decl: bool,
@@ -85,40 +88,45 @@ pub fn transform_script_setup_macro_expr(
     let sym = &callee_ident.sym;
     let span = call_expr.span;
     if DEFINE_PROPS.eq(sym) {
-        if let Some(arg0) = &call_expr.args.get(0) {
-            // TODO Check if this was re-assigned before
-            sfc_object_helper.props = Some(arg0.expr.to_owned());
-
-            // Add props as bindings
-            let mut raw_bindings = Vec::new();
-            match arg0.expr.as_ref() {
-                Expr::Array(props_arr) => {
-                    collect_string_arr(props_arr, &mut raw_bindings);
-                }
-                Expr::Object(props_obj) => {
-                    collect_obj_fields(props_obj, &mut raw_bindings);
+        transform_define_props(
+            call_expr,
+            None,
+            ctx,
+            bindings_helper,
+            sfc_object_helper,
+            is_var_decl,
+        )
+    } else if WITH_DEFAULTS.eq(sym) {
+        // `withDefaults` only works with a type-based `defineProps` as its first argument
+        let define_props_call = match call_expr.args.first() {
+            Some(ExprOrSpread { spread: None, expr }) => match **expr {
+                Expr::Call(ref define_props_call)
+                    if is_type_based_define_props(define_props_call) =>
+                {
+                    Some(define_props_call)
                 }
-                _ => {}
-            }
-            bindings_helper.setup_bindings.extend(
-                raw_bindings
-                    .into_iter()
-                    .map(|raw| SetupBinding(raw, BindingTypes::Props)),
-            );
-        }
-
-        // Return `__props` when in var mode
-        if is_var_decl {
-            sfc_object_helper.is_setup_props_referenced = true;
+                _ => None,
+            },
+            _ => None,
+        };
 
-            valid_macro!(Some(Box::new(Expr::Ident(Ident {
+        let Some(define_props_call) = define_props_call else {
+            return TransformMacroResult::Error(TransformError::ScriptError(ScriptError {
                 span,
-                sym: PROPS_HELPER.to_owned(),
-                optional: false,
-            }))))
-        } else {
-            valid_macro!(None)
-        }
+                kind: ScriptErrorKind::WithDefaultsWithoutTypeBasedDefineProps,
+            }));
+        };
+
+        let defaults = call_expr.args.get(1).map(|arg| arg.expr.as_ref());
+
+        transform_define_props(
+            define_props_call,
+            defaults,
+            ctx,
+            bindings_helper,
+            sfc_object_helper,
+            is_var_decl,
+        )
     } else if DEFINE_EMITS.eq(sym) {
         // Validation: duplicate call
         if sfc_object_helper.emits.is_some() {
@@ -146,7 +154,7 @@ pub fn transform_script_setup_macro_expr(
                 }));
             };
 
-            let runtime_emits = match extract_runtime_emits(&ts_type) {
+            let runtime_emits = match extract_runtime_emits(ctx, ts_type) {
                 Ok(v) => v,
                 Err(e) => return TransformMacroResult::Error(TransformError::ScriptError(e)),
             };
@@ -322,6 +330,222 @@ pub fn transform_script_setup_macro_expr(
     }
 }
 
+/// Processes `defineProps`, either on its own or wrapped in `withDefaults`.
+/// `defaults` is the second argument of `withDefaults(defineProps<Props>(), defaults)`.
+fn transform_define_props(
+    call_expr: &CallExpr,
+    defaults: Option<&Expr>,
+    ctx: &mut TypeResolveContext,
+    bindings_helper: &mut BindingsHelper,
+    sfc_object_helper: &mut SfcExportedObjectHelper,
+    is_var_decl: bool,
+) -> TransformMacroResult {
+    let span = call_expr.span;
+
+    // Validation: both runtime and types
+    if !call_expr.args.is_empty() && call_expr.type_args.is_some() {
+        return TransformMacroResult::Error(TransformError::ScriptError(ScriptError {
+            span,
+            kind: ScriptErrorKind::DefinePropsTypeAndNonTypeArguments,
+        }));
+    }
+
+    let mut raw_bindings = Vec::new();
+    if let Some(arg0) = call_expr.args.first() {
+        // TODO Check if this was re-assigned before
+        sfc_object_helper.props = Some(arg0.expr.to_owned());
+
+        // Add props as bindings
+        match arg0.expr.as_ref() {
+            Expr::Array(props_arr) => {
+                collect_string_arr(props_arr, &mut raw_bindings);
+            }
+            Expr::Object(props_obj) => {
+                collect_obj_fields(props_obj, &mut raw_bindings);
+            }
+            _ => {}
+        }
+    } else if let Some(ts_type) = call_expr
+        .type_args
+        .as_ref()
+        .and_then(|type_args| type_args.params.first())
+    {
+        // Static defaults are added to the runtime props directly,
+        // other defaults are merged in runtime using `mergeDefaults`
+        let static_defaults = defaults.and_then(as_static_defaults);
+
+        let runtime_props = match extract_runtime_props(
+            ctx,
+            ts_type,
+            static_defaults,
+            bindings_helper.is_prod,
+            &mut raw_bindings,
+        ) {
+            Ok(v) => v,
+            Err(e) => return TransformMacroResult::Error(TransformError::ScriptError(e)),
+        };
+
+        let mut props = Box::new(Expr::Object(runtime_props));
+        if let (Some(defaults), None) = (defaults, static_defaults) {
+            // `_mergeDefaults({ foo: { type: String, required: false } }, defaults)`
+            bindings_helper.vue_imports |= VueImports::MergeDefaults;
+            props = Box::new(Expr::Call(CallExpr {
+                span: DUMMY_SP,
+                callee: Callee::Expr(Box::new(Expr::Ident(Ident {
+                    span: DUMMY_SP,
+                    sym: VueImports::MergeDefaults.as_atom(),
+                    optional: false,
+                }))),
+                args: vec![
+                    ExprOrSpread {
+                        spread: None,
+                        expr: props,
+                    },
+                    ExprOrSpread {
+                        spread: None,
+                        expr: Box::new(defaults.to_owned()),
+                    },
+                ],
+                type_args: None,
+            }));
+        }
+
+        sfc_object_helper.props = Some(props);
+    }
+
+    bindings_helper.setup_bindings.extend(
+        raw_bindings
+            .into_iter()
+            .map(|raw| SetupBinding(raw, BindingTypes::Props)),
+    );
+
+    // Return `__props` when in var mode
+    if is_var_decl {
+        sfc_object_helper.is_setup_props_referenced = true;
+
+        TransformMacroResult::ValidMacro(Some(Box::new(Expr::Ident(Ident {
+            span,
+            sym: PROPS_HELPER.to_owned(),
+            optional: false,
+        }))))
+    } else {
+        TransformMacroResult::ValidMacro(None)
+    }
+}
+
+/// Checks that the call is `defineProps<Props>()`
+fn is_type_based_define_props(call_expr: &CallExpr) -> bool {
+    let Callee::Expr(ref callee_expr) = call_expr.callee else {
+        return false;
+    };
+
+    matches!(**callee_expr, Expr::Ident(ref ident) if DEFINE_PROPS.eq(&ident.sym))
+        && call_expr.type_args.is_some()
+        && call_expr.args.is_empty()
+}
+
+/// Defaults from `withDefaults` are static when they are an object literal
+/// without spreads and computed keys, e.g. `{ foo: 1, bar: () => [] }`
+fn as_static_defaults(defaults: &Expr) -> Option<&ObjectLit> {
+    let Expr::Object(ref defaults_obj) = *defaults else {
+        return None;
+    };
+
+    let is_static = defaults_obj.props.iter().all(|prop| match prop {
+        PropOrSpread::Spread(_) => false,
+        PropOrSpread::Prop(prop) => match **prop {
+            Prop::KeyValue(KeyValueProp { ref key, .. })
+            | Prop::Method(MethodProp { ref key, .. })
+            | Prop::Getter(GetterProp { ref key, .. })
+            | Prop::Setter(SetterProp { ref key, .. }) => match key {
+                PropName::Computed(computed) => computed.expr.is_lit(),
+                _ => true,
+            },
+            Prop::Shorthand(_) => true,
+            Prop::Assign(_) => false,
+        },
+    });
+
+    if is_static {
+        Some(defaults_obj)
+    } else {
+        None
+    }
+}
+
+/// Finds the default value of a prop in static `withDefaults` defaults
+/// and converts it to a `default` field of prop options,
+/// e.g. `foo: 1` -> `default: 1` or `foo() { return [] }` -> `default() { return [] }`.
+/// Returns whether the default is a function expression.
+fn static_default_prop(defaults: &ObjectLit, key: &str) -> Option<(PropOrSpread, bool)> {
+    let default_key = || {
+        PropName::Ident(Ident {
+            span: DUMMY_SP,
+            sym: fervid_atom!("default"),
+            optional: false,
+        })
+    };
+
+    let matches_key = |prop_name: &PropName| match prop_name {
+        PropName::Ident(ident) => ident.sym == *key,
+        PropName::Str(s) => s.value == *key,
+        PropName::Num(n) => n.value.to_string() == key,
+        PropName::Computed(computed) => match *computed.expr {
+            Expr::Lit(Lit::Str(ref s)) => s.value == *key,
+            Expr::Lit(Lit::Num(ref n)) => n.value.to_string() == key,
+            _ => false,
+        },
+        PropName::BigInt(_) => false,
+    };
+
+    defaults.props.iter().find_map(|prop| {
+        let PropOrSpread::Prop(prop) = prop else {
+            return None;
+        };
+
+        let (default_prop, is_function) = match **prop {
+            Prop::KeyValue(ref key_value) if matches_key(&key_value.key) => (
+                Prop::KeyValue(KeyValueProp {
+                    key: default_key(),
+                    value: key_value.value.to_owned(),
+                }),
+                matches!(*key_value.value, Expr::Fn(_) | Expr::Arrow(_)),
+            ),
+            Prop::Shorthand(ref ident) if ident.sym == *key => (
+                Prop::KeyValue(KeyValueProp {
+                    key: default_key(),
+                    value: Box::new(Expr::Ident(ident.to_owned())),
+                }),
+                false,
+            ),
+            Prop::Method(ref method) if matches_key(&method.key) => (
+                Prop::Method(MethodProp {
+                    key: default_key(),
+                    function: method.function.to_owned(),
+                }),
+                false,
+            ),
+            Prop::Getter(ref getter) if matches_key(&getter.key) => (
+                Prop::Getter(GetterProp {
+                    key: default_key(),
+                    ..getter.to_owned()
+                }),
+                false,
+            ),
+            Prop::Setter(ref setter) if matches_key(&setter.key) => (
+                Prop::Setter(SetterProp {
+                    key: default_key(),
+                    ..setter.to_owned()
+                }),
+                false,
+            ),
+            _ => return None,
+        };
+
+        Some((PropOrSpread::Prop(Box::new(default_prop)), is_function))
+    })
+}
+
 /// Mainly used to process `models` by adding them to `props` and `emits`
 pub fn postprocess_macros(
     bindings_helper: &mut BindingsHelper,
@@ -366,6 +590,9 @@ pub fn postprocess_macros(
     }
 
     match sfc_object_helper.props.take() {
+        Some(existing_props) if new_props.is_empty() => {
+            sfc_object_helper.props = Some(existing_props);
+        }
         Some(mut existing_props) => {
             // Try merging into an object if previous props is an object
             if let Expr::Object(ref mut existing_props_obj) = *existing_props {
@@ -413,6 +640,9 @@ pub fn postprocess_macros(
     }
 
     match sfc_object_helper.emits.take() {
+        Some(existing_emits) if new_emits.is_empty() => {
+            sfc_object_helper.emits = Some(existing_emits);
+        }
         Some(mut existing_emits) => {
             // Try merging into an array if previous emits is an array
             if let Expr::Array(ref mut existing_emits_arr) = *existing_emits {
@@ -535,11 +765,177 @@ fn is_local(options: Option<&ExprOrSpread>) -> bool {
     local_bool.value
 }
 
+/// Extracts runtime props from type-only `defineProps` declaration.
+/// Names of the props are pushed to `out_bindings`.
+/// Defaults from `withDefaults` are added to the props when they are static.
+/// In production, only the options needed at runtime are kept, e.g. `{ type: Boolean }` or `{}`.
+/// Adapted from https://github.com/vuejs/core/blob/0ac0f2e338f6f8f0bea7237db539c68bfafb88ae/packages/compiler-sfc/src/script/defineProps.ts#L190-L239
+fn extract_runtime_props(
+    ctx: &mut TypeResolveContext,
+    type_arg: &TsType,
+    static_defaults: Option<&ObjectLit>,
+    is_prod: bool,
+    out_bindings: &mut Vec<FervidAtom>,
+) -> Result<ObjectLit, ScriptError> {
+    let ResolvedElements { props, .. } = resolve_type_elements(ctx, type_arg)?;
+
+    let mut runtime_props = Vec::with_capacity(props.len());
+    for (key, prop) in props.into_iter() {
+        let mut runtime_types = infer_runtime_type_element(ctx, &prop.element, &prop.owner_scope);
+
+        // Skip the check of unknown types, or allow any value when the type cannot be checked
+        let mut skip_check = false;
+        if runtime_types.iter().any(|t| t == UNKNOWN_TYPE) {
+            if runtime_types
+                .iter()
+                .any(|t| t == "Boolean" || t == "Function")
+            {
+                runtime_types.retain(|t| t != UNKNOWN_TYPE);
+                skip_check = true;
+            } else {
+                runtime_types = vec![fervid_atom!("null")];
+            }
+        }
+
+        let is_optional = match prop.element {
+            TsTypeElement::TsPropertySignature(ref s) => s.optional,
+            TsTypeElement::TsMethodSignature(ref s) => s.optional,
+            _ => false,
+        };
+
+        // `default: 'foo'`
+        let default_prop = static_defaults.and_then(|defaults| static_default_prop(defaults, &key));
+
+        // A function default of a prop which may also be a function must not be called
+        let is_function_type = runtime_types.iter().any(|t| t == "Function");
+        let needs_skip_factory = is_function_type
+            && default_prop
+                .as_ref()
+                .is_some_and(|(_, is_function)| *is_function);
+
+        let mut prop_options = Vec::with_capacity(4);
+        if !is_prod {
+            // `{ type: String, required: true }`
+            prop_options.push(key_value_prop("type", runtime_type_expr(runtime_types)));
+            prop_options.push(key_value_prop(
+                "required",
+                Box::new(Expr::Lit(Lit::Bool(Bool {
+                    span: DUMMY_SP,
+                    value: !is_optional,
+                }))),
+            ));
+            if skip_check {
+                prop_options.push(key_value_prop(
+                    "skipCheck",
+                    Box::new(Expr::Lit(Lit::Bool(Bool {
+                        span: DUMMY_SP,
+                        value: true,
+                    }))),
+                ));
+            }
+        } else if runtime_types.iter().any(|t| {
+            t == "Boolean"
+                || (t == "Function" && (static_defaults.is_none() || default_prop.is_some()))
+        }) {
+            // In production the checks are useless, but the type is still needed
+            // for the boolean casting and for not calling the function defaults
+            prop_options.push(key_value_prop("type", runtime_type_expr(runtime_types)));
+        }
+
+        if let Some((default_prop, _)) = default_prop {
+            prop_options.push(default_prop);
+            if needs_skip_factory {
+                prop_options.push(key_value_prop(
+                    "skipFactory",
+                    Box::new(Expr::Lit(Lit::Bool(Bool {
+                        span: DUMMY_SP,
+                        value: true,
+                    }))),
+                ));
+            }
+        }
+
+        let prop_key = if Ident::verify_symbol(&key).is_ok() {
+            PropName::Ident(Ident {
+                span: DUMMY_SP,
+                sym: key.to_owned(),
+                optional: false,
+            })
+        } else {
+            PropName::Str(Str {
+                span: DUMMY_SP,
+                value: key.to_owned(),
+                raw: None,
+            })
+        };
+
+        runtime_props.push(PropOrSpread::Prop(Box::new(Prop::KeyValue(KeyValueProp {
+            key: prop_key,
+            value: Box::new(Expr::Object(ObjectLit {
+                span: DUMMY_SP,
+                props: prop_options,
+            })),
+        }))));
+        out_bindings.push(key);
+    }
+
+    Ok(ObjectLit {
+        span: DUMMY_SP,
+        props: runtime_props,
+    })
+}
+
+/// `String` for a single runtime type, `[String, Number]` for multiple
+fn runtime_type_expr(mut runtime_types: Vec<FervidAtom>) -> Box<Expr> {
+    let to_expr = |runtime_type: FervidAtom| {
+        if runtime_type == "null" {
+            Box::new(Expr::Lit(Lit::Null(Null { span: DUMMY_SP })))
+        } else {
+            Box::new(Expr::Ident(Ident {
+                span: DUMMY_SP,
+                sym: runtime_type,
+                optional: false,
+            }))
+        }
+    };
+
+    if runtime_types.len() == 1 {
+        if let Some(runtime_type) = runtime_types.pop() {
+            return to_expr(runtime_type);
+        }
+    }
+
+    Box::new(Expr::Array(ArrayLit {
+        span: DUMMY_SP,
+        elems: runtime_types
+            .into_iter()
+            .map(|runtime_type| {
+                Some(ExprOrSpread {
+                    spread: None,
+                    expr: to_expr(runtime_type),
+                })
+            })
+            .collect(),
+    }))
+}
+
+fn key_value_prop(key: &str, value: Box<Expr>) -> PropOrSpread {
+    PropOrSpread::Prop(Box::new(Prop::KeyValue(KeyValueProp {
+        key: PropName::Ident(Ident {
+            span: DUMMY_SP,
+            sym: FervidAtom::from(key),
+            optional: false,
+        }),
+        value,
+    })))
+}
+
 /// Extracts runtime emits from type-only `defineEmits` declaration
 /// Adapted from https://github.com/vuejs/core/blob/0ac0f2e338f6f8f0bea7237db539c68bfafb88ae/packages/compiler-sfc/src/script/defineEmits.ts#L73-L103
-fn extract_runtime_emits(type_arg: &TsType) -> Result<FxHashSet<FervidAtom>, ScriptError> {
-    let mut ctx = TypeResolveContext::new("todo".to_owned());
-
+fn extract_runtime_emits(
+    ctx: &mut TypeResolveContext,
+    type_arg: &TsType,
+) -> Result<FxHashSet<FervidAtom>, ScriptError> {
     let mut emits = FxHashSet::<FervidAtom>::default();
 
     // Handle cases like `defineEmits<(e: 'foo' | 'bar') => void>()`
@@ -553,12 +949,12 @@ fn extract_runtime_emits(type_arg: &TsType) -> Result<FxHashSet<FervidAtom>, Scr
             });
         };
 
-        extract_event_names(&mut ctx, first_fn_param, &mut emits);
+        extract_event_names(ctx, first_fn_param, &mut emits)?;
 
         return Ok(emits);
     }
 
-    let ResolvedElements { props, calls } = resolve_type_elements(&mut ctx, type_arg)?;
+    let ResolvedElements { props, calls } = resolve_type_elements(ctx, type_arg)?;
 
     let mut has_property = false;
     for key in props.into_keys() {
@@ -586,7 +982,7 @@ fn extract_runtime_emits(type_arg: &TsType) -> Result<FxHashSet<FervidAtom>, Scr
                     kind: ScriptErrorKind::ResolveTypeMissingTypeParam,
                 });
             };
-            extract_event_names(&mut ctx, first_param, &mut emits);
+            extract_event_names(ctx, first_param, &mut emits)?;
         }
     }
 
@@ -598,18 +994,18 @@ fn extract_event_names(
     ctx: &mut TypeResolveContext,
     event_name: &TsFnParam,
     emits: &mut FxHashSet<FervidAtom>,
-) {
+) -> Result<(), ScriptError> {
     let TsFnParam::Ident(ident) = event_name else {
-        return;
+        return Ok(());
     };
 
     let Some(ref type_annotation) = ident.type_ann else {
-        return;
+        return Ok(());
     };
 
     let scope = ctx.scope.clone();
 
-    let types = resolve_union_type(ctx, &type_annotation.type_ann, &scope);
+    let types = resolve_union_type(ctx, &type_annotation.type_ann, &scope)?;
     for ts_type in types {
         if let TsType::TsLitType(ts_lit_type) = ts_type {
             // No UnaryExpression
@@ -630,4 +1026,6 @@ fn extract_event_names(
             }
         }
     }
+
+    Ok(())
 }
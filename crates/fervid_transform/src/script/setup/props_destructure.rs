@@ -0,0 +1,917 @@
+//! Reactive props destructure, e.g. `const { foo = 1, bar: baz } = defineProps()`.
+//!
+//! Adapted from https://github.com/vuejs/core/blob/main/packages/compiler-sfc/src/script/definePropsDestructure.ts
+
+use fervid_core::{fervid_atom, BindingTypes, FervidAtom, VueImports};
+use swc_core::{
+    common::{Spanned, DUMMY_SP},
+    ecma::{
+        ast::{
+            ArrayLit, ArrowExpr, BlockStmt, BlockStmtOrExpr, Bool, CallExpr, Callee, CatchClause,
+            Decl, Expr, ExprOrSpread, ForInStmt, ForOfStmt, ForStmt, Function, Ident, KeyValueProp,
+            Lit, ObjectLit, ObjectPat, ObjectPatProp, ParenExpr, Pat, Prop, PropName, PropOrSpread,
+            SimpleAssignTarget, Stmt, Str, VarDeclOrExpr, VarDeclarator,
+        },
+        visit::{VisitMut, VisitMutWith},
+    },
+};
+
+use crate::{
+    atoms::{DEFINE_PROPS, PROPS_HELPER},
+    error::{ScriptError, ScriptErrorKind, TransformError},
+    script::{common::extract_variables_from_pat, utils::create_member_access},
+    structs::{PropsDestructureBinding, SfcExportedObjectHelper},
+    BindingsHelper, SetupBinding,
+};
+
+/// Checks if a variable declarator is `const { foo } = defineProps()`
+pub fn is_props_destructure(var_declarator: &VarDeclarator) -> bool {
+    let (Pat::Object(_), Some(init)) = (&var_declarator.name, &var_declarator.init) else {
+        return false;
+    };
+
+    let Expr::Call(ref call_expr) = **init else {
+        return false;
+    };
+
+    matches!(
+        call_expr.callee,
+        Callee::Expr(ref callee) if matches!(**callee, Expr::Ident(ref ident) if DEFINE_PROPS.eq(&ident.sym))
+    )
+}
+
+/// Records the props destructured from `defineProps` and their bindings.
+///
+/// Returns the rest proxy initializer, e.g. `_createPropsRestProxy(__props, ["foo"])`
+/// for `const { foo, ...rest } = defineProps()`, along with the `rest` pattern.
+pub fn record_props_destructure(
+    obj_pat: &ObjectPat,
+    bindings_helper: &mut BindingsHelper,
+    sfc_object_helper: &mut SfcExportedObjectHelper,
+) -> Result<Option<(Pat, Box<Expr>)>, ScriptError> {
+    let mut rest = None;
+
+    for prop in obj_pat.props.iter() {
+        let (key, local, default) = match prop {
+            // `{ foo }` or `{ foo = 1 }`
+            ObjectPatProp::Assign(assign) => (
+                assign.key.sym.to_owned(),
+                assign.key.sym.to_owned(),
+                assign.value.to_owned(),
+            ),
+
+            // `{ foo: bar }` or `{ foo: bar = 1 }`
+            ObjectPatProp::KeyValue(key_value) => {
+                let key = match key_value.key {
+                    PropName::Ident(ref ident) => ident.sym.to_owned(),
+                    PropName::Str(ref s) => s.value.to_owned(),
+                    PropName::Num(ref n) => FervidAtom::from(n.value.to_string()),
+                    PropName::Computed(_) | PropName::BigInt(_) => {
+                        return Err(ScriptError {
+                            span: key_value.key.span(),
+                            kind: ScriptErrorKind::DefinePropsDestructureComputedKey,
+                        });
+                    }
+                };
+
+                let (local, default) = match *key_value.value {
+                    Pat::Ident(ref binding) => (binding, None),
+                    Pat::Assign(ref assign) => match *assign.left {
+                        Pat::Ident(ref binding) => (binding, Some(assign.right.to_owned())),
+                        _ => {
+                            return Err(ScriptError {
+                                span: assign.left.span(),
+                                kind: ScriptErrorKind::DefinePropsDestructureNestedPattern,
+                            });
+                        }
+                    },
+                    _ => {
+                        return Err(ScriptError {
+                            span: key_value.value.span(),
+                            kind: ScriptErrorKind::DefinePropsDestructureNestedPattern,
+                        });
+                    }
+                };
+
+                (key, local.sym.to_owned(), default)
+            }
+
+            // `{ ...rest }`
+            ObjectPatProp::Rest(rest_pat) => {
+                let Pat::Ident(ref binding) = *rest_pat.arg else {
+                    return Err(ScriptError {
+                        span: rest_pat.arg.span(),
+                        kind: ScriptErrorKind::DefinePropsDestructureNestedPattern,
+                    });
+                };
+
+                rest = Some(binding.to_owned());
+                continue;
+            }
+        };
+
+        if local == key {
+            let is_declared = bindings_helper
+                .setup_bindings
+                .iter()
+                .any(|binding| binding.0 == key);
+            if !is_declared {
+                bindings_helper
+                    .setup_bindings
+                    .push(SetupBinding(key.to_owned(), BindingTypes::Props));
+            }
+        } else {
+            bindings_helper
+                .setup_bindings
+                .push(SetupBinding(local.to_owned(), BindingTypes::PropsAliased));
+            bindings_helper
+                .props_aliases
+                .insert(local.to_owned(), key.to_owned());
+        }
+
+        sfc_object_helper
+            .props_destructure
+            .push(PropsDestructureBinding {
+                key,
+                local,
+                default,
+            });
+    }
+
+    let Some(rest) = rest else {
+        return Ok(None);
+    };
+
+    bindings_helper.setup_bindings.push(SetupBinding(
+        rest.sym.to_owned(),
+        BindingTypes::SetupReactiveConst,
+    ));
+    bindings_helper.vue_imports |= VueImports::CreatePropsRestProxy;
+    sfc_object_helper.is_setup_props_referenced = true;
+
+    // `_createPropsRestProxy(__props, ["foo", "bar"])`
+    let excluded_keys = sfc_object_helper
+        .props_destructure
+        .iter()
+        .map(|binding| {
+            Some(ExprOrSpread {
+                spread: None,
+                expr: Box::new(Expr::Lit(Lit::Str(Str {
+                    span: DUMMY_SP,
+                    value: binding.key.to_owned(),
+                    raw: None,
+                }))),
+            })
+        })
+        .collect();
+
+    let rest_proxy = Box::new(Expr::Call(CallExpr {
+        span: DUMMY_SP,
+        callee: Callee::Expr(Box::new(Expr::Ident(Ident {
+            span: DUMMY_SP,
+            sym: VueImports::CreatePropsRestProxy.as_atom(),
+            optional: false,
+        }))),
+        args: vec![
+            ExprOrSpread {
+                spread: None,
+                expr: Box::new(Expr::Ident(Ident {
+                    span: DUMMY_SP,
+                    sym: PROPS_HELPER.to_owned(),
+                    optional: false,
+                })),
+            },
+            ExprOrSpread {
+                spread: None,
+                expr: Box::new(Expr::Array(ArrayLit {
+                    span: DUMMY_SP,
+                    elems: excluded_keys,
+                })),
+            },
+        ],
+        type_args: None,
+    }));
+
+    Ok(Some((Pat::Ident(rest), rest_proxy)))
+}
+
+/// Adds the default values of the destructured props to the props declaration,
+/// e.g. `foo: { type: Number, required: false, default: 1 }`.
+/// Defaults of the props which are not found in an object declaration
+/// are merged using `mergeDefaults(props, { foo: 1 })`.
+pub fn apply_props_destructure_defaults(
+    bindings_helper: &mut BindingsHelper,
+    sfc_object_helper: &mut SfcExportedObjectHelper,
+) {
+    let Some(ref mut props) = sfc_object_helper.props else {
+        return;
+    };
+
+    let mut merged_defaults = Vec::<PropOrSpread>::new();
+
+    for binding in sfc_object_helper.props_destructure.iter() {
+        let Some(ref default) = binding.default else {
+            continue;
+        };
+
+        let prop_options = match **props {
+            Expr::Object(ref mut props_obj) => find_prop_value_mut(props_obj, &binding.key),
+            _ => None,
+        };
+
+        let Some(prop_options) = prop_options else {
+            let (default_value, skip_factory) = destructured_default_value(default, None);
+            merged_defaults.push(key_value_prop(prop_name(&binding.key), default_value));
+            if skip_factory {
+                merged_defaults.push(key_value_prop(
+                    prop_name(&FervidAtom::from(format!("__skip_{}", binding.key))),
+                    bool_expr(true),
+                ));
+            }
+            continue;
+        };
+
+        // `foo: String` -> `foo: { type: String }`
+        if !prop_options.is_object() {
+            let prop_type =
+                std::mem::replace(prop_options, Box::new(Expr::Invalid(Default::default())));
+            *prop_options = Box::new(Expr::Object(ObjectLit {
+                span: DUMMY_SP,
+                props: vec![key_value_prop(ident_prop_name("type"), prop_type)],
+            }));
+        }
+
+        let Expr::Object(ref mut prop_options) = **prop_options else {
+            continue;
+        };
+
+        let inferred_types = infer_prop_types(prop_options);
+        let (default_value, skip_factory) = destructured_default_value(default, inferred_types);
+        prop_options
+            .props
+            .push(key_value_prop(ident_prop_name("default"), default_value));
+        if skip_factory {
+            prop_options.props.push(key_value_prop(
+                ident_prop_name("skipFactory"),
+                bool_expr(true),
+            ));
+        }
+    }
+
+    if merged_defaults.is_empty() {
+        return;
+    }
+
+    // `_mergeDefaults(props, { foo: 1 })`
+    bindings_helper.vue_imports |= VueImports::MergeDefaults;
+    let existing_props = std::mem::replace(props, Box::new(Expr::Invalid(Default::default())));
+    *props = Box::new(Expr::Call(CallExpr {
+        span: DUMMY_SP,
+        callee: Callee::Expr(Box::new(Expr::Ident(Ident {
+            span: DUMMY_SP,
+            sym: VueImports::MergeDefaults.as_atom(),
+            optional: false,
+        }))),
+        args: vec![
+            ExprOrSpread {
+                spread: None,
+                expr: existing_props,
+            },
+            ExprOrSpread {
+                spread: None,
+                expr: Box::new(Expr::Object(ObjectLit {
+                    span: DUMMY_SP,
+                    props: merged_defaults,
+                })),
+            },
+        ],
+        type_args: None,
+    }));
+}
+
+/// Rewrites the references to the destructured props in `<script setup>`,
+/// e.g. `console.log(foo)` -> `console.log(__props.foo)`.
+/// Reports assignments to the destructured props and passing them to `watch()` or `toRef()`.
+pub fn transform_destructured_props(
+    stmts: &mut [Stmt],
+    bindings_helper: &BindingsHelper,
+    sfc_object_helper: &SfcExportedObjectHelper,
+    errors: &mut Vec<TransformError>,
+) {
+    // `watch` and `toRef` may be imported under a different name
+    let vue_imports = &bindings_helper.vue_resolved_imports;
+    let checked_fns = [&vue_imports.watch, &vue_imports.to_ref]
+        .into_iter()
+        .flatten()
+        .map(|id| id.0.to_owned())
+        .collect();
+
+    let mut visitor = PropsDestructureVisitor {
+        destructured: sfc_object_helper
+            .props_destructure
+            .iter()
+            .map(|binding| (binding.local.to_owned(), binding.key.to_owned()))
+            .collect(),
+        shadowed: Vec::new(),
+        checked_fns,
+        errors,
+    };
+
+    for stmt in stmts.iter_mut() {
+        stmt.visit_mut_with(&mut visitor);
+    }
+}
+
+struct PropsDestructureVisitor<'e> {
+    /// Local names of the destructured props and the prop keys
+    destructured: Vec<(FervidAtom, FervidAtom)>,
+    /// Variables declared in the nested scopes, they shadow the destructured props
+    shadowed: Vec<FervidAtom>,
+    /// Local names of `watch` and `toRef`
+    checked_fns: Vec<FervidAtom>,
+    errors: &'e mut Vec<TransformError>,
+}
+
+impl PropsDestructureVisitor<'_> {
+    /// Gets the prop key when the identifier refers to a destructured prop
+    fn get_prop_key(&self, sym: &FervidAtom) -> Option<&FervidAtom> {
+        if self.shadowed.contains(sym) {
+            return None;
+        }
+
+        self.destructured
+            .iter()
+            .find_map(|(local, key)| if local == sym { Some(key) } else { None })
+    }
+
+    fn report(&mut self, kind: ScriptErrorKind, span: swc_core::common::Span) {
+        self.errors
+            .push(TransformError::ScriptError(ScriptError { span, kind }));
+    }
+
+    /// Visits a node with the variables of a nested scope
+    fn with_scope(&mut self, variables: Vec<SetupBinding>, f: impl FnOnce(&mut Self)) {
+        let old_len = self.shadowed.len();
+        self.shadowed
+            .extend(variables.into_iter().map(|binding| binding.0));
+        f(self);
+        self.shadowed.truncate(old_len);
+    }
+}
+
+impl VisitMut for PropsDestructureVisitor<'_> {
+    fn visit_mut_expr(&mut self, n: &mut Expr) {
+        match n {
+            Expr::Ident(ident) => {
+                if let Some(key) = self.get_prop_key(&ident.sym).cloned() {
+                    *n = *props_access(&key, ident);
+                }
+                return;
+            }
+
+            // `foo = 1`
+            Expr::Assign(assign_expr) => {
+                if let Some(SimpleAssignTarget::Ident(ident)) = assign_expr.left.as_simple() {
+                    if self.get_prop_key(&ident.sym).is_some() {
+                        let span = ident.span;
+                        self.report(ScriptErrorKind::DefinePropsDestructureCannotAssign, span);
+                    }
+                }
+            }
+
+            // `foo++`
+            Expr::Update(update_expr) => {
+                if let Expr::Ident(ref ident) = *update_expr.arg {
+                    if self.get_prop_key(&ident.sym).is_some() {
+                        let span = ident.span;
+                        self.report(ScriptErrorKind::DefinePropsDestructureCannotAssign, span);
+                        return;
+                    }
+                }
+            }
+
+            // `watch(foo)` or `toRef(foo)`
+            Expr::Call(call_expr) => {
+                let is_checked_fn = matches!(
+                    call_expr.callee,
+                    Callee::Expr(ref callee) if matches!(**callee, Expr::Ident(ref ident) if self.checked_fns.contains(&ident.sym))
+                );
+
+                if is_checked_fn {
+                    if let Some(ExprOrSpread { spread: None, expr }) = call_expr.args.first() {
+                        if let Expr::Ident(ref ident) = **expr {
+                            if self.get_prop_key(&ident.sym).is_some() {
+                                let span = ident.span;
+                                self.report(
+                                    ScriptErrorKind::DefinePropsDestructurePassedDirectly,
+                                    span,
+                                );
+                            }
+                        }
+                    }
+                }
+            }
+
+            _ => {}
+        }
+
+        n.visit_mut_children_with(self);
+    }
+
+    // `{ foo }` -> `{ foo: __props.foo }`
+    fn visit_mut_prop(&mut self, n: &mut Prop) {
+        if let Prop::Shorthand(ident) = n {
+            if let Some(key) = self.get_prop_key(&ident.sym) {
+                let value = props_access(key, ident);
+                *n = Prop::KeyValue(KeyValueProp {
+                    key: PropName::Ident(ident.to_owned()),
+                    value,
+                });
+            }
+            return;
+        }
+
+        n.visit_mut_children_with(self);
+    }
+
+    fn visit_mut_function(&mut self, n: &mut Function) {
+        let mut variables = Vec::new();
+        for param in n.params.iter() {
+            extract_variables_from_pat(&param.pat, &mut variables, true);
+        }
+
+        self.with_scope(variables, |this| n.visit_mut_children_with(this));
+    }
+
+    fn visit_mut_arrow_expr(&mut self, n: &mut ArrowExpr) {
+        let mut variables = Vec::new();
+        for param in n.params.iter() {
+            extract_variables_from_pat(param, &mut variables, true);
+        }
+
+        self.with_scope(variables, |this| n.visit_mut_children_with(this));
+    }
+
+    fn visit_mut_block_stmt(&mut self, n: &mut BlockStmt) {
+        let mut variables = Vec::new();
+        for stmt in n.stmts.iter() {
+            let Stmt::Decl(decl) = stmt else {
+                continue;
+            };
+
+            match decl {
+                Decl::Class(class_decl) => variables.push(SetupBinding(
+                    class_decl.ident.sym.to_owned(),
+                    BindingTypes::SetupConst,
+                )),
+                Decl::Fn(fn_decl) => variables.push(SetupBinding(
+                    fn_decl.ident.sym.to_owned(),
+                    BindingTypes::SetupConst,
+                )),
+                Decl::Var(var_decl) => {
+                    for var_declarator in var_decl.decls.iter() {
+                        extract_variables_from_pat(&var_declarator.name, &mut variables, true);
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        self.with_scope(variables, |this| n.visit_mut_children_with(this));
+    }
+
+    fn visit_mut_for_stmt(&mut self, n: &mut ForStmt) {
+        let mut variables = Vec::new();
+        if let Some(VarDeclOrExpr::VarDecl(ref var_decl)) = n.init {
+            for var_declarator in var_decl.decls.iter() {
+                extract_variables_from_pat(&var_declarator.name, &mut variables, true);
+            }
+        }
+
+        self.with_scope(variables, |this| n.visit_mut_children_with(this));
+    }
+
+    fn visit_mut_for_in_stmt(&mut self, n: &mut ForInStmt) {
+        let mut variables = Vec::new();
+        if let Some(var_decl) = n.left.as_var_decl() {
+            for var_declarator in var_decl.decls.iter() {
+                extract_variables_from_pat(&var_declarator.name, &mut variables, true);
+            }
+        }
+
+        self.with_scope(variables, |this| n.visit_mut_children_with(this));
+    }
+
+    fn visit_mut_for_of_stmt(&mut self, n: &mut ForOfStmt) {
+        let mut variables = Vec::new();
+        if let Some(var_decl) = n.left.as_var_decl() {
+            for var_declarator in var_decl.decls.iter() {
+                extract_variables_from_pat(&var_declarator.name, &mut variables, true);
+            }
+        }
+
+        self.with_scope(variables, |this| n.visit_mut_children_with(this));
+    }
+
+    fn visit_mut_catch_clause(&mut self, n: &mut CatchClause) {
+        let mut variables = Vec::new();
+        if let Some(ref param) = n.param {
+            extract_variables_from_pat(param, &mut variables, true);
+        }
+
+        self.with_scope(variables, |this| n.visit_mut_children_with(this));
+    }
+}
+
+/// Finds the value of a prop in an object literal, e.g. `String` in `{ foo: String }`
+fn find_prop_value_mut<'o>(obj: &'o mut ObjectLit, key: &FervidAtom) -> Option<&'o mut Box<Expr>> {
+    obj.props.iter_mut().find_map(|prop| {
+        let PropOrSpread::Prop(prop) = prop else {
+            return None;
+        };
+
+        let Prop::KeyValue(ref mut key_value) = **prop else {
+            return None;
+        };
+
+        let matches_key = match key_value.key {
+            PropName::Ident(ref ident) => ident.sym == *key,
+            PropName::Str(ref s) => s.value == *key,
+            _ => false,
+        };
+
+        if matches_key {
+            Some(&mut key_value.value)
+        } else {
+            None
+        }
+    })
+}
+
+/// Runtime types from the `type` field of prop options, e.g. `["String", "Number"]`
+/// for `{ type: [String, Number] }`
+fn infer_prop_types(prop_options: &ObjectLit) -> Option<Vec<FervidAtom>> {
+    let prop_type = find_prop_value(prop_options, "type")?;
+
+    let to_type = |expr: &Expr| match expr {
+        Expr::Ident(ident) => Some(ident.sym.to_owned()),
+        Expr::Lit(Lit::Null(_)) => Some(fervid_atom!("null")),
+        _ => None,
+    };
+
+    match prop_type {
+        Expr::Array(arr) => Some(
+            arr.elems
+                .iter()
+                .flatten()
+                .filter_map(|elem| to_type(&elem.expr))
+                .collect(),
+        ),
+        _ => to_type(prop_type).map(|t| vec![t]),
+    }
+}
+
+fn find_prop_value<'o>(obj: &'o ObjectLit, key: &str) -> Option<&'o Expr> {
+    obj.props.iter().find_map(|prop| match prop {
+        PropOrSpread::Prop(prop) => match **prop {
+            Prop::KeyValue(ref key_value) => match key_value.key {
+                PropName::Ident(ref ident) if ident.sym == *key => Some(key_value.value.as_ref()),
+                PropName::Str(ref s) if s.value == *key => Some(key_value.value.as_ref()),
+                _ => None,
+            },
+            _ => None,
+        },
+        _ => None,
+    })
+}
+
+/// Generates the `default` of a prop from the destructure default value.
+/// Non-literal values are wrapped in a factory function, e.g. `() => ([])`.
+/// Returns whether `skipFactory` is needed, which is the case for functions
+/// and identifiers when the prop type is not known.
+fn destructured_default_value(
+    value: &Expr,
+    inferred_types: Option<Vec<FervidAtom>>,
+) -> (Box<Expr>, bool) {
+    let mut unwrapped = value;
+    loop {
+        unwrapped = match unwrapped {
+            Expr::TsAs(e) => &e.expr,
+            Expr::TsTypeAssertion(e) => &e.expr,
+            Expr::TsNonNull(e) => &e.expr,
+            Expr::TsSatisfies(e) => &e.expr,
+            _ => break,
+        };
+    }
+
+    let is_function = matches!(unwrapped, Expr::Fn(_) | Expr::Arrow(_));
+    let is_literal = matches!(unwrapped, Expr::Lit(_) | Expr::Tpl(_));
+
+    let need_skip_factory = inferred_types.is_none() && (is_function || unwrapped.is_ident());
+    let need_factory_wrap = !need_skip_factory
+        && !is_literal
+        && !inferred_types.is_some_and(|types| types.iter().any(|t| t == "Function"));
+
+    if !need_factory_wrap {
+        return (Box::new(value.to_owned()), need_skip_factory);
+    }
+
+    // `() => (value)`
+    let factory = Box::new(Expr::Arrow(ArrowExpr {
+        span: DUMMY_SP,
+        params: vec![],
+        body: Box::new(BlockStmtOrExpr::Expr(Box::new(Expr::Paren(ParenExpr {
+            span: DUMMY_SP,
+            expr: Box::new(value.to_owned()),
+        })))),
+        is_async: false,
+        is_generator: false,
+        type_params: None,
+        return_type: None,
+    }));
+
+    (factory, need_skip_factory)
+}
+
+/// `__props.foo` for a destructured `foo`
+fn props_access(key: &FervidAtom, ident: &Ident) -> Box<Expr> {
+    Box::new(Expr::Member(create_member_access(
+        PROPS_HELPER.to_owned(),
+        key,
+        ident.span,
+    )))
+}
+
+fn prop_name(key: &FervidAtom) -> PropName {
+    if Ident::verify_symbol(key).is_ok() {
+        PropName::Ident(Ident {
+            span: DUMMY_SP,
+            sym: key.to_owned(),
+            optional: false,
+        })
+    } else {
+        PropName::Str(Str {
+            span: DUMMY_SP,
+            value: key.to_owned(),
+            raw: None,
+        })
+    }
+}
+
+fn ident_prop_name(key: &str) -> PropName {
+    PropName::Ident(Ident {
+        span: DUMMY_SP,
+        sym: FervidAtom::from(key),
+        optional: false,
+    })
+}
+
+fn key_value_prop(key: PropName, value: Box<Expr>) -> PropOrSpread {
+    PropOrSpread::Prop(Box::new(Prop::KeyValue(KeyValueProp { key, value })))
+}
+
+fn bool_expr(value: bool) -> Box<Expr> {
+    Box::new(Expr::Lit(Lit::Bool(Bool {
+        span: DUMMY_SP,
+        value,
+    })))
+}
+
+#[cfg(test)]
+mod tests {
+    use fervid_core::{fervid_atom, BindingTypes, SfcScriptBlock};
+    use swc_core::common::DUMMY_SP;
+
+    use crate::{
+        error::{ScriptError, ScriptErrorKind, TransformError},
+        script::{resolve_type::TypeResolveContext, setup::transform_and_record_script_setup},
+        test_utils::{parser::parse_typescript_module, to_str},
+        BindingsHelper, SetupBinding,
+    };
+
+    struct Compiled {
+        setup: String,
+        props: String,
+        bindings_helper: BindingsHelper,
+        errors: Vec<TransformError>,
+    }
+
+    fn compile(input: &str) -> Compiled {
+        let parsed = parse_typescript_module(input, 0, Default::default())
+            .expect("compile expects the input to be parseable")
+            .0;
+
+        let script_setup = SfcScriptBlock {
+            content: Box::new(parsed),
+            lang: fervid_core::SfcScriptLang::Typescript,
+            is_setup: true,
+            generic: None,
+            span: DUMMY_SP,
+        };
+
+        let mut ctx = TypeResolveContext::new("anonymous.vue".into(), Default::default());
+        ctx.record_scope(script_setup.content.body.iter(), None);
+
+        let mut bindings_helper = BindingsHelper::default();
+        let mut errors = Vec::new();
+        let result = transform_and_record_script_setup(
+            script_setup,
+            &mut ctx,
+            &mut bindings_helper,
+            &mut errors,
+        );
+
+        Compiled {
+            setup: result
+                .setup_fn
+                .and_then(|setup_fn| setup_fn.body)
+                .map_or_else(String::new, |body| to_str(&body)),
+            props: result
+                .sfc_object_helper
+                .props
+                .map_or_else(String::new, |props| to_str(&props)),
+            bindings_helper,
+            errors,
+        }
+    }
+
+    fn error_kinds(errors: &[TransformError]) -> Vec<&ScriptErrorKind> {
+        errors
+            .iter()
+            .filter_map(|e| match e {
+                TransformError::ScriptError(ScriptError { kind, .. }) => Some(kind),
+                _ => None,
+            })
+            .collect()
+    }
+
+    // https://github.com/vuejs/core/blob/main/packages/compiler-sfc/__tests__/compileScript/definePropsDestructure.spec.ts
+    #[test]
+    fn it_rewrites_references() {
+        let compiled = compile(
+            r"
+            const { foo, bar: baz } = defineProps(['foo', 'bar'])
+            console.log(foo, baz)
+            function test(foo) {
+                console.log(foo, baz)
+            }
+            const obj = { foo, baz }
+            ",
+        );
+
+        assert!(compiled.errors.is_empty());
+        assert_eq!(
+            compiled.setup,
+            "{console.log(__props.foo,__props.bar);function test(foo){console.log(foo,__props.bar);}const obj={foo:__props.foo,baz:__props.bar};}"
+        );
+        assert_eq!(compiled.props, r#"["foo","bar"]"#);
+        assert_eq!(
+            compiled.bindings_helper.setup_bindings,
+            vec![
+                SetupBinding(fervid_atom!("foo"), BindingTypes::Props),
+                SetupBinding(fervid_atom!("bar"), BindingTypes::Props),
+                SetupBinding(fervid_atom!("baz"), BindingTypes::PropsAliased),
+                SetupBinding(fervid_atom!("test"), BindingTypes::SetupConst),
+                SetupBinding(fervid_atom!("obj"), BindingTypes::SetupConst),
+            ]
+        );
+        assert_eq!(
+            compiled
+                .bindings_helper
+                .props_aliases
+                .get(&fervid_atom!("baz")),
+            Some(&fervid_atom!("bar"))
+        );
+    }
+
+    #[test]
+    fn it_respects_nested_scopes() {
+        let compiled = compile(
+            r"
+            const { foo } = defineProps(['foo'])
+            const a = (foo) => foo
+            const b = () => {
+                const foo = 1
+                return foo
+            }
+            for (const foo of []) { console.log(foo) }
+            try {} catch (foo) { console.log(foo) }
+            if (true) { console.log(foo) }
+            ",
+        );
+
+        assert!(compiled.errors.is_empty());
+        assert_eq!(
+            compiled.setup,
+            "{const a=foo=>foo;const b=()=>{const foo=1;return foo;};for(const foo of[]){console.log(foo);}try{}catch(foo){console.log(foo);}if(true){console.log(__props.foo);}}"
+        );
+    }
+
+    #[test]
+    fn it_merges_defaults_for_runtime_props() {
+        let compiled = compile(
+            r"
+            const { foo = 1, bar = {}, func = () => {}, ext = x } = defineProps(['foo', 'bar', 'func', 'ext'])
+            ",
+        );
+
+        assert!(compiled.errors.is_empty());
+        assert_eq!(compiled.setup, "{}");
+        assert_eq!(
+            compiled.props,
+            r#"_mergeDefaults(["foo","bar","func","ext"],{foo:1,bar:()=>({}),func:()=>{},__skip_func:true,ext:x,__skip_ext:true})"#
+        );
+    }
+
+    #[test]
+    fn it_adds_defaults_to_object_props() {
+        let compiled = compile(
+            r"
+            const { foo = 1, bar = {}, func = () => {} } = defineProps<{
+                foo?: number
+                bar?: object
+                func?: () => any
+            }>()
+            ",
+        );
+
+        assert!(compiled.errors.is_empty());
+        assert_eq!(
+            compiled.props,
+            "{foo:{type:Number,required:false,default:1},bar:{type:Object,required:false,default:()=>({})},func:{type:Function,required:false,default:()=>{}}}"
+        );
+
+        // Runtime object, including the props not declared
+        let compiled = compile(
+            r"
+            const { foo = 1, bar = 'bar' } = defineProps({ foo: Number, ...other })
+            ",
+        );
+
+        assert!(compiled.errors.is_empty());
+        assert_eq!(
+            compiled.props,
+            r#"_mergeDefaults({foo:{type:Number,default:1},...other},{bar:"bar"})"#
+        );
+    }
+
+    #[test]
+    fn it_creates_rest_proxy() {
+        let compiled = compile(
+            r"
+            const { foo, 'bar-baz': barBaz, ...rest } = defineProps(['foo', 'bar-baz', 'qux'])
+            console.log(barBaz, rest)
+            ",
+        );
+
+        assert!(compiled.errors.is_empty());
+        assert_eq!(
+            compiled.setup,
+            r#"{const rest=_createPropsRestProxy(__props,["foo","bar-baz"]);console.log(__props["bar-baz"],rest);}"#
+        );
+        assert!(compiled
+            .bindings_helper
+            .setup_bindings
+            .contains(&SetupBinding(
+                fervid_atom!("rest"),
+                BindingTypes::SetupReactiveConst
+            )));
+    }
+
+    #[test]
+    fn it_reports_misuse() {
+        let compiled = compile(
+            r"
+            import { watch as w, toRef } from 'vue'
+            const { foo, bar } = defineProps(['foo', 'bar'])
+            foo = 1
+            bar++
+            w(foo, () => {})
+            toRef(bar)
+            w(() => foo, () => {})
+            ",
+        );
+
+        assert!(matches!(
+            error_kinds(&compiled.errors)[..],
+            [
+                ScriptErrorKind::DefinePropsDestructureCannotAssign,
+                ScriptErrorKind::DefinePropsDestructureCannotAssign,
+                ScriptErrorKind::DefinePropsDestructurePassedDirectly,
+                ScriptErrorKind::DefinePropsDestructurePassedDirectly,
+            ]
+        ));
+
+        let compiled = compile("const { foo: { bar } } = defineProps(['foo'])");
+        assert!(matches!(
+            error_kinds(&compiled.errors)[..],
+            [ScriptErrorKind::DefinePropsDestructureNestedPattern]
+        ));
+
+        let compiled = compile("const { [foo]: bar } = defineProps(['foo'])");
+        assert!(matches!(
+            error_kinds(&compiled.errors)[..],
+            [ScriptErrorKind::DefinePropsDestructureComputedKey]
+        ));
+    }
+}
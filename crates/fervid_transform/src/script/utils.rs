@@ -1,9 +1,13 @@
 //! A collection of utils for working with SWC structs
 
 use fervid_core::{fervid_atom, FervidAtom};
-use swc_core::ecma::ast::{
-    ArrayLit, BlockStmt, Callee, Expr, ExprOrSpread, Function, Lit, Module, ModuleDecl, ModuleItem,
-    ObjectLit, Prop, PropName, PropOrSpread, ReturnStmt, Stmt, Tpl,
+use swc_core::{
+    common::{Span, DUMMY_SP},
+    ecma::ast::{
+        ArrayLit, BlockStmt, Callee, ComputedPropName, Expr, ExprOrSpread, Function, Ident, Lit,
+        MemberExpr, MemberProp, Module, ModuleDecl, ModuleItem, ObjectLit, Prop, PropName,
+        PropOrSpread, ReturnStmt, Stmt, Str, Tpl,
+    },
 };
 
 #[deprecated]
@@ -290,3 +294,34 @@ pub fn unroll_paren_seq(expr: &Expr) -> &Expr {
         _ => expr,
     }
 }
+
+/// Creates a property access, e.g. `__props.foo` or `__props["foo-bar"]`
+/// when the key is not a valid identifier
+pub fn create_member_access(obj: FervidAtom, key: &FervidAtom, span: Span) -> MemberExpr {
+    let prop = if Ident::verify_symbol(key).is_ok() {
+        MemberProp::Ident(Ident {
+            span,
+            sym: key.to_owned(),
+            optional: false,
+        })
+    } else {
+        MemberProp::Computed(ComputedPropName {
+            span,
+            expr: Box::new(Expr::Lit(Lit::Str(Str {
+                span,
+                value: key.to_owned(),
+                raw: None,
+            }))),
+        })
+    };
+
+    MemberExpr {
+        span,
+        obj: Box::new(Expr::Ident(Ident {
+            span: DUMMY_SP,
+            sym: obj,
+            optional: false,
+        })),
+        prop,
+    }
+}
@@ -4,6 +4,7 @@ use fervid_core::{
     BindingTypes, ComponentBinding, CustomDirectiveBinding, FervidAtom, SfcCustomBlock,
     SfcStyleBlock, SfcTemplateBlock, TemplateGenerationMode, VueImportsSet,
 };
+use fervid_css::{CssModulesMode, CssOutputOptions};
 use fxhash::FxHashMap as HashMap;
 use smallvec::SmallVec;
 use swc_core::ecma::{
@@ -11,6 +12,10 @@ use swc_core::ecma::{
     atoms::JsWord,
 };
 
+use crate::{
+    script::resolve_type::TypeResolveOptions, style::StylePreprocessor, template::AssetUrlOptions,
+};
+
 /// A helper which encompasses all the logic related to bindings,
 /// such as their types, which of them were used, what components and directives
 /// were seen in the template, etc.
@@ -24,10 +29,18 @@ pub struct BindingsHelper {
     pub is_prod: bool,
     /// Is Typescript or Javascript used
     pub is_ts: bool,
+    /// Whether literal constants of `<script setup>` are hoisted to the module scope
+    pub hoist_static: bool,
     /// Scopes of the `<template>` for in-template variable resolutions
     pub template_scopes: Vec<TemplateScope>,
+    /// The outermost template scope which had its variables referenced.
+    /// Used to find out whether component slots depend on `v-for` or `v-slot` variables
+    pub outermost_scope_ref: Option<u32>,
     /// Bindings in `<script setup>`
     pub setup_bindings: Vec<SetupBinding>,
+    /// Local aliases of the destructured props and the props they refer to,
+    /// e.g. `bar` -> `foo` for `const { foo: bar } = defineProps()`
+    pub props_aliases: HashMap<FervidAtom, FervidAtom>,
     /// Bindings in `<script>`
     pub options_api_bindings: Option<Box<OptionsApiBindings>>,
     /// The mode with which `<template>` variables are resolved.
@@ -43,6 +56,8 @@ pub struct BindingsHelper {
     pub vue_imports: VueImportsSet,
     /// User imports from `vue` package
     pub vue_resolved_imports: Box<VueResolvedImports>,
+    /// Paths of the asset URLs from `<template>`, imported as `_imports_N` by their index
+    pub asset_imports: Vec<FervidAtom>,
 }
 
 // Todo maybe use SmallVec?
@@ -93,6 +108,8 @@ pub struct VueResolvedImports {
     pub ref_import: Option<Id>,
     pub computed: Option<Id>,
     pub reactive: Option<Id>,
+    pub to_ref: Option<Id>,
+    pub watch: Option<Id>,
 }
 
 /// https://github.com/vuejs/rfcs/discussions/503
@@ -118,10 +135,22 @@ pub struct SfcExportedObjectHelper {
     pub models: Vec<SfcDefineModel>,
     /// `props` property
     pub props: Option<Box<Expr>>,
+    /// Props destructured from `defineProps`, e.g. `const { foo = 1 } = defineProps()`
+    pub props_destructure: Vec<PropsDestructureBinding>,
     /// Other fields of the object
     pub untyped_fields: Vec<PropOrSpread>,
 }
 
+/// A single prop from `const { foo: bar = 1 } = defineProps()`
+pub struct PropsDestructureBinding {
+    /// Name of the prop, `foo`
+    pub key: FervidAtom,
+    /// Local variable, `bar`
+    pub local: FervidAtom,
+    /// Default value, `1`
+    pub default: Option<Box<Expr>>,
+}
+
 pub struct TransformScriptsResult {
     /// EcmaScript module
     pub module: Box<Module>,
@@ -133,8 +162,27 @@ pub struct TransformScriptsResult {
 
 pub struct TransformSfcOptions<'s> {
     pub is_prod: bool,
+    /// Generate the code for server-side rendering
+    pub is_ssr: bool,
+    /// Hoist the literal constants of `<script setup>` out of `setup()`.
+    /// Only applies when there is no normal `<script>`.
+    pub hoist_static: bool,
     pub scope_id: &'s str,
     pub filename: &'s str,
+    /// Original SFC source, used for the source maps of the `<style>` blocks
+    pub source: &'s str,
+    /// Pattern for the names in `<style module>`, default is `[name]_[local]_[hash]`
+    pub css_modules_pattern: Option<&'s str>,
+    /// Whether names in `<style module>` are local or global by default
+    pub css_modules_mode: CssModulesMode,
+    /// Turns the asset URLs in `<template>`, e.g. `<img src="./logo.png">`, into imports
+    pub transform_asset_urls: Option<&'s AssetUrlOptions>,
+    /// Converts `<style lang="scss">` and other non-CSS blocks to CSS
+    pub style_preprocessor: Option<&'s dyn StylePreprocessor>,
+    /// Minification and lowering of the `<style>` blocks
+    pub css_output: CssOutputOptions,
+    /// Resolution of the types imported from other files, e.g. in `defineProps<Props>()`
+    pub type_resolve: TypeResolveOptions<'s>,
 }
 
 pub struct TransformSfcResult {
@@ -150,6 +198,16 @@ pub struct TransformSfcResult {
     pub template_block: Option<SfcTemplateBlock>,
     /// Transformed style blocks
     pub style_blocks: Vec<SfcStyleBlock>,
+    /// Source maps of the `style_blocks` by index when `css_output.source_map` is enabled
+    pub style_source_maps: Vec<Option<String>>,
+    /// Whether the `style_blocks` were compiled, by index
+    pub style_compiled: Vec<bool>,
+    /// `v-bind()`s of the `<style>` blocks for SSR, e.g. `{ "--abcd1234-color": _ctx.color }`.
+    /// The root nodes of `template_block` are bound to them as `v-bind="_cssVars"`.
+    pub ssr_css_vars: Option<Box<Expr>>,
     /// Custom blocks
     pub custom_blocks: Vec<SfcCustomBlock>,
+    /// Files other than the SFC it depends on: sources of the resolved types
+    /// and of the blocks imported with `src`
+    pub deps: Vec<String>,
 }
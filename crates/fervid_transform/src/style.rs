@@ -1,11 +1,28 @@
-use fervid_core::{fervid_atom, SfcStyleBlock};
+use fervid_core::{
+    fervid_atom, AttributeOrBinding, BuiltinType, ElementKind, ElementNode, FervidAtom, Node,
+    SfcStyleBlock, SfcTemplateBlock, TemplateGenerationMode, VBindDirective, VueImports,
+};
 use fervid_css::*;
 use swc_core::{
-    common::DUMMY_SP,
-    ecma::ast::{Expr, Ident, KeyValueProp, Lit, Prop, PropName, PropOrSpread, Str},
+    common::{Span, DUMMY_SP},
+    ecma::{
+        ast::{
+            ArrowExpr, BindingIdent, BlockStmt, BlockStmtOrExpr, CallExpr, Callee, EsVersion, Expr,
+            ExprOrSpread, ExprStmt, Function, Ident, KeyValueProp, Lit, MethodProp, ObjectLit,
+            Param, ParenExpr, Pat, Prop, PropName, PropOrSpread, ReturnStmt, Stmt, Str,
+        },
+        visit::{noop_visit_type, Visit, VisitWith},
+    },
 };
+use swc_ecma_parser::{lexer::Lexer, Parser, StringInput, Syntax};
 
-use crate::{error::TransformError, structs::TransformScriptsResult};
+use crate::{
+    atoms::{PROPS_HELPER, SETUP},
+    error::{ScriptError, ScriptErrorKind, TransformError},
+    structs::TransformScriptsResult,
+    template::expr_transform::BindingsHelperTransform,
+    BindingsHelper,
+};
 
 const CSS_PREFIX: &'static str = "data-v-";
 
@@ -36,44 +53,1109 @@ pub fn create_style_scope(file_hash: &str) -> String {
     scope
 }
 
+/// Adds `__cssModules: { $style: { red: "Comp_red_1a2b3c4d" } }`
+pub fn attach_css_modules(
+    transform_result: &mut TransformScriptsResult,
+    css_modules: &[(FervidAtom, CssModuleExports)],
+) {
+    if css_modules.is_empty() {
+        return;
+    }
+
+    let to_str = |value: &FervidAtom| Str {
+        span: DUMMY_SP,
+        value: value.to_owned(),
+        raw: None,
+    };
+
+    let modules = css_modules
+        .iter()
+        .map(|(module_name, exports)| {
+            let exports_obj = exports
+                .iter()
+                .map(|(local, generated)| {
+                    PropOrSpread::Prop(Box::new(Prop::KeyValue(KeyValueProp {
+                        key: PropName::Str(to_str(local)),
+                        value: Box::new(Expr::Lit(Lit::Str(to_str(generated)))),
+                    })))
+                })
+                .collect();
+
+            PropOrSpread::Prop(Box::new(Prop::KeyValue(KeyValueProp {
+                key: PropName::Str(to_str(module_name)),
+                value: Box::new(Expr::Object(ObjectLit {
+                    span: DUMMY_SP,
+                    props: exports_obj,
+                })),
+            })))
+        })
+        .collect();
+
+    transform_result
+        .export_obj
+        .props
+        .push(PropOrSpread::Prop(Box::new(Prop::KeyValue(KeyValueProp {
+            key: PropName::Ident(Ident {
+                span: DUMMY_SP,
+                sym: fervid_atom!("__cssModules"),
+                optional: false,
+            }),
+            value: Box::new(Expr::Object(ObjectLit {
+                span: DUMMY_SP,
+                props: modules,
+            })),
+        }))));
+}
+
+/// Converts `<style lang="scss">` and other non-CSS languages to CSS.
+///
+/// The output is then handled as a regular CSS block, e.g. scoped or renamed as CSS Modules.
+pub trait StylePreprocessor {
+    /// Preprocesses the `content` of the block, `lang` is e.g. `scss`, `less`, `stylus` or `postcss`.
+    /// Positions in the returned map are relative to the `content`.
+    fn preprocess(
+        &self,
+        lang: &str,
+        content: &str,
+        filename: &str,
+    ) -> Result<PreprocessedStyle, String>;
+}
+
+impl std::fmt::Debug for dyn StylePreprocessor + '_ {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("StylePreprocessor")
+    }
+}
+
+pub struct PreprocessedStyle {
+    pub code: String,
+    /// Source map of the preprocessor, if it produced one
+    pub map: Option<String>,
+}
+
+/// Runs the `preprocessor` on the `<style>` blocks which are not plain CSS.
+/// The successfully preprocessed blocks become `lang="css"`.
+///
+/// Returns the source maps of the preprocessor by block index.
+pub fn preprocess_style_blocks(
+    style_blocks: &mut [SfcStyleBlock],
+    preprocessor: &dyn StylePreprocessor,
+    filename: &str,
+    errors: &mut Vec<TransformError>,
+) -> Vec<Option<String>> {
+    let mut maps = Vec::with_capacity(style_blocks.len());
+
+    for style_block in style_blocks.iter_mut() {
+        if style_block.lang == "css" {
+            maps.push(None);
+            continue;
+        }
+
+        match preprocessor.preprocess(&style_block.lang, &style_block.content, filename) {
+            Ok(preprocessed) => {
+                style_block.content = preprocessed.code.into();
+                style_block.lang = fervid_atom!("css");
+                maps.push(preprocessed.map);
+            }
+            Err(message) => {
+                errors.push(TransformError::CssError(CssError {
+                    span: style_block.span,
+                    kind: CssErrorKind::PreprocessError(message),
+                }));
+                maps.push(None);
+            }
+        }
+    }
+
+    maps
+}
+
+/// What the source maps of the `<style>` blocks are generated against
+#[derive(Debug, Default, Clone, Copy)]
+pub struct StyleSourceMapOptions<'s> {
+    /// Original SFC source the block spans point into
+    pub source: &'s str,
+    pub filename: &'s str,
+    /// Maps of the preprocessors by block index, see [`preprocess_style_blocks`]
+    pub input_maps: &'s [Option<String>],
+}
+
+pub struct TransformStyleBlocksResult {
+    /// Whether there were any `<style scoped>` blocks
+    pub had_scoped_blocks: bool,
+    /// Exports of `<style module>` blocks by module name, e.g. `$style`.
+    /// Blocks with the same module name are merged.
+    pub css_modules: Vec<(FervidAtom, CssModuleExports)>,
+    /// Source maps by block index when `output.source_map` is enabled
+    pub source_maps: Vec<Option<String>>,
+    /// Whether the block was compiled, by block index.
+    /// Decided before the transform, as `v-bind()` is not in the content after it.
+    pub compiled: Vec<bool>,
+}
+
+/// Transforms the `<style>` blocks: adds the scope to scoped blocks, rewrites `v-bind()`
+/// and renames the local names in `<style module>` blocks.
+/// With `output` enabled, all the CSS blocks are also minified, lowered
+/// and/or mapped to the original SFC using `source_map`.
 pub fn transform_style_blocks(
     style_blocks: &mut Vec<SfcStyleBlock>,
     scope: &str,
+    css_vars: CssVarsOptions,
+    css_modules: CssModulesOptions,
+    output: CssOutputOptions,
+    source_map: StyleSourceMapOptions,
     errors: &mut Vec<TransformError>,
-) -> bool {
-    // Check work
-    if !style_blocks.iter().any(should_transform_style_block) {
-        return false;
+) -> TransformStyleBlocksResult {
+    let mut result = TransformStyleBlocksResult {
+        had_scoped_blocks: false,
+        css_modules: Vec::new(),
+        source_maps: Vec::new(),
+        compiled: style_blocks
+            .iter()
+            .map(|block| should_transform_style_block(block, &output))
+            .collect(),
+    };
+    if output.source_map {
+        result.source_maps.resize(style_blocks.len(), None);
     }
 
-    // TODO Config
-    // TODO Allow minifying CSS
+    // Check work
+    if !result.compiled.iter().any(|compiled| *compiled) {
+        return result;
+    }
 
     // Map errors from `fervid_css` to `fervid_transform`
     let mut css_errors = Vec::new();
 
-    for style_block in style_blocks.iter_mut() {
-        if style_block.is_scoped && style_block.lang == "css" {
-            let result = transform_css(
-                &style_block.content,
-                style_block.span,
-                Some(&scope),
-                &mut css_errors,
-                TransformCssConfig::default(),
-            );
+    for (idx, style_block) in style_blocks.iter_mut().enumerate() {
+        if !result.compiled[idx] {
+            continue;
+        }
+
+        result.had_scoped_blocks |= style_block.is_scoped;
+
+        let transform_result = transform_css(
+            &style_block.content,
+            style_block.span,
+            style_block.is_scoped.then_some(scope),
+            &mut css_errors,
+            TransformCssConfig {
+                css_vars: has_css_vars(style_block).then_some(css_vars),
+                css_modules: style_block.is_module.then_some(css_modules),
+                output,
+                source_map: output.source_map.then(|| CssSourceMapOptions {
+                    source: source_map.source,
+                    filename: source_map.filename,
+                    input_map: source_map.input_maps.get(idx).and_then(Option::as_deref),
+                }),
+                ..Default::default()
+            },
+        );
+
+        let Some(transformed) = transform_result else {
+            continue;
+        };
+        style_block.content = transformed.code.into();
+        if let Some(map) = result.source_maps.get_mut(idx) {
+            *map = transformed.map;
+        }
+
+        let Some(exports) = transformed.exports else {
+            continue;
+        };
+        let module_name = style_block
+            .module_name
+            .to_owned()
+            .unwrap_or_else(|| fervid_atom!("$style"));
 
-            if let Some(transformed) = result {
-                style_block.content = transformed.into();
+        match result
+            .css_modules
+            .iter_mut()
+            .find(|(name, _)| *name == module_name)
+        {
+            Some((_, existing)) => {
+                for export in exports {
+                    if !existing.iter().any(|(local, _)| *local == export.0) {
+                        existing.push(export);
+                    }
+                }
             }
+            None => result.css_modules.push((module_name, exports)),
         }
     }
 
     errors.extend(css_errors.into_iter().map(From::from));
 
-    true
+    result
 }
 
+/// Gets the `[name]` for CSS Modules from the file name, e.g. `Comp` for `src/Comp.vue`
+pub fn css_modules_name(filename: &str) -> String {
+    let file = filename.rsplit(['/', '\\']).next().unwrap_or(filename);
+    let stem = file.strip_suffix(".vue").unwrap_or(file);
+
+    stem.chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '_' || c == '-' {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect()
+}
+
+#[inline]
+pub fn should_transform_style_block(block: &SfcStyleBlock, output: &CssOutputOptions) -> bool {
+    block.lang == "css"
+        && (block.is_scoped || block.is_module || has_css_vars(block) || output.is_enabled())
+}
+
+/// Quick check if `v-bind()` may be used inside the `<style>` block
 #[inline]
-pub fn should_transform_style_block(block: &SfcStyleBlock) -> bool {
-    block.is_scoped && block.lang == "css"
+fn has_css_vars(block: &SfcStyleBlock) -> bool {
+    block.content.contains("v-bind")
+}
+
+/// Collects distinct `v-bind()` expressions from all the `<style>` blocks
+pub fn collect_css_vars(style_blocks: &[SfcStyleBlock]) -> Vec<CssVar> {
+    let mut result: Vec<CssVar> = Vec::new();
+
+    for style_block in style_blocks.iter().filter(|b| has_css_vars(b)) {
+        for css_var in parse_css_vars(&style_block.content, style_block.span) {
+            if !result.iter().any(|v| v.expr == css_var.expr) {
+                result.push(css_var);
+            }
+        }
+    }
+
+    result
+}
+
+/// Generates `{ "--abcd1234-color": _ctx.color }` for the SSR render function
+/// and binds it to the root elements and components as `v-bind="_cssVars"`,
+/// because `_useCssVars` does nothing on the server.
+/// Port of `ssrInjectCssVars` from the official compiler.
+pub fn inject_ssr_css_vars(
+    css_vars: &[CssVar],
+    options: CssVarsOptions,
+    template: &mut SfcTemplateBlock,
+    bindings_helper: &mut BindingsHelper,
+) -> Option<Box<Expr>> {
+    if css_vars.is_empty() {
+        return None;
+    }
+
+    let mut props = Vec::with_capacity(css_vars.len());
+    for css_var in css_vars {
+        // Malformed expressions are already reported by `inject_css_vars`
+        let Ok(mut expr) = parse_css_var_expr(&css_var.expr, css_var.span) else {
+            continue;
+        };
+
+        bindings_helper.transform_expr(&mut expr, 0);
+
+        let name = generate_css_var_name(options.id, &css_var.expr, options.is_prod);
+        props.push(PropOrSpread::Prop(Box::new(Prop::KeyValue(KeyValueProp {
+            key: PropName::Str(Str {
+                span: DUMMY_SP,
+                value: format!("--{}", escape_css_var_name(&name)).into(),
+                raw: None,
+            }),
+            value: expr,
+        }))));
+    }
+
+    for root in template.roots.iter_mut() {
+        match root {
+            Node::Element(element_node) => bind_ssr_css_vars(element_node),
+            Node::ConditionalSeq(conditional_seq) => {
+                bind_ssr_css_vars(&mut conditional_seq.if_node.node);
+                for else_if_node in conditional_seq.else_if_nodes.iter_mut() {
+                    bind_ssr_css_vars(&mut else_if_node.node);
+                }
+                if let Some(ref mut else_node) = conditional_seq.else_node {
+                    bind_ssr_css_vars(else_node);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    Some(Box::new(Expr::Object(ObjectLit {
+        span: DUMMY_SP,
+        props,
+    })))
+}
+
+/// Adds `v-bind="_cssVars"` to an element or a component, except the ones with `v-for`
+fn bind_ssr_css_vars(element_node: &mut ElementNode) {
+    let has_v_for = element_node
+        .starting_tag
+        .directives
+        .as_ref()
+        .map_or(false, |directives| directives.v_for.is_some());
+    if has_v_for || element_node.starting_tag.tag_name == "template" {
+        return;
+    }
+
+    match element_node.kind {
+        // Content of `<Suspense>` is rendered instead of it
+        ElementKind::Builtin(BuiltinType::Suspense) => {
+            for child in element_node.children.iter_mut() {
+                if let Node::Element(child_element) = child {
+                    bind_ssr_css_vars(child_element);
+                }
+            }
+        }
+        ElementKind::Builtin(BuiltinType::Slot) => {}
+        _ => element_node
+            .starting_tag
+            .attributes
+            .push(AttributeOrBinding::VBind(VBindDirective {
+                argument: None,
+                value: Box::new(Expr::Ident(Ident {
+                    span: DUMMY_SP,
+                    sym: fervid_atom!("_cssVars"),
+                    optional: false,
+                })),
+                is_camel: false,
+                is_prop: false,
+                is_attr: false,
+                span: DUMMY_SP,
+            })),
+    }
+}
+
+/// Injects `_useCssVars(_ctx => ({ "abcd1234-color": color.value }))` into `setup`.
+///
+/// Expressions are resolved the same way as in the inline template,
+/// e.g. refs get `.value` and props become `__props.foo`.
+/// When there is no `<script setup>`, everything is accessed from `_ctx`.
+pub fn inject_css_vars(
+    css_vars: &[CssVar],
+    options: CssVarsOptions,
+    transform_result: &mut TransformScriptsResult,
+    bindings_helper: &mut BindingsHelper,
+    errors: &mut Vec<TransformError>,
+) {
+    if css_vars.is_empty() {
+        return;
+    }
+
+    let is_setup = transform_result.setup_fn.is_some();
+
+    // `<script setup>` bindings are accessible directly,
+    // while Options API ones are only accessible from `_ctx`
+    let mut options_api_helper = BindingsHelper::default();
+    let helper = if is_setup {
+        &mut *bindings_helper
+    } else {
+        &mut options_api_helper
+    };
+    let prev_generation_mode = std::mem::replace(
+        &mut helper.template_generation_mode,
+        TemplateGenerationMode::Inline,
+    );
+
+    let mut props = Vec::with_capacity(css_vars.len());
+    for css_var in css_vars {
+        let mut expr = match parse_css_var_expr(&css_var.expr, css_var.span) {
+            Ok(expr) => expr,
+            Err(_) => {
+                errors.push(TransformError::ScriptError(ScriptError {
+                    span: css_var.span,
+                    kind: ScriptErrorKind::CssVarsMalformedExpression,
+                }));
+                continue;
+            }
+        };
+
+        helper.transform_expr(&mut expr, 0);
+
+        let name = generate_css_var_name(options.id, &css_var.expr, options.is_prod);
+        let raw = format!("\"{}\"", escape_css_var_name(&name));
+        props.push(PropOrSpread::Prop(Box::new(Prop::KeyValue(KeyValueProp {
+            key: PropName::Str(Str {
+                span: DUMMY_SP,
+                value: name.into(),
+                raw: Some(raw.into()),
+            }),
+            value: expr,
+        }))));
+    }
+
+    helper.template_generation_mode = prev_generation_mode;
+    bindings_helper.vue_imports |= VueImports::UseCssVars;
+
+    // `_useCssVars(_ctx => ({ /* vars */ }))`
+    let use_css_vars = Stmt::Expr(ExprStmt {
+        span: DUMMY_SP,
+        expr: Box::new(Expr::Call(CallExpr {
+            span: DUMMY_SP,
+            callee: Callee::Expr(Box::new(Expr::Ident(Ident {
+                span: DUMMY_SP,
+                sym: VueImports::UseCssVars.as_atom(),
+                optional: false,
+            }))),
+            args: vec![ExprOrSpread {
+                spread: None,
+                expr: Box::new(Expr::Arrow(ArrowExpr {
+                    span: DUMMY_SP,
+                    params: vec![ident_pat(fervid_atom!("_ctx"))],
+                    body: Box::new(BlockStmtOrExpr::Expr(Box::new(Expr::Paren(ParenExpr {
+                        span: DUMMY_SP,
+                        expr: Box::new(Expr::Object(ObjectLit {
+                            span: DUMMY_SP,
+                            props,
+                        })),
+                    })))),
+                    is_async: false,
+                    is_generator: false,
+                    type_params: None,
+                    return_type: None,
+                })),
+            }],
+            type_args: None,
+        })),
+    });
+
+    // `<script setup>`: put as the first statement of `setup`
+    if let Some(ref mut setup_fn) = transform_result.setup_fn {
+        // Props may be used only in `<style>`
+        if setup_fn.params.is_empty() && uses_props_helper(&use_css_vars) {
+            setup_fn.params.push(Param {
+                span: DUMMY_SP,
+                decorators: vec![],
+                pat: ident_pat(PROPS_HELPER.to_owned()),
+            });
+        }
+
+        let body = setup_fn.body.get_or_insert_with(|| BlockStmt {
+            span: DUMMY_SP,
+            stmts: vec![],
+        });
+        body.stmts.insert(0, use_css_vars);
+        return;
+    }
+
+    // Options API: put into the existing `setup` or create a new one
+    let existing_setup = transform_result
+        .export_obj
+        .props
+        .iter_mut()
+        .find_map(|prop| match prop {
+            PropOrSpread::Prop(prop) => match prop.as_mut() {
+                Prop::Method(method) if is_setup_key(&method.key) => Some(prop),
+                Prop::KeyValue(key_value) if is_setup_key(&key_value.key) => Some(prop),
+                _ => None,
+            },
+            PropOrSpread::Spread(_) => None,
+        });
+
+    match existing_setup.map(|prop| prop.as_mut()) {
+        // `setup() { _useCssVars(/*...*/); /*...*/ }`
+        Some(Prop::Method(method)) => {
+            let body = method.function.body.get_or_insert_with(|| BlockStmt {
+                span: DUMMY_SP,
+                stmts: vec![],
+            });
+            body.stmts.insert(0, use_css_vars);
+        }
+
+        // `setup: (props, ctx) => { _useCssVars(/*...*/); return (original)(props, ctx) }`
+        Some(Prop::KeyValue(key_value)) => {
+            let props_ident = fervid_atom!("props");
+            let ctx_ident = fervid_atom!("ctx");
+
+            let original_setup = std::mem::replace(
+                &mut key_value.value,
+                Box::new(Expr::Invalid(swc_core::ecma::ast::Invalid {
+                    span: DUMMY_SP,
+                })),
+            );
+            let call_original = Stmt::Return(ReturnStmt {
+                span: DUMMY_SP,
+                arg: Some(Box::new(Expr::Call(CallExpr {
+                    span: DUMMY_SP,
+                    callee: Callee::Expr(Box::new(Expr::Paren(ParenExpr {
+                        span: DUMMY_SP,
+                        expr: original_setup,
+                    }))),
+                    args: vec![
+                        ExprOrSpread {
+                            spread: None,
+                            expr: Box::new(ident_expr(props_ident.to_owned())),
+                        },
+                        ExprOrSpread {
+                            spread: None,
+                            expr: Box::new(ident_expr(ctx_ident.to_owned())),
+                        },
+                    ],
+                    type_args: None,
+                }))),
+            });
+
+            key_value.value = Box::new(Expr::Arrow(ArrowExpr {
+                span: DUMMY_SP,
+                params: vec![ident_pat(props_ident), ident_pat(ctx_ident)],
+                body: Box::new(BlockStmtOrExpr::BlockStmt(BlockStmt {
+                    span: DUMMY_SP,
+                    stmts: vec![use_css_vars, call_original],
+                })),
+                is_async: false,
+                is_generator: false,
+                type_params: None,
+                return_type: None,
+            }));
+        }
+
+        // `setup() { _useCssVars(/*...*/) }`
+        _ => {
+            transform_result
+                .export_obj
+                .props
+                .push(PropOrSpread::Prop(Box::new(Prop::Method(MethodProp {
+                    key: PropName::Ident(Ident {
+                        span: DUMMY_SP,
+                        sym: SETUP.to_owned(),
+                        optional: false,
+                    }),
+                    function: Box::new(Function {
+                        params: vec![],
+                        decorators: vec![],
+                        span: DUMMY_SP,
+                        body: Some(BlockStmt {
+                            span: DUMMY_SP,
+                            stmts: vec![use_css_vars],
+                        }),
+                        is_generator: false,
+                        is_async: false,
+                        type_params: None,
+                        return_type: None,
+                    }),
+                }))));
+        }
+    }
+}
+
+/// Parses the expression inside `v-bind()`
+fn parse_css_var_expr(expr: &str, span: Span) -> Result<Box<Expr>, swc_ecma_parser::error::Error> {
+    let lexer = Lexer::new(
+        Syntax::Es(Default::default()),
+        EsVersion::EsNext,
+        StringInput::new(
+            expr,
+            span.lo,
+            span.lo + swc_core::common::BytePos(expr.len() as u32),
+        ),
+        None,
+    );
+
+    let mut parser = Parser::new_from(lexer);
+    let parsed = parser.parse_expr()?;
+
+    // Recoverable errors are also not allowed
+    match parser.take_errors().into_iter().next() {
+        Some(e) => Err(e),
+        None => Ok(parsed),
+    }
+}
+
+fn is_setup_key(key: &PropName) -> bool {
+    match key {
+        PropName::Ident(ident) => ident.sym == *SETUP,
+        PropName::Str(s) => s.value == *SETUP,
+        _ => false,
+    }
+}
+
+fn uses_props_helper(stmt: &Stmt) -> bool {
+    struct PropsHelperVisitor(bool);
+
+    impl Visit for PropsHelperVisitor {
+        noop_visit_type!();
+
+        fn visit_ident(&mut self, n: &Ident) {
+            self.0 |= n.sym == *PROPS_HELPER;
+        }
+    }
+
+    let mut visitor = PropsHelperVisitor(false);
+    stmt.visit_with(&mut visitor);
+    visitor.0
+}
+
+fn ident_expr(sym: fervid_core::FervidAtom) -> Expr {
+    Expr::Ident(Ident {
+        span: DUMMY_SP,
+        sym,
+        optional: false,
+    })
+}
+
+fn ident_pat(sym: fervid_core::FervidAtom) -> Pat {
+    Pat::Ident(BindingIdent {
+        id: Ident {
+            span: DUMMY_SP,
+            sym,
+            optional: false,
+        },
+        type_ann: None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use fervid_core::{BindingTypes, StartingTag};
+    use swc_core::{
+        common::{BytePos, SyntaxContext},
+        ecma::ast::Module,
+    };
+
+    use super::*;
+    use crate::{test_utils::to_str, SetupBinding};
+
+    #[test]
+    fn it_injects_css_vars_into_script_setup() {
+        let mut bindings_helper = BindingsHelper {
+            setup_bindings: vec![
+                SetupBinding(fervid_atom!("color"), BindingTypes::SetupRef),
+                SetupBinding(fervid_atom!("theme"), BindingTypes::SetupConst),
+                SetupBinding(fervid_atom!("size"), BindingTypes::Props),
+            ],
+            ..Default::default()
+        };
+
+        let mut transform_result = scripts_result(Some(Box::new(Function {
+            params: vec![],
+            decorators: vec![],
+            span: DUMMY_SP,
+            body: Some(BlockStmt {
+                span: DUMMY_SP,
+                stmts: vec![],
+            }),
+            is_generator: false,
+            is_async: false,
+            type_params: None,
+            return_type: None,
+        })));
+
+        inject(
+            ".a { color: v-bind(color); background: v-bind('theme.primary'); font-size: v-bind(size) }",
+            &mut transform_result,
+            &mut bindings_helper,
+        );
+
+        let setup_fn = transform_result.setup_fn.expect("setup should exist");
+        assert_eq!(setup_fn.params.len(), 1);
+        assert_eq!(
+            to_str(&setup_fn.body.expect("setup should have body").stmts[0]),
+            "_useCssVars(_ctx=>({\"abcd1234-color\":color.value,\"abcd1234-theme.primary\":theme.primary,\"abcd1234-size\":__props.size}));"
+        );
+        assert!(bindings_helper.vue_imports.contains(VueImports::UseCssVars));
+    }
+
+    #[test]
+    fn it_injects_css_vars_into_options_api() {
+        let mut bindings_helper = BindingsHelper::default();
+        let mut transform_result = scripts_result(None);
+
+        inject(
+            ".a { color: v-bind(color) }",
+            &mut transform_result,
+            &mut bindings_helper,
+        );
+
+        assert_eq!(
+            to_str(&transform_result.export_obj),
+            "{setup(){_useCssVars(_ctx=>({\"abcd1234-color\":_ctx.color}));}}"
+        );
+    }
+
+    #[test]
+    fn it_injects_ssr_css_vars() {
+        let mut bindings_helper = BindingsHelper {
+            setup_bindings: vec![SetupBinding(fervid_atom!("color"), BindingTypes::SetupRef)],
+            ..Default::default()
+        };
+
+        let element = |tag_name: &str, kind: ElementKind| {
+            Node::Element(ElementNode {
+                kind,
+                starting_tag: StartingTag {
+                    tag_name: tag_name.into(),
+                    attributes: vec![],
+                    directives: None,
+                },
+                children: vec![],
+                template_scope: 0,
+                patch_hints: Default::default(),
+                span: DUMMY_SP,
+            })
+        };
+        let mut template = SfcTemplateBlock {
+            lang: fervid_atom!("html"),
+            roots: vec![
+                element("div", ElementKind::Element),
+                element("Comp", ElementKind::Component),
+                element("slot", ElementKind::Builtin(BuiltinType::Slot)),
+            ],
+            span: DUMMY_SP,
+        };
+
+        let style = ".a { color: v-bind(color); background: v-bind('theme.primary') }";
+        let ssr_css_vars = inject_ssr_css_vars(
+            &collect_css_vars(&[style_block(style)]),
+            CssVarsOptions {
+                id: "abcd1234",
+                is_prod: false,
+            },
+            &mut template,
+            &mut bindings_helper,
+        )
+        .expect("should have vars");
+
+        assert_eq!(
+            to_str(&ssr_css_vars),
+            r#"{"--abcd1234-color":$setup.color,"--abcd1234-theme\\.primary":_ctx.theme.primary}"#
+        );
+
+        // Elements and components are bound to the vars, `<slot>` is not
+        let bound_attrs: Vec<usize> = template
+            .roots
+            .iter()
+            .map(|root| match root {
+                Node::Element(element_node) => element_node.starting_tag.attributes.len(),
+                _ => 0,
+            })
+            .collect();
+        assert_eq!(bound_attrs, vec![1, 1, 0]);
+        let Node::Element(ref div) = template.roots[0] else {
+            unreachable!()
+        };
+        assert!(matches!(
+            &div.starting_tag.attributes[0],
+            AttributeOrBinding::VBind(VBindDirective { argument: None, value, .. })
+                if to_str(value) == "_cssVars"
+        ));
+    }
+
+    #[test]
+    fn it_attaches_css_modules() {
+        let style_block = |content: &str, module_name: Option<FervidAtom>| SfcStyleBlock {
+            lang: fervid_atom!("css"),
+            content: content.into(),
+            is_scoped: false,
+            is_module: true,
+            module_name,
+            span: Span::new(
+                BytePos(1),
+                BytePos(1 + content.len() as u32),
+                SyntaxContext::empty(),
+            ),
+        };
+        let mut style_blocks = vec![
+            style_block(".red { color: red }", None),
+            style_block(".blue { color: blue }", None),
+            style_block(".big { font-size: 2em }", Some(fervid_atom!("classes"))),
+        ];
+
+        let mut errors = Vec::new();
+        let result = transform_style_blocks(
+            &mut style_blocks,
+            "data-v-abcd1234",
+            CssVarsOptions {
+                id: "abcd1234",
+                is_prod: false,
+            },
+            CssModulesOptions {
+                pattern: "[name]_[local]",
+                mode: CssModulesMode::Local,
+                name: &css_modules_name("src/components/My Comp.vue"),
+                id: "abcd1234",
+            },
+            Default::default(),
+            Default::default(),
+            &mut errors,
+        );
+        assert!(errors.is_empty());
+        assert!(!result.had_scoped_blocks);
+        assert_eq!(&*style_blocks[0].content, ".My_Comp_red{color:red}");
+
+        let mut transform_result = scripts_result(None);
+        attach_css_modules(&mut transform_result, &result.css_modules);
+        assert_eq!(
+            to_str(&transform_result.export_obj),
+            "{__cssModules:{\"$style\":{\"red\":\"My_Comp_red\",\"blue\":\"My_Comp_blue\"},\"classes\":{\"big\":\"My_Comp_big\"}}}"
+        );
+    }
+
+    #[test]
+    fn it_preprocesses_style_blocks() {
+        /// Replaces `$color` with `red`, but only understands `lang="scss"`
+        struct TestPreprocessor;
+
+        impl StylePreprocessor for TestPreprocessor {
+            fn preprocess(
+                &self,
+                lang: &str,
+                content: &str,
+                filename: &str,
+            ) -> Result<PreprocessedStyle, String> {
+                assert_eq!(filename, "Comp.vue");
+                match lang {
+                    "scss" => Ok(PreprocessedStyle {
+                        code: content.replace("$color", "red"),
+                        map: None,
+                    }),
+                    _ => Err(format!("Unknown lang {lang}")),
+                }
+            }
+        }
+
+        let style_block = |lang: FervidAtom, content: &str| SfcStyleBlock {
+            lang,
+            content: content.into(),
+            is_scoped: true,
+            is_module: false,
+            module_name: None,
+            span: Span::new(
+                BytePos(1),
+                BytePos(1 + content.len() as u32),
+                SyntaxContext::empty(),
+            ),
+        };
+        let mut style_blocks = vec![
+            style_block(fervid_atom!("scss"), ".a { color: $color }"),
+            style_block(fervid_atom!("css"), ".b { color: $color }"),
+            style_block(fervid_atom!("less"), ".c { color: @color }"),
+        ];
+
+        let mut errors = Vec::new();
+        preprocess_style_blocks(&mut style_blocks, &TestPreprocessor, "Comp.vue", &mut errors);
+        assert_eq!(errors.len(), 1);
+        assert!(matches!(
+            errors[0],
+            TransformError::CssError(CssError {
+                kind: CssErrorKind::PreprocessError(_),
+                ..
+            })
+        ));
+        assert_eq!(&*style_blocks[0].lang, "css");
+        assert_eq!(&*style_blocks[1].content, ".b { color: $color }");
+        assert_eq!(&*style_blocks[2].lang, "less");
+
+        let mut errors = Vec::new();
+        let result = transform_style_blocks(
+            &mut style_blocks,
+            "data-v-abcd1234",
+            CssVarsOptions {
+                id: "abcd1234",
+                is_prod: false,
+            },
+            CssModulesOptions {
+                pattern: DEFAULT_CSS_MODULES_PATTERN,
+                mode: CssModulesMode::Local,
+                name: "Comp",
+                id: "abcd1234",
+            },
+            Default::default(),
+            Default::default(),
+            &mut errors,
+        );
+        assert!(result.had_scoped_blocks);
+        assert_eq!(&*style_blocks[0].content, ".a[data-v-abcd1234]{color:red}");
+        assert_eq!(&*style_blocks[2].content, ".c { color: @color }");
+    }
+
+    #[test]
+    fn it_reports_css_vars_blocks_as_compiled() {
+        let mut style_blocks = vec![
+            style_block(".a { color: v-bind(color) }"),
+            style_block(".b { color: red }"),
+        ];
+
+        let mut errors = Vec::new();
+        let result = transform_style_blocks(
+            &mut style_blocks,
+            "data-v-abcd1234",
+            CssVarsOptions {
+                id: "abcd1234",
+                is_prod: false,
+            },
+            CssModulesOptions {
+                pattern: DEFAULT_CSS_MODULES_PATTERN,
+                mode: CssModulesMode::Local,
+                name: "Comp",
+                id: "abcd1234",
+            },
+            Default::default(),
+            Default::default(),
+            &mut errors,
+        );
+        assert!(errors.is_empty());
+
+        // `v-bind()` is no longer in the content, but the block was compiled
+        assert_eq!(&*style_blocks[0].content, ".a{color:var(--abcd1234-color)}");
+        assert_eq!(result.compiled, vec![true, false]);
+    }
+
+    #[test]
+    fn it_minifies_non_scoped_style_blocks() {
+        let content = ".a { color: #FFFFFF; color: red }";
+        let mut style_blocks = vec![SfcStyleBlock {
+            lang: fervid_atom!("css"),
+            content: content.into(),
+            is_scoped: false,
+            is_module: false,
+            module_name: None,
+            span: Span::new(
+                BytePos(1),
+                BytePos(1 + content.len() as u32),
+                SyntaxContext::empty(),
+            ),
+        }];
+
+        let output = CssOutputOptions {
+            minify: true,
+            ..Default::default()
+        };
+        assert!(!should_transform_style_block(&style_blocks[0], &Default::default()));
+        assert!(should_transform_style_block(&style_blocks[0], &output));
+
+        let mut errors = Vec::new();
+        transform_style_blocks(
+            &mut style_blocks,
+            "data-v-abcd1234",
+            CssVarsOptions {
+                id: "abcd1234",
+                is_prod: false,
+            },
+            CssModulesOptions {
+                pattern: DEFAULT_CSS_MODULES_PATTERN,
+                mode: CssModulesMode::Local,
+                name: "Comp",
+                id: "abcd1234",
+            },
+            output,
+            Default::default(),
+            &mut errors,
+        );
+        assert!(errors.is_empty());
+        assert_eq!(&*style_blocks[0].content, ".a{color:red}");
+    }
+
+    #[test]
+    fn it_maps_style_blocks_to_the_sfc() {
+        let source = "<template></template>\n<style>.a { color: red }</style>\n<style lang=\"scss\">.b { color: $c }</style>";
+        let style_block = |lang: FervidAtom, content: &str| {
+            let lo = source.find(content).expect("content is in source") as u32 + 1;
+            SfcStyleBlock {
+                lang,
+                content: content.into(),
+                is_scoped: false,
+                is_module: false,
+                module_name: None,
+                span: Span::new(
+                    BytePos(lo),
+                    BytePos(lo + content.len() as u32),
+                    SyntaxContext::empty(),
+                ),
+            }
+        };
+        let mut style_blocks = vec![
+            style_block(fervid_atom!("css"), ".a { color: red }"),
+            style_block(fervid_atom!("scss"), ".b { color: $c }"),
+        ];
+        let output = CssOutputOptions {
+            source_map: true,
+            ..Default::default()
+        };
+
+        let mut errors = Vec::new();
+        let result = transform_style_blocks(
+            &mut style_blocks,
+            "data-v-abcd1234",
+            CssVarsOptions {
+                id: "abcd1234",
+                is_prod: false,
+            },
+            CssModulesOptions {
+                pattern: DEFAULT_CSS_MODULES_PATTERN,
+                mode: CssModulesMode::Local,
+                name: "Comp",
+                id: "abcd1234",
+            },
+            output,
+            StyleSourceMapOptions {
+                source,
+                filename: "Comp.vue",
+                input_maps: &[],
+            },
+            &mut errors,
+        );
+        assert!(errors.is_empty());
+        assert_eq!(result.source_maps.len(), 2);
+        assert_eq!(&*style_blocks[0].content, ".a{color:red}");
+
+        // Mapped to the second line, after `<style>`
+        let map = result.source_maps[0].as_deref().expect("should have a map");
+        assert!(map.contains(r#""sources":["Comp.vue"]"#));
+        assert!(map.contains(r#""mappings":"AACO"#));
+
+        // Not CSS, not transformed
+        assert!(result.source_maps[1].is_none());
+    }
+
+    fn inject(
+        style: &str,
+        transform_result: &mut TransformScriptsResult,
+        bindings_helper: &mut BindingsHelper,
+    ) {
+        let style_blocks = vec![style_block(style)];
+
+        let mut errors = Vec::new();
+        inject_css_vars(
+            &collect_css_vars(&style_blocks),
+            CssVarsOptions {
+                id: "abcd1234",
+                is_prod: false,
+            },
+            transform_result,
+            bindings_helper,
+            &mut errors,
+        );
+        assert!(errors.is_empty());
+    }
+
+    fn style_block(style: &str) -> SfcStyleBlock {
+        SfcStyleBlock {
+            lang: fervid_atom!("css"),
+            content: style.into(),
+            is_scoped: false,
+            is_module: false,
+            module_name: None,
+            span: Span::new(
+                BytePos(1),
+                BytePos(1 + style.len() as u32),
+                SyntaxContext::empty(),
+            ),
+        }
+    }
+
+    fn scripts_result(setup_fn: Option<Box<Function>>) -> TransformScriptsResult {
+        TransformScriptsResult {
+            module: Box::new(Module {
+                span: DUMMY_SP,
+                body: vec![],
+                shebang: None,
+            }),
+            export_obj: ObjectLit {
+                span: DUMMY_SP,
+                props: vec![],
+            },
+            setup_fn,
+        }
+    }
 }
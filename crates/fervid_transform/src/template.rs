@@ -1,11 +1,13 @@
 //! Handles template AST transformations.
 
+mod asset_urls;
 mod ast_transform;
 mod collect_vars;
-mod expr_transform;
+pub(crate) mod expr_transform;
 mod js_builtins;
 mod resolutions;
 mod v_on;
 mod utils;
 
+pub use asset_urls::{transform_asset_urls, AssetUrlOptions};
 pub use ast_transform::transform_and_record_template;
@@ -0,0 +1,644 @@
+//! Turns the static asset URLs in the `<template>` into imports,
+//! e.g. `<img src="./logo.png">` gets `src: _imports_0` and `import _imports_0 from './logo.png'`.
+//!
+//! https://github.com/vuejs/core/blob/v3.4.21/packages/compiler-sfc/src/template/transformAssetUrl.ts
+//! https://github.com/vuejs/core/blob/v3.4.21/packages/compiler-sfc/src/template/transformSrcset.ts
+
+use fervid_core::{
+    fervid_atom, AttributeOrBinding, ElementNode, FervidAtom, Node, SfcTemplateBlock, StrOrExpr,
+    VBindDirective,
+};
+use swc_core::{
+    common::Span,
+    ecma::ast::{BinExpr, BinaryOp, Expr, Ident, Lit, Str},
+};
+
+use crate::BindingsHelper;
+
+/// Which attributes of which tags hold the asset URLs
+#[derive(Debug, Clone)]
+pub struct AssetUrlOptions {
+    /// When set, relative URLs are rewritten to start with `base` instead of being imported
+    pub base: Option<FervidAtom>,
+    /// Also import the absolute URLs, e.g. `/logo.png`
+    pub include_absolute: bool,
+    /// Tag name and its attribute names, `*` matches any tag
+    pub tags: Vec<(FervidAtom, Vec<FervidAtom>)>,
+}
+
+impl Default for AssetUrlOptions {
+    fn default() -> Self {
+        Self {
+            base: None,
+            include_absolute: false,
+            tags: vec![
+                (
+                    fervid_atom!("video"),
+                    vec![fervid_atom!("src"), fervid_atom!("poster")],
+                ),
+                (fervid_atom!("source"), vec![fervid_atom!("src")]),
+                (fervid_atom!("img"), vec![fervid_atom!("src")]),
+                (
+                    fervid_atom!("image"),
+                    vec![fervid_atom!("xlink:href"), fervid_atom!("href")],
+                ),
+                (
+                    fervid_atom!("use"),
+                    vec![fervid_atom!("xlink:href"), fervid_atom!("href")],
+                ),
+            ],
+        }
+    }
+}
+
+impl AssetUrlOptions {
+    fn is_asset_attribute(&self, tag_name: &str, attr_name: &str) -> bool {
+        self.tags.iter().any(|(tag, attrs)| {
+            (tag == tag_name || tag == "*") && attrs.iter().any(|attr| attr == attr_name)
+        })
+    }
+
+    fn should_import(&self, url: &str) -> bool {
+        !is_external_url(url)
+            && !is_data_url(url)
+            && (self.include_absolute || is_relative_url(url))
+    }
+}
+
+/// New value of an asset attribute
+enum AssetUrl {
+    /// Plain string, e.g. when `base` is used
+    Rewritten(FervidAtom),
+    /// Expression using the imports, e.g. `_imports_0`
+    Imported(Box<Expr>),
+}
+
+/// Replaces the asset URLs with `_imports_N` and records the imported paths
+/// into [`BindingsHelper::asset_imports`].
+///
+/// Must run after the template expressions were transformed,
+/// so that `_imports_N` is neither prefixed nor considered dynamic.
+pub fn transform_asset_urls(
+    template: &mut SfcTemplateBlock,
+    options: &AssetUrlOptions,
+    bindings_helper: &mut BindingsHelper,
+) {
+    for node in template.roots.iter_mut() {
+        visit_node(node, options, &mut bindings_helper.asset_imports);
+    }
+}
+
+fn visit_node(node: &mut Node, options: &AssetUrlOptions, imports: &mut Vec<FervidAtom>) {
+    match node {
+        Node::Element(element_node) => visit_element_node(element_node, options, imports),
+        Node::ConditionalSeq(seq) => {
+            visit_element_node(&mut seq.if_node.node, options, imports);
+            for else_if in seq.else_if_nodes.iter_mut() {
+                visit_element_node(&mut else_if.node, options, imports);
+            }
+            if let Some(ref mut else_node) = seq.else_node {
+                visit_element_node(else_node, options, imports);
+            }
+        }
+        _ => {}
+    }
+}
+
+fn visit_element_node(
+    element_node: &mut ElementNode,
+    options: &AssetUrlOptions,
+    imports: &mut Vec<FervidAtom>,
+) {
+    let tag_name = &element_node.starting_tag.tag_name;
+    let is_srcset_tag = tag_name == "img" || tag_name == "source";
+
+    for attr in element_node.starting_tag.attributes.iter_mut() {
+        let AttributeOrBinding::RegularAttribute { name, value, span } = attr else {
+            continue;
+        };
+
+        let asset_url = if is_srcset_tag && name == "srcset" {
+            transform_srcset(value, *span, options, imports)
+        } else if options.is_asset_attribute(tag_name, name) {
+            transform_asset_url(value, *span, options, imports)
+        } else {
+            None
+        };
+
+        match asset_url {
+            Some(AssetUrl::Rewritten(url)) => *value = url,
+            Some(AssetUrl::Imported(expr)) => {
+                let span = *span;
+                *attr = AttributeOrBinding::VBind(VBindDirective {
+                    argument: Some(StrOrExpr::Str(name.to_owned())),
+                    value: expr,
+                    is_camel: false,
+                    is_prop: false,
+                    is_attr: false,
+                    span,
+                });
+            }
+            None => {}
+        }
+    }
+
+    for child in element_node.children.iter_mut() {
+        visit_node(child, options, imports);
+    }
+}
+
+fn transform_asset_url(
+    value: &str,
+    span: Span,
+    options: &AssetUrlOptions,
+    imports: &mut Vec<FervidAtom>,
+) -> Option<AssetUrl> {
+    if value.is_empty()
+        || is_external_url(value)
+        || is_data_url(value)
+        || value.starts_with('#')
+        || (!options.include_absolute && !is_relative_url(value))
+    {
+        return None;
+    }
+
+    let (path, hash) = parse_url(value);
+
+    // Explicit base: relative URLs become absolute
+    if let Some(ref base) = options.base {
+        if value.starts_with('.') {
+            let (host, base_path) = split_base(base);
+            let joined = join_path(base_path, &format!("{path}{hash}"));
+            return Some(AssetUrl::Rewritten(format!("{host}{joined}").into()));
+        }
+    }
+
+    if path.is_empty() {
+        return Some(AssetUrl::Imported(str_expr("", span)));
+    }
+
+    let mut expr = import_expr(path, span, imports);
+    if !hash.is_empty() {
+        expr = Box::new(Expr::Bin(BinExpr {
+            span,
+            op: BinaryOp::Add,
+            left: expr,
+            right: str_expr(hash, span),
+        }));
+    }
+
+    Some(AssetUrl::Imported(expr))
+}
+
+/// `<img srcset="./a.png 2x, ./b.png 3x">` becomes `_imports_0 + ' 2x, ' + _imports_1 + ' 3x'`
+fn transform_srcset(
+    value: &str,
+    span: Span,
+    options: &AssetUrlOptions,
+    imports: &mut Vec<FervidAtom>,
+) -> Option<AssetUrl> {
+    if value.is_empty() {
+        return None;
+    }
+
+    let mut candidates: Vec<(String, Option<String>)> = value
+        .split(',')
+        .map(|candidate| {
+            let normalized = candidate.split_ascii_whitespace().collect::<Vec<_>>();
+            let url = normalized.first().copied().unwrap_or_default().to_owned();
+            let descriptor = normalized.get(1).map(|d| (*d).to_owned());
+            (url, descriptor)
+        })
+        .collect();
+
+    // Data URLs contain a comma after the encoding, re-merge them
+    let mut idx = 0;
+    while idx + 1 < candidates.len() {
+        if is_data_url(&candidates[idx].0) {
+            let (url, _) = candidates.remove(idx);
+            candidates[idx].0 = format!("{url},{}", candidates[idx].0);
+        }
+        idx += 1;
+    }
+
+    // When srcset does not contain any qualified URLs, skip transforming
+    if !candidates.iter().any(|(url, _)| options.should_import(url)) {
+        return None;
+    }
+
+    if let Some(ref base) = options.base {
+        let (host, base_path) = split_base(base);
+        let mut set = Vec::with_capacity(candidates.len());
+        let mut needs_import = false;
+
+        for (url, descriptor) in candidates.iter() {
+            let descriptor = descriptor
+                .as_ref()
+                .map_or_else(String::new, |d| format!(" {d}"));
+
+            if url.starts_with('.') {
+                set.push(format!("{host}{}{descriptor}", join_path(base_path, url)));
+            } else if options.should_import(url) {
+                needs_import = true;
+            } else {
+                set.push(format!("{url}{descriptor}"));
+            }
+        }
+
+        if !needs_import {
+            return Some(AssetUrl::Rewritten(set.join(", ").into()));
+        }
+    }
+
+    let last_idx = candidates.len() - 1;
+    let mut parts: Vec<Box<Expr>> = Vec::with_capacity(candidates.len() * 2);
+    for (idx, (url, descriptor)) in candidates.iter().enumerate() {
+        if options.should_import(url) {
+            let (path, _) = parse_url(url);
+            if !path.is_empty() {
+                parts.push(import_expr(path, span, imports));
+            }
+        } else {
+            parts.push(str_expr(url, span));
+        }
+
+        let is_not_last = idx != last_idx;
+        match (descriptor, is_not_last) {
+            (Some(descriptor), true) => parts.push(str_expr(&format!(" {descriptor}, "), span)),
+            (Some(descriptor), false) => parts.push(str_expr(&format!(" {descriptor}"), span)),
+            (None, true) => parts.push(str_expr(", ", span)),
+            (None, false) => {}
+        }
+    }
+
+    parts
+        .into_iter()
+        .reduce(|left, right| {
+            Box::new(Expr::Bin(BinExpr {
+                span,
+                op: BinaryOp::Add,
+                left,
+                right,
+            }))
+        })
+        .map(AssetUrl::Imported)
+}
+
+/// Gets `_imports_N` for the `path`, adding the import if it is new
+fn import_expr(path: &str, span: Span, imports: &mut Vec<FervidAtom>) -> Box<Expr> {
+    let path = decode_uri_component(path);
+    let idx = match imports.iter().position(|import| *import == *path) {
+        Some(idx) => idx,
+        None => {
+            imports.push(path.into());
+            imports.len() - 1
+        }
+    };
+
+    Box::new(Expr::Ident(Ident {
+        span,
+        sym: format!("_imports_{idx}").into(),
+        optional: false,
+    }))
+}
+
+fn str_expr(value: &str, span: Span) -> Box<Expr> {
+    Box::new(Expr::Lit(Lit::Str(Str {
+        span,
+        value: value.into(),
+        raw: None,
+    })))
+}
+
+/// `./`, `~` and `@` (aliases) are relative
+#[inline]
+fn is_relative_url(url: &str) -> bool {
+    matches!(url.as_bytes().first(), Some(b'.' | b'~' | b'@'))
+}
+
+/// `https://`, `http://` and `//`
+#[inline]
+fn is_external_url(url: &str) -> bool {
+    url.starts_with("//") || url.starts_with("http://") || url.starts_with("https://")
+}
+
+#[inline]
+fn is_data_url(url: &str) -> bool {
+    url.trim_start()
+        .get(..5)
+        .map_or(false, |scheme| scheme.eq_ignore_ascii_case("data:"))
+}
+
+/// Splits the URL into path and `#hash`. `~` is stripped, e.g. `~@/a.png` becomes `@/a.png`
+fn parse_url(url: &str) -> (&str, &str) {
+    let url = match url.strip_prefix('~') {
+        Some(rest) => rest.strip_prefix('/').unwrap_or(rest),
+        None => url,
+    };
+
+    match url.find('#') {
+        Some(idx) => url.split_at(idx),
+        None => (url, ""),
+    }
+}
+
+/// Splits `https://cdn.com/assets/` into `https://cdn.com` and `/assets/`
+fn split_base(base: &str) -> (&str, &str) {
+    let host_end = if let Some(idx) = base.find("://") {
+        idx + 3
+    } else if base.starts_with("//") {
+        2
+    } else {
+        return ("", if base.is_empty() { "/" } else { base });
+    };
+
+    match base[host_end..].find('/') {
+        Some(idx) => base.split_at(host_end + idx),
+        None => (base, "/"),
+    }
+}
+
+/// Same as `path.posix.join(base, path)`
+fn join_path(base: &str, path: &str) -> String {
+    let joined = format!("{base}/{path}");
+    let is_absolute = joined.starts_with('/');
+    let has_trailing_slash = joined.ends_with('/');
+
+    let mut segments: Vec<&str> = Vec::new();
+    for segment in joined.split('/') {
+        match segment {
+            "" | "." => {}
+            ".." if matches!(segments.last(), Some(last) if *last != "..") => {
+                segments.pop();
+            }
+            ".." if is_absolute => {}
+            _ => segments.push(segment),
+        }
+    }
+
+    let mut result = segments.join("/");
+    if is_absolute {
+        result.insert(0, '/');
+    }
+    if has_trailing_slash && !result.ends_with('/') {
+        result.push('/');
+    }
+    if result.is_empty() {
+        result.push('.');
+    }
+
+    result
+}
+
+/// Decodes `%XX` sequences, invalid ones are kept as-is
+fn decode_uri_component(input: &str) -> String {
+    if !input.contains('%') {
+        return input.to_owned();
+    }
+
+    let bytes = input.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut idx = 0;
+    while idx < bytes.len() {
+        let hex = bytes
+            .get(idx + 1..idx + 3)
+            .filter(|_| bytes[idx] == b'%')
+            .and_then(|hex| std::str::from_utf8(hex).ok())
+            .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+
+        match hex {
+            Some(byte) => {
+                decoded.push(byte);
+                idx += 3;
+            }
+            None => {
+                decoded.push(bytes[idx]);
+                idx += 1;
+            }
+        }
+    }
+
+    String::from_utf8(decoded).unwrap_or_else(|_| input.to_owned())
+}
+
+#[cfg(test)]
+mod tests {
+    use swc_core::common::DUMMY_SP;
+
+    use crate::test_utils::{element, regular_attribute, to_str};
+
+    use super::*;
+
+    /// Transforms a single `<tag>` and returns its attributes as `name=value`
+    fn transform(
+        tag_name: &str,
+        attributes: &[(&str, &str)],
+        options: &AssetUrlOptions,
+    ) -> (Vec<String>, Vec<FervidAtom>) {
+        let mut template = SfcTemplateBlock {
+            lang: fervid_atom!("html"),
+            roots: vec![Node::Element(element(
+                "div",
+                vec![],
+                vec![Node::Element(element(
+                    tag_name,
+                    attributes
+                        .iter()
+                        .map(|(name, value)| regular_attribute(name, value))
+                        .collect(),
+                    vec![],
+                ))],
+            ))],
+            span: DUMMY_SP,
+        };
+        let mut bindings_helper = BindingsHelper::default();
+        transform_asset_urls(&mut template, options, &mut bindings_helper);
+
+        let Node::Element(ref root) = template.roots[0] else {
+            unreachable!()
+        };
+        let Node::Element(ref element) = root.children[0] else {
+            unreachable!()
+        };
+
+        let attributes = element
+            .starting_tag
+            .attributes
+            .iter()
+            .map(|attr| match attr {
+                AttributeOrBinding::RegularAttribute { name, value, .. } => {
+                    format!("{name}={value}")
+                }
+                AttributeOrBinding::VBind(VBindDirective {
+                    argument: Some(StrOrExpr::Str(name)),
+                    value,
+                    ..
+                }) => format!(":{name}={}", to_str(value)),
+                _ => unreachable!(),
+            })
+            .collect();
+
+        (attributes, bindings_helper.asset_imports)
+    }
+
+    #[test]
+    fn it_imports_relative_urls() {
+        let options = AssetUrlOptions::default();
+
+        let (attrs, imports) = transform(
+            "img",
+            &[("src", "./logo.png"), ("alt", "./logo.png")],
+            &options,
+        );
+        assert_eq!(attrs, vec![":src=_imports_0", "alt=./logo.png"]);
+        assert_eq!(imports, vec!["./logo.png"]);
+
+        let (attrs, imports) = transform(
+            "video",
+            &[
+                ("src", "~/assets/a.mp4"),
+                ("poster", "@/assets/a%20b.png#frag"),
+            ],
+            &options,
+        );
+        assert_eq!(
+            attrs,
+            vec![":src=_imports_0", r##":poster=_imports_1+"#frag""##]
+        );
+        assert_eq!(imports, vec!["assets/a.mp4", "@/assets/a b.png"]);
+
+        let (attrs, imports) = transform("use", &[("href", "./sprite.svg#icon")], &options);
+        assert_eq!(attrs, vec![r##":href=_imports_0+"#icon""##]);
+        assert_eq!(imports, vec!["./sprite.svg"]);
+    }
+
+    #[test]
+    fn it_skips_non_asset_urls() {
+        let options = AssetUrlOptions::default();
+
+        for url in [
+            "/logo.png",
+            "https://example.com/logo.png",
+            "//example.com/logo.png",
+            "data:image/png;base64,AAAA",
+            "#icon",
+            "",
+        ] {
+            let (attrs, imports) = transform("img", &[("src", url)], &options);
+            assert_eq!(attrs, vec![format!("src={url}")]);
+            assert!(imports.is_empty());
+        }
+
+        // Not in `tags`
+        let (attrs, _) = transform("div", &[("src", "./logo.png")], &options);
+        assert_eq!(attrs, vec!["src=./logo.png"]);
+
+        // Absolute with `include_absolute`
+        let options = AssetUrlOptions {
+            include_absolute: true,
+            ..Default::default()
+        };
+        let (attrs, imports) = transform("img", &[("src", "/logo.png")], &options);
+        assert_eq!(attrs, vec![":src=_imports_0"]);
+        assert_eq!(imports, vec!["/logo.png"]);
+    }
+
+    #[test]
+    fn it_uses_custom_tags() {
+        let options = AssetUrlOptions {
+            tags: vec![
+                (fervid_atom!("my-image"), vec![fervid_atom!("image")]),
+                (fervid_atom!("*"), vec![fervid_atom!("data-src")]),
+            ],
+            ..Default::default()
+        };
+
+        let (attrs, _) = transform(
+            "my-image",
+            &[("image", "./a.png"), ("data-src", "./b.png")],
+            &options,
+        );
+        assert_eq!(attrs, vec![":image=_imports_0", ":data-src=_imports_1"]);
+
+        let (attrs, _) = transform("img", &[("src", "./a.png")], &options);
+        assert_eq!(attrs, vec!["src=./a.png"]);
+    }
+
+    #[test]
+    fn it_rewrites_relative_urls_with_base() {
+        let options = AssetUrlOptions {
+            base: Some(fervid_atom!("/foo/")),
+            ..Default::default()
+        };
+        let (attrs, imports) = transform("img", &[("src", "./logo.png")], &options);
+        assert_eq!(attrs, vec!["src=/foo/logo.png"]);
+        assert!(imports.is_empty());
+
+        // Aliases are still imported
+        let (attrs, imports) = transform("img", &[("src", "@/logo.png")], &options);
+        assert_eq!(attrs, vec![":src=_imports_0"]);
+        assert_eq!(imports, vec!["@/logo.png"]);
+
+        let options = AssetUrlOptions {
+            base: Some(fervid_atom!("https://cdn.com/assets")),
+            ..Default::default()
+        };
+        let (attrs, _) = transform("img", &[("src", "../logo.png#a")], &options);
+        assert_eq!(attrs, vec!["src=https://cdn.com/logo.png#a"]);
+    }
+
+    #[test]
+    fn it_transforms_srcset() {
+        let options = AssetUrlOptions::default();
+
+        let (attrs, imports) = transform(
+            "img",
+            &[(
+                "srcset",
+                "./a.png 2x,  ./b.png\t3x, https://example.com/c.png 4x",
+            )],
+            &options,
+        );
+        assert_eq!(
+            attrs,
+            vec![
+                r#":srcset=_imports_0+" 2x, "+_imports_1+" 3x, "+"https://example.com/c.png"+" 4x""#
+            ]
+        );
+        assert_eq!(imports, vec!["./a.png", "./b.png"]);
+
+        let (attrs, imports) = transform("source", &[("srcset", "./a.png, ./a.png")], &options);
+        assert_eq!(attrs, vec![r#":srcset=_imports_0+", "+_imports_0"#]);
+        assert_eq!(imports, vec!["./a.png"]);
+
+        // Data URL contains a comma
+        let (attrs, _) = transform(
+            "img",
+            &[("srcset", "data:image/png;base64,AAAA 1x, ./a.png 2x")],
+            &options,
+        );
+        assert_eq!(
+            attrs,
+            vec![r#":srcset="data:image/png;base64,AAAA"+" 1x, "+_imports_0+" 2x""#]
+        );
+
+        // Nothing to import
+        let (attrs, _) = transform("img", &[("srcset", "/a.png 2x")], &options);
+        assert_eq!(attrs, vec!["srcset=/a.png 2x"]);
+
+        // Base
+        let options = AssetUrlOptions {
+            base: Some(fervid_atom!("/foo/")),
+            ..Default::default()
+        };
+        let (attrs, _) = transform(
+            "img",
+            &[("srcset", "./a.png 2x, https://example.com/c.png 4x")],
+            &options,
+        );
+        assert_eq!(
+            attrs,
+            vec!["srcset=/foo/a.png 2x, https://example.com/c.png 4x"]
+        );
+    }
+}
@@ -1,13 +1,13 @@
 use fervid_core::{
     check_attribute_name, fervid_atom, is_from_default_slot, is_html_tag, AttributeOrBinding,
     BindingTypes, BuiltinType, Conditional, ConditionalNodeSequence, ElementKind, ElementNode,
-    FervidAtom, Interpolation, Node, PatchFlags, SfcTemplateBlock, StartingTag, StrOrExpr,
-    TemplateGenerationMode, VBindDirective, VSlotDirective, VUE_BUILTINS,
+    FervidAtom, Interpolation, Node, PatchFlags, PatchHints, SfcTemplateBlock, StartingTag,
+    StrOrExpr, TemplateGenerationMode, VBindDirective, VSlotDirective, VueDirectives, VUE_BUILTINS,
 };
 use smallvec::SmallVec;
 use swc_core::{
     common::DUMMY_SP,
-    ecma::ast::{Bool, Expr, Ident, Lit},
+    ecma::ast::{Bool, Expr, Ident, Lit, Number},
 };
 
 use crate::{BindingsHelper, TemplateScope};
@@ -35,12 +35,12 @@ pub fn transform_and_record_template(
 
     // Merge more than 1 child into a separate `<template>` element so that Fragment gets generated.
     // #11: Do this only when all children are `TextNode`s.
-    if template.roots.len() > 1
+    let is_root_fragment = template.roots.len() > 1
         && !template
             .roots
             .iter()
-            .all(|r| matches!(r, Node::Text(_, _) | Node::Interpolation(_)))
-    {
+            .all(|r| matches!(r, Node::Text(_, _) | Node::Interpolation(_)));
+    if is_root_fragment {
         let all_roots = std::mem::replace(&mut template.roots, Vec::with_capacity(1));
         let new_root = Node::Element(ElementNode {
             kind: ElementKind::Element,
@@ -63,8 +63,31 @@ pub fn transform_and_record_template(
         v_for_scope: false,
     };
 
+    let mut branch_key = 0;
     for node in template.roots.iter_mut() {
         node.visit_mut_with(&mut template_visitor);
+
+        if let Node::ConditionalSeq(conditional_seq) = node {
+            inject_branch_keys(conditional_seq, &mut branch_key);
+        }
+    }
+
+    // https://github.com/vuejs/core/blob/v3.4.21/packages/compiler-core/src/transform.ts (`createRootCodegen`)
+    // Root with multiple nodes is a stable fragment block.
+    // In DEV, a fragment of a single node with the rest being comments is marked as well.
+    if is_root_fragment {
+        if let Some(Node::Element(root)) = template.roots.first_mut() {
+            root.patch_hints.flags = PatchFlags::StableFragment.into();
+
+            let non_comment_count = root
+                .children
+                .iter()
+                .filter(|child| !matches!(child, Node::Comment(_, _)))
+                .count();
+            if !template_visitor.bindings_helper.is_prod && non_comment_count == 1 {
+                root.patch_hints.flags |= PatchFlags::DevRootFragment;
+            }
+        }
     }
 }
 
@@ -211,7 +234,13 @@ fn optimize_children(children: &mut Vec<Node>, element_kind: ElementKind) {
                 continue;
             }
 
-            // No directives, just push the child
+            // No conditional directives, just push the child
+            let child = match child {
+                Node::Element(child_element) => {
+                    Node::Element(optimize_template_v_for(child_element))
+                }
+                _ => child,
+            };
             finish_seq!(child);
         }
 
@@ -270,6 +299,145 @@ fn optimize_v_if_plus_v_for(mut parent: ElementNode) -> ElementNode {
     child
 }
 
+// `<template v-for>` with a single element child does not need a Fragment for each item,
+// the child is rendered instead, and the `key` is moved to it.
+// https://github.com/vuejs/core/blob/v3.4.21/packages/compiler-core/src/transforms/vFor.ts#L172
+fn optimize_template_v_for(mut parent: ElementNode) -> ElementNode {
+    if parent.starting_tag.tag_name != "template" {
+        return parent;
+    }
+
+    // Parent must only have `v-for` and at most a `key`
+    let has_only_v_for = parent.starting_tag.directives.as_ref().map_or(false, |d| {
+        d.v_for.is_some()
+            && d.custom.is_empty()
+            && d.v_memo.is_none()
+            && d.v_once.is_none()
+            && d.v_slot.is_none()
+    });
+    let has_only_key = parent
+        .starting_tag
+        .attributes
+        .iter()
+        .all(|attr| check_attribute_name(attr, "key"));
+    if !has_only_v_for || !has_only_key {
+        return parent;
+    }
+
+    // There must be exactly one element child, whitespace does not count
+    let mut non_whitespace_children = parent
+        .children
+        .iter()
+        .enumerate()
+        .filter(|(_, child)| !matches!(child, Node::Text(text, _) if text.trim().is_empty()));
+    let child_idx = match (
+        non_whitespace_children.next(),
+        non_whitespace_children.next(),
+    ) {
+        (Some((idx, Node::Element(child))), None)
+            if child.starting_tag.tag_name != "template"
+                && child.starting_tag.tag_name != "slot"
+                && child.starting_tag.directives.as_ref().map_or(true, |d| {
+                    d.v_for.is_none()
+                        && d.v_if.is_none()
+                        && d.v_else_if.is_none()
+                        && d.v_else.is_none()
+                }) =>
+        {
+            idx
+        }
+        _ => return parent,
+    };
+
+    let Node::Element(mut child) = parent.children.swap_remove(child_idx) else {
+        unreachable!()
+    };
+
+    // Move `v-for` and `key`
+    let Some(mut parent_directives) = parent.starting_tag.directives.take() else {
+        unreachable!()
+    };
+    child
+        .starting_tag
+        .directives
+        .get_or_insert_with(Default::default)
+        .v_for = parent_directives.v_for.take();
+
+    let child_has_key = child
+        .starting_tag
+        .attributes
+        .iter()
+        .any(|attr| check_attribute_name(attr, "key"));
+    if let (Some(key), false) = (parent.starting_tag.attributes.pop(), child_has_key) {
+        child.starting_tag.attributes.insert(0, key);
+    }
+
+    child
+}
+
+/// Adds a `key` to each branch of the conditional sequence,
+/// so that the runtime does not patch one branch into another.
+/// The keys continue from the previous sequences of the same parent.
+/// https://github.com/vuejs/core/blob/v3.4.21/packages/compiler-core/src/transforms/vIf.ts#L79
+fn inject_branch_keys(conditional_seq: &mut ConditionalNodeSequence, next_key: &mut u32) {
+    let mut inject = |element_node: &mut ElementNode| {
+        let key = *next_key;
+        *next_key += 1;
+
+        if let Some(ref mut directives) = element_node.starting_tag.directives {
+            // Conditional slots are not rendered as branches
+            if directives.v_slot.is_some() {
+                return;
+            }
+
+            // `v-for` Fragment gets the key, not its items
+            if let Some(ref mut v_for) = directives.v_for {
+                v_for.fragment_key = Some(key);
+                return;
+            }
+        }
+
+        // `<template v-if>` is rendered as a Fragment
+        if element_node.starting_tag.tag_name == "template" {
+            element_node.patch_hints.flags |= PatchFlags::StableFragment;
+        }
+
+        // User-defined key takes precedence
+        let has_key = element_node
+            .starting_tag
+            .attributes
+            .iter()
+            .any(|attr| check_attribute_name(attr, "key"));
+        if has_key {
+            return;
+        }
+
+        element_node.starting_tag.attributes.insert(
+            0,
+            AttributeOrBinding::VBind(VBindDirective {
+                argument: Some(StrOrExpr::Str(fervid_atom!("key"))),
+                value: Box::new(Expr::Lit(Lit::Num(Number {
+                    span: DUMMY_SP,
+                    value: key.into(),
+                    raw: None,
+                }))),
+                is_camel: false,
+                is_prop: false,
+                is_attr: false,
+                span: DUMMY_SP,
+            }),
+        );
+    };
+
+    inject(&mut conditional_seq.if_node.node);
+    for else_if_node in conditional_seq.else_if_nodes.iter_mut() {
+        inject(&mut else_if_node.node);
+    }
+    if let Some(ref mut else_node) = conditional_seq.else_node {
+        inject(else_node);
+    }
+}
+
 trait Visitor {
     fn visit_element_node(&mut self, element_node: &mut ElementNode);
     fn visit_conditional_node(&mut self, conditional_node: &mut ConditionalNodeSequence);
@@ -290,6 +458,10 @@ impl<'a> Visitor for TemplateVisitor<'_> {
         let is_component = matches!(element_kind, ElementKind::Component);
         element_node.kind = element_kind;
 
+        // Vue built-ins (except `<slot>`) are components when it comes to props and slots
+        let is_component_like = is_component
+            || matches!(element_kind, ElementKind::Builtin(builtin) if !matches!(builtin, BuiltinType::Slot));
+
         if is_component {
             self.maybe_resolve_component(&element_node.starting_tag.tag_name);
         }
@@ -308,6 +480,10 @@ impl<'a> Visitor for TemplateVisitor<'_> {
         let mut ref_key = Option::<FervidAtom>::None;
         let mut should_use_block = false;
 
+        // Scopes which exist outside of this element's slots.
+        // Referencing their variables makes the component slots dynamic.
+        let mut outer_scopes_len = self.bindings_helper.template_scopes.len() as u32;
+
         // Check if there is a scoping directive.
         // Find a `v-for` or `v-slot` directive when in ElementNode
         // and collect their variables into the new template scope
@@ -328,6 +504,7 @@ impl<'a> Visitor for TemplateVisitor<'_> {
             // Collect `v-for` bindings
             if let Some(v_for) = v_for {
                 self.v_for_scope = true;
+                outer_scopes_len = scope_to_use + 1;
 
                 // Get the iterator variable and collect its variables
                 let mut scope = &mut self.bindings_helper.template_scopes[scope_to_use as usize];
@@ -381,6 +558,9 @@ impl<'a> Visitor for TemplateVisitor<'_> {
         element_node.template_scope = scope_to_use;
         self.current_scope = scope_to_use;
 
+        // Start tracking the scope references of this element and its children
+        let parent_scope_ref = self.bindings_helper.outermost_scope_ref.take();
+
         // TODO Refactor the directives transformation logic
         // and maybe the Visitor as well
 
@@ -444,7 +624,7 @@ impl<'a> Visitor for TemplateVisitor<'_> {
 
                     // Adding `class` and `style` bindings depends on `is_component`
                     // They are added to PROPS for the components.
-                    if is_component {
+                    if is_component_like {
                         patch_hints.flags |= PatchFlags::Props;
                         patch_hints.props.push(argument.to_owned());
                         continue;
@@ -470,28 +650,33 @@ impl<'a> Visitor for TemplateVisitor<'_> {
                         should_use_block = true;
                     }
 
-                    self.transform_v_on(v_on, scope_to_use);
-
-                    // TODO Transform the event name beforehand (?) and make sure the condition is 100% the same
-                    // https://github.com/vuejs/core/blob/f1068fc60ca511f68ff0aaedcc18b39124791d29/packages/compiler-core/src/transforms/transformElement.ts#L430
-                    if let Some(StrOrExpr::Str(evt_name)) = v_on.event.as_ref() {
-                        let has_v_node = evt_name.starts_with("vue:");
-
-                        // TODO Adjust condition due to the latest transformation changes
-                        if (!is_component
-                            || matches!(element_kind, ElementKind::Builtin(BuiltinType::Component)))
-                            && evt_name != "click"
-                            && evt_name != "update:modelValue"
-                            && evt_name != "update:model-value"
-                            && !has_v_node
-                        {
-                            has_hydration_event_binding = true;
-                        }
-
-                        has_vnode_hook |= has_v_node;
-                    } else {
-                        // https://github.com/vuejs/core/blob/f1068fc60ca511f68ff0aaedcc18b39124791d29/packages/compiler-core/src/transforms/transformElement.ts#L605
+                    let is_dynamic_handler = self.transform_v_on(v_on, element_kind, scope_to_use);
+
+                    // https://github.com/vuejs/core/blob/v3.4.21/packages/compiler-core/src/transforms/transformElement.ts#L511
+                    let Some(StrOrExpr::Str(evt_name)) = v_on.event.as_ref() else {
+                        // `v-on="handlers"` or `@[event]="handler"`
                         has_dynamic_keys = true;
+                        continue;
+                    };
+
+                    // Omit the flag for click handlers because hydration gives click
+                    // a dedicated fast path, as well as for `v-model` handlers and `onVnodeXXX` hooks.
+                    // `<component :is>` may render an element, thus it is not omitted.
+                    let is_vnode_hook = evt_name.starts_with("onVnode");
+                    let is_dynamic_component =
+                        matches!(element_kind, ElementKind::Builtin(BuiltinType::Component));
+                    if (!is_component_like || is_dynamic_component)
+                        && !evt_name.eq_ignore_ascii_case("onclick")
+                        && evt_name != "onUpdate:modelValue"
+                        && !is_vnode_hook
+                    {
+                        has_hydration_event_binding = true;
+                    }
+                    has_vnode_hook |= is_vnode_hook;
+
+                    // Cached handlers and constants do not change
+                    if is_dynamic_handler {
+                        add_dynamic_prop(patch_hints, evt_name);
                     }
                 }
 
@@ -558,8 +743,35 @@ impl<'a> Visitor for TemplateVisitor<'_> {
             maybe_transform!(v_text);
 
             for v_model in directives.v_model.iter_mut() {
-                self.bindings_helper
-                    .transform_v_model(v_model, scope_to_use, patch_hints);
+                if is_component_like {
+                    self.bindings_helper
+                        .transform_v_model(v_model, scope_to_use, patch_hints);
+                } else {
+                    // Native `v-model` is a runtime directive,
+                    // its value is not passed as a `modelValue` prop
+                    self.bindings_helper.transform_v_model(
+                        v_model,
+                        scope_to_use,
+                        &mut PatchHints::default(),
+                    );
+                    has_runtime_directives = true;
+                }
+
+                // Update handler is a prop as well
+                if !v_model.is_handler_cacheable {
+                    if let Some(StrOrExpr::Str(ref argument)) = v_model.argument {
+                        let mut handler_name = String::from("onUpdate:");
+                        handler_name.push_str(argument);
+                        add_dynamic_prop(patch_hints, &FervidAtom::from(handler_name));
+                    } else if v_model.argument.is_none() {
+                        add_dynamic_prop(patch_hints, &fervid_atom!("onUpdate:modelValue"));
+                    }
+                }
+            }
+
+            // `v-show` is a runtime directive
+            if directives.v_show.is_some() {
+                has_runtime_directives = true;
             }
 
             // https://github.com/vuejs/core/blob/ee4cd78a06e6aa92b12564e527d131d1064c2cd0/packages/compiler-core/src/transforms/transformElement.ts#L700
@@ -598,9 +810,14 @@ impl<'a> Visitor for TemplateVisitor<'_> {
         let mut has_dynamic_interpolation = false;
 
         // Recursively visit children
+        let mut branch_key = 0;
         for child in element_node.children.iter_mut() {
             child.visit_mut_with(self);
 
+            if let Node::ConditionalSeq(conditional_seq) = child {
+                inject_branch_keys(conditional_seq, &mut branch_key);
+            }
+
             match child {
                 // When Elements are present, TEXT patch flag does not apply
                 Node::Element(_) | Node::ConditionalSeq(_) => {
@@ -645,9 +862,48 @@ impl<'a> Visitor for TemplateVisitor<'_> {
         }
         self.v_for_scope = old_v_for_scope;
 
+        // Component slots are dynamic when they are conditional, looped or have a dynamic name,
+        // or when they reference the `v-for` or `v-slot` variables from outside
+        // https://github.com/vuejs/core/blob/v3.4.21/packages/compiler-core/src/transforms/vSlot.ts#L121
+        let scope_ref = self.bindings_helper.outermost_scope_ref;
+        if is_component_like
+            && has_children
+            && !matches!(
+                element_kind,
+                ElementKind::Builtin(BuiltinType::KeepAlive | BuiltinType::Teleport)
+            )
+            && (scope_ref.map_or(false, |it| it < outer_scopes_len)
+                || has_dynamic_slot_templates(&element_node.starting_tag, &element_node.children))
+        {
+            patch_hints.flags |= PatchFlags::DynamicSlots;
+        }
+
+        // Restore the scope references tracking
+        self.bindings_helper.outermost_scope_ref = match (parent_scope_ref, scope_ref) {
+            (Some(a), Some(b)) => Some(a.min(b)),
+            (a, b) => a.or(b),
+        };
+
+        // `<template v-for>` is rendered as a Fragment for each item
+        if element_node.starting_tag.tag_name == "template"
+            && matches!(element_kind, ElementKind::Element)
+            && element_node
+                .starting_tag
+                .directives
+                .as_ref()
+                .map_or(false, |d| d.v_for.is_some())
+        {
+            patch_hints.flags |= PatchFlags::StableFragment;
+        }
+
         // Apply other flags
         // https://github.com/vuejs/core/blob/ee4cd78a06e6aa92b12564e527d131d1064c2cd0/packages/compiler-core/src/transforms/transformElement.ts#L732
-        if !has_dynamic_keys && has_hydration_event_binding {
+        // From docs: [FULL_PROPS is] exclusive with CLASS, STYLE and PROPS.
+        if has_dynamic_keys || patch_hints.flags.contains(PatchFlags::FullProps) {
+            patch_hints.flags &= !(PatchFlags::Props | PatchFlags::Class | PatchFlags::Style);
+            patch_hints.flags |= PatchFlags::FullProps;
+            patch_hints.props.clear();
+        } else if has_hydration_event_binding {
             patch_hints.flags |= PatchFlags::NeedHydration;
         }
         if !should_use_block
@@ -662,6 +918,8 @@ impl<'a> Visitor for TemplateVisitor<'_> {
             patch_hints.flags |= PatchFlags::Text;
         }
 
+        patch_hints.should_use_block = should_use_block;
+
         // Restore the parent scope
         self.current_scope = parent_scope;
     }
@@ -699,6 +957,49 @@ impl<'a> Visitor for TemplateVisitor<'_> {
     }
 }
 
+/// Adds a prop to the dynamic props of the `PROPS` patch flag,
+/// unless the props are already fully dynamic
+fn add_dynamic_prop(patch_hints: &mut PatchHints, prop: &FervidAtom) {
+    if patch_hints.flags.contains(PatchFlags::FullProps) || patch_hints.props.contains(prop) {
+        return;
+    }
+
+    patch_hints.flags |= PatchFlags::Props;
+    patch_hints.props.push(prop.to_owned());
+}
+
+/// Checks if a component has slots which cannot be compiled to a static slots object,
+/// i.e. `<template v-slot:[name]>`, `<template v-if v-slot>` or `<template v-for v-slot>`
+fn has_dynamic_slot_templates(starting_tag: &StartingTag, children: &[Node]) -> bool {
+    let is_dynamic_v_slot = |directives: &VueDirectives| {
+        directives.v_slot.as_ref().map_or(false, |v_slot| {
+            matches!(v_slot.slot_name, Some(StrOrExpr::Expr(_))) || directives.v_for.is_some()
+        })
+    };
+
+    // `v-slot` on the component itself
+    if starting_tag
+        .directives
+        .as_deref()
+        .map_or(false, is_dynamic_v_slot)
+    {
+        return true;
+    }
+
+    children.iter().any(|child| match child {
+        Node::Element(element_node) => {
+            element_node.starting_tag.tag_name == "template"
+                && element_node
+                    .starting_tag
+                    .directives
+                    .as_deref()
+                    .map_or(false, is_dynamic_v_slot)
+        }
+        Node::ConditionalSeq(_) => !is_from_default_slot(child),
+        _ => false,
+    })
+}
+
 impl TemplateVisitor<'_> {
     // TODO Maybe do this in parser instead, because it sometimes needs this info
     fn recognize_element_kind(&self, starting_tag: &StartingTag) -> ElementKind {
@@ -743,7 +1044,9 @@ impl VisitMut for Node {
 
 #[cfg(test)]
 mod tests {
-    use fervid_core::{ElementKind, Node, PatchHints, VForDirective, VueDirectives};
+    use fervid_core::{
+        ElementKind, Node, PatchFlagsSet, PatchHints, VForDirective, VOnDirective, VueDirectives,
+    };
     use swc_core::common::DUMMY_SP;
 
     use crate::test_utils::{js, to_str};
@@ -1211,6 +1514,7 @@ mod tests {
                         iterable: js("3"),
                         itervar: js("i"),
                         patch_flags: Default::default(),
+                        fragment_key: None,
                         span: DUMMY_SP,
                     })),
                 ),
@@ -1240,6 +1544,7 @@ mod tests {
                     iterable: js("3"),
                     itervar: js("j"),
                     patch_flags: Default::default(),
+                    fragment_key: None,
                     span: DUMMY_SP,
                 }))),
                 false,
@@ -1267,6 +1572,7 @@ mod tests {
                         iterable: js("3"),
                         itervar: js("i"),
                         patch_flags: Default::default(),
+                        fragment_key: None,
                         span: DUMMY_SP,
                     })),
                 ),
@@ -1274,6 +1580,7 @@ mod tests {
                     iterable: js("3"),
                     itervar: js("j"),
                     patch_flags: Default::default(),
+                    fragment_key: None,
                     span: DUMMY_SP,
                 }))),
                 false,
@@ -1330,6 +1637,358 @@ mod tests {
         };
     }
 
+    #[test]
+    fn it_injects_branch_keys_and_root_flags() {
+        // <template>
+        //   <h1 v-if="true">if</h1>
+        //   <h2 v-else-if="foo">else-if</h2>
+        //   <h3 v-else>else</h3>
+        //   <h1 v-if="true">if</h1>
+        // </template>
+        let mut sfc_template = SfcTemplateBlock {
+            lang: "html".into(),
+            roots: vec![if_node(), else_if_node(), else_node(), if_node()],
+            span: DUMMY_SP,
+        };
+
+        transform_and_record_template(&mut sfc_template, &mut Default::default());
+
+        // Multiple roots are a stable Fragment
+        let Some(Node::Element(root)) = sfc_template.roots.first() else {
+            panic!("Root is not an element")
+        };
+        assert_eq!(
+            PatchFlagsSet::from(PatchFlags::StableFragment),
+            root.patch_hints.flags
+        );
+
+        // Keys continue across the sibling sequences
+        let [Node::ConditionalSeq(first), Node::ConditionalSeq(second)] = &root.children[..] else {
+            panic!("Expected two conditional sequences")
+        };
+        assert_eq!(Some("0".into()), branch_key(&first.if_node.node));
+        assert_eq!(Some("1".into()), branch_key(&first.else_if_nodes[0].node));
+        assert_eq!(
+            Some("2".into()),
+            branch_key(first.else_node.as_ref().expect("Should exist"))
+        );
+        assert_eq!(Some("3".into()), branch_key(&second.if_node.node));
+    }
+
+    #[test]
+    fn it_marks_dev_root_fragment() {
+        // <template>
+        //   <!-- comment -->
+        //   <h1 v-if="true">if</h1>
+        // </template>
+        let sfc_template = SfcTemplateBlock {
+            lang: "html".into(),
+            roots: vec![Node::Comment("comment".into(), DUMMY_SP), if_node()],
+            span: DUMMY_SP,
+        };
+
+        let root_flags = |is_prod: bool| {
+            let mut sfc_template = sfc_template.clone();
+            let mut bindings_helper = BindingsHelper {
+                is_prod,
+                ..Default::default()
+            };
+            transform_and_record_template(&mut sfc_template, &mut bindings_helper);
+
+            let Some(Node::Element(root)) = sfc_template.roots.first() else {
+                panic!("Root is not an element")
+            };
+            root.patch_hints.flags.bits()
+        };
+
+        assert_eq!(64 | 2048, root_flags(false));
+        assert_eq!(64, root_flags(true));
+    }
+
+    #[test]
+    fn it_computes_event_patch_flags() {
+        // Convenience
+        let transform_attrs = |tag_name: &str, attributes: Vec<AttributeOrBinding>| {
+            let mut sfc_template = SfcTemplateBlock {
+                lang: "html".into(),
+                roots: vec![Node::Element(ElementNode {
+                    starting_tag: StartingTag {
+                        tag_name: tag_name.into(),
+                        attributes,
+                        directives: None,
+                    },
+                    children: vec![],
+                    template_scope: 0,
+                    kind: ElementKind::Element,
+                    patch_hints: Default::default(),
+                    span: DUMMY_SP,
+                })],
+                span: DUMMY_SP,
+            };
+            transform_and_record_template(&mut sfc_template, &mut Default::default());
+
+            let Some(Node::Element(root)) = sfc_template.roots.pop() else {
+                panic!("Root is not an element")
+            };
+            root.patch_hints
+        };
+
+        // <div @click="foo"></div>
+        let patch_hints = transform_attrs("div", vec![v_on(Some("click".into()), "foo")]);
+        assert!(patch_hints.flags.is_empty());
+
+        // <div @keyup="foo"></div>
+        let patch_hints = transform_attrs("div", vec![v_on(Some("keyup".into()), "foo")]);
+        assert_eq!(
+            PatchFlagsSet::from(PatchFlags::NeedHydration),
+            patch_hints.flags
+        );
+
+        // <div @vue:mounted="foo"></div>
+        let patch_hints = transform_attrs("div", vec![v_on(Some("vue:mounted".into()), "foo")]);
+        assert_eq!(
+            PatchFlagsSet::from(PatchFlags::NeedPatch),
+            patch_hints.flags
+        );
+
+        // <my-component @keyup="foo"></my-component>
+        // Member expressions are passed to components as-is, thus not cached
+        let patch_hints = transform_attrs("my-component", vec![v_on(Some("keyup".into()), "foo")]);
+        assert_eq!(PatchFlagsSet::from(PatchFlags::Props), patch_hints.flags);
+        assert_eq!(vec![FervidAtom::from("onKeyup")], patch_hints.props);
+
+        // <component :is="Comp" @keyup="handle"></component>
+        let patch_hints = transform_attrs(
+            "component",
+            vec![
+                AttributeOrBinding::VBind(VBindDirective {
+                    argument: Some("is".into()),
+                    value: js("Comp"),
+                    is_camel: false,
+                    is_prop: false,
+                    is_attr: false,
+                    span: DUMMY_SP,
+                }),
+                v_on(Some("keyup".into()), "handle"),
+            ],
+        );
+        assert_eq!(
+            PatchFlags::Props | PatchFlags::NeedHydration,
+            patch_hints.flags
+        );
+        assert_eq!(vec![FervidAtom::from("onKeyup")], patch_hints.props);
+
+        // <my-component @keyup="() => foo()"></my-component>
+        let patch_hints = transform_attrs(
+            "my-component",
+            vec![v_on(Some("keyup".into()), "() => foo()")],
+        );
+        assert!(patch_hints.flags.is_empty());
+
+        // <div @[evt]="foo" :id="bar"></div>
+        let patch_hints = transform_attrs(
+            "div",
+            vec![
+                v_on(Some(StrOrExpr::Expr(js("evt"))), "foo"),
+                AttributeOrBinding::VBind(VBindDirective {
+                    argument: Some("id".into()),
+                    value: js("bar"),
+                    is_camel: false,
+                    is_prop: false,
+                    is_attr: false,
+                    span: DUMMY_SP,
+                }),
+            ],
+        );
+        assert_eq!(
+            PatchFlagsSet::from(PatchFlags::FullProps),
+            patch_hints.flags
+        );
+        assert!(patch_hints.props.is_empty());
+    }
+
+    #[test]
+    fn it_computes_dynamic_slots() {
+        // Convenience
+        let transform_root = |root: ElementNode| {
+            let mut sfc_template = SfcTemplateBlock {
+                lang: "html".into(),
+                roots: vec![Node::Element(root)],
+                span: DUMMY_SP,
+            };
+            transform_and_record_template(&mut sfc_template, &mut Default::default());
+
+            let Some(Node::Element(root)) = sfc_template.roots.pop() else {
+                panic!("Root is not an element")
+            };
+            root
+        };
+
+        // <my-component>{{ i }}</my-component>
+        let component = ElementNode {
+            starting_tag: StartingTag {
+                tag_name: "my-component".into(),
+                attributes: vec![],
+                directives: None,
+            },
+            children: vec![Node::Interpolation(Interpolation {
+                value: js("i"),
+                template_scope: 0,
+                patch_flag: false,
+                span: DUMMY_SP,
+            })],
+            template_scope: 0,
+            kind: ElementKind::Element,
+            patch_hints: Default::default(),
+            span: DUMMY_SP,
+        };
+
+        // Slots without the scope variables are stable
+        let root = transform_root(component.clone());
+        assert!(root.patch_hints.flags.is_empty());
+
+        // <div v-for="i in 3"><my-component>{{ i }}</my-component></div>
+        let root = transform_root(ElementNode {
+            starting_tag: StartingTag {
+                tag_name: "div".into(),
+                attributes: vec![],
+                directives: Some(Box::new(VueDirectives {
+                    v_for: Some(VForDirective {
+                        iterable: js("3"),
+                        itervar: js("i"),
+                        patch_flags: Default::default(),
+                        fragment_key: None,
+                        span: DUMMY_SP,
+                    }),
+                    ..Default::default()
+                })),
+            },
+            children: vec![Node::Element(component.clone())],
+            template_scope: 0,
+            kind: ElementKind::Element,
+            patch_hints: Default::default(),
+            span: DUMMY_SP,
+        });
+        let Some(Node::Element(component_node)) = root.children.first() else {
+            panic!("Child is not an element")
+        };
+        assert_eq!(
+            PatchFlagsSet::from(PatchFlags::DynamicSlots),
+            component_node.patch_hints.flags
+        );
+
+        // <my-component><template v-slot:[name]>{{ i }}</template></my-component>
+        let mut slot_template = component.clone();
+        slot_template.starting_tag.tag_name = "template".into();
+        slot_template.starting_tag.directives = Some(Box::new(VueDirectives {
+            v_slot: Some(VSlotDirective {
+                slot_name: Some(StrOrExpr::Expr(js("name"))),
+                value: None,
+            }),
+            ..Default::default()
+        }));
+        let mut dynamic_slot_component = component;
+        dynamic_slot_component.children = vec![Node::Element(slot_template)];
+
+        let root = transform_root(dynamic_slot_component);
+        assert_eq!(
+            PatchFlagsSet::from(PatchFlags::DynamicSlots),
+            root.patch_hints.flags
+        );
+    }
+
+    #[test]
+    fn it_moves_template_v_for_to_child() {
+        // <template v-for="i in list" :key="i"><p>text</p></template>
+        let mut sfc_template = SfcTemplateBlock {
+            lang: "html".into(),
+            roots: vec![Node::Element(ElementNode {
+                starting_tag: StartingTag {
+                    tag_name: "template".into(),
+                    attributes: vec![AttributeOrBinding::VBind(VBindDirective {
+                        argument: Some("key".into()),
+                        value: js("i"),
+                        is_camel: false,
+                        is_prop: false,
+                        is_attr: false,
+                        span: DUMMY_SP,
+                    })],
+                    directives: Some(Box::new(VueDirectives {
+                        v_for: Some(VForDirective {
+                            iterable: js("list"),
+                            itervar: js("i"),
+                            patch_flags: Default::default(),
+                            fragment_key: None,
+                            span: DUMMY_SP,
+                        }),
+                        ..Default::default()
+                    })),
+                },
+                children: vec![Node::Element(ElementNode {
+                    starting_tag: StartingTag {
+                        tag_name: "p".into(),
+                        attributes: vec![],
+                        directives: None,
+                    },
+                    children: vec![text_node()],
+                    template_scope: 0,
+                    kind: ElementKind::Element,
+                    patch_hints: Default::default(),
+                    span: DUMMY_SP,
+                })],
+                template_scope: 0,
+                kind: ElementKind::Element,
+                patch_hints: Default::default(),
+                span: DUMMY_SP,
+            })],
+            span: DUMMY_SP,
+        };
+
+        transform_and_record_template(&mut sfc_template, &mut Default::default());
+
+        // Folded to `<p v-for="i in list" :key="i">text</p>`
+        let Some(Node::Element(p)) = sfc_template.roots.first() else {
+            panic!("Root is not an element")
+        };
+        assert_eq!(p.starting_tag.tag_name, "p");
+        assert_eq!(Some("i".into()), branch_key(p));
+        let v_for = p
+            .starting_tag
+            .directives
+            .as_ref()
+            .and_then(|d| d.v_for.as_ref())
+            .expect("Should have v-for");
+        assert!(v_for.patch_flags.contains(PatchFlags::KeyedFragment));
+    }
+
+    // `@event="handler"`
+    fn v_on(event: Option<StrOrExpr>, handler: &str) -> AttributeOrBinding {
+        AttributeOrBinding::VOn(VOnDirective {
+            event,
+            handler: Some(js(handler)),
+            modifiers: vec![],
+            key_modifiers: vec![],
+            is_handler_cacheable: false,
+            span: DUMMY_SP,
+        })
+    }
+
+    // Value of `:key`
+    fn branch_key(element_node: &ElementNode) -> Option<String> {
+        element_node
+            .starting_tag
+            .attributes
+            .iter()
+            .find_map(|attr| match attr {
+                AttributeOrBinding::VBind(VBindDirective {
+                    argument: Some(StrOrExpr::Str(name)),
+                    value,
+                    ..
+                }) if name == "key" => Some(to_str(value)),
+                _ => None,
+            })
+    }
+
     // text
     fn text_node() -> Node {
         Node::Text("text".into(), DUMMY_SP)
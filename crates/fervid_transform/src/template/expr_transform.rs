@@ -17,8 +17,9 @@ use swc_core::{
 };
 
 use crate::{
-    script::common::extract_variables_from_pat, template::js_builtins::JS_BUILTINS, BindingsHelper,
-    SetupBinding,
+    script::{common::extract_variables_from_pat, utils::create_member_access},
+    template::js_builtins::JS_BUILTINS,
+    BindingsHelper, SetupBinding,
 };
 
 use super::utils::wrap_in_event_arrow;
@@ -27,6 +28,8 @@ struct TransformVisitor<'s> {
     current_scope: u32,
     bindings_helper: &'s mut BindingsHelper,
     has_js_bindings: bool,
+    /// Whether the template scope variables (e.g. from `v-for` or `v-slot`) were referenced
+    has_scope_refs: bool,
     is_inline: bool,
 
     /// In ({ x } = y)
@@ -59,12 +62,16 @@ pub enum IdentTransformStrategy {
     Unref,
     /// Add the prefix, e.g. `$setup` or `_ctx`
     Prefix(FervidAtom),
+    /// Access the prop of a destructured alias using a prefix,
+    /// e.g. `__props.foo` for `bar` from `const { foo: bar } = defineProps()`
+    PropAlias(FervidAtom, FervidAtom),
     /// Generate `isRef(e) ? e.value++ : e++`
     IsRefCheckUpdate,
 }
 
 pub trait BindingsHelperTransform {
     fn transform_expr(&mut self, expr: &mut Expr, scope_to_use: u32) -> bool;
+    fn transform_handler(&mut self, handler: &mut Expr, scope_to_use: u32) -> bool;
     fn transform_v_model(
         &mut self,
         v_model: &mut VModelDirective,
@@ -77,25 +84,16 @@ pub trait BindingsHelperTransform {
 impl BindingsHelperTransform for BindingsHelper {
     /// Transforms the template expression
     fn transform_expr(&mut self, expr: &mut Expr, scope_to_use: u32) -> bool {
-        let is_inline = matches!(
-            self.template_generation_mode,
-            TemplateGenerationMode::Inline
-        );
-        let mut visitor = TransformVisitor {
-            current_scope: scope_to_use,
-            bindings_helper: self,
-            has_js_bindings: false,
-            is_inline,
-            is_in_assign_target: false,
-            is_in_destructure_assign: false,
-            is_v_model_transform: false,
-            local_vars: Vec::new(),
-            update_expr_helper: None,
-            should_consume_update_expr: false,
-        };
-        expr.visit_mut_with(&mut visitor);
+        let (has_js_bindings, _) = transform_with_visitor(self, expr, scope_to_use);
+        has_js_bindings
+    }
 
-        visitor.has_js_bindings
+    /// Transforms the event handler expression.
+    /// Returns `true` when the handler references the template scope variables
+    /// (e.g. from `v-for` or `v-slot`), meaning that it must not be cached.
+    fn transform_handler(&mut self, handler: &mut Expr, scope_to_use: u32) -> bool {
+        let (_, has_scope_refs) = transform_with_visitor(self, handler, scope_to_use);
+        has_scope_refs
     }
 
     /// Transforms `v-model` directive by producing
@@ -135,6 +133,7 @@ impl BindingsHelperTransform for BindingsHelper {
                 current_scope: scope_to_use,
                 bindings_helper: self,
                 has_js_bindings: false,
+                has_scope_refs: false,
                 is_inline,
                 is_in_assign_target: false,
                 is_in_destructure_assign: false,
@@ -144,6 +143,7 @@ impl BindingsHelperTransform for BindingsHelper {
                 should_consume_update_expr: false,
             };
             handler.visit_mut_with(&mut visitor);
+            v_model.is_handler_cacheable = !visitor.has_scope_refs;
         }
 
         // 3. Assign handler
@@ -188,6 +188,11 @@ impl BindingsHelperTransform for BindingsHelper {
             let found = current_scope.variables.iter().find(|it| *it == variable);
 
             if let Some(_) = found {
+                // Parent scopes always come before their children
+                self.outermost_scope_ref = Some(
+                    self.outermost_scope_ref
+                        .map_or(current_scope_index, |it| it.min(current_scope_index)),
+                );
                 return BindingTypes::TemplateLocal;
             }
 
@@ -429,6 +434,11 @@ impl<'s> VisitMut for TransformVisitor<'s> {
                 return;
             }
 
+            IdentTransformStrategy::PropAlias(prefix, key) => {
+                *expr = Expr::Member(create_member_access(prefix, &key, span));
+                return;
+            }
+
             IdentTransformStrategy::IsRefCheckUpdate => {
                 let Some((update_op, update_prefix)) = self.update_expr_helper.take() else {
                     // TODO This should be unreachable, signify error
@@ -563,7 +573,8 @@ impl<'s> VisitMut for TransformVisitor<'s> {
                         }
 
                         IdentTransformStrategy::Unref
-                        | IdentTransformStrategy::IsRefCheckUpdate => {
+                        | IdentTransformStrategy::IsRefCheckUpdate
+                        | IdentTransformStrategy::PropAlias(_, _) => {
                             // TODO Error: this is not a valid transform strategy
                             // Error hint: this is a bug in `fervid`, please report it
                         }
@@ -641,7 +652,9 @@ impl<'s> VisitMut for TransformVisitor<'s> {
                         return;
                     }
 
-                    IdentTransformStrategy::Unref | IdentTransformStrategy::IsRefCheckUpdate => {
+                    IdentTransformStrategy::Unref
+                    | IdentTransformStrategy::IsRefCheckUpdate
+                    | IdentTransformStrategy::PropAlias(_, _) => {
                         // TODO Error: this is not a valid transform strategy
                         // (technically this is a syntax error, so should be impossible)
                     }
@@ -691,15 +704,17 @@ impl<'s> VisitMut for TransformVisitor<'s> {
                         None => {
                             let symbol = &assign.key.sym;
 
-                            let is_local =
-                                self.local_vars.iter().rfind(|it| &it.0 == symbol).is_some()
-                                    || matches!(
-                                        self.bindings_helper
-                                            .get_var_binding_type(self.current_scope, symbol),
-                                        BindingTypes::TemplateLocal
-                                    );
-
-                            if !is_local {
+                            let is_local_var =
+                                self.local_vars.iter().rfind(|it| &it.0 == symbol).is_some();
+                            let is_template_local = !is_local_var
+                                && matches!(
+                                    self.bindings_helper
+                                        .get_var_binding_type(self.current_scope, symbol),
+                                    BindingTypes::TemplateLocal
+                                );
+                            self.has_scope_refs |= is_template_local;
+
+                            if !is_local_var && !is_template_local {
                                 let mut value = Box::new(Pat::Ident(assign.key.to_owned()));
                                 value.visit_mut_with(self);
                                 *elem = ObjectPatProp::KeyValue(KeyValuePatProp {
@@ -718,6 +733,35 @@ impl<'s> VisitMut for TransformVisitor<'s> {
     }
 }
 
+/// Transforms the expression and returns whether it has JS bindings
+/// and whether it references the template scope variables
+fn transform_with_visitor(
+    bindings_helper: &mut BindingsHelper,
+    expr: &mut Expr,
+    scope_to_use: u32,
+) -> (bool, bool) {
+    let is_inline = matches!(
+        bindings_helper.template_generation_mode,
+        TemplateGenerationMode::Inline
+    );
+    let mut visitor = TransformVisitor {
+        current_scope: scope_to_use,
+        bindings_helper,
+        has_js_bindings: false,
+        has_scope_refs: false,
+        is_inline,
+        is_in_assign_target: false,
+        is_in_destructure_assign: false,
+        is_v_model_transform: false,
+        local_vars: Vec::new(),
+        update_expr_helper: None,
+        should_consume_update_expr: false,
+    };
+    expr.visit_mut_with(&mut visitor);
+
+    (visitor.has_js_bindings, visitor.has_scope_refs)
+}
+
 impl TransformVisitor<'_> {
     /// Determines the strategy with which an Ident needs to be transformed.
     /// This function is needed because SWC's AST is strongly-typed and we cannot simply
@@ -738,12 +782,21 @@ impl TransformVisitor<'_> {
         // Template local binding doesn't need any processing
         if let BindingTypes::TemplateLocal = binding_type {
             self.has_js_bindings = true;
+            self.has_scope_refs = true;
             return IdentTransformStrategy::LeaveUnchanged;
         }
 
         // Get the prefix which fits the scope (e.g. `_ctx.` for unknown scopes, `$setup.` for setup scope)
         if let Some(prefix) = get_prefix(&binding_type, self.is_inline) {
             self.has_js_bindings = true;
+
+            // Destructured props are accessed by their original name
+            if let BindingTypes::PropsAliased = binding_type {
+                if let Some(key) = self.bindings_helper.props_aliases.get(symbol) {
+                    return IdentTransformStrategy::PropAlias(prefix, key.to_owned());
+                }
+            }
+
             return IdentTransformStrategy::Prefix(prefix);
         }
 
@@ -803,7 +856,7 @@ pub fn get_prefix(binding_type: &BindingTypes, is_inline: bool) -> Option<Fervid
             BindingTypes::Data | BindingTypes::Options | BindingTypes::Unresolved => {
                 Some(FervidAtom::from("_ctx"))
             }
-            BindingTypes::Props => Some(FervidAtom::from("__props")),
+            BindingTypes::Props | BindingTypes::PropsAliased => Some(FervidAtom::from("__props")),
             // TODO This is not correct. The transform implementation must handle `unref`
             _ => None,
         };
@@ -811,20 +864,19 @@ pub fn get_prefix(binding_type: &BindingTypes, is_inline: bool) -> Option<Fervid
 
     match binding_type {
         BindingTypes::Data => Some(FervidAtom::from("$data")),
-        BindingTypes::Props => Some(FervidAtom::from("$props")),
+        BindingTypes::Props | BindingTypes::PropsAliased => Some(FervidAtom::from("$props")),
         BindingTypes::Options => Some(FervidAtom::from("$options")),
-        BindingTypes::TemplateLocal
-        | BindingTypes::JsGlobal
-        | BindingTypes::LiteralConst
-        | BindingTypes::Component
-        | BindingTypes::Imported => None,
-        BindingTypes::SetupConst
+        BindingTypes::TemplateLocal | BindingTypes::JsGlobal | BindingTypes::Component => None,
+        // Imports and literal constants of both `<script>` and `<script setup>`
+        // are returned from `setup()` and are thus accessed from `$setup`
+        BindingTypes::LiteralConst
+        | BindingTypes::Imported
+        | BindingTypes::SetupConst
         | BindingTypes::SetupLet
         | BindingTypes::SetupMaybeRef
         | BindingTypes::SetupReactiveConst
         | BindingTypes::SetupRef => Some(FervidAtom::from("$setup")),
         BindingTypes::Unresolved => Some(FervidAtom::from("_ctx")),
-        BindingTypes::PropsAliased => unimplemented!(),
     }
 }
 
@@ -1120,6 +1172,7 @@ mod tests {
                     value: js($value),
                     update_handler: None,
                     modifiers: vec![],
+                    is_handler_cacheable: false,
                     span: DUMMY_SP,
                 };
                 let mut patch_hints = PatchHints::default();
@@ -1226,6 +1279,7 @@ mod tests {
                     value: js("dummy"),
                     update_handler: None,
                     modifiers: vec![],
+                    is_handler_cacheable: false,
                     span: DUMMY_SP,
                 };
                 let mut patch_hints = PatchHints::default();
@@ -1252,6 +1306,43 @@ mod tests {
         test!("\"string\"", "\"string\"");
     }
 
+    #[test]
+    fn it_transforms_destructured_props() {
+        let mut helper = BindingsHelper::default();
+        helper.setup_bindings.extend([
+            SetupBinding(FervidAtom::from("foo"), BindingTypes::Props),
+            SetupBinding(FervidAtom::from("bar"), BindingTypes::PropsAliased),
+            SetupBinding(FervidAtom::from("baz"), BindingTypes::PropsAliased),
+        ]);
+        helper
+            .props_aliases
+            .insert(FervidAtom::from("bar"), FervidAtom::from("barOriginal"));
+        helper
+            .props_aliases
+            .insert(FervidAtom::from("baz"), FervidAtom::from("baz-original"));
+
+        macro_rules! test {
+            ($expr: literal, $expected: literal) => {
+                let mut expr = js($expr);
+                helper.transform_expr(&mut expr, 0);
+                assert_eq!(to_str(&expr), $expected);
+            };
+        }
+
+        // Render function
+        test!(
+            "foo + bar + baz",
+            "$props.foo+$props.barOriginal+$props[\"baz-original\"]"
+        );
+
+        // Inline
+        helper.template_generation_mode = TemplateGenerationMode::Inline;
+        test!(
+            "foo + bar + baz",
+            "__props.foo+__props.barOriginal+__props[\"baz-original\"]"
+        );
+    }
+
     #[test]
     fn it_works_with_template_scope_hierarchy() {
         let v_root = FervidAtom::from("root");
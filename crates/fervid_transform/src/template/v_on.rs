@@ -1,9 +1,12 @@
-use fervid_core::{fervid_atom, BindingTypes, FervidAtom, StrOrExpr, VOnDirective};
+use fervid_core::{
+    fervid_atom, BindingTypes, BuiltinType, ElementKind, FervidAtom, StrOrExpr, VOnDirective,
+    VueImports,
+};
 use swc_core::{
     common::DUMMY_SP,
     ecma::ast::{
-        ArrowExpr, BinExpr, BinaryOp, BindingIdent, BlockStmtOrExpr, CallExpr, Callee, Expr,
-        ExprOrSpread, Ident, Pat, RestPat,
+        ArrowExpr, BinExpr, BinaryOp, BindingIdent, BlockStmtOrExpr, CallExpr, Callee, CondExpr,
+        Expr, ExprOrSpread, Ident, Lit, ParenExpr, Pat, RestPat, Str,
     },
 };
 
@@ -14,7 +17,16 @@ use super::{
 };
 
 impl TemplateVisitor<'_> {
-    pub fn transform_v_on(&mut self, v_on: &mut VOnDirective, scope_to_use: u32) {
+    /// Transforms the `v-on` event and handler.
+    /// Returns `true` when the handler changes between renders,
+    /// i.e. it is neither cached nor a constant.
+    /// `element_kind` is needed because components get the member expressions as-is.
+    pub fn transform_v_on(
+        &mut self,
+        v_on: &mut VOnDirective,
+        element_kind: ElementKind,
+        scope_to_use: u32,
+    ) -> bool {
         match v_on.event.as_mut() {
             Some(StrOrExpr::Str(static_event)) => {
                 transform_v_on_static_event(static_event);
@@ -23,15 +35,38 @@ impl TemplateVisitor<'_> {
             Some(StrOrExpr::Expr(dynamic_event)) => {
                 self.bindings_helper
                     .transform_expr(dynamic_event, scope_to_use);
+
+                // `@[event]` -> `[_toHandlerKey(event)]`
+                self.bindings_helper.vue_imports |= VueImports::ToHandlerKey;
+                *dynamic_event = Box::new(Expr::Call(CallExpr {
+                    span: DUMMY_SP,
+                    callee: Callee::Expr(Box::new(Expr::Ident(Ident {
+                        span: DUMMY_SP,
+                        sym: VueImports::ToHandlerKey.as_atom(),
+                        optional: false,
+                    }))),
+                    args: vec![ExprOrSpread {
+                        spread: None,
+                        expr: dynamic_event.to_owned(),
+                    }],
+                    type_args: None,
+                }));
             }
 
             None => {}
         }
 
+        resolve_modifiers(v_on);
+
+        // Empty handler `() => {}` is always cached
+        v_on.is_handler_cacheable = true;
+        let mut is_dynamic = false;
+
         if let Some(mut handler) = v_on.handler.take() {
             // 1. Check the handler shape
             let mut is_member_or_paren = false;
             let mut is_non_null_or_opt_chain = false;
+            let mut is_const_ident = false;
             let mut is_non_const_ident = false;
             let mut needs_event = false;
 
@@ -41,13 +76,14 @@ impl TemplateVisitor<'_> {
 
                 // This is either as-is (if const) or `(...args) => _ctx.smth && _ctx.smth(...args)`
                 Expr::Ident(ident) => {
-                    is_non_const_ident = !matches!(
+                    is_const_ident = matches!(
                         self.bindings_helper
                             .get_var_binding_type(scope_to_use, &ident.sym),
                         BindingTypes::SetupConst
                             | BindingTypes::LiteralConst
                             | BindingTypes::SetupReactiveConst
                     );
+                    is_non_const_ident = !is_const_ident;
                 }
 
                 // This is getting `(...args) => _ctx.smth && _ctx.smth(...args)`
@@ -104,18 +140,39 @@ impl TemplateVisitor<'_> {
                 handler = wrap_in_event_arrow(handler);
             }
 
-            // 3. Transform the handler
-            self.bindings_helper
-                .transform_expr(&mut handler, scope_to_use);
+            // 3. Transform the handler.
+            // Constants do not change between renders, so they do not need to be cached,
+            // and the handlers using the scope variables must be fresh to not use stale values
+            let has_scope_refs = self
+                .bindings_helper
+                .transform_handler(&mut handler, scope_to_use);
+
+            // Components get the member expressions as-is to preserve the function arity,
+            // e.g. `<transition>` checks `cb.length`, thus these are neither cached nor wrapped.
+            // https://github.com/vuejs/core/blob/v3.4.21/packages/compiler-core/src/transforms/vOn.ts
+            let is_member_exp =
+                is_non_const_ident || is_member_or_paren || is_non_null_or_opt_chain;
+            let is_component = match element_kind {
+                ElementKind::Component => true,
+                ElementKind::Builtin(builtin) => !matches!(builtin, BuiltinType::Slot),
+                ElementKind::Element => false,
+            };
+            let is_component_member_exp = is_member_exp && is_component;
+
+            v_on.is_handler_cacheable =
+                !is_const_ident && !has_scope_refs && !is_component_member_exp;
+            is_dynamic = (has_scope_refs || is_component_member_exp) && !is_const_ident;
 
             // 4. Wrap in `(...args)` arrow if needed
-            if is_non_const_ident || is_member_or_paren || is_non_null_or_opt_chain {
+            if is_member_exp && !is_component_member_exp {
                 handler = wrap_in_args_arrow(handler, !is_non_null_or_opt_chain);
             }
 
             // Re-assign because it was `take`n
             v_on.handler = Some(handler);
         }
+
+        is_dynamic
     }
 }
 
@@ -136,6 +193,146 @@ fn transform_v_on_static_event(static_event: &mut FervidAtom) {
     *static_event = FervidAtom::from(transformed_event);
 }
 
+/// Splits the modifiers into the runtime guards for `withModifiers`,
+/// the key guards for `withKeys` and the event options (`.once`, `.capture`, `.passive`),
+/// adjusting the event name accordingly.
+///
+/// https://github.com/vuejs/core/blob/v3.4.21/packages/compiler-dom/src/transforms/vOn.ts
+fn resolve_modifiers(v_on: &mut VOnDirective) {
+    let Some(ref mut event) = v_on.event else {
+        return;
+    };
+    if v_on.modifiers.is_empty() {
+        return;
+    }
+
+    let is_static = matches!(event, StrOrExpr::Str(_));
+    let is_keyboard = matches!(event, StrOrExpr::Str(s) if is_keyboard_event(s));
+
+    let mut non_key_modifiers = Vec::new();
+    let mut key_modifiers = Vec::new();
+    let mut event_option_modifiers = Vec::new();
+
+    for modifier in v_on.modifiers.drain(..) {
+        match modifier.as_ref() {
+            // Options for `addEventListener()`
+            "passive" | "once" | "capture" => event_option_modifiers.push(modifier),
+
+            // `left` and `right` could be mouse or key modifiers based on event type
+            "left" | "right" if !is_static => {
+                key_modifiers.push(modifier.to_owned());
+                non_key_modifiers.push(modifier);
+            }
+            "left" | "right" if is_keyboard => key_modifiers.push(modifier),
+            "left" | "right" => non_key_modifiers.push(modifier),
+
+            // Event propagation management, system modifiers and `exact`, mouse
+            "stop" | "prevent" | "self" | "ctrl" | "shift" | "alt" | "meta" | "exact"
+            | "middle" => non_key_modifiers.push(modifier),
+
+            _ => key_modifiers.push(modifier),
+        }
+    }
+
+    // Normalize `click.right` and `click.middle` since they don't actually fire
+    if non_key_modifiers.iter().any(|it| it == "right") {
+        transform_click(event, "onContextmenu");
+    }
+    if non_key_modifiers.iter().any(|it| it == "middle") {
+        transform_click(event, "onMouseup");
+    }
+
+    // Key guards are only needed for keyboard events.
+    // If event name is dynamic, the guard is always added.
+    if is_static && !is_keyboard {
+        key_modifiers.clear();
+    }
+
+    // `@click.capture.once` -> `onClickCaptureOnce`
+    if !event_option_modifiers.is_empty() {
+        let mut postfix = String::new();
+        for modifier in event_option_modifiers.iter() {
+            let mut chars = modifier.chars();
+            if let Some(first) = chars.next() {
+                postfix.push(first.to_ascii_uppercase());
+                postfix.push_str(chars.as_str());
+            }
+        }
+
+        match event {
+            StrOrExpr::Str(s) => {
+                let mut new_event = String::with_capacity(s.len() + postfix.len());
+                new_event.push_str(s);
+                new_event.push_str(&postfix);
+                *s = FervidAtom::from(new_event);
+            }
+
+            // `(event) + "Once"`
+            StrOrExpr::Expr(expr) => {
+                *expr = Box::new(Expr::Bin(BinExpr {
+                    span: DUMMY_SP,
+                    op: BinaryOp::Add,
+                    left: paren(expr.to_owned()),
+                    right: str_lit(&postfix),
+                }));
+            }
+        }
+    }
+
+    v_on.modifiers = non_key_modifiers;
+    v_on.key_modifiers = key_modifiers;
+}
+
+/// Replaces the `onClick` event with another one,
+/// e.g. `(event) === "onClick" ? "onContextmenu" : (event)` for the dynamic events
+fn transform_click(event: &mut StrOrExpr, replacement: &str) {
+    match event {
+        StrOrExpr::Str(s) => {
+            if s.eq_ignore_ascii_case("onclick") {
+                *s = FervidAtom::from(replacement);
+            }
+        }
+
+        StrOrExpr::Expr(expr) => {
+            *expr = Box::new(Expr::Cond(CondExpr {
+                span: DUMMY_SP,
+                test: Box::new(Expr::Bin(BinExpr {
+                    span: DUMMY_SP,
+                    op: BinaryOp::EqEqEq,
+                    left: paren(expr.to_owned()),
+                    right: str_lit("onClick"),
+                })),
+                cons: str_lit(replacement),
+                alt: paren(expr.to_owned()),
+            }));
+        }
+    }
+}
+
+#[inline]
+fn is_keyboard_event(event: &str) -> bool {
+    event.eq_ignore_ascii_case("onkeyup")
+        || event.eq_ignore_ascii_case("onkeydown")
+        || event.eq_ignore_ascii_case("onkeypress")
+}
+
+#[inline]
+fn paren(expr: Box<Expr>) -> Box<Expr> {
+    Box::new(Expr::Paren(ParenExpr {
+        span: DUMMY_SP,
+        expr,
+    }))
+}
+
+#[inline]
+fn str_lit(value: &str) -> Box<Expr> {
+    Box::new(Expr::Lit(Lit::Str(Str {
+        span: DUMMY_SP,
+        value: FervidAtom::from(value),
+        raw: None,
+    })))
+}
+
 /// Turns an event name to an `on` event handler.
 /// The algorithm is as follows:
 /// 0. Push `on` to buffer;
@@ -258,7 +455,9 @@ mod tests {
         fervid_atom, BindingTypes, TemplateGenerationMode,
     };
 
-    use crate::{test_utils::{to_str, ts}, BindingsHelper, SetupBinding};
+    use smallvec::SmallVec;
+
+    use crate::{test_utils::{to_str, ts}, BindingsHelper, SetupBinding, TemplateScope};
 
     use super::*;
 
@@ -304,13 +503,18 @@ mod tests {
     // @evt="$in"
     macro_rules! test_with {
         ($visitor: ident, $in: literal, $expected: literal) => {
+            test_with!($visitor, ElementKind::Element, $in, $expected)
+        };
+        ($visitor: ident, $element_kind: expr, $in: literal, $expected: literal) => {
             let mut v_on = VOnDirective {
                 event: Some("evt".into()),
                 handler: Some(ts($in)),
                 modifiers: vec![],
+                key_modifiers: vec![],
+                is_handler_cacheable: false,
                 span: DUMMY_SP,
             };
-            $visitor.transform_v_on(&mut v_on, 0);
+            $visitor.transform_v_on(&mut v_on, $element_kind, 0);
             assert_eq!($expected, to_str(&v_on.handler.expect("should exist")));
         };
     }
@@ -397,6 +601,15 @@ mod tests {
         test!("foo?.bar", "(...args)=>foo.value?.bar(...args)");
         test!("bar?.baz", "(...args)=>_ctx.bar?.baz(...args)");
 
+        // member expr on a component is passed as-is
+        test_with!(template_visitor, ElementKind::Component, "bar", "_ctx.bar");
+        test_with!(
+            template_visitor,
+            ElementKind::Component,
+            "foo.bar",
+            "foo.value.bar"
+        );
+
         // call
         test!("func()", "$event=>func()");
         test!("func($event)", "$event=>func($event)");
@@ -634,6 +847,157 @@ mod tests {
         test!("({ lett } = val)", "$event=>({lett:lett}=val)");
     }
 
+    #[test]
+    fn it_resolves_modifiers() {
+        let mut bindings_helper = helper(vec![SetupBinding(
+            fervid_atom!("evt"),
+            BindingTypes::SetupMaybeRef,
+        )]);
+        let mut template_visitor = TemplateVisitor {
+            bindings_helper: &mut bindings_helper,
+            current_scope: 0,
+            v_for_scope: false,
+        };
+
+        macro_rules! test {
+            ($event: expr, [$($modifier: literal),*], $expected_event: literal, [$($expected_mod: literal),*], [$($expected_key: literal),*]) => {{
+                let mut v_on = VOnDirective {
+                    event: Some($event),
+                    handler: None,
+                    modifiers: vec![$(fervid_atom!($modifier)),*],
+                    key_modifiers: vec![],
+                    is_handler_cacheable: false,
+                    span: DUMMY_SP,
+                };
+                template_visitor.transform_v_on(&mut v_on, ElementKind::Element, 0);
+                let event = match v_on.event {
+                    Some(StrOrExpr::Str(s)) => s.to_string(),
+                    Some(StrOrExpr::Expr(e)) => to_str(&e),
+                    None => unreachable!(),
+                };
+                assert_eq!(event, $expected_event);
+                let expected_mods: Vec<FervidAtom> = vec![$(fervid_atom!($expected_mod)),*];
+                let expected_keys: Vec<FervidAtom> = vec![$(fervid_atom!($expected_key)),*];
+                assert_eq!(v_on.modifiers, expected_mods);
+                assert_eq!(v_on.key_modifiers, expected_keys);
+            }};
+        }
+
+        // Key modifiers
+        test!(StrOrExpr::Str(fervid_atom!("keyup")), ["enter"], "onKeyup", [], ["enter"]);
+        test!(StrOrExpr::Str(fervid_atom!("keydown")), ["esc"], "onKeydown", [], ["esc"]);
+        test!(StrOrExpr::Str(fervid_atom!("keyup")), ["ctrl", "s"], "onKeyup", ["ctrl"], ["s"]);
+        test!(StrOrExpr::Str(fervid_atom!("keyup")), ["left"], "onKeyup", [], ["left"]);
+
+        // Key modifiers are dropped for non-keyboard events
+        test!(StrOrExpr::Str(fervid_atom!("click")), ["stop", "enter"], "onClick", ["stop"], []);
+
+        // System modifiers and `.exact`
+        test!(StrOrExpr::Str(fervid_atom!("click")), ["ctrl", "exact"], "onClick", ["ctrl", "exact"], []);
+
+        // Mouse buttons
+        test!(StrOrExpr::Str(fervid_atom!("click")), ["left"], "onClick", ["left"], []);
+        test!(StrOrExpr::Str(fervid_atom!("click")), ["right"], "onContextmenu", ["right"], []);
+        test!(StrOrExpr::Str(fervid_atom!("click")), ["middle"], "onMouseup", ["middle"], []);
+
+        // Event options
+        test!(StrOrExpr::Str(fervid_atom!("click")), ["once"], "onClickOnce", [], []);
+        test!(StrOrExpr::Str(fervid_atom!("scroll")), ["capture", "passive"], "onScrollCapturePassive", [], []);
+        test!(StrOrExpr::Str(fervid_atom!("keyup")), ["enter", "once"], "onKeyupOnce", [], ["enter"]);
+
+        // Dynamic events
+        test!(StrOrExpr::Expr(ts("evt")), [], "_toHandlerKey(_unref(evt))", [], []);
+        test!(StrOrExpr::Expr(ts("evt")), ["enter"], "_toHandlerKey(_unref(evt))", [], ["enter"]);
+        test!(StrOrExpr::Expr(ts("evt")), ["left"], "_toHandlerKey(_unref(evt))", ["left"], ["left"]);
+        test!(
+            StrOrExpr::Expr(ts("evt")),
+            ["right"],
+            r#"(_toHandlerKey(_unref(evt)))==="onClick"?"onContextmenu":(_toHandlerKey(_unref(evt)))"#,
+            ["right"],
+            ["right"]
+        );
+        test!(StrOrExpr::Expr(ts("evt")), ["once"], r#"(_toHandlerKey(_unref(evt)))+"Once""#, [], []);
+    }
+
+    #[test]
+    fn it_marks_cacheable_handlers() {
+        // `const foo = ref()`
+        // `function func() {}`
+        let mut bindings_helper = helper(vec![
+            SetupBinding(fervid_atom!("foo"), BindingTypes::SetupRef),
+            SetupBinding(fervid_atom!("func"), BindingTypes::SetupConst),
+        ]);
+
+        // `v-for="item in items"` creates scope 1
+        bindings_helper.template_scopes.push(TemplateScope {
+            variables: Default::default(),
+            parent: 0,
+        });
+        bindings_helper.template_scopes.push(TemplateScope {
+            variables: SmallVec::from_vec(vec![fervid_atom!("item")]),
+            parent: 0,
+        });
+
+        let mut template_visitor = TemplateVisitor {
+            bindings_helper: &mut bindings_helper,
+            current_scope: 0,
+            v_for_scope: false,
+        };
+
+        macro_rules! test {
+            ($in: expr, $expected: literal) => {
+                test!(ElementKind::Element, $in, $expected)
+            };
+            ($element_kind: expr, $in: expr, $expected: literal) => {{
+                let mut v_on = VOnDirective {
+                    event: Some("evt".into()),
+                    handler: $in,
+                    modifiers: vec![],
+                    key_modifiers: vec![],
+                    is_handler_cacheable: false,
+                    span: DUMMY_SP,
+                };
+                template_visitor.transform_v_on(&mut v_on, $element_kind, 1);
+                assert_eq!(v_on.is_handler_cacheable, $expected);
+            }};
+        }
+
+        // Empty handler
+        test!(None, true);
+
+        // Inline statements and functions
+        test!(Some(ts("foo = 2")), true);
+        test!(Some(ts("() => func(foo)")), true);
+        test!(Some(ts("$event => foo = $event")), true);
+
+        // Non-constant identifiers and member expressions
+        test!(Some(ts("bar")), true);
+        test!(Some(ts("foo.bar")), true);
+
+        // Constants are not cached
+        test!(Some(ts("func")), false);
+
+        // Scope variables are not cached
+        test!(Some(ts("func(item)")), false);
+        test!(Some(ts("() => foo = item")), false);
+        test!(Some(ts("item.handle")), false);
+
+        // Local variables shadowing the scope variables are fine
+        test!(Some(ts("item => foo = item")), true);
+
+        // Member expressions are passed to components as-is
+        test!(ElementKind::Component, Some(ts("bar")), false);
+        test!(ElementKind::Component, Some(ts("foo.bar")), false);
+        test!(
+            ElementKind::Builtin(BuiltinType::Transition),
+            Some(ts("foo.bar")),
+            false
+        );
+        test!(ElementKind::Component, Some(ts("() => foo.bar()")), true);
+        test!(ElementKind::Component, Some(ts("foo = 2")), true);
+        test!(ElementKind::Builtin(BuiltinType::Slot), Some(ts("foo.bar")), true);
+    }
+
     fn helper(bindings: Vec<SetupBinding>) -> BindingsHelper {
         let mut bindings_helper = BindingsHelper::default();
         bindings_helper.setup_bindings.extend(bindings);
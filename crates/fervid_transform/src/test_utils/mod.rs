@@ -1,6 +1,9 @@
 pub mod parser;
 
-use swc_core::common::{sync::Lrc, SourceMap};
+use fervid_core::{
+    AttributeOrBinding, ElementKind, ElementNode, Node as TemplateNode, StartingTag,
+};
+use swc_core::common::{sync::Lrc, SourceMap, DUMMY_SP};
 use swc_core::ecma::ast::Expr;
 use swc_ecma_codegen::{text_writer::JsWriter, Emitter, Node};
 
@@ -34,3 +37,30 @@ pub fn to_str(swc_node: &impl Node) -> String {
 
     String::from_utf8(buff).unwrap()
 }
+
+pub fn regular_attribute(name: &str, value: &str) -> AttributeOrBinding {
+    AttributeOrBinding::RegularAttribute {
+        name: name.into(),
+        value: value.into(),
+        span: DUMMY_SP,
+    }
+}
+
+pub fn element(
+    tag_name: &str,
+    attributes: Vec<AttributeOrBinding>,
+    children: Vec<TemplateNode>,
+) -> ElementNode {
+    ElementNode {
+        starting_tag: StartingTag {
+            tag_name: tag_name.into(),
+            attributes,
+            directives: None,
+        },
+        children,
+        template_scope: 0,
+        kind: ElementKind::Element,
+        patch_hints: Default::default(),
+        span: DUMMY_SP,
+    }
+}
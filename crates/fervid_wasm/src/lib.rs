@@ -34,7 +34,17 @@ pub fn compile_sync(source: &str, is_prod: Option<bool>) -> Result<WasmCompileRe
             id: "".into(),
             is_prod,
             ssr: Some(false),
+            transform_asset_urls: None,
             gen_default_as: None,
+            hoist_static: None,
+            css_modules_pattern: None,
+            css_modules_mode: None,
+            style_preprocessor: None,
+            css_minify: None,
+            css_targets: None,
+            type_resolve_fs: None,
+            type_scope_cache: None,
+            global_type_files: None,
             source_map: None,
         },
     );